@@ -52,6 +52,10 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    #[cfg(feature = "checkpoint-rocksdb")]
+    #[error("can not open checkpoint store. {:?}", inner)]
+    CheckpointStoreError { inner: String },
+
     #[error("error: invalid log path")]
     InvalidLogPath,
 
@@ -82,6 +86,16 @@ impl Error {
         }
     }
 
+    #[cfg(feature = "checkpoint-rocksdb")]
+    pub fn checkpoint_store_error<T>(msg: T) -> Error
+    where
+        T: Display,
+    {
+        Error::CheckpointStoreError {
+            inner: msg.to_string(),
+        }
+    }
+
     pub fn invalid_log_path() -> Error {
         Error::InvalidLogPath
     }
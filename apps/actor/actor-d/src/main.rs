@@ -7,11 +7,13 @@ use std::fs::File;
 use std::io::Read;
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::Arc;
 use toy::api_client::http::HttpApiClient;
 use toy::core::prelude::*;
 use toy::executor::ExecutorFactory;
 use toy::actor::exporters::{ToyExporter, TracingExporter};
 use toy::actor::ActorConfig;
+use toy::core::checkpoint::CheckpointStore;
 use toy_api::authentication::Claims;
 use toy_jwt::Algorithm;
 use toy_tracing::{LogGuard, CONSOLE_DEFAULT_IP, CONSOLE_DEFAULT_PORT};
@@ -24,6 +26,8 @@ struct Config {
     heart_beat_interval_mills: u64,
     event_export_interval_mills: u64,
     metrics_export_interval_mills: u64,
+    checkpoint_store: Option<Arc<dyn CheckpointStore>>,
+    checkpoint_interval_mills: u64,
 }
 
 impl ActorConfig for Config {
@@ -61,6 +65,14 @@ impl ActorConfig for Config {
     fn event_exporter(&self) -> Self::EventExporter {
         ToyExporter
     }
+
+    fn checkpoint_store(&self) -> Option<Arc<dyn CheckpointStore>> {
+        self.checkpoint_store.clone()
+    }
+
+    fn checkpoint_interval_mills(&self) -> u64 {
+        self.checkpoint_interval_mills
+    }
 }
 
 fn main() {
@@ -85,6 +97,9 @@ fn go() -> Result<(), Error> {
         .with(toy_plugin_commons::timer::all())
         .with(toy_plugin_commons::sort::all())
         .with(toy_plugin_commons::stat::all())
+        .with(toy_plugin_commons::window::all())
+        .with(toy_plugin_commons::join::all())
+        .with(toy_plugin_commons::dedup::all())
         .build();
 
     let thread_name = format!(
@@ -125,10 +140,13 @@ fn go() -> Result<(), Error> {
                 .expect("invalid IP Address.");
 
             let api_client = HttpApiClient::new(&c.api_root, auth).unwrap();
+            let (checkpoint_store, checkpoint_interval_mills) = get_checkpoint_store(c)?;
             let config = Config {
                 heart_beat_interval_mills: c.heart_beat_interval_mills,
                 event_export_interval_mills: c.event_export_interval_mills,
                 metrics_export_interval_mills: c.metrics_export_interval_mills,
+                checkpoint_store,
+                checkpoint_interval_mills,
             };
             let (sv, _, _) =
                 toy::actor::subscribe(&c.name, ExecutorFactory, app, api_client, addr, config);
@@ -193,6 +211,25 @@ fn get_credential(user: &str, kid: &str, path_string: &str) -> Result<String, Er
     Ok(token)
 }
 
+/// Checkpoints are stored only with the `checkpoint-rocksdb` feature and `--checkpoint-path`.
+#[cfg(feature = "checkpoint-rocksdb")]
+fn get_checkpoint_store(c: &Subscribe) -> Result<(Option<Arc<dyn CheckpointStore>>, u64), Error> {
+    let store = match &c.checkpoint.checkpoint_path {
+        Some(path) => {
+            let store = toy::actor::RocksCheckpointStore::new(path)
+                .map_err(Error::checkpoint_store_error)?;
+            Some(Arc::new(store) as Arc<dyn CheckpointStore>)
+        }
+        None => None,
+    };
+    Ok((store, c.checkpoint.checkpoint_interval_mills))
+}
+
+#[cfg(not(feature = "checkpoint-rocksdb"))]
+fn get_checkpoint_store(_c: &Subscribe) -> Result<(Option<Arc<dyn CheckpointStore>>, u64), Error> {
+    Ok((None, toy::core::checkpoint::DEFAULT_CHECKPOINT_INTERVAL_MILLS))
+}
+
 fn get_graph(file: &PathBuf) -> Result<Graph, Error> {
     let mut f = File::open(file)?;
     let mut buffer = String::new();
@@ -73,6 +73,23 @@ pub struct Subscribe {
         default_value = "10000"
     )]
     pub metrics_export_interval_mills: u64,
+    #[cfg(feature = "checkpoint-rocksdb")]
+    #[clap(flatten)]
+    pub checkpoint: CheckpointOption,
+}
+
+#[cfg(feature = "checkpoint-rocksdb")]
+#[derive(Parser, Debug)]
+pub struct CheckpointOption {
+    /// Directory of the rocksdb to store the checkpoints of tasks. Checkpoints are disabled if not set.
+    #[clap(long, env = "TOY_ACTOR_CHECKPOINT_PATH", value_hint = ValueHint::DirPath)]
+    pub checkpoint_path: Option<PathBuf>,
+    #[clap(
+        long,
+        env = "TOY_ACTOR_CHECKPOINT_INTERVAL",
+        default_value = "10000"
+    )]
+    pub checkpoint_interval_mills: u64,
 }
 
 #[derive(Parser, Debug)]
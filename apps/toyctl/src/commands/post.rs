@@ -5,7 +5,9 @@ use std::io::Write;
 use toy::api_client::client::GraphClient;
 use toy::api_client::http::HttpApiClient;
 use toy::api_client::toy_api::common::PostOption;
+use toy::api_client::toy_api::graph::DispatchRequest;
 use toy::api_client::ApiClient;
+use toy::core::data::{Map, Value};
 
 pub async fn execute<W>(c: PostCommand, client: HttpApiClient, writer: W) -> Result<(), Error>
 where
@@ -14,10 +16,21 @@ where
     let PostCommand { resource } = c;
 
     match resource {
-        PostResources::Graphs(c) => client
-            .graph()
-            .dispatch(c.name, PostOption::new())
-            .await
-            .write(writer, false),
+        PostResources::Graphs(c) => {
+            let params = c
+                .params
+                .into_iter()
+                .map(|(k, v)| (k, Value::from(v)))
+                .collect::<Map<_, _>>();
+            let request = match c.resume {
+                Some(id) => DispatchRequest::new(params).with_resume(id),
+                None => DispatchRequest::new(params),
+            };
+            client
+                .graph()
+                .dispatch(c.name, request, PostOption::new())
+                .await
+                .write(writer, false)
+        }
     }
 }
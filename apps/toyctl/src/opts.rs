@@ -1,5 +1,6 @@
 use clap::{Parser, ValueHint};
 use std::path::PathBuf;
+use toy::core::task::TaskId;
 
 #[derive(Parser, Debug)]
 pub struct LogOption {
@@ -119,6 +120,23 @@ pub enum PostResources {
 pub struct PostResourceCommand {
     #[clap(short, long)]
     pub name: String,
+    /// Params of graph, "key=value". Repeatable.
+    #[clap(short, long = "param", value_parser = parse_param)]
+    pub params: Vec<(String, String)>,
+    /// Task id to resume from its last checkpoint.
+    #[clap(short, long, value_parser = parse_task_id)]
+    pub resume: Option<TaskId>,
+}
+
+fn parse_param(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((k, v)) if !k.is_empty() => Ok((k.to_string(), v.to_string())),
+        _ => Err(format!("invalid param \"{}\", expected \"key=value\".", s)),
+    }
+}
+
+fn parse_task_id(s: &str) -> Result<TaskId, String> {
+    TaskId::parse_str(s).map_err(|_| format!("invalid task id \"{}\".", s))
 }
 
 #[derive(Debug, Parser)]
@@ -17,8 +17,10 @@ use std::time::Duration;
 use tokio::sync::Mutex;
 use toy_api::common::{PostOption, PutOption};
 use toy_api::services::ServiceSpec;
+use toy_api::task::StopMode;
 use toy_api_client::client::{ActorClient, ServiceClient, TaskClient};
 use toy_api_client::{ApiClient, NoopApiClient};
+use toy_core::checkpoint::Checkpoints;
 use toy_core::data::Frame;
 use toy_core::executor::{TaskExecutor, TaskExecutorFactory};
 use toy_core::graph::Graph;
@@ -113,7 +115,7 @@ where
         tracing::info!(name=?self.ctx.name(), "oneshot actor");
 
         let id = TaskId::new();
-        let ctx = TaskContext::new(id, g);
+        let ctx = with_checkpoints(TaskContext::new(id, g), &self.config);
         let (e, _) = TF::new(ctx.clone());
         e.run(&self.app, Frame::default())
             .await
@@ -138,11 +140,13 @@ where
 
                     let app = Arc::clone(&self.app);
                     let ctx = self.ctx.clone();
+                    let config = self.config.clone();
                     let _ = toy_rt::spawn_named(
                         async move {
                             let tasks = ctx.tasks();
                             let events = metrics::context::events_by_task_id(id).await;
                             let task_ctx = TaskContext::with_parts(id, g, events);
+                            let task_ctx = with_checkpoints(task_ctx, &config);
 
                             let (executor, tx_signal) = TF::new(task_ctx.clone());
                             let task = RunningTask::new(&task_ctx, tx_signal);
@@ -197,11 +201,30 @@ where
                     };
                     let _ = tx.send(r).await;
                 }
-                Request::Stop(uuid) => {
+                Request::Stop(uuid, req) => {
                     let tasks = self.ctx.tasks();
                     let mut tasks = tasks.lock().await;
                     if let Some(t) = tasks.get_mut(&uuid) {
-                        send_stop_signal(t).await;
+                        match req.mode() {
+                            StopMode::Stop => send_stop_signal(t).await,
+                            StopMode::Drain => {
+                                send_drain_signal(t).await;
+                                if let Some(deadline) = req.deadline_mills() {
+                                    let tasks = self.ctx.tasks();
+                                    toy_rt::spawn_named(
+                                        async move {
+                                            toy_rt::sleep(deadline).await;
+                                            let mut tasks = tasks.lock().await;
+                                            if let Some(t) = tasks.get_mut(&uuid) {
+                                                tracing::warn!(uuid = ?uuid, deadline, "drain deadline exceeded.");
+                                                send_stop_signal(t).await;
+                                            }
+                                        },
+                                        "actor-drainDeadline",
+                                    );
+                                }
+                            }
+                        }
                     }
                 }
                 Request::Services(tx) => {
@@ -367,6 +390,16 @@ where
     }
 }
 
+/// The task with the same id resumes from the last checkpoint.
+fn with_checkpoints<Config: ActorConfig>(ctx: TaskContext, config: &Config) -> TaskContext {
+    match config.checkpoint_store() {
+        Some(store) => {
+            ctx.with_checkpoints(Checkpoints::new(store, config.checkpoint_interval_mills()))
+        }
+        None => ctx,
+    }
+}
+
 async fn send_stop_signal(task: &mut RunningTask) {
     for (uri, tx) in task.tx_signal().iter_mut() {
         for p in tx.ports() {
@@ -382,6 +415,19 @@ async fn send_stop_signal(task: &mut RunningTask) {
     tracing::info!(uuid = ?task.id(), "send stop signal to task.");
 }
 
+/// Stop only the first nodes of task, the rest of nodes finish after in-flight frames.
+async fn send_drain_signal(task: &mut RunningTask) {
+    for (uri, tx) in task.tx_signal().starters_mut() {
+        let r = tx.send_ok(Frame::drain()).await;
+        tracing::debug!(
+            uri = ?uri,
+            ret = ?r,
+            "send drain signal.",
+        );
+    }
+    tracing::info!(uuid = ?task.id(), "send drain signal to task.");
+}
+
 impl<TF, P, C, Config> std::fmt::Debug for Actor<TF, P, C, Config> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mode = if self.ctx.client().is_none() {
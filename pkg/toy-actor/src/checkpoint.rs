@@ -0,0 +1,57 @@
+//! `CheckpointStore` on the local rocksdb.
+
+use async_trait::async_trait;
+use std::path::Path;
+use toy_core::checkpoint::CheckpointStore;
+use toy_core::data::{Map, Value};
+use toy_core::error::ServiceError;
+use toy_core::task::TaskId;
+use toy_rocksdb::{Client, RocksError};
+
+const CHECKPOINT_CF: &str = "checkpoints";
+
+/// Checkpoints are stored as json, key is the task id.
+#[derive(Debug)]
+pub struct RocksCheckpointStore {
+    client: Client,
+}
+
+impl RocksCheckpointStore {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, RocksError> {
+        Ok(Self {
+            client: Client::new(path, CHECKPOINT_CF)?,
+        })
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for RocksCheckpointStore {
+    async fn load(&self, id: TaskId) -> Result<Option<Map<String, Value>>, ServiceError> {
+        let bytes = self
+            .client
+            .get(id.to_string())
+            .map_err(ServiceError::error)?;
+        match bytes {
+            Some(bytes) => match toy_pack_json::unpack::<Value>(&bytes) {
+                Ok(Value::Map(states)) => Ok(Some(states)),
+                Ok(_) => Err(ServiceError::error("invalid checkpoint, must be map type.")),
+                Err(e) => Err(ServiceError::error(e)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    async fn save(&self, id: TaskId, states: &Map<String, Value>) -> Result<(), ServiceError> {
+        let bytes =
+            toy_pack_json::pack(&Value::Map(states.clone())).map_err(ServiceError::error)?;
+        self.client
+            .put(id.to_string(), bytes)
+            .map_err(ServiceError::error)
+    }
+
+    async fn remove(&self, id: TaskId) -> Result<(), ServiceError> {
+        self.client
+            .delete(id.to_string())
+            .map_err(ServiceError::error)
+    }
+}
@@ -1,4 +1,6 @@
 use crate::exporters::{EventExporter, MetricsExporter};
+use std::sync::Arc;
+use toy_core::checkpoint::{CheckpointStore, DEFAULT_CHECKPOINT_INTERVAL_MILLS};
 
 pub trait ActorConfig {
     type EventExporter: EventExporter;
@@ -21,4 +23,13 @@ pub trait ActorConfig {
     fn metrics_exporter(&self) -> Self::MetricsExporter;
 
     fn event_exporter(&self) -> Self::EventExporter;
+
+    /// Store of task checkpoints. Checkpoints are disabled if `None`.
+    fn checkpoint_store(&self) -> Option<Arc<dyn CheckpointStore>> {
+        None
+    }
+
+    fn checkpoint_interval_mills(&self) -> u64 {
+        DEFAULT_CHECKPOINT_INTERVAL_MILLS
+    }
 }
@@ -5,7 +5,7 @@ use crate::{ActorError, Request, RunTaskResponse, TaskResponse};
 use chrono::Utc;
 use toy_api::common::{FindOption, ListOption, ListOptionLike, PostOption};
 use toy_api::services::{ServiceSpec, ServiceSpecListOption};
-use toy_api::task::{AllocateResponse, PendingTask};
+use toy_api::task::{AllocateResponse, FinishResponse, PendingTask, StopRequest};
 use toy_api_client::ApiClient;
 use toy_api_http_common::axum::extract::{Path, Query, State};
 use toy_api_http_common::axum::http::StatusCode;
@@ -117,6 +117,30 @@ where
     }
 }
 
+pub async fn tasks_stop<C>(
+    State(mut ctx): State<ActorContext<C>>,
+    Path(key): Path<String>,
+    Query(opt): Query<PostOption>,
+    request: Bytes,
+) -> Result<impl IntoResponse, ActorError>
+where
+    C: ApiClient + Clone + Send + Sync + 'static,
+{
+    let format = opt.format();
+    match TaskId::parse_str(&key) {
+        Ok(id) => {
+            let v = toy_api_http_common::codec::decode::<_, StopRequest>(request, format)?;
+            let _ = ctx.tx_mut().send_ok(Request::Stop(id, v)).await;
+            Ok(toy_api_http_common::reply::into_response(
+                &FinishResponse::ok(id),
+                format,
+                None,
+            ))
+        }
+        Err(_) => Err(ActorError::task_id_invalid_format(key)),
+    }
+}
+
 pub async fn tasks_list<C>(
     State(mut ctx): State<ActorContext<C>>,
     Query(opt): Query<ListOption>,
@@ -1,4 +1,6 @@
 mod actor;
+#[cfg(feature = "rocksdb")]
+mod checkpoint;
 mod config;
 mod context;
 mod error;
@@ -11,6 +13,8 @@ pub mod exporters;
 
 pub use self::actor::Actor;
 pub use self::actor::{local, subscribe};
+#[cfg(feature = "rocksdb")]
+pub use self::checkpoint::RocksCheckpointStore;
 pub use self::config::ActorConfig;
 pub use self::error::ActorError;
 pub use self::msg::{Request, Response, RunTaskResponse, TaskResponse};
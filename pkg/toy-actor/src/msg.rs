@@ -1,5 +1,6 @@
 use std::time::SystemTime;
 use serde::Serialize;
+use toy_api::task::StopRequest;
 use toy_core::data::Value;
 use toy_core::mpsc::OutgoingMessage;
 use toy_core::oneshot;
@@ -12,7 +13,7 @@ pub enum Request {
     RunTask(TaskId, Graph, oneshot::Outgoing<RunTaskResponse>),
     Tasks(oneshot::Outgoing<Vec<TaskResponse>>),
     Task(TaskId, oneshot::Outgoing<Option<TaskResponse>>),
-    Stop(TaskId),
+    Stop(TaskId, StopRequest),
     Services(oneshot::Outgoing<Response>),
     Shutdown,
 }
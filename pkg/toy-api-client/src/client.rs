@@ -5,12 +5,14 @@ use async_trait::async_trait;
 use toy_api::actors::{Actor, ActorBeatResponse, ActorList, ActorListOption};
 use toy_api::common;
 use toy_api::common::{CommonPostResponse, CommonPutResponse};
-use toy_api::graph::{Graph, GraphList};
+use toy_api::graph::{DispatchRequest, Graph, GraphList};
 use toy_api::metrics::Metrics;
 use toy_api::role::{Role, RoleList};
 use toy_api::role_binding::{RoleBinding, RoleBindingList};
 use toy_api::services::{ServiceSpec, ServiceSpecList, ServiceSpecListOption};
-use toy_api::task::{self, FinishResponse, PendingResult, TaskEvent, TaskEventList, TaskList};
+use toy_api::task::{
+    self, FinishResponse, PendingResult, StopRequest, TaskEvent, TaskEventList, TaskList,
+};
 use toy_core::task::TaskId;
 
 /// Composit All Api Client
@@ -64,9 +66,11 @@ pub trait GraphClient: Send + Sync {
 
     async fn delete(&self, key: String, opt: common::DeleteOption) -> Result<(), ApiClientError>;
 
+    /// Dispatch the graph by `/graphs/{key}/dispatch`, with the params of graph.
     async fn dispatch(
         &self,
         key: String,
+        v: DispatchRequest,
         opt: common::PostOption,
     ) -> Result<PendingResult, ApiClientError>;
 }
@@ -79,6 +83,14 @@ pub trait TaskClient: Send + Sync {
         opt: common::PostOption,
     ) -> Result<FinishResponse, ApiClientError>;
 
+    /// Stop the running task by `/tasks/{key}/finish`, with stop mode.
+    async fn stop(
+        &self,
+        key: TaskId,
+        v: StopRequest,
+        opt: common::PostOption,
+    ) -> Result<FinishResponse, ApiClientError>;
+
     async fn list(&self, opt: task::TaskListOption) -> Result<TaskList, ApiClientError>;
 
     async fn find_event(
@@ -5,7 +5,7 @@ use std::sync::Arc;
 use toy_api::common::{
     CommonPutResponse, DeleteOption, FindOption, ListOption, PostOption, PutOption,
 };
-use toy_api::graph::{Graph, GraphList};
+use toy_api::graph::{DispatchRequest, Graph, GraphList};
 use toy_api::task::PendingResult;
 use toy_api_http_common::{auth::Auth, request};
 use toy_h::HttpClient;
@@ -77,9 +77,11 @@ where
     async fn dispatch(
         &self,
         key: String,
+        v: DispatchRequest,
         opt: PostOption,
     ) -> Result<PendingResult, ApiClientError> {
-        request::post(&self.inner, Some(&self.auth), &self.root, PATH, &key, opt)
+        let path = format!("{}/{}/dispatch", PATH, key);
+        request::post(&self.inner, Some(&self.auth), &self.root, &path, &v, opt)
             .await
             .map_err(|e| e.into())
     }
@@ -3,7 +3,9 @@ use crate::error::ApiClientError;
 use async_trait::async_trait;
 use std::sync::Arc;
 use toy_api::common::{CommonPostResponse, FindOption, PostOption};
-use toy_api::task::{FinishResponse, TaskEvent, TaskEventList, TaskList, TaskListOption};
+use toy_api::task::{
+    FinishResponse, StopRequest, TaskEvent, TaskEventList, TaskList, TaskListOption,
+};
 use toy_api_http_common::{auth::Auth, request};
 use toy_core::prelude::TaskId;
 use toy_h::{HttpClient, RequestBuilder, Uri};
@@ -42,6 +44,18 @@ where
             .map_err(|e| e.into())
     }
 
+    async fn stop(
+        &self,
+        key: TaskId,
+        v: StopRequest,
+        opt: PostOption,
+    ) -> Result<FinishResponse, ApiClientError> {
+        let path = format!("{}/{}/finish", PATH, key);
+        request::post(&self.inner, Some(&self.auth), &self.root, &path, &v, opt)
+            .await
+            .map_err(|e| e.into())
+    }
+
     async fn list(&self, opt: TaskListOption) -> Result<TaskList, ApiClientError> {
         request::list(&self.inner, Some(&self.auth), &self.root, PATH, opt)
             .await
@@ -13,7 +13,7 @@ use toy_api::metrics::Metrics;
 use toy_api::role::{Role, RoleList};
 use toy_api::role_binding::{RoleBinding, RoleBindingList};
 use toy_api::services::{ServiceSpec, ServiceSpecList, ServiceSpecListOption};
-use toy_api::task::{FinishResponse, PendingResult, TaskEvent, TaskListOption};
+use toy_api::task::{FinishResponse, PendingResult, StopRequest, TaskEvent, TaskListOption};
 use toy_core::prelude::TaskId;
 
 #[derive(Clone)]
@@ -98,6 +98,7 @@ impl GraphClient for NoopApiClient {
     async fn dispatch(
         &self,
         _key: String,
+        _v: toy_api::graph::DispatchRequest,
         _opt: PostOption,
     ) -> Result<PendingResult, ApiClientError> {
         unimplemented!()
@@ -114,6 +115,15 @@ impl TaskClient for NoopApiClient {
         unimplemented!()
     }
 
+    async fn stop(
+        &self,
+        _key: TaskId,
+        _v: StopRequest,
+        _opt: PostOption,
+    ) -> Result<FinishResponse, ApiClientError> {
+        unimplemented!()
+    }
+
     async fn list(&self, _opt: TaskListOption) -> Result<toy_api::task::TaskList, ApiClientError> {
         unimplemented!()
     }
@@ -85,6 +85,9 @@ pub enum ApiError {
     #[error("not found. key:{key}")]
     NotFound { key: String },
 
+    #[error("conflict. {inner}")]
+    Conflict { inner: String },
+
     #[error("{:?}", inner)]
     Error { inner: String },
 }
@@ -108,6 +111,7 @@ impl ApiError {
             ApiError::InvalidField { .. } => StatusCode::BAD_REQUEST,
             ApiError::AuthorizationFailed { .. } => StatusCode::FORBIDDEN,
             ApiError::NotFound { .. } => StatusCode::NOT_FOUND,
+            ApiError::Conflict { .. } => StatusCode::CONFLICT,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -196,6 +200,10 @@ impl ApiError {
     pub fn not_found(key: impl Into<String>) -> ApiError {
         ApiError::NotFound { key: key.into() }
     }
+
+    pub fn conflict(msg: impl Into<String>) -> ApiError {
+        ApiError::Conflict { inner: msg.into() }
+    }
 }
 
 impl IntoResponse for ApiError {
@@ -96,9 +96,10 @@ pub async fn dispatch<S>(
     State(state): State<WrappedState<S>>,
     Path(key): Path<String>,
     Query(api_opt): Query<PostOption>,
+    request: Bytes,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: ServerState,
 {
-    handlers::dispatch(ctx, state.raw().kv_store(), key, api_opt).await
+    handlers::dispatch(ctx, state.raw().kv_store(), key, api_opt, request).await
 }
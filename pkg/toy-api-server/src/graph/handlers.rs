@@ -1,13 +1,15 @@
 use crate::common::constants;
 use crate::context::Context;
+use crate::graph::validator;
 use crate::store::kv;
-use crate::store::kv::{Find, KvStore, Put, PutOption, PutResult};
+use crate::store::kv::{Find, KvStore, Put, PutOption, PutResult, Update, UpdateResult};
 use crate::ApiError;
 use toy_api::common::{self as api_common};
-use toy_api::graph::Graph;
+use toy_api::graph::{DispatchRequest, Graph};
 use toy_api::task::{PendingResult, PendingTask};
 use toy_api_http_common::axum::response::IntoResponse;
-use toy_api_http_common::reply;
+use toy_api_http_common::bytes::Bytes;
+use toy_api_http_common::{codec, reply};
 use toy_core::prelude::TaskId;
 use toy_h::HttpClient;
 
@@ -16,12 +18,19 @@ pub async fn dispatch<T>(
     store: &impl KvStore<T>,
     graph_name: String,
     opt: api_common::PostOption,
+    request: Bytes,
 ) -> Result<impl IntoResponse, ApiError>
 where
     T: HttpClient,
 {
     tracing::debug!("handle: {:?}", ctx);
 
+    let dispatch = if request.is_empty() {
+        DispatchRequest::default()
+    } else {
+        codec::decode::<_, Option<DispatchRequest>>(request, opt.format())?.unwrap_or_default()
+    };
+
     let graph = match store
         .ops()
         .find::<Graph>(
@@ -41,25 +50,48 @@ where
         }
     };
 
-    let id = TaskId::new();
+    let graph = toy_core::data::pack(graph)?;
+    let graph = validator::expand_includes(store, &graph).await?;
+    let graph = toy_core::graph::bind_params(&graph, dispatch.params())
+        .map_err(ApiError::validation_failed)?;
+    let graph = toy_core::data::unpack::<Graph>(&graph)?;
+
+    // resumed task is dispatched again with the same id, the actor restores its checkpoints.
+    let id = dispatch.resume().unwrap_or_else(TaskId::new);
     let pending = PendingTask::new(id, graph);
     let key = constants::pending_key(id);
-    match store
-        .ops()
-        .put(
-            store.con().unwrap(),
-            key,
-            pending,
-            PutOption::new().with_create_only(),
-        )
-        .await
-    {
-        Ok(PutResult::Create) => Ok(reply::into_response(
-            &(PendingResult::from_id(id)),
-            opt.format(),
-            opt.indent(),
-        )),
-        Ok(PutResult::Update(_)) => unreachable!(),
-        Err(e) => Err(ApiError::store_operation_failed(e)),
+    match dispatch.resume() {
+        // only the task not running is resumed, not to run the task twice.
+        Some(_) => {
+            let f = move |v: PendingTask| v.is_resumable().then_some(pending);
+            match store.ops().update(store.con().unwrap(), key, f).await {
+                Ok(UpdateResult::Update(_)) => (),
+                Ok(UpdateResult::NotFound) => return Err(ApiError::not_found(id.to_string())),
+                Ok(UpdateResult::None) => {
+                    return Err(ApiError::conflict(format!(
+                        "task is not finished, can not resume. id:{}",
+                        id
+                    )))
+                }
+                Err(e) => return Err(ApiError::store_operation_failed(e)),
+            }
+        }
+        None => {
+            let put_opt = PutOption::new().with_create_only();
+            match store
+                .ops()
+                .put(store.con().unwrap(), key.clone(), pending, put_opt)
+                .await
+            {
+                Ok(PutResult::Create) => (),
+                Ok(PutResult::Update(_)) => return Err(ApiError::allready_exists(key)),
+                Err(e) => return Err(ApiError::store_operation_failed(e)),
+            }
+        }
     }
+    Ok(reply::into_response(
+        &(PendingResult::from_id(id)),
+        opt.format(),
+        opt.indent(),
+    ))
 }
@@ -1,9 +1,13 @@
+use crate::common::constants;
 use crate::common::validator::Validator;
 use crate::context::Context;
-use crate::store::kv::{Find, FindOption, KvStore};
+use crate::store::kv::{Find, FindOption, KvStore, List, ListOption};
 use crate::ApiError;
 use async_trait::async_trait;
+use std::collections::HashMap;
 use toy_api::graph::Graph;
+use toy_api::services::ServiceSpec;
+use toy_core::data::Value;
 use toy_h::HttpClient;
 
 pub struct GraphPutValidator;
@@ -44,13 +48,110 @@ where
             )));
         }
 
-        // TODO: service exists check
+        // deserialize check, include graphs are expanded before check
+        let value = toy_core::data::pack(v.clone())?;
+        toy_core::graph::check_params(&value).map_err(ApiError::validation_failed)?;
+        let value = expand_includes(store, &value).await?;
+        let graph = toy_core::graph::Graph::from(value).map_err(ApiError::validation_failed)?;
 
-        // TODO: service schema check
+        // service exists check, wires and ports check
+        let specs = find_service_specs(store).await?;
+        graph
+            .validate_with(|tp| specs.get(tp.full_name()).map(|x| x.port_type().clone()))
+            .map_err(ApiError::validation_failed)?;
+
+        // service schema check, skip the config which has params. (bound at dispatch time)
+        let errors = graph
+            .iter()
+            .filter(|node| !toy_core::graph::has_placeholder(&node.config()))
+            .filter_map(|node| {
+                let schema = specs.get(node.service_type().full_name())?.schema()?;
+                schema
+                    .validate(&node.config())
+                    .err()
+                    .map(|e| (node.uri(), e))
+            })
+            .flat_map(|(uri, errors)| {
+                errors.into_iter().map(move |e| {
+                    let path = if e.path().is_empty() {
+                        "config".to_string()
+                    } else {
+                        format!("config.{}", e.path())
+                    };
+                    format!("[uri:{}, path:{}, {}]", uri, path, e.message())
+                })
+            })
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            return Err(ApiError::validation_failed(format!(
+                "invalid service config. {}",
+                errors.join(", ")
+            )));
+        }
 
-        // deserialize check
-        let value = toy_core::data::pack(v.clone())?;
-        let _ = toy_core::graph::Graph::from(value).map_err(|e| ApiError::validation_failed(e))?;
         Ok(v)
     }
 }
+
+/// Expand `toy.graph.Include` nodes by the graphs in the store.
+pub(crate) async fn expand_includes<H, Store>(store: &Store, v: &Value) -> Result<Value, ApiError>
+where
+    H: HttpClient,
+    Store: KvStore<H>,
+{
+    let graphs = match store
+        .ops()
+        .list::<Graph>(
+            store.con().unwrap(),
+            constants::GRAPHS_KEY_PREFIX.to_string(),
+            ListOption::new(),
+        )
+        .await
+    {
+        Ok(v) => v
+            .into_iter()
+            .map(|x| {
+                let graph = x.into_value();
+                (graph.name().to_owned(), graph)
+            })
+            .collect::<HashMap<_, _>>(),
+        Err(e) => {
+            tracing::error!("error:{:?}", e);
+            return Err(ApiError::store_operation_failed(e));
+        }
+    };
+    toy_core::graph::expand_includes(v, |name| {
+        graphs.get(name).and_then(|x| toy_core::data::pack(x).ok())
+    })
+    .map_err(ApiError::validation_failed)
+}
+
+async fn find_service_specs<H, Store>(
+    store: &Store,
+) -> Result<HashMap<String, ServiceSpec>, ApiError>
+where
+    H: HttpClient,
+    Store: KvStore<H>,
+{
+    match store
+        .ops()
+        .list::<ServiceSpec>(
+            store.con().unwrap(),
+            constants::SERVICES_KEY_PREFIX.to_string(),
+            ListOption::new(),
+        )
+        .await
+    {
+        Ok(v) => Ok(v
+            .into_iter()
+            .map(|x| {
+                let spec = x.into_value();
+                (spec.service_type().full_name().to_owned(), spec)
+            })
+            .collect()),
+        Err(e) => {
+            tracing::error!("error:{:?}", e);
+            Err(ApiError::store_operation_failed(e))
+        }
+    }
+}
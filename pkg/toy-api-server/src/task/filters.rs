@@ -33,11 +33,20 @@ pub async fn finish<S>(
     State(state): State<WrappedState<S>>,
     Path(key): Path<String>,
     Query(api_opt): Query<PostOption>,
+    request: Bytes,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: ServerState,
 {
-    handlers::finish(ctx, state.raw().kv_store(), key, api_opt).await
+    handlers::finish(
+        ctx,
+        state.raw().kv_store(),
+        state.raw().client(),
+        key,
+        api_opt,
+        request,
+    )
+    .await
 }
 
 pub async fn list_task<S>(
@@ -1,15 +1,19 @@
 use crate::common::constants;
 use crate::context::Context;
-use crate::store::kv::{KvStore, Update, UpdateResult};
+use crate::store::kv::{Find, FindOption, KvStore, Update, UpdateResult};
 use crate::store::task_event::{
     CreateOption, ListEventOption, ListTaskOption, TaskEventStore, TaskEventStoreOps,
 };
 use crate::ApiError;
 use chrono::{DateTime, Duration, Utc};
-use toy_api::common::{self as api_common, CommonPostResponse, ListOptionLike};
+use toy_api::actors::Actor;
+use toy_api::common::{self as api_common, CommonPostResponse, Format, ListOptionLike};
 use toy_api::selection::selector::Predicate;
 use toy_api::selection::Operator;
-use toy_api::task::{FinishResponse, PendingTask, TaskEvent, TaskEventListOption, TaskListOption};
+use toy_api::task::{
+    FinishResponse, PendingStatus, PendingTask, StopRequest, TaskEvent, TaskEventListOption,
+    TaskListOption,
+};
 use toy_api_http_common::axum::http::StatusCode;
 use toy_api_http_common::axum::response::IntoResponse;
 use toy_api_http_common::bytes::Bytes;
@@ -17,11 +21,18 @@ use toy_api_http_common::{codec, reply};
 use toy_core::task::TaskId;
 use toy_h::HttpClient;
 
+/// Finish task.
+///
+/// If the request has [`StopRequest`] and the task is allocated to an actor,
+/// the request is forwarded to the actor, and the actor finishes the task after it stopped.
+/// Otherwise, only the status of the task is changed.
 pub async fn finish<T>(
     ctx: Context,
     store: &impl KvStore<T>,
+    client: &T,
     key: String,
     api_opt: api_common::PostOption,
+    request: Bytes,
 ) -> Result<impl IntoResponse, ApiError>
 where
     T: HttpClient,
@@ -36,6 +47,18 @@ where
 
     let format = api_opt.format();
     let key = constants::generate_key(constants::PENDINGS_KEY_PREFIX, key);
+
+    let stop = if request.is_empty() {
+        None
+    } else {
+        codec::decode::<_, Option<StopRequest>>(request, format)?
+    };
+    if let Some(stop) = stop {
+        if let Some(r) = stop_task(store, client, id, &key, stop).await? {
+            return Ok(reply::into_response(&r, format, None));
+        }
+    }
+
     let now = Utc::now();
     let f = |v: PendingTask| Some(v.finished(now));
     match store.ops().update(store.con().unwrap(), key, f).await {
@@ -55,6 +78,66 @@ where
     }
 }
 
+/// Send stop request to the actor running the task.
+/// Returns `None` if the task is not allocated to any actor.
+async fn stop_task<T>(
+    store: &impl KvStore<T>,
+    client: &T,
+    id: TaskId,
+    key: &str,
+    stop: StopRequest,
+) -> Result<Option<FinishResponse>, ApiError>
+where
+    T: HttpClient,
+{
+    let task = match store
+        .ops()
+        .find::<PendingTask>(store.con().unwrap(), key.to_string(), FindOption::new())
+        .await
+    {
+        Ok(Some(v)) => v.into_value(),
+        Ok(None) => return Ok(Some(FinishResponse::not_found(id))),
+        Err(e) => {
+            tracing::error!("error:{:?}", e);
+            return Err(ApiError::store_operation_failed(e));
+        }
+    };
+    let name = match (task.status(), task.allocated_actor()) {
+        (PendingStatus::Allocated, Some(name)) => name,
+        _ => return Ok(None),
+    };
+    let actor = match store
+        .ops()
+        .find::<Actor>(
+            store.con().unwrap(),
+            constants::generate_key(constants::ACTORS_KEY_PREFIX, name),
+            FindOption::new(),
+        )
+        .await
+    {
+        Ok(Some(v)) => v.into_value(),
+        Ok(None) => return Err(ApiError::not_found(name)),
+        Err(e) => {
+            tracing::error!("error:{:?}", e);
+            return Err(ApiError::store_operation_failed(e));
+        }
+    };
+
+    tracing::info!(?id, mode = ?stop.mode(), "request stop task to {}:{}", name, actor.addr().port());
+
+    toy_api_http_common::request::post(
+        client,
+        None,
+        &format!("https://{}:{}", name, actor.addr().port()),
+        &format!("tasks/{}/stop", id),
+        &stop,
+        api_common::PostOption::new().with_format(Format::MessagePack),
+    )
+    .await
+    .map(Some)
+    .map_err(|e| e.into())
+}
+
 pub async fn list_task<T>(
     ctx: Context,
     opt: TaskListOption,
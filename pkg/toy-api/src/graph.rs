@@ -3,7 +3,10 @@
 use crate::common::{KVObject, Label, ListObject, ListOption, ListOptionLike, SelectionCandidate};
 use crate::selection::candidate::Candidates;
 use serde::{Deserialize, Serialize};
-use toy_core::prelude::Value;
+use toy_core::data::Map;
+use toy_core::graph::{ErrorPolicy, GraphParam, Parallelism};
+use toy_core::mpsc::ChannelConfig;
+use toy_core::prelude::{TaskId, Value};
 use toy_core::registry::PortType;
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -17,6 +20,8 @@ pub struct Graph {
     name: String,
     #[serde(default)]
     disabled: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    params: Vec<GraphParam>,
     services: Vec<GraphNode>,
     #[serde(default = "Vec::new")]
     labels: Vec<Label>,
@@ -31,7 +36,38 @@ pub struct GraphNode {
     position: Position,
     port_type: Option<PortType>,
     config: Value,
-    wires: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    channel: Option<ChannelConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    on_error: Option<ErrorPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parallelism: Option<Parallelism>,
+    wires: Vec<GraphWire>,
+}
+
+/// Wire to downstream node. Uri only (`"uri"` or `"uri:port"`), or uri with the input port and the config of channel.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GraphWire {
+    Uri(String),
+    Detail {
+        to: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        port: Option<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        channel: Option<ChannelConfig>,
+    },
+}
+
+/// Request body of `/graphs/{key}/dispatch`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DispatchRequest {
+    #[serde(default)]
+    params: Map<String, Value>,
+    /// Id of the task to resume from its last checkpoint. A new task is created if `None`.
+    /// Only the finished or allocate failed task can be resumed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resume: Option<TaskId>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -76,11 +112,17 @@ impl Graph {
         Self {
             name: name.into(),
             disabled,
+            params: Vec::new(),
             services,
             labels,
         }
     }
 
+    pub fn with_params(mut self, params: Vec<GraphParam>) -> Self {
+        self.params = params;
+        self
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -89,6 +131,10 @@ impl Graph {
         self.disabled
     }
 
+    pub fn params(&self) -> &[GraphParam] {
+        &self.params
+    }
+
     pub fn services(&self) -> &[GraphNode] {
         &self.services
     }
@@ -105,7 +151,7 @@ impl GraphNode {
         position: Position,
         port_type: Option<PortType>,
         config: Value,
-        wires: Vec<String>,
+        wires: Vec<GraphWire>,
     ) -> Self {
         Self {
             tp: tp.into(),
@@ -113,9 +159,66 @@ impl GraphNode {
             position,
             port_type,
             config,
+            channel: None,
+            on_error: None,
+            parallelism: None,
             wires,
         }
     }
+
+    pub fn with_channel(mut self, channel: ChannelConfig) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    pub fn with_error_policy(mut self, on_error: ErrorPolicy) -> Self {
+        self.on_error = Some(on_error);
+        self
+    }
+
+    pub fn with_parallelism(mut self, parallelism: Parallelism) -> Self {
+        self.parallelism = Some(parallelism);
+        self
+    }
+}
+
+impl GraphWire {
+    pub fn to(&self) -> &str {
+        match self {
+            GraphWire::Uri(to) => to,
+            GraphWire::Detail { to, .. } => to,
+        }
+    }
+}
+
+impl From<String> for GraphWire {
+    fn from(v: String) -> Self {
+        GraphWire::Uri(v)
+    }
+}
+
+impl DispatchRequest {
+    pub fn new(params: Map<String, Value>) -> Self {
+        Self {
+            params,
+            resume: None,
+        }
+    }
+
+    pub fn with_resume(self, id: TaskId) -> Self {
+        Self {
+            resume: Some(id),
+            ..self
+        }
+    }
+
+    pub fn params(&self) -> &Map<String, Value> {
+        &self.params
+    }
+
+    pub fn resume(&self) -> Option<TaskId> {
+        self.resume
+    }
 }
 
 impl GraphList {
@@ -56,6 +56,22 @@ pub enum AllocateStatus {
     None,
 }
 
+/// How to stop a running task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopMode {
+    /// Stop all nodes immediately. In-flight data is dropped.
+    Stop,
+    /// Stop sources, and finish after in-flight data reaches sinks.
+    Drain,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopRequest {
+    mode: StopMode,
+    /// for `Drain`. if the task is still running after this time, stop it immediately.
+    deadline_mills: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum FinishResponse {
     Ok { id: TaskId },
@@ -125,6 +141,10 @@ impl PendingTask {
         &self.graph
     }
 
+    pub fn allocated_actor(&self) -> Option<&ActorName> {
+        self.allocated_actor.as_ref()
+    }
+
     pub fn allocate<S: Into<ActorName>>(self, name: S, allocated_at: DateTime<Utc>) -> Self {
         Self {
             status: PendingStatus::Allocated,
@@ -150,6 +170,14 @@ impl PendingTask {
         }
     }
 
+    /// Returns true if the task is not waiting or running, so it can be dispatched again to resume.
+    pub fn is_resumable(&self) -> bool {
+        matches!(
+            self.status,
+            PendingStatus::AllocateFailed | PendingStatus::Finished
+        )
+    }
+
     pub fn is_dispatchable(&self) -> bool {
         match self.status {
             PendingStatus::Created | PendingStatus::AllocateFailed => true,
@@ -214,6 +242,30 @@ impl AllocateResponse {
     }
 }
 
+impl StopRequest {
+    pub fn stop() -> Self {
+        Self {
+            mode: StopMode::Stop,
+            deadline_mills: None,
+        }
+    }
+
+    pub fn drain(deadline_mills: Option<u64>) -> Self {
+        Self {
+            mode: StopMode::Drain,
+            deadline_mills,
+        }
+    }
+
+    pub fn mode(&self) -> StopMode {
+        self.mode
+    }
+
+    pub fn deadline_mills(&self) -> Option<u64> {
+        self.deadline_mills
+    }
+}
+
 impl FinishResponse {
     pub fn ok(id: TaskId) -> Self {
         FinishResponse::Ok { id }
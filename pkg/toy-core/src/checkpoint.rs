@@ -0,0 +1,152 @@
+//! Checkpoints of task, to resume the task stopped halfway.
+//!
+//! Services report their resumable state (e.g. the offset of source, the counter of collect)
+//! by [`TaskContext::checkpoint`], and get the state saved by the previous run of the same task
+//! by [`TaskContext::restored`].
+//! The executor saves the states to the [`CheckpointStore`] periodically,
+//! and removes them when the task finished.
+//!
+//! The state is keyed by the uri of service, and the index of replica if the node runs in parallel.
+//!
+//! The states are saved without synchronizing with the downstream nodes,
+//! the frames in flight when the task died are not sent again on resume.
+//!
+//! [`TaskContext::checkpoint`]: crate::task::TaskContext::checkpoint
+//! [`TaskContext::restored`]: crate::task::TaskContext::restored
+
+use crate::data::{Map, Value};
+use crate::error::ServiceError;
+use crate::task::TaskId;
+use crate::Uri;
+use async_trait::async_trait;
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Default interval of saving the checkpoints.
+pub const DEFAULT_CHECKPOINT_INTERVAL_MILLS: u64 = 10_000;
+
+/// Storage of checkpoints, states of services by uri.
+#[async_trait]
+pub trait CheckpointStore: Send + Sync {
+    async fn load(&self, id: TaskId) -> Result<Option<Map<String, Value>>, ServiceError>;
+
+    async fn save(&self, id: TaskId, states: &Map<String, Value>) -> Result<(), ServiceError>;
+
+    async fn remove(&self, id: TaskId) -> Result<(), ServiceError>;
+}
+
+/// `CheckpointStore` on memory. The checkpoints are lost when the process exits.
+#[derive(Debug, Default)]
+pub struct MemoryCheckpointStore {
+    checkpoints: Mutex<HashMap<TaskId, Map<String, Value>>>,
+}
+
+/// Checkpoints of running task.
+pub struct Checkpoints {
+    store: Arc<dyn CheckpointStore>,
+    interval_mills: u64,
+    restored: OnceCell<Map<String, Value>>,
+    /// latest states, and whether it is changed after saved.
+    states: std::sync::Mutex<(Map<String, Value>, bool)>,
+}
+
+impl MemoryCheckpointStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for MemoryCheckpointStore {
+    async fn load(&self, id: TaskId) -> Result<Option<Map<String, Value>>, ServiceError> {
+        let lock = self.checkpoints.lock().await;
+        Ok(lock.get(&id).cloned())
+    }
+
+    async fn save(&self, id: TaskId, states: &Map<String, Value>) -> Result<(), ServiceError> {
+        let mut lock = self.checkpoints.lock().await;
+        lock.insert(id, states.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, id: TaskId) -> Result<(), ServiceError> {
+        let mut lock = self.checkpoints.lock().await;
+        lock.remove(&id);
+        Ok(())
+    }
+}
+
+impl Checkpoints {
+    pub fn new(store: Arc<dyn CheckpointStore>, interval_mills: u64) -> Self {
+        Self {
+            store,
+            interval_mills,
+            restored: OnceCell::new(),
+            states: std::sync::Mutex::new((Map::new(), false)),
+        }
+    }
+
+    pub fn interval_mills(&self) -> u64 {
+        self.interval_mills
+    }
+
+    /// Load the checkpoints saved by the previous run of the task.
+    /// Must be called before the services started.
+    pub async fn restore(&self, id: TaskId) -> Result<(), ServiceError> {
+        let restored = self.store.load(id).await?.unwrap_or_default();
+        if !restored.is_empty() {
+            tracing::info!(task = %id, uris = ?restored.keys().collect::<Vec<_>>(), "restore checkpoints.");
+        }
+        {
+            // the states of services which do not report again are kept.
+            let mut lock = self.states.lock().unwrap();
+            lock.0 = restored.clone();
+        }
+        let _ = self.restored.set(restored);
+        Ok(())
+    }
+
+    /// The state of service restored from the checkpoint.
+    pub fn restored(&self, uri: &Uri) -> Option<&Value> {
+        self.restored
+            .get()
+            .and_then(|x| x.get(uri.to_string().as_str()))
+    }
+
+    /// Update the state of service, saved on the next checkpoint.
+    pub fn update(&self, uri: &Uri, state: Value) {
+        let mut lock = self.states.lock().unwrap();
+        lock.0.insert(uri.to_string(), state);
+        lock.1 = true;
+    }
+
+    /// Save the states if changed after the last save.
+    pub async fn save(&self, id: TaskId) -> Result<(), ServiceError> {
+        let states = {
+            let mut lock = self.states.lock().unwrap();
+            if !lock.1 {
+                return Ok(());
+            }
+            lock.1 = false;
+            lock.0.clone()
+        };
+        self.store.save(id, &states).await
+    }
+
+    /// Remove the checkpoints of finished task.
+    pub async fn clear(&self, id: TaskId) -> Result<(), ServiceError> {
+        self.store.remove(id).await
+    }
+}
+
+impl fmt::Debug for Checkpoints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Checkpoints")
+            .field("interval_mills", &self.interval_mills)
+            .field("restored", &self.restored.get())
+            .finish()
+    }
+}
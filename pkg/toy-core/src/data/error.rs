@@ -90,3 +90,39 @@ impl serde::de::Error for DeserializeError {
         DeserializeError::error(msg)
     }
 }
+
+#[derive(Debug, Clone, PartialEq, ThisError)]
+pub enum ExprError {
+    #[error("expression syntax error at {}: {}", position, message)]
+    Syntax { position: usize, message: String },
+
+    #[error("unknown function: {}", name)]
+    UnknownFunction { name: String },
+
+    #[error(
+        "function {} expects {} arguments, but {} given.",
+        name,
+        expected,
+        actual
+    )]
+    ArgumentCount {
+        name: String,
+        expected: String,
+        actual: usize,
+    },
+
+    #[error("expression nests too deep. max depth: {}", max)]
+    TooDeep { max: usize },
+}
+
+impl ExprError {
+    pub fn syntax<T>(position: usize, msg: T) -> ExprError
+    where
+        T: Display,
+    {
+        ExprError::Syntax {
+            position,
+            message: msg.to_string(),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use super::function::Function;
+use super::parser::{BinaryOp, Node, UnaryOp};
+use crate::data::Value;
+use chrono::TimeDelta;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+pub(crate) fn eval<'a>(node: &'a Node, v: &'a Value) -> Cow<'a, Value> {
+    match node {
+        Node::Literal(x) => Cow::Borrowed(x),
+        Node::Path(path) => match v.path(path) {
+            Some(x) => Cow::Borrowed(x),
+            None => Cow::Owned(Value::None),
+        },
+        Node::Unary(op, x) => {
+            let x = eval(x, v);
+            Cow::Owned(match op {
+                UnaryOp::Not => Value::from(!is_truthy(&x)),
+                UnaryOp::Neg => match x.as_ref() {
+                    Value::Integer(x) => x.checked_neg().map(Value::from).unwrap_or(Value::None),
+                    Value::Number(x) => Value::from(-x),
+                    _ => Value::None,
+                },
+            })
+        }
+        Node::Binary(op, left, right) => binary(*op, left, right, v),
+        Node::Call(Function::If, args) => {
+            if is_truthy(&eval(&args[0], v)) {
+                eval(&args[1], v)
+            } else {
+                eval(&args[2], v)
+            }
+        }
+        Node::Call(Function::Coalesce, args) => args
+            .iter()
+            .map(|x| eval(x, v))
+            .find(|x| x.as_ref() != &Value::None)
+            .unwrap_or(Cow::Owned(Value::None)),
+        Node::Call(f, args) => {
+            let args = args
+                .iter()
+                .map(|x| eval(x, v).into_owned())
+                .collect::<Vec<_>>();
+            Cow::Owned(f.call(&args))
+        }
+    }
+}
+
+fn binary<'a>(op: BinaryOp, left: &'a Node, right: &'a Node, v: &'a Value) -> Cow<'a, Value> {
+    // short circuit.
+    match op {
+        BinaryOp::And => {
+            let r = is_truthy(&eval(left, v)) && is_truthy(&eval(right, v));
+            return Cow::Owned(Value::from(r));
+        }
+        BinaryOp::Or => {
+            let r = is_truthy(&eval(left, v)) || is_truthy(&eval(right, v));
+            return Cow::Owned(Value::from(r));
+        }
+        BinaryOp::Coalesce => {
+            let l = eval(left, v);
+            return if l.as_ref() == &Value::None {
+                eval(right, v)
+            } else {
+                l
+            };
+        }
+        _ => {}
+    }
+
+    let l = eval(left, v);
+    let r = eval(right, v);
+    let (l, r) = (l.as_ref(), r.as_ref());
+    let value = match op {
+        BinaryOp::Eq => Value::from(equals(l, r)),
+        BinaryOp::Ne => Value::from(!equals(l, r)),
+        BinaryOp::Lt => Value::from(compare(l, r) == Some(Ordering::Less)),
+        BinaryOp::Le => Value::from(matches!(
+            compare(l, r),
+            Some(Ordering::Less | Ordering::Equal)
+        )),
+        BinaryOp::Gt => Value::from(compare(l, r) == Some(Ordering::Greater)),
+        BinaryOp::Ge => Value::from(matches!(
+            compare(l, r),
+            Some(Ordering::Greater | Ordering::Equal)
+        )),
+        BinaryOp::Add => add(l, r),
+        BinaryOp::Sub => sub(l, r),
+        BinaryOp::Mul => arithmetic(l, r, i64::checked_mul, |a, b| a * b),
+        BinaryOp::Div => arithmetic(l, r, i64::checked_div, |a, b| a / b),
+        BinaryOp::Rem => arithmetic(l, r, i64::checked_rem, |a, b| a % b),
+        BinaryOp::And | BinaryOp::Or | BinaryOp::Coalesce => unreachable!(),
+    };
+    Cow::Owned(value)
+}
+
+/// `false` and null are false, and any other values are true.
+pub(crate) fn is_truthy(v: &Value) -> bool {
+    match v {
+        Value::Bool(b) => *b,
+        Value::None => false,
+        _ => true,
+    }
+}
+
+/// Integer and Number are compared as number.
+pub(crate) fn equals(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Integer(x), Value::Number(y)) | (Value::Number(y), Value::Integer(x)) => {
+            *x as f64 == *y
+        }
+        _ => a == b,
+    }
+}
+
+/// Order of the values of the same kind, `None` if they are not comparable.
+/// String is compared with TimeStamp as RFC3339.
+pub(crate) fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Integer(x), Value::Integer(y)) => Some(x.cmp(y)),
+        (Value::Integer(_) | Value::Number(_), Value::Integer(_) | Value::Number(_)) => {
+            a.parse_f64()?.partial_cmp(&b.parse_f64()?)
+        }
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        (Value::Bool(x), Value::Bool(y)) => Some(x.cmp(y)),
+        (Value::TimeStamp(_), Value::TimeStamp(_) | Value::String(_))
+        | (Value::String(_), Value::TimeStamp(_)) => {
+            Some(a.parse_timestamp()?.cmp(&b.parse_timestamp()?))
+        }
+        _ => None,
+    }
+}
+
+/// Number, concatenation of string, or TimeStamp added milliseconds.
+fn add(l: &Value, r: &Value) -> Value {
+    match (l, r) {
+        (Value::None, _) | (_, Value::None) => Value::None,
+        (Value::String(x), other) | (other, Value::String(x)) if !other.is_string() => {
+            let other = other.parse_str().unwrap_or_default();
+            if l.is_string() {
+                Value::from(format!("{}{}", x, other))
+            } else {
+                Value::from(format!("{}{}", other, x))
+            }
+        }
+        (Value::String(x), Value::String(y)) => Value::from(format!("{}{}", x, y)),
+        (Value::TimeStamp(t), Value::Integer(millis))
+        | (Value::Integer(millis), Value::TimeStamp(t)) => TimeDelta::try_milliseconds(*millis)
+            .and_then(|d| t.checked_add_signed(d))
+            .map(Value::from)
+            .unwrap_or(Value::None),
+        _ => arithmetic(l, r, i64::checked_add, |a, b| a + b),
+    }
+}
+
+/// Number, TimeStamp subtracted milliseconds, or milliseconds between TimeStamps.
+fn sub(l: &Value, r: &Value) -> Value {
+    match (l, r) {
+        (Value::TimeStamp(t), Value::Integer(millis)) => TimeDelta::try_milliseconds(*millis)
+            .and_then(|d| t.checked_sub_signed(d))
+            .map(Value::from)
+            .unwrap_or(Value::None),
+        (Value::TimeStamp(a), Value::TimeStamp(b)) => {
+            Value::from(a.signed_duration_since(b).num_milliseconds())
+        }
+        _ => arithmetic(l, r, i64::checked_sub, |a, b| a - b),
+    }
+}
+
+/// Integer if the both are Integer, otherwise Number.
+/// Null for the other types, overflow, or division by zero.
+fn arithmetic<I, F>(l: &Value, r: &Value, int: I, float: F) -> Value
+where
+    I: FnOnce(i64, i64) -> Option<i64>,
+    F: FnOnce(f64, f64) -> f64,
+{
+    match (l, r) {
+        (Value::Integer(x), Value::Integer(y)) => {
+            int(*x, *y).map(Value::from).unwrap_or(Value::None)
+        }
+        (Value::Integer(_) | Value::Number(_), Value::Integer(_) | Value::Number(_)) => {
+            match (l.parse_f64(), r.parse_f64()) {
+                (Some(x), Some(y)) => {
+                    let v = float(x, y);
+                    if v.is_finite() {
+                        Value::from(v)
+                    } else {
+                        Value::None
+                    }
+                }
+                _ => Value::None,
+            }
+        }
+        _ => Value::None,
+    }
+}
@@ -0,0 +1,317 @@
+use crate::data::error::ExprError;
+use crate::data::Value;
+use chrono::{DateTime, Utc};
+
+/// Built-in functions of the expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Function {
+    Len,
+    Lower,
+    Upper,
+    Trim,
+    Concat,
+    Contains,
+    StartsWith,
+    EndsWith,
+    Substr,
+    Replace,
+    Split,
+    String,
+    Int,
+    Float,
+    Abs,
+    Round,
+    Floor,
+    Ceil,
+    Min,
+    Max,
+    Coalesce,
+    IsNull,
+    If,
+    Now,
+    Timestamp,
+    Millis,
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+}
+
+impl Function {
+    pub(crate) fn lookup(name: &str) -> Result<Function, ExprError> {
+        let f = match name {
+            "len" => Function::Len,
+            "lower" => Function::Lower,
+            "upper" => Function::Upper,
+            "trim" => Function::Trim,
+            "concat" => Function::Concat,
+            "contains" => Function::Contains,
+            "starts_with" => Function::StartsWith,
+            "ends_with" => Function::EndsWith,
+            "substr" => Function::Substr,
+            "replace" => Function::Replace,
+            "split" => Function::Split,
+            "string" => Function::String,
+            "int" => Function::Int,
+            "float" => Function::Float,
+            "abs" => Function::Abs,
+            "round" => Function::Round,
+            "floor" => Function::Floor,
+            "ceil" => Function::Ceil,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "coalesce" => Function::Coalesce,
+            "is_null" => Function::IsNull,
+            "if" => Function::If,
+            "now" => Function::Now,
+            "timestamp" => Function::Timestamp,
+            "millis" => Function::Millis,
+            "seconds" => Function::Seconds,
+            "minutes" => Function::Minutes,
+            "hours" => Function::Hours,
+            "days" => Function::Days,
+            _ => {
+                return Err(ExprError::UnknownFunction {
+                    name: name.to_string(),
+                })
+            }
+        };
+        Ok(f)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Function::Len => "len",
+            Function::Lower => "lower",
+            Function::Upper => "upper",
+            Function::Trim => "trim",
+            Function::Concat => "concat",
+            Function::Contains => "contains",
+            Function::StartsWith => "starts_with",
+            Function::EndsWith => "ends_with",
+            Function::Substr => "substr",
+            Function::Replace => "replace",
+            Function::Split => "split",
+            Function::String => "string",
+            Function::Int => "int",
+            Function::Float => "float",
+            Function::Abs => "abs",
+            Function::Round => "round",
+            Function::Floor => "floor",
+            Function::Ceil => "ceil",
+            Function::Min => "min",
+            Function::Max => "max",
+            Function::Coalesce => "coalesce",
+            Function::IsNull => "is_null",
+            Function::If => "if",
+            Function::Now => "now",
+            Function::Timestamp => "timestamp",
+            Function::Millis => "millis",
+            Function::Seconds => "seconds",
+            Function::Minutes => "minutes",
+            Function::Hours => "hours",
+            Function::Days => "days",
+        }
+    }
+
+    /// Min and max count of the arguments, `None` is unlimited.
+    fn arity(&self) -> (usize, Option<usize>) {
+        match self {
+            Function::Now => (0, Some(0)),
+            Function::Concat | Function::Min | Function::Max | Function::Coalesce => (1, None),
+            Function::Contains | Function::StartsWith | Function::EndsWith | Function::Split => {
+                (2, Some(2))
+            }
+            Function::Substr => (2, Some(3)),
+            Function::Replace | Function::If => (3, Some(3)),
+            _ => (1, Some(1)),
+        }
+    }
+
+    pub(crate) fn check_args(&self, actual: usize) -> Result<(), ExprError> {
+        let (min, max) = self.arity();
+        if actual >= min && max.is_none_or(|x| actual <= x) {
+            return Ok(());
+        }
+        let expected = match max {
+            Some(max) if max == min => min.to_string(),
+            Some(max) => format!("{} to {}", min, max),
+            None => format!("at least {}", min),
+        };
+        Err(ExprError::ArgumentCount {
+            name: self.name().to_string(),
+            expected,
+            actual,
+        })
+    }
+
+    /// Call the function with the evaluated arguments.
+    pub(crate) fn call(&self, args: &[Value]) -> Value {
+        let arg = |i: usize| args.get(i).unwrap_or(&Value::None);
+        match self {
+            Function::Len => match arg(0) {
+                Value::String(s) => Value::from(s.chars().count() as u64),
+                Value::Bytes(v) => Value::from(v.len() as u64),
+                Value::Seq(v) => Value::from(v.len() as u64),
+                Value::Map(v) => Value::from(v.len() as u64),
+                _ => Value::None,
+            },
+            Function::Lower => string_fn(arg(0), |s| Value::from(s.to_lowercase())),
+            Function::Upper => string_fn(arg(0), |s| Value::from(s.to_uppercase())),
+            Function::Trim => string_fn(arg(0), |s| Value::from(s.trim())),
+            Function::Concat => {
+                let mut r = String::new();
+                for v in args {
+                    match v {
+                        Value::None => {}
+                        other => r.push_str(&other.parse_str().unwrap_or_default()),
+                    }
+                }
+                Value::from(r)
+            }
+            Function::Contains => match (arg(0), arg(1)) {
+                (Value::String(s), Value::String(x)) => Value::from(s.contains(x.as_str())),
+                (Value::Seq(v), x) => Value::from(v.iter().any(|e| super::eval::equals(e, x))),
+                (Value::Map(m), Value::String(x)) => Value::from(m.contains_key(x)),
+                _ => Value::None,
+            },
+            Function::StartsWith => match (arg(0), arg(1)) {
+                (Value::String(s), Value::String(x)) => Value::from(s.starts_with(x.as_str())),
+                _ => Value::None,
+            },
+            Function::EndsWith => match (arg(0), arg(1)) {
+                (Value::String(s), Value::String(x)) => Value::from(s.ends_with(x.as_str())),
+                _ => Value::None,
+            },
+            Function::Substr => match (arg(0), arg(1).as_u64()) {
+                (Value::String(s), Some(start)) => {
+                    let chars = s.chars().skip(start as usize);
+                    match args.get(2) {
+                        Some(len) => match len.as_u64() {
+                            Some(len) => Value::from(chars.take(len as usize).collect::<String>()),
+                            None => Value::None,
+                        },
+                        None => Value::from(chars.collect::<String>()),
+                    }
+                }
+                _ => Value::None,
+            },
+            Function::Replace => match (arg(0), arg(1), arg(2)) {
+                (Value::String(s), Value::String(from), Value::String(to)) => {
+                    Value::from(s.replace(from.as_str(), to))
+                }
+                _ => Value::None,
+            },
+            Function::Split => match (arg(0), arg(1)) {
+                (Value::String(s), Value::String(sep)) => {
+                    Value::from(s.split(sep.as_str()).map(Value::from).collect::<Vec<_>>())
+                }
+                _ => Value::None,
+            },
+            Function::String => match arg(0) {
+                Value::None => Value::None,
+                other => other.parse_str().map(Value::from).unwrap_or(Value::None),
+            },
+            Function::Int => match arg(0) {
+                Value::Number(v) if v.is_finite() => Value::from(v.trunc() as i64),
+                Value::Bool(v) => Value::from(*v as i64),
+                Value::TimeStamp(v) => Value::from(v.timestamp_millis()),
+                other => other
+                    .parse_integer::<i64>()
+                    .map(Value::from)
+                    .unwrap_or(Value::None),
+            },
+            Function::Float => arg(0).parse_f64().map(Value::from).unwrap_or(Value::None),
+            Function::Abs => match arg(0) {
+                Value::Integer(v) => v.checked_abs().map(Value::from).unwrap_or(Value::None),
+                Value::Number(v) => Value::from(v.abs()),
+                _ => Value::None,
+            },
+            Function::Round => float_fn(arg(0), f64::round),
+            Function::Floor => float_fn(arg(0), f64::floor),
+            Function::Ceil => float_fn(arg(0), f64::ceil),
+            Function::Min => extreme(args, std::cmp::Ordering::Less),
+            Function::Max => extreme(args, std::cmp::Ordering::Greater),
+            Function::Coalesce => args
+                .iter()
+                .find(|x| **x != Value::None)
+                .cloned()
+                .unwrap_or(Value::None),
+            Function::IsNull => Value::from(arg(0) == &Value::None),
+            Function::If => {
+                if super::eval::is_truthy(arg(0)) {
+                    arg(1).clone()
+                } else {
+                    arg(2).clone()
+                }
+            }
+            Function::Now => Value::now(),
+            Function::Timestamp => match arg(0) {
+                Value::Integer(v) => DateTime::<Utc>::from_timestamp_millis(*v)
+                    .map(Value::from)
+                    .unwrap_or(Value::None),
+                other => other
+                    .parse_timestamp()
+                    .map(Value::from)
+                    .unwrap_or(Value::None),
+            },
+            Function::Millis => match arg(0).parse_timestamp() {
+                Some(v) => Value::from(v.timestamp_millis()),
+                None => Value::None,
+            },
+            Function::Seconds => duration(arg(0), 1_000),
+            Function::Minutes => duration(arg(0), 60_000),
+            Function::Hours => duration(arg(0), 3_600_000),
+            Function::Days => duration(arg(0), 86_400_000),
+        }
+    }
+}
+
+fn string_fn<F>(v: &Value, f: F) -> Value
+where
+    F: FnOnce(&str) -> Value,
+{
+    match v {
+        Value::String(s) => f(s),
+        _ => Value::None,
+    }
+}
+
+fn float_fn<F>(v: &Value, f: F) -> Value
+where
+    F: FnOnce(f64) -> f64,
+{
+    match v {
+        Value::Integer(_) => v.clone(),
+        Value::Number(x) => Value::from(f(*x)),
+        _ => Value::None,
+    }
+}
+
+/// Min or max of the arguments, null and incomparable values are ignored.
+fn extreme(args: &[Value], ord: std::cmp::Ordering) -> Value {
+    let mut r: Option<&Value> = None;
+    for v in args {
+        if *v == Value::None {
+            continue;
+        }
+        match r {
+            None => r = Some(v),
+            Some(current) => {
+                if super::eval::compare(v, current) == Some(ord) {
+                    r = Some(v);
+                }
+            }
+        }
+    }
+    r.cloned().unwrap_or(Value::None)
+}
+
+/// Milliseconds of the count of the unit.
+fn duration(v: &Value, unit: i64) -> Value {
+    match v {
+        Value::Integer(x) => x.checked_mul(unit).map(Value::from).unwrap_or(Value::None),
+        Value::Number(x) => Value::from((x * unit as f64).round() as i64),
+        _ => Value::None,
+    }
+}
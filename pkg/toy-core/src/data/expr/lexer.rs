@@ -0,0 +1,199 @@
+use crate::data::error::ExprError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Integer(i64),
+    Number(f64),
+    String(String),
+    /// Path of the value, `""` is the value itself.
+    Path(String),
+    /// Identifier followed by `(`.
+    Function(String),
+    True,
+    False,
+    Null,
+    LParen,
+    RParen,
+    Comma,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    Not,
+    Coalesce,
+    Eof,
+}
+
+/// Token and its position in the source.
+pub(crate) type Spanned = (Token, usize);
+
+pub(crate) fn tokenize(source: &str) -> Result<Vec<Spanned>, ExprError> {
+    let chars = source.char_indices().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        let next = chars.get(i + 1).map(|x| x.1);
+        let two = |a: char, b: char| c == a && next == Some(b);
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let (token, len) = if two('=', '=') {
+            (Token::Eq, 2)
+        } else if two('!', '=') {
+            (Token::Ne, 2)
+        } else if two('<', '=') {
+            (Token::Le, 2)
+        } else if two('>', '=') {
+            (Token::Ge, 2)
+        } else if two('&', '&') {
+            (Token::And, 2)
+        } else if two('|', '|') {
+            (Token::Or, 2)
+        } else if two('?', '?') {
+            (Token::Coalesce, 2)
+        } else {
+            match c {
+                '(' => (Token::LParen, 1),
+                ')' => (Token::RParen, 1),
+                ',' => (Token::Comma, 1),
+                '+' => (Token::Plus, 1),
+                '-' => (Token::Minus, 1),
+                '*' => (Token::Star, 1),
+                '/' => (Token::Slash, 1),
+                '%' => (Token::Percent, 1),
+                '<' => (Token::Lt, 1),
+                '>' => (Token::Gt, 1),
+                '!' => (Token::Not, 1),
+                '$' => (Token::Path("".to_string()), 1),
+                '\'' | '"' => string(&chars, i)?,
+                '`' => quoted_path(&chars, i)?,
+                c if c.is_ascii_digit() => number(&chars, i)?,
+                c if is_ident_start(c) => ident(&chars, i),
+                other => {
+                    return Err(ExprError::syntax(
+                        pos,
+                        format_args!("unexpected character '{}'", other),
+                    ))
+                }
+            }
+        };
+        tokens.push((token, pos));
+        i += len;
+    }
+    tokens.push((Token::Eof, source.len()));
+    Ok(tokens)
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Returns the token and the count of chars consumed.
+fn string(chars: &[(usize, char)], start: usize) -> Result<(Token, usize), ExprError> {
+    let quote = chars[start].1;
+    let mut r = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i].1 {
+            c if c == quote => return Ok((Token::String(r), i - start + 1)),
+            '\\' => {
+                let escaped = match chars.get(i + 1).map(|x| x.1) {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some(c @ ('\\' | '\'' | '"')) => c,
+                    _ => return Err(ExprError::syntax(chars[i].0, "invalid escape sequence")),
+                };
+                r.push(escaped);
+                i += 2;
+            }
+            c => {
+                r.push(c);
+                i += 1;
+            }
+        }
+    }
+    Err(ExprError::syntax(chars[start].0, "unterminated string"))
+}
+
+/// Path quoted by backquote, to access the keys including the characters other than identifier.
+fn quoted_path(chars: &[(usize, char)], start: usize) -> Result<(Token, usize), ExprError> {
+    let end = chars[start + 1..]
+        .iter()
+        .position(|x| x.1 == '`')
+        .ok_or_else(|| ExprError::syntax(chars[start].0, "unterminated quoted path"))?;
+    let path = chars[start + 1..start + 1 + end]
+        .iter()
+        .map(|x| x.1)
+        .collect::<String>();
+    Ok((Token::Path(path), end + 2))
+}
+
+fn number(chars: &[(usize, char)], start: usize) -> Result<(Token, usize), ExprError> {
+    let mut i = start;
+    while i < chars.len() && chars[i].1.is_ascii_digit() {
+        i += 1;
+    }
+    let is_float = i + 1 < chars.len() && chars[i].1 == '.' && chars[i + 1].1.is_ascii_digit();
+    if is_float {
+        i += 1;
+        while i < chars.len() && chars[i].1.is_ascii_digit() {
+            i += 1;
+        }
+    }
+    let s = chars[start..i].iter().map(|x| x.1).collect::<String>();
+    let token = if is_float {
+        s.parse().map(Token::Number).ok()
+    } else {
+        s.parse().map(Token::Integer).ok()
+    };
+    token
+        .map(|x| (x, i - start))
+        .ok_or_else(|| ExprError::syntax(chars[start].0, format_args!("invalid number {}", s)))
+}
+
+/// Keyword, function name, or path such as `a.b.0`.
+fn ident(chars: &[(usize, char)], start: usize) -> (Token, usize) {
+    let mut i = start;
+    loop {
+        while i < chars.len() && is_ident_continue(chars[i].1) {
+            i += 1;
+        }
+        let continues =
+            i + 1 < chars.len() && chars[i].1 == '.' && is_ident_continue(chars[i + 1].1);
+        if !continues {
+            break;
+        }
+        i += 1;
+    }
+    let s = chars[start..i].iter().map(|x| x.1).collect::<String>();
+    let token = match s.as_str() {
+        "true" => Token::True,
+        "false" => Token::False,
+        "null" => Token::Null,
+        "and" => Token::And,
+        "or" => Token::Or,
+        "not" => Token::Not,
+        _ if chars.get(i).map(|x| x.1) == Some('(') && !s.contains('.') => Token::Function(s),
+        _ => Token::Path(s),
+    };
+    (token, i - start)
+}
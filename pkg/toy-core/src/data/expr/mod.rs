@@ -0,0 +1,101 @@
+//! Small expression language over [`Value`], used by the configs of services.
+//!
+//! The expression is compiled once, and evaluated for each value.
+//! It has no loops, no variables, and no access to the outside of the value,
+//! so evaluation always terminates and does not fail. Type mismatch, overflow,
+//! and division by zero result in `null` instead of an error.
+//!
+//! # Syntax
+//!
+//! - Literal: `1`, `1.5`, `'text'`, `"text"`, `true`, `false`, `null`.
+//! - Path: `a.b.0` is the value at the path, or `null` if not found.
+//!   Keys including other characters are quoted by backquote, such as `` `user-name` ``.
+//!   `$` is the value itself.
+//! - Arithmetic: `+ - * / %`. Integer operations stay integer, `+` concatenates strings.
+//!   TimeStamp `+`/`-` Integer adds or subtracts milliseconds,
+//!   and TimeStamp `-` TimeStamp is the milliseconds between them.
+//! - Comparison: `== != < <= > >=`, Integer and Number are compared as number,
+//!   and TimeStamp is compared with RFC3339 string.
+//! - Boolean: `&&`/`and`, `||`/`or`, `!`/`not`. `false` and `null` are false, others are true.
+//! - Null coalescing: `a ?? b` is `b` if `a` is null.
+//! - Functions:
+//!   - string: `len`, `lower`, `upper`, `trim`, `concat`, `contains`, `starts_with`, `ends_with`,
+//!     `substr(s, start, len?)`, `replace(s, from, to)`, `split(s, sep)`.
+//!   - conversion: `string`, `int`, `float`.
+//!   - number: `abs`, `round`, `floor`, `ceil`, `min(...)`, `max(...)`.
+//!   - null and condition: `coalesce(...)`, `is_null`, `if(cond, then, else)`.
+//!   - timestamp: `now()`, `timestamp(rfc3339 or millis)`, `millis(ts)`,
+//!     and `seconds`, `minutes`, `hours`, `days` to milliseconds.
+//!
+//! # Example
+//!
+//! ```
+//! # use toy_core::data::expr::Expr;
+//! # use toy_core::data::Value;
+//! # use toy_core::map_value;
+//! let expr = Expr::compile("price * qty > 100 && lower(status ?? 'none') == 'paid'").unwrap();
+//!
+//! let v = map_value! {
+//!     "price" => 30,
+//!     "qty" => 4,
+//!     "status" => "PAID",
+//! };
+//!
+//! assert!(expr.is_match(&v));
+//! ```
+
+use crate::data::error::ExprError;
+use crate::data::Value;
+use std::fmt;
+use std::str::FromStr;
+
+mod eval;
+mod function;
+mod lexer;
+mod parser;
+
+/// Compiled expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    source: String,
+    node: parser::Node,
+}
+
+impl Expr {
+    pub fn compile(source: &str) -> Result<Expr, ExprError> {
+        let tokens = lexer::tokenize(source)?;
+        let node = parser::parse(tokens)?;
+        Ok(Expr {
+            source: source.to_string(),
+            node,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluate the expression with the value.
+    pub fn eval(&self, v: &Value) -> Value {
+        eval::eval(&self.node, v).into_owned()
+    }
+
+    /// Whether the result of the expression is true, neither `false` nor `null`.
+    pub fn is_match(&self, v: &Value) -> bool {
+        eval::is_truthy(&eval::eval(&self.node, v))
+    }
+}
+
+impl FromStr for Expr {
+    type Err = ExprError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Expr::compile(s)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
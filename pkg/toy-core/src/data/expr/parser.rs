@@ -0,0 +1,199 @@
+use super::function::Function;
+use super::lexer::{Spanned, Token};
+use crate::data::error::ExprError;
+use crate::data::Value;
+
+/// Max nesting and height of the expression, to limit the recursion of parse and evaluation.
+pub(crate) const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Node {
+    Literal(Value),
+    Path(String),
+    Unary(UnaryOp, Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum UnaryOp {
+    Not,
+    Neg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Coalesce,
+}
+
+impl BinaryOp {
+    fn from_token(token: &Token) -> Option<BinaryOp> {
+        let op = match token {
+            Token::Or => BinaryOp::Or,
+            Token::And => BinaryOp::And,
+            Token::Eq => BinaryOp::Eq,
+            Token::Ne => BinaryOp::Ne,
+            Token::Lt => BinaryOp::Lt,
+            Token::Le => BinaryOp::Le,
+            Token::Gt => BinaryOp::Gt,
+            Token::Ge => BinaryOp::Ge,
+            Token::Plus => BinaryOp::Add,
+            Token::Minus => BinaryOp::Sub,
+            Token::Star => BinaryOp::Mul,
+            Token::Slash => BinaryOp::Div,
+            Token::Percent => BinaryOp::Rem,
+            Token::Coalesce => BinaryOp::Coalesce,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    /// Binding power, the operator of higher power binds first. All operators are left associative.
+    fn power(&self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Eq | BinaryOp::Ne => 3,
+            BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => 4,
+            BinaryOp::Add | BinaryOp::Sub => 5,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 6,
+            BinaryOp::Coalesce => 7,
+        }
+    }
+}
+
+/// Binding power of the unary operators, higher than all binary operators.
+const UNARY_POWER: u8 = 8;
+
+pub(crate) fn parse(tokens: Vec<Spanned>) -> Result<Node, ExprError> {
+    let mut parser = Parser { tokens, pos: 0 };
+    let (node, _) = parser.expr(0, 0)?;
+    match parser.peek() {
+        Token::Eof => Ok(node),
+        other => Err(unexpected(other.clone(), parser.position())),
+    }
+}
+
+fn unexpected(token: Token, position: usize) -> ExprError {
+    match token {
+        Token::Eof => ExprError::syntax(position, "unexpected end of expression"),
+        other => ExprError::syntax(position, format_args!("unexpected {:?}", other)),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if token != Token::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ExprError> {
+        if self.peek() == &expected {
+            self.next();
+            Ok(())
+        } else {
+            Err(unexpected(self.peek().clone(), self.position()))
+        }
+    }
+
+    /// Returns the node and its height.
+    ///
+    /// Both the nesting and the height of the tree are limited, so the chain of
+    /// left associative operators (e.g. `a + b + c ...`) is limited as well as the nested one.
+    fn expr(&mut self, min_power: u8, depth: usize) -> Result<(Node, usize), ExprError> {
+        if depth > MAX_DEPTH {
+            return Err(ExprError::TooDeep { max: MAX_DEPTH });
+        }
+        let (mut left, mut height) = self.prefix(depth)?;
+        while let Some(op) = BinaryOp::from_token(self.peek()) {
+            if op.power() <= min_power {
+                break;
+            }
+            self.next();
+            let (right, right_height) = self.expr(op.power(), depth + 1)?;
+            height = height.max(right_height) + 1;
+            if depth + height > MAX_DEPTH {
+                return Err(ExprError::TooDeep { max: MAX_DEPTH });
+            }
+            left = Node::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok((left, height))
+    }
+
+    fn prefix(&mut self, depth: usize) -> Result<(Node, usize), ExprError> {
+        let position = self.position();
+        let node = match self.next() {
+            Token::Integer(v) => (Node::Literal(Value::from(v)), 0),
+            Token::Number(v) => (Node::Literal(Value::from(v)), 0),
+            Token::String(v) => (Node::Literal(Value::from(v)), 0),
+            Token::True => (Node::Literal(Value::from(true)), 0),
+            Token::False => (Node::Literal(Value::from(false)), 0),
+            Token::Null => (Node::Literal(Value::None), 0),
+            Token::Path(v) => (Node::Path(v), 0),
+            Token::Not => {
+                let (node, height) = self.expr(UNARY_POWER, depth + 1)?;
+                (Node::Unary(UnaryOp::Not, Box::new(node)), height + 1)
+            }
+            Token::Minus => {
+                let (node, height) = self.expr(UNARY_POWER, depth + 1)?;
+                (Node::Unary(UnaryOp::Neg, Box::new(node)), height + 1)
+            }
+            Token::LParen => {
+                let node = self.expr(0, depth + 1)?;
+                self.expect(Token::RParen)?;
+                node
+            }
+            Token::Function(name) => {
+                self.expect(Token::LParen)?;
+                let mut args = Vec::new();
+                let mut height = 0;
+                if self.peek() != &Token::RParen {
+                    loop {
+                        let (arg, arg_height) = self.expr(0, depth + 1)?;
+                        args.push(arg);
+                        height = height.max(arg_height);
+                        if self.peek() != &Token::Comma {
+                            break;
+                        }
+                        self.next();
+                    }
+                }
+                self.expect(Token::RParen)?;
+                let f = Function::lookup(&name)?;
+                f.check_args(args.len())?;
+                (Node::Call(f, args), height + 1)
+            }
+            other => return Err(unexpected(other, position)),
+        };
+        Ok(node)
+    }
+}
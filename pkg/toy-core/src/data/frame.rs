@@ -16,6 +16,23 @@ pub struct Frame {
 struct Header {
     port: u8,
     frame_type: FrameType,
+    /// User metadata of the frame.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    entries: Map<String, Value>,
+}
+
+/// Well-known keys of the frame headers.
+pub mod headers {
+    /// Where the frame comes from, e.g. the path of file.
+    pub const SOURCE: &str = "source";
+    /// Line number in the source.
+    pub const LINE: &str = "line";
+    /// Id to correlate the frames of the same message.
+    pub const CORRELATION_ID: &str = "correlation_id";
+    /// When the frame was ingested.
+    pub const TIMESTAMP: &str = "timestamp";
+    /// Trace context of the message which produced the frame.
+    pub const TRACE_ID: &str = "trace_id";
 }
 
 /// Represents the type of frame.
@@ -34,6 +51,8 @@ pub enum FrameType {
 pub enum Signal {
     Stop,
     UpstreamFinish,
+    /// Stop producing, and finish after in-flight frames.
+    Drain,
 }
 
 impl Frame {
@@ -70,6 +89,37 @@ impl Frame {
         }
     }
 
+    /// Headers of the frame, the services keep them when passing the frame through.
+    #[inline]
+    pub fn headers(&self) -> &Map<String, Value> {
+        &self.header.entries
+    }
+
+    #[inline]
+    pub fn headers_mut(&mut self) -> &mut Map<String, Value> {
+        &mut self.header.entries
+    }
+
+    #[inline]
+    pub fn header(&self, key: &str) -> Option<&Value> {
+        self.header.entries.get(key)
+    }
+
+    pub fn set_header<K: Into<String>, V: Into<Value>>(&mut self, key: K, value: V) {
+        self.header.entries.insert(key.into(), value.into());
+    }
+
+    pub fn with_header<K: Into<String>, V: Into<Value>>(mut self, key: K, value: V) -> Self {
+        self.set_header(key, value);
+        self
+    }
+
+    /// Replace the headers. Use to keep the headers of the request on a new frame.
+    pub fn with_headers(mut self, headers: Map<String, Value>) -> Self {
+        self.header.entries = headers;
+        self
+    }
+
     #[inline]
     pub fn port(&self) -> u8 {
         self.header.port
@@ -94,6 +144,17 @@ impl Frame {
         }
     }
 
+    pub fn drain() -> Frame {
+        Frame {
+            header: Header::signal(Signal::Drain),
+            payload: None,
+        }
+    }
+
+    pub fn is_signal(&self) -> bool {
+        matches!(self.header.frame_type, FrameType::Signal(_))
+    }
+
     pub fn is_stop(&self) -> bool {
         self.header.frame_type == FrameType::Signal(Signal::Stop)
     }
@@ -101,6 +162,10 @@ impl Frame {
     pub fn is_upstream_finish(&self) -> bool {
         self.header.frame_type == FrameType::Signal(Signal::UpstreamFinish)
     }
+
+    pub fn is_drain(&self) -> bool {
+        self.header.frame_type == FrameType::Signal(Signal::Drain)
+    }
 }
 
 impl Header {
@@ -108,6 +173,7 @@ impl Header {
         Self {
             port: 0,
             frame_type: FrameType::Data,
+            entries: Map::new(),
         }
     }
 
@@ -115,6 +181,7 @@ impl Header {
         Self {
             port: 0,
             frame_type: FrameType::Signal(v),
+            entries: Map::new(),
         }
     }
 }
@@ -138,6 +205,7 @@ impl fmt::Display for Header {
         f.debug_struct("Header")
             .field("frame_type", &self.frame_type)
             .field("port", &self.port)
+            .field("entries", &self.entries)
             .finish()
     }
 }
@@ -146,6 +214,10 @@ impl OutgoingMessage for Frame {
     fn set_port(&mut self, port: u8) {
         self.set_port(port)
     }
+
+    fn is_signal(&self) -> bool {
+        self.is_signal()
+    }
 }
 
 macro_rules! impl_from_to_frame {
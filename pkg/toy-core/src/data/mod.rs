@@ -3,11 +3,12 @@
 //! It is used for passing data between nodes and serializing graph information.
 //!
 
-pub use self::frame::{Frame, FrameType, Signal};
+pub use self::frame::{headers, Frame, FrameType, Signal};
 pub use self::value::Value;
 pub use toy_map::Map;
 
 pub mod error;
+pub mod expr;
 mod frame;
 mod value;
 mod value_impl_pack;
@@ -28,3 +28,33 @@ impl schema::Error for SchemaScanError {
         SchemaScanError::error(msg)
     }
 }
+
+/// Error validating Value by Json Schema.
+#[derive(Debug, Clone, ThisError)]
+#[error("path:{:?}, {}", path, inner)]
+pub struct SchemaValidationError {
+    path: String,
+    inner: String,
+}
+
+impl SchemaValidationError {
+    pub fn new<P, T>(path: P, msg: T) -> SchemaValidationError
+    where
+        P: Into<String>,
+        T: Display,
+    {
+        SchemaValidationError {
+            path: path.into(),
+            inner: msg.to_string(),
+        }
+    }
+
+    /// Path of the invalid value, separated by `.`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.inner
+    }
+}
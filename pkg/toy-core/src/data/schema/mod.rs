@@ -17,7 +17,8 @@
 pub mod builders;
 mod error;
 mod json_schema;
+mod validator;
 pub mod visitors;
 
-pub use error::SchemaScanError;
+pub use error::{SchemaScanError, SchemaValidationError};
 pub use json_schema::{JsonSchema, SchemaTypes};
@@ -0,0 +1,272 @@
+//! Validate [`Value`] by [`JsonSchema`].
+//!
+//! Supports the subset of keywords that [`JsonSchemaVisitor`] emits.
+//! (type, properties, required, oneOf, const, minimum, maximum, minLength, maxLength, additionalProperties, items)
+//!
+//! [`Value`]: crate::data::Value
+//! [`JsonSchema`]: crate::data::schema::JsonSchema
+//! [`JsonSchemaVisitor`]: crate::data::schema::visitors::JsonSchemaVisitor
+
+use crate::data::schema::json_schema::RangeValue;
+use crate::data::schema::{JsonSchema, SchemaTypes, SchemaValidationError};
+use crate::data::Value;
+
+impl JsonSchema {
+    /// Validate value, returns all errors with the path of the invalid value.
+    ///
+    /// The path is separated by `.` in the same way as [`Value::path`].
+    ///
+    /// [`Value::path`]: crate::data::Value::path
+    pub fn validate(&self, v: &Value) -> Result<(), Vec<SchemaValidationError>> {
+        self.validate_at(v, "")
+    }
+
+    fn validate_at(&self, v: &Value, path: &str) -> Result<(), Vec<SchemaValidationError>> {
+        let mut errors = Vec::new();
+        validate_0(self, v, path, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn validate_0(schema: &JsonSchema, v: &Value, path: &str, errors: &mut Vec<SchemaValidationError>) {
+    if let Some(ref c) = schema.const_ {
+        if v.as_str() != Some(c.as_str()) {
+            errors.push(SchemaValidationError::new(
+                path,
+                format!("must be {:?}.", c),
+            ));
+            return;
+        }
+    }
+
+    if let Some(ref candidates) = schema.one_of {
+        let matched = candidates
+            .iter()
+            .filter(|x| x.validate_at(v, path).is_ok())
+            .count();
+        if matched != 1 {
+            errors.push(SchemaValidationError::new(
+                path,
+                format!(
+                    "must match exactly one of the candidates. matched:{}",
+                    matched
+                ),
+            ));
+            return;
+        }
+    }
+
+    let tp = match schema.tp {
+        Some(tp) => tp,
+        None => return,
+    };
+    if !is_type_of(tp, v) {
+        errors.push(SchemaValidationError::new(
+            path,
+            format!(
+                "invalid type. expected:{}, actual:{}",
+                type_name(tp),
+                value_type_name(v)
+            ),
+        ));
+        return;
+    }
+
+    match v {
+        Value::Integer(_) | Value::Number(_) => validate_range(schema, v, path, errors),
+        Value::String(s) => validate_length(schema, s, path, errors),
+        Value::Seq(seq) => validate_items(schema, seq, path, errors),
+        Value::Map(map) => {
+            if let Some(ref required) = schema.required {
+                for key in required {
+                    match map.get(key) {
+                        None | Some(Value::None) => {
+                            errors.push(SchemaValidationError::new(join(path, key), "required."))
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            for (key, item) in map.iter() {
+                if let Value::None = item {
+                    continue;
+                }
+                let prop = schema
+                    .properties
+                    .as_ref()
+                    .and_then(|x| x.get(key))
+                    .or(schema.additional_properties.as_deref());
+                if let Some(prop) = prop {
+                    validate_0(prop, item, &join(path, key), errors);
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+fn validate_range(
+    schema: &JsonSchema,
+    v: &Value,
+    path: &str,
+    errors: &mut Vec<SchemaValidationError>,
+) {
+    if let Some(ref min) = schema.minimum {
+        if less_than(v, min) {
+            errors.push(SchemaValidationError::new(
+                path,
+                format!("must be greater than or equal to {}.", range_to_string(min)),
+            ));
+        }
+    }
+    if let Some(ref max) = schema.maximum {
+        if greater_than(v, max) {
+            errors.push(SchemaValidationError::new(
+                path,
+                format!("must be less than or equal to {}.", range_to_string(max)),
+            ));
+        }
+    }
+}
+
+fn validate_length(
+    schema: &JsonSchema,
+    s: &str,
+    path: &str,
+    errors: &mut Vec<SchemaValidationError>,
+) {
+    let len = s.chars().count() as u64;
+    if let Some(min) = schema.min_length {
+        if len < min {
+            errors.push(SchemaValidationError::new(
+                path,
+                format!("length must be greater than or equal to {}.", min),
+            ));
+        }
+    }
+    if let Some(max) = schema.max_length {
+        if len > max {
+            errors.push(SchemaValidationError::new(
+                path,
+                format!("length must be less than or equal to {}.", max),
+            ));
+        }
+    }
+}
+
+/// A single item schema applies to every element (`Vec`),
+/// multiple item schemas apply to each position (tuple variant).
+fn validate_items(
+    schema: &JsonSchema,
+    seq: &[Value],
+    path: &str,
+    errors: &mut Vec<SchemaValidationError>,
+) {
+    let items = match schema.items {
+        Some(ref items) if !items.is_empty() => items,
+        _ => return,
+    };
+    if items.len() == 1 {
+        for (idx, item) in seq.iter().enumerate() {
+            validate_0(&items[0], item, &join(path, &idx.to_string()), errors);
+        }
+    } else {
+        if seq.len() != items.len() {
+            errors.push(SchemaValidationError::new(
+                path,
+                format!(
+                    "invalid length. expected:{}, actual:{}",
+                    items.len(),
+                    seq.len()
+                ),
+            ));
+            return;
+        }
+        for (idx, (s, item)) in items.iter().zip(seq.iter()).enumerate() {
+            validate_0(s, item, &join(path, &idx.to_string()), errors);
+        }
+    }
+}
+
+fn is_type_of(tp: SchemaTypes, v: &Value) -> bool {
+    matches!(
+        (tp, v),
+        (SchemaTypes::Array, Value::Seq(_))
+            | (SchemaTypes::Boolean, Value::Bool(_))
+            | (SchemaTypes::Integer, Value::Integer(_))
+            | (SchemaTypes::Null, Value::None)
+            | (SchemaTypes::Number, Value::Number(_) | Value::Integer(_))
+            | (SchemaTypes::Object, Value::Map(_))
+            | (SchemaTypes::String, Value::String(_))
+    )
+}
+
+fn type_name(tp: SchemaTypes) -> &'static str {
+    match tp {
+        SchemaTypes::Array => "array",
+        SchemaTypes::Boolean => "boolean",
+        SchemaTypes::Integer => "integer",
+        SchemaTypes::Null => "null",
+        SchemaTypes::Number => "number",
+        SchemaTypes::Object => "object",
+        SchemaTypes::String => "string",
+    }
+}
+
+fn value_type_name(v: &Value) -> &'static str {
+    match v {
+        Value::Bool(_) => "boolean",
+        Value::Integer(_) => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Bytes(_) => "bytes",
+        Value::None => "null",
+        Value::Seq(_) => "array",
+        Value::Map(_) => "object",
+        Value::TimeStamp(_) => "timestamp",
+    }
+}
+
+fn less_than(v: &Value, r: &RangeValue) -> bool {
+    match (v, r) {
+        (Value::Integer(v), RangeValue::U64(r)) => *v < 0 || (*v as u64) < *r,
+        (Value::Integer(v), RangeValue::I64(r)) => v < r,
+        (Value::Integer(v), RangeValue::F64(r)) => (*v as f64) < *r,
+        (Value::Number(v), RangeValue::U64(r)) => *v < *r as f64,
+        (Value::Number(v), RangeValue::I64(r)) => *v < *r as f64,
+        (Value::Number(v), RangeValue::F64(r)) => v < r,
+        _ => false,
+    }
+}
+
+fn greater_than(v: &Value, r: &RangeValue) -> bool {
+    match (v, r) {
+        (Value::Integer(v), RangeValue::U64(r)) => *v >= 0 && (*v as u64) > *r,
+        (Value::Integer(v), RangeValue::I64(r)) => v > r,
+        (Value::Integer(v), RangeValue::F64(r)) => (*v as f64) > *r,
+        (Value::Number(v), RangeValue::U64(r)) => *v > *r as f64,
+        (Value::Number(v), RangeValue::I64(r)) => *v > *r as f64,
+        (Value::Number(v), RangeValue::F64(r)) => v > r,
+        _ => false,
+    }
+}
+
+fn range_to_string(r: &RangeValue) -> String {
+    match r {
+        RangeValue::U64(v) => v.to_string(),
+        RangeValue::I64(v) => v.to_string(),
+        RangeValue::F64(v) => v.to_string(),
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
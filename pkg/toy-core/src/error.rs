@@ -189,6 +189,9 @@ pub enum OutgoingError {
     #[error("send error. the receiver dropped.")]
     ReceiverDropped,
 
+    #[error("send error. channel is full. port:{}", port)]
+    ChannelFull { port: u8 },
+
     #[error("error: {}", inner)]
     Error { inner: String },
 }
@@ -207,6 +210,10 @@ impl OutgoingError {
     pub fn receiver_dropped() -> OutgoingError {
         OutgoingError::ReceiverDropped
     }
+
+    pub fn channel_full(port: u8) -> OutgoingError {
+        OutgoingError::ChannelFull { port }
+    }
 }
 
 impl Error for OutgoingError {
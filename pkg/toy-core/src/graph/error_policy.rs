@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+/// Behavior of node when the service returns an error for a frame.
+///
+/// The service reports the error with [`ServiceContext::Failed`] to keep the context for the next frame.
+/// If the service returns `Err`, the context is consumed by the failed call,
+/// so the node fails unless the service is stateless, and its context is created again from the config of node.
+///
+/// A source is resumed after the error, as well as before.
+///
+/// [`ServiceContext::Failed`]: crate::service::ServiceContext::Failed
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorPolicy {
+    /// Stop the node, and the task fails.
+    #[default]
+    Fail,
+
+    /// Discard the frame and continue.
+    Skip,
+
+    /// Retry the frame up to `max` times.
+    /// Waits `backoff_mills * 2^n` before the n-th retry (0-origin).
+    /// If all retries fail, behaves as `Fail`.
+    Retry { max: u32, backoff_mills: u64 },
+
+    /// Send the frame and the error information to the output port, and continue.
+    DeadLetter { port: u8 },
+}
+
+impl ErrorPolicy {
+    /// Wait time before the retry of `count` (0-origin).
+    pub fn backoff_mills(&self, count: u32) -> Option<u64> {
+        match *self {
+            ErrorPolicy::Retry { max, backoff_mills } if count < max => {
+                Some(backoff_mills.saturating_mul(2u64.saturating_pow(count)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Output port reserved for dead letter, if exists.
+    pub fn dead_letter_port(&self) -> Option<u8> {
+        match *self {
+            ErrorPolicy::DeadLetter { port } => Some(port),
+            _ => None,
+        }
+    }
+}
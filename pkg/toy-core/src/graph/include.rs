@@ -0,0 +1,371 @@
+//! Expand `toy.graph.Include` nodes, inline the referenced graph.
+//!
+//! ```yaml
+//! - type: toy.graph.Include
+//!   uri: csv
+//!   config:
+//!     graph: read-csv   # name of the included graph
+//!     entry: [reader]   # optional. default: nodes of the included graph without upstream.
+//!     exit: [filter]    # optional. default: the node of the included graph without wires.
+//!   wires: [next]
+//! ```
+//!
+//! The nodes of the included graph are renamed to `{uri of include node}/{uri}`.
+//! `entry` and `exit` are indexed by the port of the include node.
+//! The wire to the input port n of the include node is connected to `entry[n]`, port 0 if not specified,
+//! and the entry may have the input port of its node, e.g. `join:1`.
+//! The wire of the output port n of the include node is appended to the wires of `exit[n]`,
+//! or all wires are appended to the exit if only one.
+//! The params of the included graph are merged into the params of the including graph.
+
+use crate::data::{self, Value};
+use crate::error::ConfigError;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+/// Service type of the node which includes another graph.
+pub const INCLUDE_SERVICE_TYPE: &str = "toy.graph.Include";
+
+#[derive(Debug, Clone, Deserialize)]
+struct IncludeConfig {
+    graph: String,
+    #[serde(default)]
+    entry: Vec<String>,
+    #[serde(default)]
+    exit: Vec<String>,
+}
+
+/// Expand all include nodes of the graph definition, recursively.
+///
+/// `resolve` returns the definition of graph by name, or `None` if not found.
+/// Returns error if the graph is not found, or includes itself directly or indirectly.
+pub fn expand_includes<F>(v: &Value, mut resolve: F) -> Result<Value, ConfigError>
+where
+    F: FnMut(&str) -> Option<Value>,
+{
+    let name = v
+        .as_map()
+        .and_then(|x| x.get("name"))
+        .and_then(|x| x.as_str())
+        .unwrap_or_default()
+        .to_string();
+    expand(v, &mut resolve, &mut vec![name])
+}
+
+fn expand<F>(v: &Value, resolve: &mut F, stack: &mut Vec<String>) -> Result<Value, ConfigError>
+where
+    F: FnMut(&str) -> Option<Value>,
+{
+    let map = match v.as_map() {
+        Some(map) => map,
+        None => {
+            return Err(ConfigError::error(
+                "invalid config. config value must be map type.",
+            ))
+        }
+    };
+    let services = match map.get("services").and_then(|x| x.as_vec()) {
+        Some(services) if services.iter().any(is_include) => services,
+        _ => return Ok(v.clone()),
+    };
+
+    let mut expanded = Vec::new();
+    let mut entries: HashMap<String, Vec<String>> = HashMap::new();
+    let mut params = match map.get("params") {
+        Some(Value::Seq(params)) => params.clone(),
+        _ => Vec::new(),
+    };
+    for service in services {
+        if !is_include(service) {
+            expanded.push(service.clone());
+            continue;
+        }
+        let uri = str_of(service, "uri")?;
+        let config = service
+            .as_map()
+            .and_then(|x| x.get("config"))
+            .ok_or_else(|| ConfigError::not_found_key("config"))?;
+        let config = data::unpack::<IncludeConfig>(config)
+            .map_err(|e| ConfigError::error(format!("invalid include config. {}", e)))?;
+
+        if stack.contains(&config.graph) {
+            return Err(ConfigError::validation_error(format!(
+                "recursive include. {} -> {}",
+                stack.join(" -> "),
+                config.graph
+            )));
+        }
+        let child = resolve(&config.graph).ok_or_else(|| {
+            ConfigError::validation_error(format!(
+                "not found include graph. uri:{}, graph:{}",
+                uri, config.graph
+            ))
+        })?;
+        stack.push(config.graph.clone());
+        let child = expand(&child, resolve, stack)?;
+        stack.pop();
+
+        merge_params(&mut params, &child);
+        let wires = service.as_map().and_then(|x| x.get("wires"));
+        let (nodes, entry) = inline(&uri, &config, &child, wires)?;
+        expanded.extend(nodes);
+        entries.insert(uri, entry);
+    }
+
+    let services = expanded
+        .into_iter()
+        .map(|mut service| -> Result<Value, ConfigError> {
+            if let Value::Map(ref mut m) = service {
+                if let Some(wires) = m.get("wires") {
+                    let wires = wire_elements(wires)
+                        .into_iter()
+                        .map(|x| {
+                            let to = target_of(&x);
+                            match to.as_ref().and_then(|to| entries.get(to).map(|e| (to, e))) {
+                                Some((to, entry)) => {
+                                    let port = port_of(&x).unwrap_or(0);
+                                    match entry.get(port as usize) {
+                                        Some(e) => Ok(retarget_entry(&x, e)),
+                                        None => Err(ConfigError::validation_error(format!(
+                                            "input port {} of include is not found in entry. uri:{}",
+                                            port, to
+                                        ))),
+                                    }
+                                }
+                                None => Ok(x),
+                            }
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    m.insert("wires".to_string(), Value::Seq(wires));
+                }
+            }
+            Ok(service)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut map = map.clone();
+    map.insert("services".to_string(), Value::Seq(services));
+    if !params.is_empty() {
+        map.insert("params".to_string(), Value::Seq(params));
+    }
+    Ok(Value::Map(map))
+}
+
+/// Returns the renamed nodes of the included graph, and the renamed entries by the input port.
+fn inline(
+    uri: &str,
+    config: &IncludeConfig,
+    child: &Value,
+    wires: Option<&Value>,
+) -> Result<(Vec<Value>, Vec<String>), ConfigError> {
+    let nodes = child
+        .as_map()
+        .and_then(|x| x.get("services"))
+        .and_then(|x| x.as_vec())
+        .ok_or_else(|| ConfigError::not_found_key("services"))?;
+    let prefixed = |x: &str| format!("{}/{}", uri, x);
+
+    let uris = nodes
+        .iter()
+        .map(|x| str_of(x, "uri"))
+        .collect::<Result<Vec<_>, _>>()?;
+    let targets = nodes
+        .iter()
+        .map(|x| wires_of(x).iter().filter_map(target_of).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let has_input = targets.iter().flatten().collect::<HashSet<_>>();
+
+    let entry = if config.entry.is_empty() {
+        uris.iter()
+            .filter(|x| !has_input.contains(x))
+            .cloned()
+            .collect::<Vec<_>>()
+    } else {
+        config.entry.clone()
+    };
+    let exit = if config.exit.is_empty() {
+        let sinks = uris
+            .iter()
+            .zip(targets.iter())
+            .filter(|(_, t)| t.is_empty())
+            .map(|(u, _)| u.clone())
+            .collect::<Vec<_>>();
+        if sinks.len() == 1 {
+            sinks
+        } else {
+            Vec::new()
+        }
+    } else {
+        config.exit.clone()
+    };
+    // the entry may have the input port.
+    let entry_uris = entry
+        .iter()
+        .map(|x| match super::parse_wire_str(x) {
+            Ok((u, _)) => u.to_string(),
+            Err(_) => x.clone(),
+        })
+        .collect::<Vec<_>>();
+    for x in entry_uris.iter().chain(exit.iter()) {
+        if !uris.contains(x) {
+            return Err(ConfigError::validation_error(format!(
+                "not found uri in include graph. uri:{}, graph:{}, target:{}",
+                uri, config.graph, x
+            )));
+        }
+    }
+
+    let outputs = wires.map(wire_elements).unwrap_or_default();
+    if !outputs.is_empty() && exit.is_empty() {
+        return Err(ConfigError::validation_error(format!(
+            "exit of include graph is ambiguous, specify \"exit\". uri:{}, graph:{}",
+            uri, config.graph
+        )));
+    }
+    if exit.len() > 1 && outputs.len() > exit.len() {
+        return Err(ConfigError::validation_error(format!(
+            "output port {} of include is not found in exit. uri:{}, graph:{}",
+            exit.len(),
+            uri,
+            config.graph
+        )));
+    }
+
+    let nodes = nodes
+        .iter()
+        .zip(uris.iter())
+        .map(|(node, u)| {
+            let mut node = node.as_map().cloned().unwrap_or_default();
+            let mut wires = wires_of(&Value::Map(node.clone()))
+                .into_iter()
+                .map(|x| match target_of(&x) {
+                    Some(to) => retarget(&x, &prefixed(&to)),
+                    None => x,
+                })
+                .collect::<Vec<_>>();
+            if exit.len() == 1 && exit[0] == *u {
+                wires.extend(outputs.iter().cloned());
+            } else {
+                // the output port n of the include node is sent from `exit[n]`.
+                wires.extend(
+                    exit.iter()
+                        .zip(outputs.iter())
+                        .filter(|(x, _)| *x == u)
+                        .map(|(_, w)| w.clone()),
+                );
+            }
+            node.insert("uri".to_string(), Value::from(prefixed(u)));
+            node.insert("wires".to_string(), Value::Seq(wires));
+            Value::Map(node)
+        })
+        .collect::<Vec<_>>();
+    let entry = entry.iter().map(|x| prefixed(x)).collect();
+    Ok((nodes, entry))
+}
+
+/// Parameters of the included graph are declared on the including graph,
+/// unless the same name is already declared.
+fn merge_params(params: &mut Vec<Value>, child: &Value) {
+    let name_of = |x: &Value| {
+        x.as_map()
+            .and_then(|x| x.get("name"))
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string())
+    };
+    if let Some(Value::Seq(child)) = child.as_map().and_then(|x| x.get("params")) {
+        for p in child {
+            let name = name_of(p);
+            if !params.iter().any(|x| name_of(x) == name) {
+                params.push(p.clone());
+            }
+        }
+    }
+}
+
+fn is_include(v: &Value) -> bool {
+    v.as_map()
+        .and_then(|x| x.get("type"))
+        .and_then(|x| x.as_str())
+        == Some(INCLUDE_SERVICE_TYPE)
+}
+
+fn str_of(v: &Value, key: &str) -> Result<String, ConfigError> {
+    match v.as_map().and_then(|x| x.get(key)) {
+        Some(Value::String(s)) => Ok(s.clone()),
+        Some(_) => Err(ConfigError::invalid_key_type(key, "String")),
+        None => Err(ConfigError::not_found_key(key)),
+    }
+}
+
+fn wires_of(v: &Value) -> Vec<Value> {
+    v.as_map()
+        .and_then(|x| x.get("wires"))
+        .map(wire_elements)
+        .unwrap_or_default()
+}
+
+/// Each wire, `String` or `Map`.
+fn wire_elements(wires: &Value) -> Vec<Value> {
+    match wires {
+        Value::String(_) | Value::Map(_) => vec![wires.clone()],
+        Value::Seq(seq) => seq.clone(),
+        _ => vec![],
+    }
+}
+
+/// Target uri of wire, without input port.
+fn target_of(wire: &Value) -> Option<String> {
+    let to = match wire {
+        Value::String(to) => to.as_str(),
+        Value::Map(m) => m.get("to").and_then(|x| x.as_str())?,
+        _ => return None,
+    };
+    super::parse_wire_str(to).ok().map(|(x, _)| x.to_string())
+}
+
+/// Input port of wire, if specified.
+fn port_of(wire: &Value) -> Option<u8> {
+    let to = match wire {
+        Value::String(to) => to.as_str(),
+        Value::Map(m) => {
+            if let Some(port) = m.get("port").and_then(|x| x.parse_integer::<u8>()) {
+                return Some(port);
+            }
+            m.get("to").and_then(|x| x.as_str())?
+        }
+        _ => return None,
+    };
+    super::parse_wire_str(to).ok().and_then(|(_, p)| p)
+}
+
+/// Replace the target of wire with the entry, the input port is replaced by the port of the entry.
+fn retarget_entry(wire: &Value, entry: &str) -> Value {
+    match wire {
+        Value::Map(m) => {
+            let mut m = m.clone();
+            m.remove("port");
+            m.insert("to".to_string(), Value::from(entry));
+            Value::Map(m)
+        }
+        _ => Value::from(entry),
+    }
+}
+
+/// Replace the target of wire, the input port and other settings of the wire are kept.
+fn retarget(wire: &Value, to: &str) -> Value {
+    let port_of = |x: &str| super::parse_wire_str(x).ok().and_then(|(_, p)| p);
+    let to = |x: &str| match port_of(x) {
+        Some(port) => format!("{}:{}", to, port),
+        None => to.to_string(),
+    };
+    match wire {
+        Value::Map(m) => {
+            let mut m = m.clone();
+            let prev = m.get("to").and_then(|x| x.as_str()).unwrap_or_default();
+            m.insert("to".to_string(), Value::from(to(prev)));
+            Value::Map(m)
+        }
+        Value::String(prev) => Value::from(to(prev)),
+        _ => Value::from(to("")),
+    }
+}
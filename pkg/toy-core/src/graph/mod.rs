@@ -0,0 +1,552 @@
+//! Workflow definition information created by combining various services.
+//!
+//! Graph is just a definition, and the executed Graph is called Task.
+//! The steps that execute a service are called Nodes, and each Node exchanges messages through channels to perform tasks.
+//!
+
+use crate::data::Value;
+use crate::error::ConfigError;
+use crate::mpsc::ChannelConfig;
+use crate::service_type::ServiceType;
+use crate::service_uri::Uri;
+use std::collections::HashMap;
+use std::sync::Arc;
+use toy_map::Map;
+
+mod error_policy;
+mod include;
+mod parallelism;
+mod param;
+mod validation;
+
+pub use error_policy::ErrorPolicy;
+pub use include::{expand_includes, INCLUDE_SERVICE_TYPE};
+pub use parallelism::{Dispatch, Parallelism};
+pub use param::{bind_params, check_params, has_placeholder, GraphParam, ParamType};
+pub use validation::{Diagnostic, DiagnosticKind, GraphValidationError};
+
+/// Workflow definition information
+#[derive(Debug, Clone)]
+pub struct Graph {
+    name: String,
+    nodes: Vec<Arc<Node>>,
+    outputs: Map<Uri, OutputWire>,
+    inputs: Map<Uri, InputWire>,
+    /// channel config of each wire. key = (from, to).
+    wire_channels: HashMap<(Uri, Uri), ChannelConfig>,
+    config_value: Value,
+    original: Value,
+}
+
+/// One step to run one service.
+#[derive(Debug, Clone)]
+pub struct Node(Inner);
+
+#[derive(Debug, Clone)]
+struct Inner {
+    tp: ServiceType,
+    uri: Uri,
+    config_value: Value,
+    channel: Option<ChannelConfig>,
+    on_error: ErrorPolicy,
+    parallelism: Parallelism,
+}
+
+impl Graph {
+    pub fn from(v: Value) -> Result<Graph, ConfigError> {
+        let name = match v {
+            Value::Map(ref map) => match map.get("name") {
+                Some(name) => name.as_str().unwrap(),
+                None => return Err(ConfigError::not_found_key("name")),
+            },
+            _ => {
+                return Err(ConfigError::error(
+                    "invalid config. config value must be map type.",
+                ))
+            }
+        };
+        let seq = match v {
+            Value::Map(ref map) => match map.get("services") {
+                Some(services) => Graph::try_traverse_services(services)?,
+                None => return Err(ConfigError::not_found_key("services")),
+            },
+            _ => {
+                return Err(ConfigError::error(
+                    "invalid config. config value must be map type.",
+                ))
+            }
+        };
+
+        let config_value = get_config_value(&v)?;
+
+        Ok(Graph {
+            name: name.to_string(),
+            nodes: Vec::<Arc<Node>>::from(seq.0),
+            outputs: Map::<Uri, OutputWire>::from(seq.1),
+            inputs: Map::<Uri, InputWire>::from(seq.2),
+            wire_channels: seq.3,
+            config_value,
+            original: v.clone(),
+        })
+    }
+
+    /// Get this [`Graph`] name.
+    ///
+    /// [`Graph`]: crate::graph::Graph
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get [`Node`] by uri.
+    ///
+    /// [`Node`]: crate::graph::Node
+    pub fn by_uri<U: AsRef<Uri>>(&self, uri: U) -> Option<Arc<Node>> {
+        self.nodes
+            .iter()
+            .find(|x| x.uri() == *uri.as_ref())
+            .map(|x| x.clone())
+    }
+
+    pub fn iter(&'_ self) -> NodeIterator<'_> {
+        let len = self.nodes.len();
+        NodeIterator {
+            graph: self,
+            cur: 0,
+            len,
+        }
+    }
+
+    pub fn outputs(&self) -> &Map<Uri, OutputWire> {
+        &self.outputs
+    }
+
+    pub fn inputs(&self) -> &Map<Uri, InputWire> {
+        &self.inputs
+    }
+
+    /// Get channel config of the wire `from` -> `to`.
+    ///
+    /// The setting of the wire takes precedence over the setting of the downstream node.
+    pub fn channel_config(&self, from: &Uri, to: &Uri) -> ChannelConfig {
+        self.wire_channels
+            .get(&(from.clone(), to.clone()))
+            .copied()
+            .or_else(|| self.by_uri(to).and_then(|x| x.channel_config()))
+            .unwrap_or_default()
+    }
+
+    pub fn config(&self) -> Value {
+        self.config_value.clone()
+    }
+
+    pub fn original(&self) -> Value {
+        self.original.clone()
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn try_traverse_services(
+        v: &Value,
+    ) -> Result<
+        (
+            Vec<Arc<Node>>,
+            Map<Uri, OutputWire>,
+            Map<Uri, InputWire>,
+            HashMap<(Uri, Uri), ChannelConfig>,
+        ),
+        ConfigError,
+    > {
+        let mut nodes: Vec<Arc<Node>> = Vec::new();
+        let mut output_wires: Map<Uri, OutputWire> = Map::new();
+        let mut input_wires: Map<Uri, InputWire> = Map::new();
+        let mut wire_channels: HashMap<(Uri, Uri), ChannelConfig> = HashMap::new();
+
+        fn push_input_wire_from_output(
+            me: &Uri,
+            other: Uri,
+            port: Option<u8>,
+            input_wires: &mut Map<Uri, InputWire>,
+        ) {
+            let new_wire = if input_wires.contains_key(&other) {
+                let v = input_wires.get(&other).unwrap();
+                v.put_output(me.clone(), port)
+            } else {
+                InputWire::Single(me.clone(), other.clone(), port.unwrap_or(0))
+            };
+            input_wires.insert(other.clone(), new_wire);
+        }
+
+        // senders of each downstream, in order of wires.
+        let mut senders: Map<Uri, Vec<(Uri, Option<u8>)>> = Map::new();
+
+        let mut targets_of_nodes = Vec::new();
+        match v {
+            Value::Seq(ref seq) => {
+                for v in seq {
+                    let (n, targets, channels) = Graph::try_traverse_service(v)?;
+                    for (to, c) in channels {
+                        wire_channels.insert((n.uri(), to), c);
+                    }
+                    for (to, port) in &targets {
+                        senders
+                            .get_or_insert(to.clone(), Vec::new())
+                            .push((n.uri(), *port));
+                    }
+                    targets_of_nodes.push((n.uri(), targets));
+                    nodes.push(Arc::new(n));
+                }
+            }
+            _ => (),
+        };
+
+        // the specified input ports are reserved first, the others take the free ports.
+        for (to, senders) in senders.into_iter() {
+            let (specified, unspecified): (Vec<_>, Vec<_>) =
+                senders.into_iter().partition(|(_, port)| port.is_some());
+            for (from, port) in specified.into_iter().chain(unspecified) {
+                push_input_wire_from_output(&from, to.clone(), port, &mut input_wires);
+            }
+        }
+
+        // input port of each downstream is resolved after all input wires are collected.
+        for (uri, targets) in targets_of_nodes {
+            let mut outs = targets
+                .into_iter()
+                .map(|(to, _)| {
+                    let port = input_wires
+                        .get(&to)
+                        .and_then(|x| x.port_of(&uri))
+                        .unwrap_or(0);
+                    (to, port)
+                })
+                .collect::<Vec<_>>();
+            let w = match outs.len() {
+                0 => OutputWire::None,
+                1 => {
+                    let (to, port) = outs.pop().unwrap();
+                    OutputWire::Single(uri.clone(), to, port)
+                }
+                _ => OutputWire::Fanout(uri.clone(), outs),
+            };
+            output_wires.insert(uri, w);
+        }
+
+        for (uri, _) in &output_wires {
+            if !input_wires.contains_key(uri) {
+                input_wires.insert(uri.clone(), InputWire::None);
+            }
+        }
+
+        Ok((nodes, output_wires, input_wires, wire_channels))
+    }
+
+    /// Returns the node, the downstream uris with the input port if specified,
+    /// and the channel configs of wires.
+    #[allow(clippy::type_complexity)]
+    fn try_traverse_service(
+        v: &Value,
+    ) -> Result<(Node, Vec<(Uri, Option<u8>)>, Vec<(Uri, ChannelConfig)>), ConfigError> {
+        if !v.is_map() {
+            return Err(ConfigError::invalid_key_type("service", "map"));
+        }
+        let map = v.as_map().unwrap();
+
+        let tp = match map.get("type") {
+            Some(kind) => match kind {
+                Value::String(k) => k,
+                _ => return Err(ConfigError::invalid_key_type("type", "String")),
+            },
+            None => return Err(ConfigError::not_found_key("type")),
+        };
+        let uri = match map.get("uri") {
+            Some(uri) => match uri {
+                Value::String(v) => v,
+                _ => return Err(ConfigError::invalid_key_type("uri", "String")),
+            },
+            None => return Err(ConfigError::not_found_key("uri")),
+        };
+
+        let config_value = get_config_value(&v)?;
+        let channel = match map.get("channel") {
+            Some(c) => Some(get_channel_config(c)?),
+            None => None,
+        };
+        let on_error = match map.get("on_error") {
+            Some(v) => crate::data::unpack::<ErrorPolicy>(v)
+                .map_err(|e| ConfigError::error(format!("invalid on_error. {}", e)))?,
+            None => ErrorPolicy::default(),
+        };
+        let parallelism = match map.get("parallelism") {
+            Some(v) => get_parallelism(v)?,
+            None => Parallelism::default(),
+        };
+
+        let mut channels = Vec::new();
+        let wire = match map.get("wires") {
+            Some(wires) => match wires {
+                Value::None => vec![],
+                Value::String(v) => vec![parse_wire_str(v)?],
+                Value::Map(_) => {
+                    let (to, port, c) = get_wire(wires)?;
+                    channels.extend(c.map(|c| (to.clone(), c)));
+                    vec![(to, port)]
+                }
+                Value::Seq(ref seq) => {
+                    let mut wires = Vec::new();
+                    for x in seq {
+                        match x {
+                            Value::String(v) => wires.push(parse_wire_str(v)?),
+                            Value::Map(_) => {
+                                let (to, port, c) = get_wire(x)?;
+                                channels.extend(c.map(|c| (to.clone(), c)));
+                                wires.push((to, port));
+                            }
+                            _ => (),
+                        }
+                    }
+                    wires
+                }
+                _ => {
+                    return Err(ConfigError::invalid_key_type(
+                        "wires",
+                        "String or Map or Seq(element:String or Map) or None",
+                    ))
+                }
+            },
+            None => return Err(ConfigError::not_found_key("wires")),
+        };
+        match ServiceType::from_full_name(tp) {
+            Ok(st) => Ok((
+                Node::new(st, uri.into(), config_value)
+                    .with_channel_config(channel)
+                    .with_error_policy(on_error)
+                    .with_parallelism(parallelism),
+                wire,
+                channels,
+            )),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Wire of map form. `{ to: "uri", port: 1, channel: { capacity: 16, policy: "drop_oldest" } }`
+#[allow(clippy::type_complexity)]
+fn get_wire(v: &Value) -> Result<(Uri, Option<u8>, Option<ChannelConfig>), ConfigError> {
+    let map = v.as_map().unwrap();
+    let (to, port) = match map.get("to") {
+        Some(Value::String(to)) => parse_wire_str(to)?,
+        Some(_) => return Err(ConfigError::invalid_key_type("wires.to", "String")),
+        None => return Err(ConfigError::not_found_key("wires.to")),
+    };
+    let port = match map.get("port") {
+        Some(Value::Integer(p)) if (0..=u8::MAX as i64).contains(p) => Some(*p as u8),
+        Some(_) => return Err(ConfigError::invalid_key_type("wires.port", "u8")),
+        None => port,
+    };
+    let channel = match map.get("channel") {
+        Some(c) => Some(get_channel_config(c)?),
+        None => None,
+    };
+    Ok((to, port, channel))
+}
+
+/// Wire of string form. `"uri"` or `"uri:port"`.
+/// If the part after the last `:` is not a number, the whole string is the uri.
+fn parse_wire_str(v: &str) -> Result<(Uri, Option<u8>), ConfigError> {
+    match v.rsplit_once(':') {
+        Some((to, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => {
+            match port.parse::<u8>() {
+                Ok(port) => Ok((Uri::from(to), Some(port))),
+                Err(_) => Err(ConfigError::invalid_key_type("wires.port", "u8")),
+            }
+        }
+        _ => Ok((Uri::from(v), None)),
+    }
+}
+
+fn get_channel_config(v: &Value) -> Result<ChannelConfig, ConfigError> {
+    let c = crate::data::unpack::<ChannelConfig>(v)
+        .map_err(|e| ConfigError::error(format!("invalid channel config. {}", e)))?;
+    if c.capacity() == 0 {
+        return Err(ConfigError::validation_error(
+            "channel capacity must be greater than 0.",
+        ));
+    }
+    Ok(c)
+}
+
+fn get_parallelism(v: &Value) -> Result<Parallelism, ConfigError> {
+    let p = crate::data::unpack::<Parallelism>(v)
+        .map_err(|e| ConfigError::error(format!("invalid parallelism. {}", e)))?;
+    if p.replicas() == 0 {
+        return Err(ConfigError::validation_error(
+            "parallelism replicas must be greater than 0.",
+        ));
+    }
+    Ok(p)
+}
+
+fn get_config_value(v: &Value) -> Result<Value, ConfigError> {
+    match v {
+        Value::Map(ref map) => match map.get("config") {
+            Some(c) => Ok(c.clone()),
+            None => Ok(Value::None),
+        },
+        _ => Err(ConfigError::error(
+            "invalid config. config value must be map type.",
+        )),
+    }
+}
+
+/// Iterate [`Node`] of This Graph.
+///
+/// [`Node`]: crate::graph::Node
+pub struct NodeIterator<'a> {
+    graph: &'a Graph,
+    cur: usize,
+    len: usize,
+}
+
+impl<'a> Iterator for NodeIterator<'a> {
+    type Item = Arc<Node>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur == self.len {
+            None
+        } else {
+            let e = self.graph.nodes.get(self.cur).map(|x| x.clone());
+            self.cur += 1;
+            e
+        }
+    }
+}
+
+impl<'a> DoubleEndedIterator for NodeIterator<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Arc<Node>> {
+        if self.cur == self.len {
+            None
+        } else {
+            self.len -= 1;
+            self.graph.nodes.get(self.len).map(|x| x.clone())
+        }
+    }
+}
+
+impl Node {
+    pub fn new(tp: ServiceType, uri: Uri, config_value: Value) -> Node {
+        Node(Inner {
+            tp,
+            uri,
+            config_value,
+            channel: None,
+            on_error: ErrorPolicy::default(),
+            parallelism: Parallelism::default(),
+        })
+    }
+
+    /// Set the replicas of this node.
+    pub fn with_parallelism(mut self, parallelism: Parallelism) -> Node {
+        self.0.parallelism = parallelism;
+        self
+    }
+
+    /// Set the behavior of this node on error.
+    pub fn with_error_policy(mut self, on_error: ErrorPolicy) -> Node {
+        self.0.on_error = on_error;
+        self
+    }
+
+    /// Set channel config of the input channel of this node.
+    pub fn with_channel_config(mut self, channel: Option<ChannelConfig>) -> Node {
+        self.0.channel = channel;
+        self
+    }
+
+    pub fn uri(&self) -> Uri {
+        self.0.uri.clone()
+    }
+
+    pub fn service_type(&self) -> ServiceType {
+        self.0.tp.clone()
+    }
+
+    pub fn config(&self) -> Value {
+        self.0.config_value.clone()
+    }
+
+    /// Channel config of the input channel of this node, if specified.
+    pub fn channel_config(&self) -> Option<ChannelConfig> {
+        self.0.channel
+    }
+
+    pub fn error_policy(&self) -> ErrorPolicy {
+        self.0.on_error
+    }
+
+    pub fn parallelism(&self) -> &Parallelism {
+        &self.0.parallelism
+    }
+}
+
+/// Join information between nodes.
+/// Indicates which URI you need to output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OutputWire {
+    /// Wire of "One to One".
+    /// Sender is "0" Value of Tuple.
+    /// "0" of Tuple is Me.
+    /// "2" of Tuple is the input port of receiver.
+    Single(Uri, Uri, u8),
+
+    /// Wire of "One To Many".
+    /// Each receiver with its input port.
+    Fanout(Uri, Vec<(Uri, u8)>),
+
+    /// Without output.
+    None,
+}
+
+/// Join information between nodes.
+/// Indicates from which URI the input comes from for you.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InputWire {
+    /// Wire of "One to One".
+    /// Sender is "0" of Tuple.
+    /// "1" of Tuple is Me.
+    /// "2" of Tuple is my input port.
+    Single(Uri, Uri, u8),
+
+    /// Wire of "Many to One".
+    /// Each sender with my input port.
+    Fanin(Vec<(Uri, u8)>, Uri),
+
+    /// Without Input.
+    None,
+}
+
+impl InputWire {
+    /// Add sender. If input port is not specified, the smallest port not used by the other senders is used.
+    pub fn put_output(&self, uri: Uri, port: Option<u8>) -> InputWire {
+        let (mut o, i) = match self {
+            InputWire::Single(o, i, p) => (vec![(o.clone(), *p)], i.clone()),
+            InputWire::Fanin(o, i) => (o.clone(), i.clone()),
+            InputWire::None => unimplemented!(),
+        };
+        let port = port.unwrap_or_else(|| {
+            (0..u8::MAX)
+                .find(|p| o.iter().all(|(_, x)| x != p))
+                .unwrap_or(u8::MAX)
+        });
+        o.push((uri, port));
+        InputWire::Fanin(o, i)
+    }
+
+    /// Get my input port of the wire from sender.
+    pub fn port_of(&self, from: &Uri) -> Option<u8> {
+        match self {
+            InputWire::Single(o, _, p) if o == from => Some(*p),
+            InputWire::Fanin(o, _) => o.iter().find(|(x, _)| x == from).map(|(_, p)| *p),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::data::{Frame, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Replicas of node, run the service by the worker pool.
+///
+/// Each replica has its own service and context created from the config of node.
+/// Shorthand `parallelism: 4` is same as `parallelism: { replicas: 4 }`.
+/// Source has no input to dispatch, so the replicas of source are rejected by the validation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "ParallelismRepr")]
+pub struct Parallelism {
+    replicas: u32,
+    dispatch: Dispatch,
+    /// Keep the order of input in output.
+    ordered: bool,
+}
+
+/// How to choose the replica for each frame.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dispatch {
+    #[default]
+    RoundRobin,
+
+    /// Hash of the value at the path, the frames of same key are handled by the same replica.
+    KeyHash { key: String },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ParallelismRepr {
+    Replicas(u32),
+    Detail {
+        replicas: u32,
+        #[serde(default)]
+        dispatch: Dispatch,
+        #[serde(default)]
+        ordered: bool,
+    },
+}
+
+impl Parallelism {
+    pub fn new(replicas: u32, dispatch: Dispatch, ordered: bool) -> Self {
+        Self {
+            replicas,
+            dispatch,
+            ordered,
+        }
+    }
+
+    pub fn replicas(&self) -> u32 {
+        self.replicas
+    }
+
+    pub fn dispatch(&self) -> &Dispatch {
+        &self.dispatch
+    }
+
+    pub fn ordered(&self) -> bool {
+        self.ordered
+    }
+}
+
+impl Default for Parallelism {
+    fn default() -> Self {
+        Parallelism::new(1, Dispatch::default(), false)
+    }
+}
+
+impl From<ParallelismRepr> for Parallelism {
+    fn from(v: ParallelismRepr) -> Self {
+        match v {
+            ParallelismRepr::Replicas(replicas) => {
+                Parallelism::new(replicas, Dispatch::default(), false)
+            }
+            ParallelismRepr::Detail {
+                replicas,
+                dispatch,
+                ordered,
+            } => Parallelism::new(replicas, dispatch, ordered),
+        }
+    }
+}
+
+impl Dispatch {
+    /// Index of replica for the frame. `seq` is the count of frames dispatched before.
+    ///
+    /// Frames without the key are handled by the first replica.
+    pub fn select(&self, frame: &Frame, seq: u64, replicas: u32) -> usize {
+        let replicas = replicas.max(1) as u64;
+        match self {
+            Dispatch::RoundRobin => (seq % replicas) as usize,
+            Dispatch::KeyHash { key } => match frame.value().and_then(|x| x.path(key)) {
+                Some(v) => (hash_of(v) % replicas) as usize,
+                None => 0,
+            },
+        }
+    }
+}
+
+fn hash_of(v: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", v).hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,280 @@
+//! Parameters of graph, bound at dispatch time.
+//!
+//! ```yaml
+//! name: read-csv
+//! params:
+//!   - name: path
+//!     type: string
+//!   - name: limit
+//!     type: integer
+//!     default: 100
+//! services:
+//!   - type: plugin.common.file.Read
+//!     uri: reader
+//!     config:
+//!       path: "${path}"     # replaced with the typed value.
+//!       name: "in-${path}"  # embedded in the string.
+//!       limit: "${limit}"
+//!       script: "`$${x}`"   # `$${` is escaped to the literal `${`.
+//! ```
+
+use crate::data::{self, Map, Value};
+use crate::error::ConfigError;
+use serde::{Deserialize, Serialize};
+
+/// Type of graph parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamType {
+    String,
+    Integer,
+    Number,
+    Bool,
+}
+
+/// Declaration of graph parameter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphParam {
+    name: String,
+    #[serde(rename = "type")]
+    tp: ParamType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default: Option<Value>,
+}
+
+impl GraphParam {
+    pub fn new(name: impl Into<String>, tp: ParamType, default: Option<Value>) -> Self {
+        Self {
+            name: name.into(),
+            tp,
+            default,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn tp(&self) -> ParamType {
+        self.tp
+    }
+
+    pub fn default(&self) -> Option<&Value> {
+        self.default.as_ref()
+    }
+
+    /// Convert value to the type of parameter.
+    /// `String` value is parsed, because parameters from command line are always string.
+    fn coerce(&self, v: &Value) -> Result<Value, ConfigError> {
+        let r = match (self.tp, v) {
+            (ParamType::String, Value::String(_)) => Some(v.clone()),
+            (ParamType::Integer, Value::Integer(_)) => Some(v.clone()),
+            (ParamType::Integer, Value::String(s)) => s.parse::<i64>().ok().map(Value::Integer),
+            (ParamType::Number, Value::Number(_)) => Some(v.clone()),
+            (ParamType::Number, Value::Integer(i)) => Some(Value::Number(*i as f64)),
+            (ParamType::Number, Value::String(s)) => s.parse::<f64>().ok().map(Value::Number),
+            (ParamType::Bool, Value::Bool(_)) => Some(v.clone()),
+            (ParamType::Bool, Value::String(s)) => s.parse::<bool>().ok().map(Value::Bool),
+            _ => None,
+        };
+        r.ok_or_else(|| {
+            ConfigError::validation_error(format!(
+                "invalid type of param. name:{}, expected:{:?}, value:{:?}",
+                self.name, self.tp, v
+            ))
+        })
+    }
+}
+
+/// Bind parameters to the configs of services in the graph definition.
+///
+/// Undeclared or ill-typed parameters, missing parameters without default,
+/// and placeholders of undeclared parameters are rejected.
+pub fn bind_params(v: &Value, given: &Map<String, Value>) -> Result<Value, ConfigError> {
+    let values = resolve_params(&params_of(v)?, given)?;
+    map_services(v, |config| apply_params(config, &values))
+}
+
+/// Check the declarations of parameters and the placeholders in the graph definition,
+/// without binding.
+pub fn check_params(v: &Value) -> Result<(), ConfigError> {
+    let params = params_of(v)?;
+    for (idx, p) in params.iter().enumerate() {
+        if params[..idx].iter().any(|x| x.name == p.name) {
+            return Err(ConfigError::validation_error(format!(
+                "duplicate param. name:{}",
+                p.name
+            )));
+        }
+        if let Some(ref d) = p.default {
+            p.coerce(d)?;
+        }
+    }
+    map_services(v, |config| {
+        let mut names = Vec::new();
+        placeholders(config, &mut names);
+        match names.iter().find(|x| !params.iter().any(|p| &p.name == *x)) {
+            Some(name) => Err(ConfigError::validation_error(format!(
+                "undeclared param. name:{}",
+                name
+            ))),
+            None => Ok(config.clone()),
+        }
+    })
+    .map(|_| ())
+}
+
+/// Returns true if the value contains any placeholder.
+pub fn has_placeholder(v: &Value) -> bool {
+    let mut names = Vec::new();
+    placeholders(v, &mut names);
+    !names.is_empty()
+}
+
+fn params_of(v: &Value) -> Result<Vec<GraphParam>, ConfigError> {
+    match v.as_map().and_then(|x| x.get("params")) {
+        None | Some(Value::None) => Ok(Vec::new()),
+        Some(params) => data::unpack::<Vec<GraphParam>>(params)
+            .map_err(|e| ConfigError::error(format!("invalid params. {}", e))),
+    }
+}
+
+fn resolve_params(
+    params: &[GraphParam],
+    given: &Map<String, Value>,
+) -> Result<Map<String, Value>, ConfigError> {
+    if let Some(name) = given.keys().find(|k| !params.iter().any(|p| &p.name == *k)) {
+        return Err(ConfigError::validation_error(format!(
+            "undeclared param. name:{}",
+            name
+        )));
+    }
+    let mut values = Map::new();
+    for p in params {
+        let v = match given.get(&p.name).or(p.default.as_ref()) {
+            Some(v) => p.coerce(v)?,
+            None => {
+                return Err(ConfigError::validation_error(format!(
+                    "param is required. name:{}",
+                    p.name
+                )))
+            }
+        };
+        values.insert(p.name.clone(), v);
+    }
+    Ok(values)
+}
+
+fn map_services<F>(v: &Value, mut f: F) -> Result<Value, ConfigError>
+where
+    F: FnMut(&Value) -> Result<Value, ConfigError>,
+{
+    let mut v = v.clone();
+    if let Value::Map(ref mut map) = v {
+        if let Some(Value::Seq(services)) = map.get_mut("services") {
+            for service in services.iter_mut() {
+                if let Value::Map(ref mut service) = service {
+                    if let Some(config) = service.get_mut("config") {
+                        *config = f(config)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(v)
+}
+
+fn apply_params(v: &Value, values: &Map<String, Value>) -> Result<Value, ConfigError> {
+    match v {
+        Value::String(s) => apply_str(s, values),
+        Value::Seq(seq) => seq
+            .iter()
+            .map(|x| apply_params(x, values))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Seq),
+        Value::Map(map) => {
+            let mut r = Map::new();
+            for (k, x) in map.iter() {
+                r.insert(k.clone(), apply_params(x, values)?);
+            }
+            Ok(Value::Map(r))
+        }
+        _ => Ok(v.clone()),
+    }
+}
+
+/// `"${name}"` is replaced with the typed value,
+/// otherwise the placeholders are embedded in the string.
+fn apply_str(s: &str, values: &Map<String, Value>) -> Result<Value, ConfigError> {
+    let lookup = |name: &str| {
+        values.get(name).ok_or_else(|| {
+            ConfigError::validation_error(format!("unresolved param. name:{}", name))
+        })
+    };
+
+    let tokens = tokenize(s);
+    if let [Token::Param(name)] = tokens.as_slice() {
+        return lookup(name).cloned();
+    }
+    let mut r = String::new();
+    for t in tokens {
+        match t {
+            Token::Text(text) => r.push_str(text),
+            Token::Param(name) => match lookup(name)? {
+                Value::String(x) => r.push_str(x),
+                Value::Integer(x) => r.push_str(&x.to_string()),
+                Value::Number(x) => r.push_str(&x.to_string()),
+                Value::Bool(x) => r.push_str(&x.to_string()),
+                other => r.push_str(&format!("{:?}", other)),
+            },
+        }
+    }
+    Ok(Value::String(r))
+}
+
+fn placeholders(v: &Value, names: &mut Vec<String>) {
+    match v {
+        Value::String(s) => {
+            for t in tokenize(s) {
+                if let Token::Param(name) = t {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        Value::Seq(seq) => seq.iter().for_each(|x| placeholders(x, names)),
+        Value::Map(map) => map.values().for_each(|x| placeholders(x, names)),
+        _ => (),
+    }
+}
+
+enum Token<'a> {
+    Text(&'a str),
+    Param(&'a str),
+}
+
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        // `$${` is the literal `${`, the following `{` does not start a placeholder.
+        if rest[..start].ends_with('$') {
+            tokens.push(Token::Text(&rest[..start]));
+            rest = &rest[start + 1..];
+            continue;
+        }
+        match rest[start + 2..].find('}') {
+            Some(len) => {
+                if start > 0 {
+                    tokens.push(Token::Text(&rest[..start]));
+                }
+                tokens.push(Token::Param(rest[start + 2..start + 2 + len].trim()));
+                rest = &rest[start + 2 + len + 1..];
+            }
+            None => break,
+        }
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    tokens
+}
@@ -0,0 +1,410 @@
+//! Static validation of a [`Graph`] before execution.
+//!
+//! Checks the structure of the graph (duplicate uri, dangling wires, cycles)
+//! and the number of wires of each node against the [`PortType`] of its service.
+//!
+//! [`Graph`]: crate::graph::Graph
+//! [`PortType`]: crate::registry::PortType
+
+use crate::graph::{Graph, InputWire, OutputWire};
+use crate::registry::{PortType, Registry};
+use crate::service_type::ServiceType;
+use crate::service_uri::Uri;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use thiserror::Error as ThisError;
+
+/// One problem found in a graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    uri: Uri,
+    wire: Option<Uri>,
+    kind: DiagnosticKind,
+}
+
+/// Kind of problem found in a graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// Same uri is defined by more than one node.
+    DuplicateUri,
+
+    /// Wire points to a uri that does not exist.
+    DanglingWire,
+
+    /// Wires make a loop. Contains the uris of the loop in order.
+    Cycle(Vec<Uri>),
+
+    /// Service type of node is not registered.
+    ServiceNotFound(ServiceType),
+
+    /// Node has upstream wires, but its service is a source.
+    UnexpectedInput { actual: u32 },
+
+    /// Node has downstream wires, but its service is a sink.
+    UnexpectedOutput { actual: u32 },
+
+    /// Node has no upstream wires, but its service requires input.
+    MissingInput,
+
+    /// Node has more upstream wires than input ports of its service.
+    TooManyInputs { expected: u32, actual: u32 },
+
+    /// Node has more downstream wires than output ports of its service.
+    TooManyOutputs { expected: u32, actual: u32 },
+
+    /// Wire points to an input port that the service does not have.
+    InvalidInputPort { port: u8, expected: u32 },
+
+    /// Input port of fan-in service is wired from more than one upstream.
+    DuplicateInputPort { port: u8 },
+
+    /// Dead letter port of `on_error` is not wired.
+    DeadLetterPortNotWired { port: u8 },
+
+    /// Source node has more than one replica, a source runs as a single service.
+    ParallelSource { replicas: u32 },
+}
+
+/// Error returned from [`Graph::validate`], contains all diagnostics.
+///
+/// [`Graph::validate`]: crate::graph::Graph::validate
+#[derive(Debug, Clone, ThisError)]
+#[error("invalid graph. {}", display_diagnostics(.diagnostics))]
+pub struct GraphValidationError {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostic {
+    pub fn new(uri: Uri, wire: Option<Uri>, kind: DiagnosticKind) -> Self {
+        Self { uri, wire, kind }
+    }
+
+    /// The node that has the problem.
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// The target of the wire that has the problem, if the problem is about a wire.
+    pub fn wire(&self) -> Option<&Uri> {
+        self.wire.as_ref()
+    }
+
+    pub fn kind(&self) -> &DiagnosticKind {
+        &self.kind
+    }
+}
+
+impl GraphValidationError {
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.wire {
+            Some(w) => write!(f, "uri:{}, wire:{}, {}", self.uri, w, self.kind),
+            None => write!(f, "uri:{}, {}", self.uri, self.kind),
+        }
+    }
+}
+
+impl Display for DiagnosticKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticKind::DuplicateUri => write!(f, "duplicate uri."),
+            DiagnosticKind::DanglingWire => write!(f, "wire target not found."),
+            DiagnosticKind::Cycle(uris) => {
+                let path = uris
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                write!(f, "wires make a cycle. {}", path)
+            }
+            DiagnosticKind::ServiceNotFound(tp) => {
+                write!(f, "not found service. service_type: {}", tp)
+            }
+            DiagnosticKind::UnexpectedInput { actual } => {
+                write!(f, "source service can not have input. input:{}", actual)
+            }
+            DiagnosticKind::UnexpectedOutput { actual } => {
+                write!(f, "sink service can not have output. output:{}", actual)
+            }
+            DiagnosticKind::MissingInput => write!(f, "input is required."),
+            DiagnosticKind::TooManyInputs { expected, actual } => write!(
+                f,
+                "too many inputs. expected:{}, actual:{}",
+                expected, actual
+            ),
+            DiagnosticKind::TooManyOutputs { expected, actual } => write!(
+                f,
+                "too many outputs. expected:{}, actual:{}",
+                expected, actual
+            ),
+            DiagnosticKind::InvalidInputPort { port, expected } => write!(
+                f,
+                "invalid input port. port:{}, input ports:{}",
+                port, expected
+            ),
+            DiagnosticKind::DuplicateInputPort { port } => {
+                write!(f, "duplicate input port. port:{}", port)
+            }
+            DiagnosticKind::DeadLetterPortNotWired { port } => {
+                write!(f, "dead letter port is not wired. port:{}", port)
+            }
+            DiagnosticKind::ParallelSource { replicas } => {
+                write!(f, "source can not run in parallel. replicas:{}", replicas)
+            }
+        }
+    }
+}
+
+fn display_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|x| format!("[{}]", x))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Graph {
+    /// Validate this graph using the service schemas of registry.
+    pub fn validate<R: Registry>(&self, registry: &R) -> Result<(), GraphValidationError> {
+        let port_types = registry
+            .schemas()
+            .into_iter()
+            .map(|x| (x.service_type().clone(), x.port_type().clone()))
+            .collect::<HashMap<_, _>>();
+        self.validate_with(|tp| port_types.get(tp).cloned())
+    }
+
+    /// Validate this graph.
+    ///
+    /// `port_type_of` returns the [`PortType`] of service type, or `None` if the service is not registered.
+    ///
+    /// [`PortType`]: crate::registry::PortType
+    pub fn validate_with<F>(&self, port_type_of: F) -> Result<(), GraphValidationError>
+    where
+        F: Fn(&ServiceType) -> Option<PortType>,
+    {
+        let mut diagnostics = Vec::new();
+
+        let mut uris = HashSet::new();
+        for node in self.iter() {
+            if !uris.insert(node.uri()) {
+                diagnostics.push(Diagnostic::new(
+                    node.uri(),
+                    None,
+                    DiagnosticKind::DuplicateUri,
+                ));
+            }
+        }
+
+        for node in self.iter() {
+            let uri = node.uri();
+            for to in downstreams(self.outputs().get(&uri)) {
+                if !uris.contains(&to) {
+                    diagnostics.push(Diagnostic::new(
+                        uri.clone(),
+                        Some(to),
+                        DiagnosticKind::DanglingWire,
+                    ));
+                }
+            }
+        }
+
+        for cycle in find_cycles(self, &uris) {
+            diagnostics.push(Diagnostic::new(
+                cycle[0].clone(),
+                None,
+                DiagnosticKind::Cycle(cycle),
+            ));
+        }
+
+        for node in self.iter() {
+            let uri = node.uri();
+            let tp = node.service_type();
+            let port_type = match port_type_of(&tp) {
+                Some(p) => p,
+                None => {
+                    diagnostics.push(Diagnostic::new(
+                        uri,
+                        None,
+                        DiagnosticKind::ServiceNotFound(tp),
+                    ));
+                    continue;
+                }
+            };
+            let replicas = node.parallelism().replicas();
+            if matches!(port_type, PortType::Source(_)) && replicas > 1 {
+                diagnostics.push(Diagnostic::new(
+                    uri.clone(),
+                    None,
+                    DiagnosticKind::ParallelSource { replicas },
+                ));
+            }
+            let upstreams = upstreams(self.inputs().get(&uri));
+            let input = input_count(&port_type, &upstreams);
+            let mut output = downstreams(self.outputs().get(&uri)).len() as u32;
+            // dead letter port is added to the ports of service.
+            if let Some(port) = node.error_policy().dead_letter_port() {
+                if port as u32 >= output {
+                    diagnostics.push(Diagnostic::new(
+                        uri.clone(),
+                        None,
+                        DiagnosticKind::DeadLetterPortNotWired { port },
+                    ));
+                } else {
+                    output -= 1;
+                }
+            }
+            let kinds = check_ports(&port_type, input, output);
+            if !kinds
+                .iter()
+                .any(|x| matches!(x, DiagnosticKind::TooManyInputs { .. }))
+            {
+                if let PortType::Flow(i, _) | PortType::Sink(i) = port_type {
+                    for (from, port) in upstreams.iter().filter(|(_, p)| i > 1 && *p as u32 >= i) {
+                        diagnostics.push(Diagnostic::new(
+                            from.clone(),
+                            Some(uri.clone()),
+                            DiagnosticKind::InvalidInputPort {
+                                port: *port,
+                                expected: i,
+                            },
+                        ));
+                    }
+                    let mut ports = HashSet::new();
+                    for (from, port) in upstreams.iter().filter(|(_, p)| i > 1 && !ports.insert(*p))
+                    {
+                        diagnostics.push(Diagnostic::new(
+                            from.clone(),
+                            Some(uri.clone()),
+                            DiagnosticKind::DuplicateInputPort { port: *port },
+                        ));
+                    }
+                }
+            }
+            for kind in kinds {
+                diagnostics.push(Diagnostic::new(uri.clone(), None, kind));
+            }
+        }
+
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(GraphValidationError { diagnostics })
+        }
+    }
+}
+
+/// A single input port accepts any number of upstreams (merged),
+/// fan-in services accept at most the number of its input ports.
+fn check_ports(port_type: &PortType, input: u32, output: u32) -> Vec<DiagnosticKind> {
+    let mut r = Vec::new();
+    let (inputs, outputs) = match *port_type {
+        PortType::Source(o) => (None, Some(o)),
+        PortType::Flow(i, o) => (Some(i), Some(o)),
+        PortType::Sink(i) => (Some(i), None),
+    };
+    match inputs {
+        None if input > 0 => r.push(DiagnosticKind::UnexpectedInput { actual: input }),
+        Some(_) if input == 0 => r.push(DiagnosticKind::MissingInput),
+        Some(i) if i > 1 && input > i => r.push(DiagnosticKind::TooManyInputs {
+            expected: i,
+            actual: input,
+        }),
+        _ => (),
+    }
+    match outputs {
+        None if output > 0 => r.push(DiagnosticKind::UnexpectedOutput { actual: output }),
+        Some(o) if output > o => r.push(DiagnosticKind::TooManyOutputs {
+            expected: o,
+            actual: output,
+        }),
+        _ => (),
+    }
+    r
+}
+
+fn downstreams(wire: Option<&OutputWire>) -> Vec<Uri> {
+    match wire {
+        Some(OutputWire::Single(_, o, _)) => vec![o.clone()],
+        Some(OutputWire::Fanout(_, o)) => o.iter().map(|(x, _)| x.clone()).collect(),
+        _ => vec![],
+    }
+}
+
+fn upstreams(wire: Option<&InputWire>) -> Vec<(Uri, u8)> {
+    match wire {
+        Some(InputWire::Single(o, _, p)) => vec![(o.clone(), *p)],
+        Some(InputWire::Fanin(o, _)) => o.clone(),
+        _ => vec![],
+    }
+}
+
+/// Upstreams wired to the same input port of fan-in service are counted as one,
+/// and reported as [`DiagnosticKind::DuplicateInputPort`].
+fn input_count(port_type: &PortType, upstreams: &[(Uri, u8)]) -> u32 {
+    match *port_type {
+        PortType::Flow(i, _) | PortType::Sink(i) if i > 1 => upstreams
+            .iter()
+            .map(|(_, p)| p)
+            .collect::<HashSet<_>>()
+            .len() as u32,
+        _ => upstreams.len() as u32,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Visiting,
+    Done,
+}
+
+/// Depth first search over wires, returns each loop found once.
+fn find_cycles(graph: &Graph, uris: &HashSet<Uri>) -> Vec<Vec<Uri>> {
+    fn visit(
+        graph: &Graph,
+        uris: &HashSet<Uri>,
+        uri: &Uri,
+        marks: &mut HashMap<Uri, Mark>,
+        path: &mut Vec<Uri>,
+        cycles: &mut Vec<Vec<Uri>>,
+    ) {
+        marks.insert(uri.clone(), Mark::Visiting);
+        path.push(uri.clone());
+        for to in downstreams(graph.outputs().get(uri)) {
+            if !uris.contains(&to) {
+                continue;
+            }
+            match marks.get(&to) {
+                Some(Mark::Visiting) => {
+                    let start = path.iter().position(|x| *x == to).unwrap();
+                    let mut cycle = path[start..].to_vec();
+                    cycle.push(to.clone());
+                    cycles.push(cycle);
+                }
+                Some(Mark::Done) => (),
+                None => visit(graph, uris, &to, marks, path, cycles),
+            }
+        }
+        path.pop();
+        marks.insert(uri.clone(), Mark::Done);
+    }
+
+    let mut marks = HashMap::new();
+    let mut cycles = Vec::new();
+    for node in graph.iter() {
+        let uri = node.uri();
+        if !marks.contains_key(&uri) {
+            visit(graph, uris, &uri, &mut marks, &mut Vec::new(), &mut cycles);
+        }
+    }
+    cycles
+}
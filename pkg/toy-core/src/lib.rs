@@ -5,6 +5,7 @@
 pub use self::service_type::ServiceType;
 pub use self::service_uri::Uri;
 
+pub mod checkpoint;
 pub mod data;
 pub mod error;
 pub mod executor;
@@ -18,6 +18,7 @@ pub enum MetricsKind {
     ReceiveRequest,
     ReceiveError,
     ReceiveStop,
+    DropFrame,
 
     RunningTask,
     RunningService,
@@ -36,6 +37,7 @@ impl MetricsKind {
             MetricsKind::ReceiveRequest => "receive_request",
             MetricsKind::ReceiveError => "receive_error",
             MetricsKind::ReceiveStop => "receive_stop",
+            MetricsKind::DropFrame => "drop_frame",
 
             MetricsKind::RunningTask => "running_task",
             MetricsKind::RunningService => "running_service",
@@ -54,6 +56,7 @@ impl MetricsKind {
             MetricsKind::ReceiveRequest => MeasureKind::Counter,
             MetricsKind::ReceiveError => MeasureKind::Counter,
             MetricsKind::ReceiveStop => MeasureKind::Counter,
+            MetricsKind::DropFrame => MeasureKind::Counter,
 
             MetricsKind::RunningTask => MeasureKind::Gauge,
             MetricsKind::RunningService => MeasureKind::Gauge,
@@ -73,6 +76,7 @@ pub enum MetricsEventKind {
     ReceiveRequest,
     ReceiveError,
     ReceiveStop,
+    ReceiveDrain,
     ReceiveUpstreamFinish,
     FinishUpstreamFinish,
     FinishUpstreamFinishAll,
@@ -90,6 +94,7 @@ impl MetricsEventKind {
             MetricsEventKind::ReceiveRequest => "receive_request",
             MetricsEventKind::ReceiveError => "receive_error",
             MetricsEventKind::ReceiveStop => "receive_stop",
+            MetricsEventKind::ReceiveDrain => "receive_drain",
             MetricsEventKind::ReceiveUpstreamFinish => "receive_upstream_finish",
             MetricsEventKind::FinishUpstreamFinish => "finish_upstream_finish",
             MetricsEventKind::FinishUpstreamFinishAll => "finish_upstream_finish_all",
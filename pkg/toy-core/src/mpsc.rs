@@ -1,30 +1,264 @@
 use crate::error::OutgoingError;
-use std::collections::{BTreeMap};
+use crate::metrics::context::metrics;
+use crate::metrics::kind::MetricsKind;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::mpsc::error::{TryRecvError, TrySendError};
 use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::sync::Mutex;
+
+/// Default capacity of the channel between nodes.
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 128;
+
+/// Behavior of the sender when the channel is full.
+///
+/// The policy applies only to data messages, signal messages are always sent with [`Block`].
+///
+/// [`Block`]: crate::mpsc::BackpressurePolicy::Block
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackpressurePolicy {
+    /// Wait until the receiver takes a message.
+    #[default]
+    Block,
+
+    /// Drop the message being sent.
+    DropNewest,
+
+    /// Drop the oldest data message in the channel, and send the message.
+    /// The signal messages in the channel are kept in order.
+    DropOldest,
+
+    /// Returns [`OutgoingError::ChannelFull`].
+    ///
+    /// [`OutgoingError::ChannelFull`]: crate::error::OutgoingError::ChannelFull
+    Error,
+}
+
+/// Capacity and backpressure policy of channel.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    #[serde(default = "default_capacity")]
+    capacity: usize,
+    #[serde(default)]
+    policy: BackpressurePolicy,
+}
+
+fn default_capacity() -> usize {
+    DEFAULT_CHANNEL_CAPACITY
+}
+
+impl ChannelConfig {
+    pub fn new(capacity: usize, policy: BackpressurePolicy) -> Self {
+        Self { capacity, policy }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn policy(&self) -> BackpressurePolicy {
+        self.policy
+    }
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHANNEL_CAPACITY, BackpressurePolicy::default())
+    }
+}
 
 pub fn channel<T>(buffer: usize) -> (Outgoing<T>, Incoming<T>) {
     let (tx, rx) = mpsc::channel(buffer);
     (Outgoing::new(tx), Incoming::new(rx))
 }
 
+/// Create channel with capacity and backpressure policy.
+///
+/// If the policy is [`DropOldest`], the receiver is shared with the sender to remove the oldest message.
+///
+/// [`DropOldest`]: crate::mpsc::BackpressurePolicy::DropOldest
+pub fn channel_with<T>(config: ChannelConfig) -> (Outgoing<T>, Incoming<T>) {
+    let (tx, rx) = mpsc::channel(config.capacity());
+    match config.policy() {
+        BackpressurePolicy::DropOldest => {
+            let rx = Arc::new(Mutex::new(SharedRx {
+                rx,
+                signals: VecDeque::new(),
+            }));
+            let tx = Outgoing::from_tx(OutgoingTx {
+                tx,
+                policy: BackpressurePolicy::DropOldest,
+                rx: Some(Arc::clone(&rx)),
+            });
+            (
+                tx,
+                Incoming {
+                    inner: Rx::Shared(rx),
+                },
+            )
+        }
+        policy => (Outgoing::new(tx).with_policy(policy), Incoming::new(rx)),
+    }
+}
+
 #[derive(Debug)]
 pub struct Incoming<T> {
-    inner: Receiver<T>,
+    inner: Rx<T>,
+}
+
+#[derive(Debug)]
+enum Rx<T> {
+    Owned(Receiver<T>),
+    Shared(Arc<Mutex<SharedRx<T>>>),
+}
+
+/// Receiver shared with the sender of `DropOldest`.
+#[derive(Debug)]
+struct SharedRx<T> {
+    rx: Receiver<T>,
+    /// signals taken from the head of the channel while removing the oldest data message.
+    /// received before the messages in the channel.
+    signals: VecDeque<T>,
+}
+
+impl<T> SharedRx<T> {
+    async fn recv(&mut self) -> Option<T> {
+        match self.signals.pop_front() {
+            Some(v) => Some(v),
+            None => self.rx.recv().await,
+        }
+    }
+
+    fn try_recv(&mut self) -> Option<T> {
+        self.signals.pop_front().or_else(|| self.rx.try_recv().ok())
+    }
 }
 
 impl<T> Incoming<T> {
     pub fn new(rx: Receiver<T>) -> Incoming<T> {
-        Incoming { inner: rx }
+        Incoming {
+            inner: Rx::Owned(rx),
+        }
     }
 
     pub async fn next(&mut self) -> Option<T> {
-        self.inner.recv().await
+        match self.inner {
+            Rx::Owned(ref mut rx) => rx.recv().await,
+            Rx::Shared(ref rx) => rx.lock().await.recv().await,
+        }
+    }
+
+    /// Receive a message if one is available, without waiting.
+    pub fn try_next(&mut self) -> Option<T> {
+        match self.inner {
+            Rx::Owned(ref mut rx) => rx.try_recv().ok(),
+            Rx::Shared(ref rx) => rx.try_lock().ok().and_then(|mut x| x.try_recv()),
+        }
     }
 }
 
+#[derive(Debug)]
+struct OutgoingTx<T> {
+    tx: Sender<T>,
+    policy: BackpressurePolicy,
+    /// receiver of the channel, exists only if the channel was created with `DropOldest`.
+    rx: Option<Arc<Mutex<SharedRx<T>>>>,
+}
+
+impl<T> Clone for OutgoingTx<T> {
+    fn clone(&self) -> Self {
+        OutgoingTx {
+            tx: self.tx.clone(),
+            policy: self.policy,
+            rx: self.rx.clone(),
+        }
+    }
+}
+
+impl<T> OutgoingTx<T>
+where
+    T: OutgoingMessage,
+{
+    async fn send(&self, port: u8, v: T) -> Result<(), OutgoingError> {
+        if v.is_signal() {
+            return self.tx.send(v).await.map_err(OutgoingError::send_error);
+        }
+        match (self.policy, &self.rx) {
+            (BackpressurePolicy::Block, _) => {
+                self.tx.send(v).await.map_err(OutgoingError::send_error)
+            }
+            (BackpressurePolicy::Error, _) => match self.tx.try_send(v) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => Err(OutgoingError::channel_full(port)),
+                Err(TrySendError::Closed(v)) => {
+                    Err(OutgoingError::send_error(mpsc::error::SendError(v)))
+                }
+            },
+            (BackpressurePolicy::DropOldest, Some(rx)) => self.send_drop_oldest(rx, v).await,
+            (BackpressurePolicy::DropNewest, _) | (BackpressurePolicy::DropOldest, None) => {
+                match self.tx.try_send(v) {
+                    Ok(()) => Ok(()),
+                    Err(TrySendError::Full(_)) => {
+                        inc_drop_frame().await;
+                        Ok(())
+                    }
+                    Err(TrySendError::Closed(v)) => {
+                        Err(OutgoingError::send_error(mpsc::error::SendError(v)))
+                    }
+                }
+            }
+        }
+    }
+
+    async fn send_drop_oldest(
+        &self,
+        rx: &Arc<Mutex<SharedRx<T>>>,
+        mut v: T,
+    ) -> Result<(), OutgoingError> {
+        loop {
+            match self.tx.try_send(v) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Closed(v)) => {
+                    return Err(OutgoingError::send_error(mpsc::error::SendError(v)))
+                }
+                Err(TrySendError::Full(back)) => v = back,
+            }
+            let dropped = {
+                let mut rx = rx.lock().await;
+                // the receiver may have taken messages while waiting for the lock.
+                if self.tx.capacity() > 0 {
+                    continue;
+                }
+                // signal must not be lost nor reordered, keep it ahead of the rest of messages.
+                loop {
+                    match rx.rx.try_recv() {
+                        Ok(oldest) if oldest.is_signal() => rx.signals.push_back(oldest),
+                        Ok(_) => break true,
+                        Err(TryRecvError::Empty) => break false,
+                        Err(TryRecvError::Disconnected) => {
+                            return Err(OutgoingError::receiver_dropped())
+                        }
+                    }
+                }
+            };
+            if dropped {
+                inc_drop_frame().await;
+            }
+        }
+    }
+}
+
+async fn inc_drop_frame() {
+    metrics()
+        .counter(&MetricsKind::DropFrame, |c| c.increment())
+        .await;
+}
+
 #[derive(Debug)]
 pub struct Outgoing<T> {
-    inner: Vec<Sender<T>>,
+    inner: Vec<OutgoingTx<T>>,
     /// key = self output port, value = target input port.
     port_map: BTreeMap<u8, u8>,
 }
@@ -38,6 +272,14 @@ impl<T> Outgoing<T> {
     }
 
     pub fn new(tx: Sender<T>) -> Outgoing<T> {
+        Outgoing::from_tx(OutgoingTx {
+            tx,
+            policy: BackpressurePolicy::default(),
+            rx: None,
+        })
+    }
+
+    fn from_tx(tx: OutgoingTx<T>) -> Outgoing<T> {
         let mut port_map = BTreeMap::new();
         port_map.insert(0, 0);
         Outgoing {
@@ -46,6 +288,19 @@ impl<T> Outgoing<T> {
         }
     }
 
+    /// Set backpressure policy of all output ports.
+    ///
+    /// [`DropOldest`] works only for the channel created by [`channel_with`] with [`DropOldest`],
+    /// otherwise it behaves as [`DropNewest`].
+    ///
+    /// [`DropOldest`]: crate::mpsc::BackpressurePolicy::DropOldest
+    /// [`DropNewest`]: crate::mpsc::BackpressurePolicy::DropNewest
+    /// [`channel_with`]: crate::mpsc::channel_with
+    pub fn with_policy(mut self, policy: BackpressurePolicy) -> Outgoing<T> {
+        self.inner.iter_mut().for_each(|x| x.policy = policy);
+        self
+    }
+
     pub fn merge(&mut self, tx: Outgoing<T>) {
         let next = self.inner.len();
         let mut inner_idx = 0;
@@ -72,7 +327,7 @@ impl<T> Outgoing<T> {
     }
 
     pub fn is_closed(&self) -> bool {
-        self.inner[0].is_closed()
+        self.inner[0].tx.is_closed()
     }
 }
 
@@ -82,11 +337,7 @@ where
 {
     pub async fn send(&mut self, mut v: T) -> Result<(), OutgoingError> {
         v.set_port(self.target_input_port(0));
-        if let Err(e) = self.inner[0].send(v).await {
-            Err(OutgoingError::send_error(e))
-        } else {
-            Ok(())
-        }
+        self.inner[0].send(0, v).await
     }
 
     pub async fn send_to(&mut self, port: u8, mut v: T) -> Result<(), OutgoingError> {
@@ -96,11 +347,7 @@ where
 
         v.set_port(self.target_input_port(port));
 
-        if let Err(e) = self.inner[port as usize].send(v).await {
-            Err(OutgoingError::send_error(e))
-        } else {
-            Ok(())
-        }
+        self.inner[port as usize].send(port, v).await
     }
 
     pub async fn send_ok(&mut self, v: T) -> Result<(), OutgoingError> {
@@ -137,7 +384,7 @@ where
             //return Result::Err(Error::custom(format!("not found output port:{}", port)));
             return Err(());
         }
-        Ok(self.inner[port as usize].is_closed())
+        Ok(self.inner[port as usize].tx.is_closed())
     }
 }
 
@@ -167,6 +414,13 @@ impl Iterator for OutgoingPortIter {
 
 pub trait OutgoingMessage {
     fn set_port(&mut self, port: u8);
+
+    /// Signal message is never dropped by [`BackpressurePolicy`].
+    ///
+    /// [`BackpressurePolicy`]: crate::mpsc::BackpressurePolicy
+    fn is_signal(&self) -> bool {
+        false
+    }
 }
 
 impl OutgoingMessage for () {
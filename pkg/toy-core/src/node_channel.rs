@@ -2,12 +2,10 @@
 
 use crate::data::Frame;
 use crate::graph::{Graph, InputWire, OutputWire};
-use crate::mpsc::{self, Incoming, Outgoing};
+use crate::mpsc::{self, BackpressurePolicy, ChannelConfig, Incoming, Outgoing};
 use crate::Uri;
 use std::collections::HashMap;
 
-const DEFAULT_CHANNEL_BUFFER_SIZE: usize = 128;
-
 #[derive(Debug)]
 struct IncomingInner {
     rx: Incoming<Frame>,
@@ -42,6 +40,7 @@ pub struct Starters {
 #[derive(Debug)]
 pub struct SignalOutgoings {
     map: HashMap<Uri, Outgoing<Frame>>,
+    starters: HashMap<Uri, Outgoing<Frame>>,
 }
 
 impl IncomingInner {
@@ -90,6 +89,11 @@ impl SignalOutgoings {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (&Uri, &mut Outgoing<Frame>)> {
         self.map.iter_mut()
     }
+
+    /// Sending channel to the first nodes of task, used to stop the sources.
+    pub fn starters_mut(&mut self) -> impl Iterator<Item = (&Uri, &mut Outgoing<Frame>)> {
+        self.starters.iter_mut()
+    }
 }
 
 /// Create channels from `Graph`.
@@ -100,7 +104,7 @@ pub fn from_graph(graph: &Graph) -> (Incomings, Outgoings, Awaiter, Starters, Si
     let mut starters: HashMap<Uri, Outgoing<Frame>> = HashMap::new();
 
     let mut awaiter_upsteram_count = 0;
-    let (awaiter_tx, awaiter_rx) = mpsc::channel::<Frame>(DEFAULT_CHANNEL_BUFFER_SIZE);
+    let (awaiter_tx, awaiter_rx) = mpsc::channel_with::<Frame>(ChannelConfig::default());
 
     // first channel
     graph
@@ -108,7 +112,12 @@ pub fn from_graph(graph: &Graph) -> (Incomings, Outgoings, Awaiter, Starters, Si
         .iter()
         .filter(|(_, w)| **w == InputWire::None)
         .for_each(|(uri, _)| {
-            let (tx, rx) = mpsc::channel::<Frame>(DEFAULT_CHANNEL_BUFFER_SIZE);
+            let capacity = graph
+                .by_uri(uri)
+                .and_then(|x| x.channel_config())
+                .unwrap_or_default()
+                .capacity();
+            let (tx, rx) = mpsc::channel::<Frame>(capacity);
             incomings.insert(uri.clone(), IncomingInner::from_rx(rx));
             starters.insert(uri.clone(), tx);
         });
@@ -127,25 +136,31 @@ pub fn from_graph(graph: &Graph) -> (Incomings, Outgoings, Awaiter, Starters, Si
         });
 
     for (_, wire) in graph.inputs() {
-        let (tx, rx) = mpsc::channel::<Frame>(DEFAULT_CHANNEL_BUFFER_SIZE);
         match wire {
-            InputWire::Single(o, i) => {
+            InputWire::Single(o, i, port) => {
+                let config = graph.channel_config(o, i);
+                let (tx, rx) = mpsc::channel_with::<Frame>(config);
                 incomings.insert(i.clone(), IncomingInner::from_rx(rx));
                 outgoings
                     .entry(o.clone())
                     .or_insert_with(|| Outgoing::empty())
-                    .merge(tx.clone());
+                    .merge_by(*port, tx);
             }
             InputWire::Fanin(o, i) => {
+                let configs = o
+                    .iter()
+                    .map(|(x, _)| graph.channel_config(x, i))
+                    .collect::<Vec<_>>();
+                let (tx, rx) = mpsc::channel_with::<Frame>(merge_channel_configs(&configs));
                 incomings.insert(
                     i.clone(),
                     IncomingInner::from_rx_and_count(rx, o.len() as u32),
                 );
-                o.iter().enumerate().for_each(|(idx, x)| {
+                o.iter().zip(configs).for_each(|((x, port), c)| {
                     outgoings
                         .entry(x.clone())
                         .or_insert_with(|| Outgoing::empty())
-                        .merge_by(idx as u8, tx.clone());
+                        .merge_by(*port, tx.clone().with_policy(c.policy()));
                 });
             }
             _ => (),
@@ -169,7 +184,34 @@ pub fn from_graph(graph: &Graph) -> (Incomings, Outgoings, Awaiter, Starters, Si
         Awaiter {
             inner: IncomingInner::from_rx_and_count(awaiter_rx, awaiter_upsteram_count),
         },
-        Starters { map: starters },
-        SignalOutgoings { map: for_sv },
+        Starters {
+            map: starters.clone(),
+        },
+        SignalOutgoings {
+            map: for_sv,
+            starters,
+        },
     )
 }
+
+/// Config of the channel shared by many upstream wires.
+///
+/// Capacity is the maximum of all wires.
+/// If any wire drops the oldest message, the channel must be created with `DropOldest`.
+/// The policy of each wire is set to its sender.
+fn merge_channel_configs(configs: &[ChannelConfig]) -> ChannelConfig {
+    let capacity = configs
+        .iter()
+        .map(|x| x.capacity())
+        .max()
+        .unwrap_or_else(|| ChannelConfig::default().capacity());
+    let policy = if configs
+        .iter()
+        .any(|x| x.policy() == BackpressurePolicy::DropOldest)
+    {
+        BackpressurePolicy::DropOldest
+    } else {
+        BackpressurePolicy::Block
+    };
+    ChannelConfig::new(capacity, policy)
+}
@@ -2,6 +2,7 @@
 //!
 
 use crate::error::{Error, ServiceError};
+use crate::graph::ErrorPolicy;
 use crate::mpsc::Outgoing;
 use crate::registry::PortType;
 use crate::service_type::ServiceType;
@@ -69,6 +70,22 @@ pub trait ServiceFactory {
     fn new_service(&self, tp: ServiceType) -> Self::Future;
 
     fn new_context(&self, tp: ServiceType, config: Self::Config) -> Self::CtxFuture;
+
+    /// Returns true if the context keeps no state between the requests,
+    /// so it is created again from the config after the service returned `Err`.
+    ///
+    /// Otherwise `Err` fails the node regardless of the `on_error` policy,
+    /// and the error to be handled by the policy is reported by [`ServiceContext::Failed`].
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    /// Returns false if the service can not handle the error by the `on_error` policy,
+    /// e.g. the error is reported after the frame failed.
+    /// The node with the policy not accepted fails to start.
+    fn accepts_error_policy(&self, _policy: &ErrorPolicy) -> bool {
+        true
+    }
 }
 
 /// A value for reporting the current context to the executor.
@@ -82,6 +99,13 @@ pub enum ServiceContext<T> {
 
     /// force next loop. not receiving next request.
     Next(T),
+
+    /// failed to process the request, but the context is still usable.
+    /// the error is handled by the `on_error` policy of node, and the context is kept.
+    ///
+    /// If the service returns `Err` instead, the context is lost,
+    /// and created again from the config only if the service is [stateless](ServiceFactory::is_stateless).
+    Failed(T, ServiceError),
 }
 
 impl<T> ServiceContext<T> {
@@ -90,6 +114,7 @@ impl<T> ServiceContext<T> {
             ServiceContext::Ready(c) => c,
             ServiceContext::Complete(c) => c,
             ServiceContext::Next(c) => c,
+            ServiceContext::Failed(c, _) => c,
         }
     }
 }
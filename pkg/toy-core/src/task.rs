@@ -1,6 +1,8 @@
 //! A task is an workflow created based on graph.
 //!
 
+use crate::checkpoint::Checkpoints;
+use crate::data::Value;
 use crate::graph::Graph;
 use crate::metrics::{EventRecord, MetricsEventKind, MetricsEvents};
 use crate::{ServiceType, Uri};
@@ -29,6 +31,8 @@ pub struct TaskContext {
     uri: Uri,
     current_span: Option<tracing::Span>,
     events: Arc<Mutex<MetricsEvents>>,
+    checkpoints: Option<Arc<Checkpoints>>,
+    replica: Option<usize>,
 }
 
 struct Inner {
@@ -119,6 +123,8 @@ impl TaskContext {
             uri: Uri::from(task_name),
             current_span: None,
             events: Arc::new(Mutex::new(MetricsEvents::new())),
+            checkpoints: None,
+            replica: None,
         }
     }
 
@@ -133,6 +139,8 @@ impl TaskContext {
             uri: Uri::from(task_name),
             current_span: None,
             events,
+            checkpoints: None,
+            replica: None,
         }
     }
 
@@ -143,6 +151,24 @@ impl TaskContext {
             uri: Uri::from(format!("{}/{}", task_name, uri.clone())),
             current_span: self.current_span,
             events: self.events,
+            checkpoints: self.checkpoints,
+            replica: self.replica,
+        }
+    }
+
+    /// The index of the replica running the service, if the node runs in parallel.
+    pub fn with_replica(self, idx: usize) -> Self {
+        Self {
+            replica: Some(idx),
+            ..self
+        }
+    }
+
+    /// Enable checkpoints of the task.
+    pub fn with_checkpoints(self, checkpoints: Checkpoints) -> Self {
+        Self {
+            checkpoints: Some(Arc::new(checkpoints)),
+            ..self
         }
     }
 
@@ -166,6 +192,11 @@ impl TaskContext {
         &self.uri
     }
 
+    /// The index of the replica, `None` if the node does not run in parallel.
+    pub fn replica(&self) -> Option<usize> {
+        self.replica
+    }
+
     /// Get current span.
     pub fn span(&self) -> &tracing::Span {
         assert!(self.current_span.is_some(), "illegal task span.");
@@ -176,6 +207,33 @@ impl TaskContext {
         self.current_span = Some(span);
     }
 
+    pub fn checkpoints(&self) -> Option<&Arc<Checkpoints>> {
+        self.checkpoints.as_ref()
+    }
+
+    /// Report the resumable state of the current service.
+    /// Do nothing if checkpoints of the task are disabled.
+    pub fn checkpoint(&self, state: Value) {
+        if let Some(ref c) = self.checkpoints {
+            c.update(&self.checkpoint_key(), state);
+        }
+    }
+
+    /// The state of the current service, saved by the previous run of the task.
+    pub fn restored(&self) -> Option<&Value> {
+        self.checkpoints
+            .as_ref()
+            .and_then(|c| c.restored(&self.checkpoint_key()))
+    }
+
+    /// Replicas share the uri, so each replica has its own state keyed by the index.
+    fn checkpoint_key(&self) -> Uri {
+        match self.replica {
+            Some(idx) => Uri::from(format!("{}#{}", self.uri, idx)),
+            None => self.uri.clone(),
+        }
+    }
+
     task_span!(debug_span, DEBUG);
     task_span!(info_span, INFO);
 
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use toy_core::checkpoint::{CheckpointStore, Checkpoints, MemoryCheckpointStore};
+use toy_core::prelude::*;
+
+fn graph() -> Graph {
+    Graph::from(map_value! {
+        "name" => "test",
+        "services" => seq_value![map_value! {
+            "type" => "test.a",
+            "uri" => "a",
+            "wires" => Value::None,
+        }],
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn checkpoint_save_and_restore() {
+    let store = Arc::new(MemoryCheckpointStore::new());
+    let id = TaskId::new();
+    let uri = Uri::from("a");
+
+    let ctx = TaskContext::new(id, graph())
+        .with_checkpoints(Checkpoints::new(store.clone(), 1000))
+        .with_uri(&uri);
+    ctx.checkpoints().unwrap().restore(id).await.unwrap();
+    assert!(ctx.restored().is_none());
+
+    ctx.checkpoint(map_value! { "line" => 10 });
+    ctx.checkpoints().unwrap().save(id).await.unwrap();
+
+    // resume the task with same id.
+    let resumed = TaskContext::new(id, graph())
+        .with_checkpoints(Checkpoints::new(store.clone(), 1000))
+        .with_uri(&uri);
+    resumed.checkpoints().unwrap().restore(id).await.unwrap();
+    assert_eq!(resumed.restored(), Some(&map_value! { "line" => 10 }));
+
+    resumed.checkpoints().unwrap().clear(id).await.unwrap();
+    assert!(store.load(id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn checkpoint_disabled() {
+    let ctx = TaskContext::new(TaskId::new(), graph()).with_uri(&Uri::from("a"));
+    ctx.checkpoint(map_value! { "line" => 10 });
+
+    assert!(ctx.checkpoints().is_none());
+    assert!(ctx.restored().is_none());
+}
+
+#[tokio::test]
+async fn checkpoint_per_replica() {
+    let store = Arc::new(MemoryCheckpointStore::new());
+    let id = TaskId::new();
+    let uri = Uri::from("a");
+    let ctx = TaskContext::new(id, graph())
+        .with_checkpoints(Checkpoints::new(store.clone(), 1000))
+        .with_uri(&uri);
+
+    ctx.clone()
+        .with_replica(0)
+        .checkpoint(map_value! { "line" => 1 });
+    ctx.clone()
+        .with_replica(1)
+        .checkpoint(map_value! { "line" => 2 });
+    ctx.checkpoints().unwrap().save(id).await.unwrap();
+
+    let resumed = TaskContext::new(id, graph())
+        .with_checkpoints(Checkpoints::new(store.clone(), 1000))
+        .with_uri(&uri);
+    resumed.checkpoints().unwrap().restore(id).await.unwrap();
+    assert_eq!(
+        resumed.clone().with_replica(0).restored(),
+        Some(&map_value! { "line" => 1 })
+    );
+    assert_eq!(
+        resumed.clone().with_replica(1).restored(),
+        Some(&map_value! { "line" => 2 })
+    );
+    assert!(resumed.restored().is_none());
+}
@@ -0,0 +1,164 @@
+use chrono::DateTime;
+use toy_core::data::error::ExprError;
+use toy_core::data::expr::Expr;
+use toy_core::data::Value;
+use toy_core::prelude::*;
+
+fn eval(src: &str, v: &Value) -> Value {
+    Expr::compile(src).unwrap().eval(v)
+}
+
+fn data() -> Value {
+    map_value! {
+        "a" => 3,
+        "b" => 2.5,
+        "name" => " Toy ",
+        "user" => map_value! {
+            "id" => 10,
+            "tags" => seq_value!["x", "y"],
+        },
+        "user-name" => "hyphen",
+        "empty" => Value::None,
+        "ts" => DateTime::from_timestamp_millis(10_000).unwrap(),
+    }
+}
+
+#[test]
+fn literal_and_path() {
+    let v = data();
+    assert_eq!(eval("1", &v), Value::from(1));
+    assert_eq!(eval("1.5", &v), Value::from(1.5));
+    assert_eq!(eval("'it\\'s'", &v), Value::from("it's"));
+    assert_eq!(eval("null", &v), Value::None);
+    assert_eq!(eval("user.id", &v), Value::from(10));
+    assert_eq!(eval("user.tags.1", &v), Value::from("y"));
+    assert_eq!(eval("`user-name`", &v), Value::from("hyphen"));
+    assert_eq!(eval("missing.path", &v), Value::None);
+    assert_eq!(eval("$", &Value::from(7)), Value::from(7));
+}
+
+#[test]
+fn arithmetic() {
+    let v = data();
+    assert_eq!(eval("a + 1 * 2", &v), Value::from(5));
+    assert_eq!(eval("(a + 1) * 2", &v), Value::from(8));
+    assert_eq!(eval("a / 2", &v), Value::from(1));
+    assert_eq!(eval("a % 2", &v), Value::from(1));
+    assert_eq!(eval("a * b", &v), Value::from(7.5));
+    assert_eq!(eval("-a", &v), Value::from(-3));
+    assert_eq!(eval("'a' + 1", &v), Value::from("a1"));
+    // null for division by zero, overflow and type mismatch.
+    assert_eq!(eval("a / 0", &v), Value::None);
+    assert_eq!(eval("9223372036854775807 + 1", &v), Value::None);
+    assert_eq!(eval("user * 2", &v), Value::None);
+    assert_eq!(eval("empty + 1", &v), Value::None);
+}
+
+#[test]
+fn comparison_and_logic() {
+    let v = data();
+    assert_eq!(eval("a == 3.0", &v), Value::from(true));
+    assert_eq!(eval("a != 3", &v), Value::from(false));
+    assert_eq!(eval("b < a && a <= 3", &v), Value::from(true));
+    assert_eq!(eval("a > 5 || not (b >= 3)", &v), Value::from(true));
+    assert_eq!(eval("!user", &v), Value::from(false));
+    assert_eq!(eval("empty < 1", &v), Value::from(false));
+    assert_eq!(eval("ts > '1970-01-01T00:00:05Z'", &v), Value::from(true));
+}
+
+#[test]
+fn null_coalescing() {
+    let v = data();
+    assert_eq!(eval("empty ?? 'default'", &v), Value::from("default"));
+    assert_eq!(eval("missing ?? 0 + 1", &v), Value::from(1));
+    assert_eq!(eval("a ?? 0", &v), Value::from(3));
+    assert_eq!(eval("coalesce(empty, missing, a)", &v), Value::from(3));
+}
+
+#[test]
+fn functions() {
+    let v = data();
+    assert_eq!(eval("upper(trim(name))", &v), Value::from("TOY"));
+    assert_eq!(eval("len(name)", &v), Value::from(5u64));
+    assert_eq!(eval("len(user.tags)", &v), Value::from(2u64));
+    assert_eq!(eval("concat('id-', user.id)", &v), Value::from("id-10"));
+    assert_eq!(eval("contains(user.tags, 'x')", &v), Value::from(true));
+    assert_eq!(eval("starts_with(trim(name), 'To')", &v), Value::from(true));
+    assert_eq!(eval("substr('abcdef', 1, 3)", &v), Value::from("bcd"));
+    assert_eq!(eval("replace('a-b-c', '-', '+')", &v), Value::from("a+b+c"));
+    assert_eq!(eval("split('a,b', ',')", &v), seq_value!["a", "b"]);
+    assert_eq!(eval("int('42') + float('0.5')", &v), Value::from(42.5));
+    assert_eq!(eval("round(b)", &v), Value::from(3.0));
+    assert_eq!(eval("max(a, b, 1)", &v), Value::from(3));
+    assert_eq!(eval("if(a > 1, 'big', 'small')", &v), Value::from("big"));
+    assert_eq!(eval("is_null(empty)", &v), Value::from(true));
+}
+
+#[test]
+fn timestamp() {
+    let v = data();
+    let ts = |millis: i64| Value::from(DateTime::from_timestamp_millis(millis).unwrap());
+    assert_eq!(eval("ts + seconds(5)", &v), ts(15_000));
+    assert_eq!(eval("ts - 1000", &v), ts(9_000));
+    assert_eq!(eval("millis(ts)", &v), Value::from(10_000i64));
+    assert_eq!(eval("ts - timestamp(4000)", &v), Value::from(6_000i64));
+    assert_eq!(
+        eval(
+            "timestamp('1970-01-01T00:01:00Z') == timestamp(minutes(1))",
+            &v
+        ),
+        Value::from(true)
+    );
+    assert_eq!(eval("now() - ts > days(1)", &v), Value::from(true));
+}
+
+#[test]
+fn is_match() {
+    let v = data();
+    assert!(Expr::compile("a > 1").unwrap().is_match(&v));
+    assert!(Expr::compile("user.id").unwrap().is_match(&v));
+    assert!(!Expr::compile("empty").unwrap().is_match(&v));
+    assert!(!Expr::compile("missing").unwrap().is_match(&v));
+}
+
+#[test]
+fn compile_error() {
+    assert!(matches!(
+        Expr::compile("a +"),
+        Err(ExprError::Syntax { position: 3, .. })
+    ));
+    assert!(matches!(Expr::compile("(a"), Err(ExprError::Syntax { .. })));
+    assert!(matches!(
+        Expr::compile("a # b"),
+        Err(ExprError::Syntax { position: 2, .. })
+    ));
+    assert!(matches!(
+        Expr::compile("'open"),
+        Err(ExprError::Syntax { .. })
+    ));
+    assert!(matches!(
+        Expr::compile("exec('rm')"),
+        Err(ExprError::UnknownFunction { .. })
+    ));
+    assert!(matches!(
+        Expr::compile("lower(a, b)"),
+        Err(ExprError::ArgumentCount { actual: 2, .. })
+    ));
+    let deep = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+    assert!(matches!(
+        Expr::compile(&deep),
+        Err(ExprError::TooDeep { .. })
+    ));
+    // left associative chain is as deep as the nested one.
+    let long = vec!["1"; 100_000].join(" + ");
+    assert!(matches!(
+        Expr::compile(&long),
+        Err(ExprError::TooDeep { .. })
+    ));
+    let long = vec!["-1"; 100_000].join(" * ");
+    assert!(matches!(
+        Expr::compile(&long),
+        Err(ExprError::TooDeep { .. })
+    ));
+    assert!(Expr::compile(&vec!["1"; 32].join(" + ")).is_ok());
+}
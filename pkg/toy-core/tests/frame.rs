@@ -0,0 +1,50 @@
+use toy_core::data::{headers, Frame};
+use toy_core::prelude::*;
+
+fn frame_with_headers() -> Frame {
+    Frame::from_value(map_value! { "a" => 1 })
+        .with_header(headers::SOURCE, "a.csv")
+        .with_header(headers::LINE, 10u32)
+}
+
+#[test]
+fn frame_headers() {
+    let mut f = frame_with_headers();
+    assert_eq!(f.header(headers::SOURCE), Some(&Value::from("a.csv")));
+    assert_eq!(f.header(headers::LINE), Some(&Value::from(10u32)));
+    assert_eq!(f.header(headers::TRACE_ID), None);
+
+    // headers are kept when the payload changed.
+    *f.value_mut().unwrap() = map_value! { "a" => 2 };
+    assert_eq!(f.headers().len(), 2);
+
+    let new = Frame::from(3u32).with_headers(f.headers().clone());
+    assert_eq!(new.header(headers::SOURCE), Some(&Value::from("a.csv")));
+}
+
+#[test]
+fn frame_headers_json() {
+    let f = frame_with_headers();
+    let bytes = toy_pack_json::pack(&f).unwrap();
+    let r = toy_pack_json::unpack::<Frame>(&bytes).unwrap();
+
+    assert_eq!(r, f);
+    assert_eq!(r.headers(), f.headers());
+}
+
+#[test]
+fn frame_headers_mp() {
+    let f = frame_with_headers();
+    let bytes = toy_pack_mp::pack(&f).unwrap();
+    let r = toy_pack_mp::unpack::<Frame>(&bytes).unwrap();
+
+    assert_eq!(r, f);
+    assert_eq!(r.headers(), f.headers());
+
+    // without headers.
+    let f = Frame::from(1u32);
+    let bytes = toy_pack_mp::pack(&f).unwrap();
+    let r = toy_pack_mp::unpack::<Frame>(&bytes).unwrap();
+    assert_eq!(r, f);
+    assert!(r.headers().is_empty());
+}
@@ -0,0 +1,727 @@
+use toy_core::graph::{
+    bind_params, check_params, expand_includes, has_placeholder, DiagnosticKind, Dispatch,
+    ErrorPolicy, Graph, InputWire, OutputWire, Parallelism,
+};
+use toy_core::mpsc::{BackpressurePolicy, ChannelConfig};
+use toy_core::prelude::*;
+
+fn port_type_of(tp: &ServiceType) -> Option<PortType> {
+    match tp.service_name() {
+        "source" => Some(PortType::source()),
+        "flow" => Some(PortType::flow()),
+        "fanout" => Some(PortType::fan_out_flow(2)),
+        "join" => Some(PortType::fan_in_flow(2)),
+        "sink" => Some(PortType::sink()),
+        _ => None,
+    }
+}
+
+fn node(tp: &str, uri: &str, wires: Value) -> Value {
+    map_value! {
+        "type" => format!("test.{}", tp),
+        "uri" => uri,
+        "wires" => wires,
+    }
+}
+
+fn graph(services: Vec<Value>) -> Graph {
+    Graph::from(map_value! {
+        "name" => "test",
+        "services" => Value::from(services),
+    })
+    .unwrap()
+}
+
+fn kinds(g: &Graph) -> Vec<(String, DiagnosticKind)> {
+    match g.validate_with(port_type_of) {
+        Ok(()) => vec![],
+        Err(e) => e
+            .into_diagnostics()
+            .into_iter()
+            .map(|x| (x.uri().to_string(), x.kind().clone()))
+            .collect(),
+    }
+}
+
+#[test]
+fn validate_ok() {
+    let g = graph(vec![
+        node("source", "a", Value::from("b")),
+        node("fanout", "b", seq_value!["c", "d"]),
+        node("flow", "c", Value::from("e")),
+        node("flow", "d", Value::from("e")),
+        node("join", "e", Value::from("f")),
+        node("sink", "f", Value::None),
+    ]);
+    assert!(g.validate_with(port_type_of).is_ok());
+}
+
+#[test]
+fn validate_duplicate_uri() {
+    let g = graph(vec![
+        node("source", "a", Value::from("b")),
+        node("sink", "b", Value::None),
+        node("sink", "b", Value::None),
+    ]);
+    assert_eq!(
+        kinds(&g),
+        vec![("b".to_string(), DiagnosticKind::DuplicateUri)]
+    );
+}
+
+#[test]
+fn validate_dangling_wire() {
+    let g = graph(vec![
+        node("source", "a", seq_value!["b"]),
+        node("flow", "b", Value::from("x")),
+    ]);
+    let e = g.validate_with(port_type_of).unwrap_err();
+    let d = &e.diagnostics()[0];
+    assert_eq!(d.uri(), &Uri::from("b"));
+    assert_eq!(d.wire(), Some(&Uri::from("x")));
+    assert_eq!(d.kind(), &DiagnosticKind::DanglingWire);
+}
+
+#[test]
+fn validate_cycle() {
+    let g = graph(vec![
+        node("source", "a", Value::from("b")),
+        node("join", "b", Value::from("c")),
+        node("flow", "c", Value::from("b")),
+    ]);
+    assert_eq!(
+        kinds(&g),
+        vec![(
+            "b".to_string(),
+            DiagnosticKind::Cycle(vec!["b".into(), "c".into(), "b".into()])
+        )]
+    );
+}
+
+#[test]
+fn validate_port_count() {
+    let g = graph(vec![
+        node("source", "a", seq_value!["b", "c"]),
+        node("flow", "b", Value::None),
+        node("sink", "c", Value::from("d")),
+        node("source", "d", Value::None),
+        node("unknown", "e", Value::None),
+    ]);
+    assert_eq!(
+        kinds(&g),
+        vec![
+            (
+                "a".to_string(),
+                DiagnosticKind::TooManyOutputs {
+                    expected: 1,
+                    actual: 2
+                }
+            ),
+            (
+                "c".to_string(),
+                DiagnosticKind::UnexpectedOutput { actual: 1 }
+            ),
+            (
+                "d".to_string(),
+                DiagnosticKind::UnexpectedInput { actual: 1 }
+            ),
+            (
+                "e".to_string(),
+                DiagnosticKind::ServiceNotFound(
+                    ServiceType::from_full_name("test.unknown").unwrap()
+                )
+            ),
+        ]
+    );
+}
+
+#[test]
+fn channel_config() {
+    let g = graph(vec![
+        node("source", "a", seq_value!["b"]),
+        map_value! {
+            "type" => "test.fanout",
+            "uri" => "b",
+            "channel" => map_value! { "capacity" => 8 },
+            "wires" => seq_value![
+                map_value! {
+                    "to" => "c",
+                    "channel" => map_value! { "capacity" => 4, "policy" => "drop_oldest" },
+                },
+                "d"
+            ],
+        },
+        node("sink", "c", Value::None),
+        node("sink", "d", Value::None),
+    ]);
+    assert!(g.validate_with(port_type_of).is_ok());
+    assert_eq!(
+        g.channel_config(&"a".into(), &"b".into()),
+        ChannelConfig::new(8, BackpressurePolicy::Block)
+    );
+    assert_eq!(
+        g.channel_config(&"b".into(), &"c".into()),
+        ChannelConfig::new(4, BackpressurePolicy::DropOldest)
+    );
+    assert_eq!(
+        g.channel_config(&"b".into(), &"d".into()),
+        ChannelConfig::default()
+    );
+}
+
+#[test]
+fn channel_config_zero_capacity() {
+    let r = Graph::from(map_value! {
+        "name" => "test",
+        "services" => seq_value![map_value! {
+            "type" => "test.source",
+            "uri" => "a",
+            "channel" => map_value! { "capacity" => 0 },
+            "wires" => Value::None,
+        }],
+    });
+    assert!(r.is_err());
+}
+
+#[test]
+fn error_policy() {
+    let g = graph(vec![
+        map_value! {
+            "type" => "test.source",
+            "uri" => "a",
+            "on_error" => map_value! { "retry" => map_value! { "max" => 2, "backoff_mills" => 100 } },
+            "wires" => "b",
+        },
+        map_value! {
+            "type" => "test.flow",
+            "uri" => "b",
+            "on_error" => map_value! { "dead_letter" => map_value! { "port" => 1 } },
+            "wires" => seq_value!["c", "d"],
+        },
+        map_value! {
+            "type" => "test.sink",
+            "uri" => "c",
+            "on_error" => "skip",
+            "wires" => Value::None,
+        },
+        node("sink", "d", Value::None),
+    ]);
+    assert!(g.validate_with(port_type_of).is_ok());
+
+    let a = g.by_uri(Uri::from("a")).unwrap().error_policy();
+    assert_eq!(
+        a,
+        ErrorPolicy::Retry {
+            max: 2,
+            backoff_mills: 100
+        }
+    );
+    assert_eq!(a.backoff_mills(0), Some(100));
+    assert_eq!(a.backoff_mills(1), Some(200));
+    assert_eq!(a.backoff_mills(2), None);
+    assert_eq!(
+        g.by_uri(Uri::from("b")).unwrap().error_policy(),
+        ErrorPolicy::DeadLetter { port: 1 }
+    );
+    assert_eq!(
+        g.by_uri(Uri::from("c")).unwrap().error_policy(),
+        ErrorPolicy::Skip
+    );
+    assert_eq!(
+        g.by_uri(Uri::from("d")).unwrap().error_policy(),
+        ErrorPolicy::Fail
+    );
+}
+
+#[test]
+fn validate_dead_letter_port_not_wired() {
+    let g = graph(vec![
+        node("source", "a", Value::from("b")),
+        map_value! {
+            "type" => "test.flow",
+            "uri" => "b",
+            "on_error" => map_value! { "dead_letter" => map_value! { "port" => 1 } },
+            "wires" => "c",
+        },
+        node("sink", "c", Value::None),
+    ]);
+    assert_eq!(
+        kinds(&g),
+        vec![(
+            "b".to_string(),
+            DiagnosticKind::DeadLetterPortNotWired { port: 1 }
+        )]
+    );
+}
+
+fn include(uri: &str, graph: &str, wires: Value) -> Value {
+    map_value! {
+        "type" => "toy.graph.Include",
+        "uri" => uri,
+        "config" => map_value! { "graph" => graph },
+        "wires" => wires,
+    }
+}
+
+fn graph_value(name: &str, services: Vec<Value>) -> Value {
+    map_value! {
+        "name" => name,
+        "services" => Value::from(services),
+    }
+}
+
+#[test]
+fn expand_include() {
+    let parent = graph_value(
+        "parent",
+        vec![
+            node("source", "a", Value::from("inc")),
+            include("inc", "child", Value::from("z")),
+            node("sink", "z", Value::None),
+        ],
+    );
+    let child = graph_value(
+        "child",
+        vec![
+            node("flow", "x", Value::from("y")),
+            node("flow", "y", Value::None),
+        ],
+    );
+    let v = expand_includes(&parent, |name| match name {
+        "child" => Some(child.clone()),
+        _ => None,
+    })
+    .unwrap();
+    let g = Graph::from(v).unwrap();
+    assert!(g.validate_with(port_type_of).is_ok());
+
+    let uris = g.iter().map(|x| x.uri().to_string()).collect::<Vec<_>>();
+    assert_eq!(uris, vec!["a", "inc/x", "inc/y", "z"]);
+    assert_eq!(
+        g.outputs().get(&Uri::from("a")),
+        Some(&OutputWire::Single(Uri::from("a"), Uri::from("inc/x"), 0))
+    );
+    assert_eq!(
+        g.outputs().get(&Uri::from("inc/x")),
+        Some(&OutputWire::Single(
+            Uri::from("inc/x"),
+            Uri::from("inc/y"),
+            0
+        ))
+    );
+    assert_eq!(
+        g.outputs().get(&Uri::from("inc/y")),
+        Some(&OutputWire::Single(Uri::from("inc/y"), Uri::from("z"), 0))
+    );
+}
+
+#[test]
+fn expand_include_entries_by_port() {
+    let mut inc = include("inc", "child", Value::from("z"));
+    inc.insert_by_path("config.entry", seq_value!["y", "x"]);
+    let parent = graph_value(
+        "parent",
+        vec![
+            node("source", "a", Value::from("inc")),
+            node("source", "b", Value::from("inc:1")),
+            inc,
+            node("sink", "z", Value::None),
+        ],
+    );
+    let child = graph_value(
+        "child",
+        vec![
+            node("flow", "x", Value::from("j")),
+            node("flow", "y", Value::from("j:1")),
+            node("join", "j", Value::None),
+        ],
+    );
+    let v = expand_includes(&parent, |name| match name {
+        "child" => Some(child.clone()),
+        _ => None,
+    })
+    .unwrap();
+    let g = Graph::from(v).unwrap();
+    assert!(g.validate_with(port_type_of).is_ok());
+
+    // input port n of the include node -> entry[n]
+    assert_eq!(
+        g.outputs().get(&Uri::from("a")),
+        Some(&OutputWire::Single(Uri::from("a"), Uri::from("inc/y"), 0))
+    );
+    assert_eq!(
+        g.outputs().get(&Uri::from("b")),
+        Some(&OutputWire::Single(Uri::from("b"), Uri::from("inc/x"), 0))
+    );
+    assert_eq!(
+        g.outputs().get(&Uri::from("inc/j")),
+        Some(&OutputWire::Single(Uri::from("inc/j"), Uri::from("z"), 0))
+    );
+
+    let parent = graph_value(
+        "parent",
+        vec![
+            node("source", "a", Value::from("inc:2")),
+            include("inc", "child", Value::None),
+        ],
+    );
+    let e = expand_includes(&parent, |_| Some(child.clone())).unwrap_err();
+    assert!(e.to_string().contains("input port 2"), "{}", e);
+}
+
+#[test]
+fn expand_include_exits_by_port() {
+    let mut inc = include("inc", "child", seq_value!["p", "q"]);
+    inc.insert_by_path("config.exit", seq_value!["y", "x"]);
+    let parent = graph_value(
+        "parent",
+        vec![
+            node("source", "a", Value::from("inc")),
+            inc,
+            node("sink", "p", Value::None),
+            node("sink", "q", Value::None),
+        ],
+    );
+    let child = graph_value(
+        "child",
+        vec![
+            node("fanout", "s", seq_value!["x", "y"]),
+            node("flow", "x", Value::None),
+            node("flow", "y", Value::None),
+        ],
+    );
+    let v = expand_includes(&parent, |name| match name {
+        "child" => Some(child.clone()),
+        _ => None,
+    })
+    .unwrap();
+    let g = Graph::from(v).unwrap();
+    assert!(g.validate_with(port_type_of).is_ok());
+
+    // exit[n] -> output port n of the include node
+    assert_eq!(
+        g.outputs().get(&Uri::from("inc/y")),
+        Some(&OutputWire::Single(Uri::from("inc/y"), Uri::from("p"), 0))
+    );
+    assert_eq!(
+        g.outputs().get(&Uri::from("inc/x")),
+        Some(&OutputWire::Single(Uri::from("inc/x"), Uri::from("q"), 0))
+    );
+
+    let mut inc = include("inc", "child", seq_value!["p", "q", "r"]);
+    inc.insert_by_path("config.exit", seq_value!["y", "x"]);
+    let parent = graph_value("parent", vec![inc]);
+    let e = expand_includes(&parent, |_| Some(child.clone())).unwrap_err();
+    assert!(e.to_string().contains("output port 2"), "{}", e);
+}
+
+#[test]
+fn expand_include_recursive() {
+    let a = graph_value("a", vec![include("inc", "b", Value::None)]);
+    let b = graph_value("b", vec![include("inc", "a", Value::None)]);
+    let r = expand_includes(&a, |name| match name {
+        "a" => Some(a.clone()),
+        "b" => Some(b.clone()),
+        _ => None,
+    });
+    let e = r.unwrap_err();
+    assert!(e.to_string().contains("a -> b -> a"), "{}", e);
+}
+
+#[test]
+fn expand_include_not_found() {
+    let a = graph_value("a", vec![include("inc", "b", Value::None)]);
+    assert!(expand_includes(&a, |_| None).is_err());
+}
+
+fn params_graph() -> Value {
+    map_value! {
+        "name" => "params",
+        "params" => seq_value![
+            map_value! { "name" => "path", "type" => "string" },
+            map_value! { "name" => "limit", "type" => "integer", "default" => 100 }
+        ],
+        "services" => seq_value![map_value! {
+            "type" => "test.source",
+            "uri" => "a",
+            "config" => map_value! {
+                "path" => "${path}",
+                "name" => "in-${path}-${limit}",
+                "limit" => "${limit}",
+            },
+            "wires" => Value::None,
+        }],
+    }
+}
+
+fn params(v: Vec<(&str, Value)>) -> Map<String, Value> {
+    v.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+}
+
+#[test]
+fn bind_params_ok() {
+    let v = params_graph();
+    assert!(check_params(&v).is_ok());
+
+    let given = params(vec![
+        ("path", Value::from("/tmp/a.csv")),
+        ("limit", Value::from("5")),
+    ]);
+    let g = Graph::from(bind_params(&v, &given).unwrap()).unwrap();
+    assert_eq!(
+        g.by_uri(Uri::from("a")).unwrap().config(),
+        map_value! {
+            "path" => "/tmp/a.csv",
+            "name" => "in-/tmp/a.csv-5",
+            "limit" => 5,
+        }
+    );
+
+    let given = params(vec![("path", Value::from("b"))]);
+    let g = Graph::from(bind_params(&v, &given).unwrap()).unwrap();
+    assert_eq!(
+        g.by_uri(Uri::from("a")).unwrap().config().path("limit"),
+        Some(&Value::from(100))
+    );
+}
+
+#[test]
+fn bind_params_rejected() {
+    let v = params_graph();
+    // missing
+    assert!(bind_params(&v, &Map::new()).is_err());
+    // ill-typed
+    let given = params(vec![
+        ("path", Value::from("a")),
+        ("limit", Value::from("x")),
+    ]);
+    assert!(bind_params(&v, &given).is_err());
+    // undeclared
+    let given = params(vec![("path", Value::from("a")), ("other", Value::from(1))]);
+    assert!(bind_params(&v, &given).is_err());
+}
+
+#[test]
+fn check_params_undeclared_placeholder() {
+    let v = map_value! {
+        "name" => "params",
+        "services" => seq_value![map_value! {
+            "type" => "test.source",
+            "uri" => "a",
+            "config" => map_value! { "path" => "${path}" },
+            "wires" => Value::None,
+        }],
+    };
+    assert!(check_params(&v).is_err());
+}
+
+#[test]
+fn bind_params_escaped() {
+    let v = map_value! {
+        "name" => "params",
+        "params" => seq_value![map_value! { "name" => "x", "type" => "integer" }],
+        "services" => seq_value![map_value! {
+            "type" => "test.source",
+            "uri" => "a",
+            "config" => map_value! {
+                "script" => "(x) => `$${x}`",
+                "mixed" => "$${x}-${x}",
+            },
+            "wires" => Value::None,
+        }],
+    };
+    assert!(check_params(&v).is_ok());
+
+    let g = Graph::from(bind_params(&v, &params(vec![("x", Value::from(1))])).unwrap()).unwrap();
+    assert_eq!(
+        g.by_uri(Uri::from("a")).unwrap().config(),
+        map_value! {
+            "script" => "(x) => `${x}`",
+            "mixed" => "${x}-1",
+        }
+    );
+
+    // only the escaped placeholder, the param is not required.
+    let v = map_value! {
+        "name" => "params",
+        "services" => seq_value![map_value! {
+            "type" => "test.source",
+            "uri" => "a",
+            "config" => map_value! { "script" => "`$${x}`" },
+            "wires" => Value::None,
+        }],
+    };
+    assert!(check_params(&v).is_ok());
+    assert!(!has_placeholder(&v));
+}
+
+#[test]
+fn input_port() {
+    let g = graph(vec![
+        node("source", "a", seq_value!["c:1"]),
+        node(
+            "source",
+            "b",
+            seq_value![map_value! { "to" => "c", "port" => 0 }],
+        ),
+        node("join", "c", Value::from("d")),
+        node("sink", "d", Value::None),
+    ]);
+    assert!(g.validate_with(port_type_of).is_ok());
+    assert_eq!(
+        g.inputs().get(&Uri::from("c")),
+        Some(&InputWire::Fanin(
+            vec![(Uri::from("a"), 1), (Uri::from("b"), 0)],
+            Uri::from("c")
+        ))
+    );
+    assert_eq!(
+        g.outputs().get(&Uri::from("a")),
+        Some(&OutputWire::Single(Uri::from("a"), Uri::from("c"), 1))
+    );
+}
+
+#[test]
+fn input_port_default_order() {
+    let g = graph(vec![
+        node("source", "a", Value::from("c")),
+        node("source", "b", Value::from("c")),
+        node("join", "c", Value::None),
+    ]);
+    assert_eq!(
+        g.inputs().get(&Uri::from("c")),
+        Some(&InputWire::Fanin(
+            vec![(Uri::from("a"), 0), (Uri::from("b"), 1)],
+            Uri::from("c")
+        ))
+    );
+}
+
+#[test]
+fn validate_invalid_input_port() {
+    let g = graph(vec![
+        node("source", "a", Value::from("c:0")),
+        node("source", "b", Value::from("c:5")),
+        node("join", "c", Value::from("d")),
+        node("sink", "d", Value::None),
+    ]);
+    let e = g.validate_with(port_type_of).unwrap_err();
+    let d = &e.diagnostics()[0];
+    assert_eq!(d.uri(), &Uri::from("b"));
+    assert_eq!(d.wire(), Some(&Uri::from("c")));
+    assert_eq!(
+        d.kind(),
+        &DiagnosticKind::InvalidInputPort {
+            port: 5,
+            expected: 2
+        }
+    );
+}
+
+#[test]
+fn input_port_next_free() {
+    let g = graph(vec![
+        node("source", "a", Value::from("c")),
+        node("source", "b", Value::from("c:0")),
+        node("join", "c", Value::from("d")),
+        node("sink", "d", Value::None),
+    ]);
+    assert!(g.validate_with(port_type_of).is_ok());
+    assert_eq!(
+        g.inputs().get(&Uri::from("c")),
+        Some(&InputWire::Fanin(
+            vec![(Uri::from("b"), 0), (Uri::from("a"), 1)],
+            Uri::from("c")
+        ))
+    );
+}
+
+#[test]
+fn validate_duplicate_input_port() {
+    let g = graph(vec![
+        node("source", "a", Value::from("c:1")),
+        node("source", "b", Value::from("c:1")),
+        node("join", "c", Value::from("d")),
+        node("sink", "d", Value::None),
+    ]);
+    let e = g.validate_with(port_type_of).unwrap_err();
+    let d = &e.diagnostics()[0];
+    assert_eq!(d.uri(), &Uri::from("b"));
+    assert_eq!(d.wire(), Some(&Uri::from("c")));
+    assert_eq!(d.kind(), &DiagnosticKind::DuplicateInputPort { port: 1 });
+}
+
+#[test]
+fn parallelism() {
+    let g = graph(vec![
+        map_value! {
+            "type" => "test.source",
+            "uri" => "a",
+            "parallelism" => 4,
+            "wires" => "b",
+        },
+        map_value! {
+            "type" => "test.sink",
+            "uri" => "b",
+            "parallelism" => map_value! {
+                "replicas" => 2,
+                "dispatch" => map_value! { "key_hash" => map_value! { "key" => "id" } },
+                "ordered" => true,
+            },
+            "wires" => Value::None,
+        },
+    ]);
+    assert_eq!(
+        g.by_uri(Uri::from("a")).unwrap().parallelism(),
+        &Parallelism::new(4, Dispatch::RoundRobin, false)
+    );
+    assert_eq!(
+        g.by_uri(Uri::from("b")).unwrap().parallelism(),
+        &Parallelism::new(
+            2,
+            Dispatch::KeyHash {
+                key: "id".to_string()
+            },
+            true
+        )
+    );
+
+    let d = Dispatch::KeyHash {
+        key: "id".to_string(),
+    };
+    let f1 = Frame::from_value(map_value! { "id" => 1 });
+    let f2 = Frame::from_value(map_value! { "id" => 1, "v" => 2 });
+    assert_eq!(d.select(&f1, 0, 4), d.select(&f2, 1, 4));
+    assert_eq!(Dispatch::RoundRobin.select(&f1, 5, 4), 1);
+}
+
+#[test]
+fn validate_parallel_source() {
+    let g = graph(vec![
+        map_value! {
+            "type" => "test.source",
+            "uri" => "a",
+            "parallelism" => 2,
+            "wires" => "b",
+        },
+        node("sink", "b", Value::None),
+    ]);
+    let e = g.validate_with(port_type_of).unwrap_err();
+    let d = &e.diagnostics()[0];
+    assert_eq!(d.uri(), &Uri::from("a"));
+    assert_eq!(d.kind(), &DiagnosticKind::ParallelSource { replicas: 2 });
+}
+
+#[test]
+fn parallelism_zero_replicas() {
+    let r = Graph::from(map_value! {
+        "name" => "test",
+        "services" => seq_value![map_value! {
+            "type" => "test.source",
+            "uri" => "a",
+            "parallelism" => 0,
+            "wires" => Value::None,
+        }],
+    });
+    assert!(r.is_err());
+}
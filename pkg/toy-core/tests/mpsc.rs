@@ -0,0 +1,68 @@
+use toy_core::data::Frame;
+use toy_core::error::OutgoingError;
+use toy_core::mpsc::{channel_with, BackpressurePolicy, ChannelConfig};
+
+#[tokio::test]
+async fn drop_newest() {
+    let (mut tx, mut rx) =
+        channel_with::<Frame>(ChannelConfig::new(2, BackpressurePolicy::DropNewest));
+    for i in 0..4u32 {
+        tx.send(Frame::from(i)).await.unwrap();
+    }
+    assert_eq!(rx.next().await, Some(Frame::from(0u32)));
+    assert_eq!(rx.next().await, Some(Frame::from(1u32)));
+}
+
+#[tokio::test]
+async fn drop_oldest() {
+    let (mut tx, mut rx) =
+        channel_with::<Frame>(ChannelConfig::new(2, BackpressurePolicy::DropOldest));
+    for i in 0..4u32 {
+        tx.send(Frame::from(i)).await.unwrap();
+    }
+    assert_eq!(rx.next().await, Some(Frame::from(2u32)));
+    assert_eq!(rx.next().await, Some(Frame::from(3u32)));
+}
+
+#[tokio::test]
+async fn drop_oldest_keep_signal() {
+    let (mut tx, mut rx) =
+        channel_with::<Frame>(ChannelConfig::new(2, BackpressurePolicy::DropOldest));
+    tx.send(Frame::upstream_finish()).await.unwrap();
+    tx.send(Frame::from(1u32)).await.unwrap();
+    tx.send(Frame::from(2u32)).await.unwrap();
+    assert!(rx.next().await.unwrap().is_upstream_finish());
+    assert_eq!(rx.next().await, Some(Frame::from(2u32)));
+}
+
+#[tokio::test]
+async fn drop_oldest_keep_signal_order() {
+    let (mut tx, mut rx) =
+        channel_with::<Frame>(ChannelConfig::new(3, BackpressurePolicy::DropOldest));
+    tx.send(Frame::from(1u32)).await.unwrap();
+    tx.send(Frame::drain()).await.unwrap();
+    tx.send(Frame::from(2u32)).await.unwrap();
+    tx.send(Frame::from(3u32)).await.unwrap();
+    tx.send(Frame::from(4u32)).await.unwrap();
+    assert!(rx.next().await.unwrap().is_drain());
+    assert_eq!(rx.next().await, Some(Frame::from(3u32)));
+    assert_eq!(rx.next().await, Some(Frame::from(4u32)));
+    assert_eq!(rx.try_next(), None);
+}
+
+#[tokio::test]
+async fn error_if_full() {
+    let (mut tx, mut rx) = channel_with::<Frame>(ChannelConfig::new(1, BackpressurePolicy::Error));
+    tx.send(Frame::from(1u32)).await.unwrap();
+    let r = tx.send(Frame::from(2u32)).await;
+    assert!(matches!(r, Err(OutgoingError::ChannelFull { port: 0 })));
+    assert_eq!(rx.next().await, Some(Frame::from(1u32)));
+}
+
+#[tokio::test]
+async fn try_next() {
+    let (mut tx, mut rx) = channel_with::<Frame>(ChannelConfig::default());
+    assert_eq!(rx.try_next(), None);
+    tx.send(Frame::drain()).await.unwrap();
+    assert!(rx.try_next().unwrap().is_drain());
+}
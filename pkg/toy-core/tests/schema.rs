@@ -2,6 +2,8 @@ use serde::Serialize;
 use std::collections::HashMap;
 use toy_core::data::schema::visitors::*;
 use toy_core::data::schema::JsonSchema;
+use toy_core::data::Value;
+use toy_core::{map_value, seq_value};
 use toy_pack::{schema::to_schema, Schema};
 
 #[test]
@@ -37,3 +39,74 @@ fn schema_struct() {
 
     assert_eq!(json, json2);
 }
+
+#[derive(Debug, Serialize, Schema)]
+struct ValidateTarget {
+    capacity: u8,
+    name: String,
+    opt: Option<u32>,
+    #[serde(default)]
+    with_default: u32,
+    keys: Vec<String>,
+    kind: Kind,
+}
+
+#[derive(Debug, Serialize, Schema)]
+enum Kind {
+    _A,
+    _B { id: u32 },
+}
+
+fn validate(v: Value) -> Vec<(String, String)> {
+    let schema = to_schema::<ValidateTarget, JsonSchemaVisitor>("v", JsonSchemaVisitor).unwrap();
+    match schema.validate(&v) {
+        Ok(()) => vec![],
+        Err(e) => e
+            .into_iter()
+            .map(|x| (x.path().to_string(), x.message().to_string()))
+            .collect(),
+    }
+}
+
+#[test]
+fn schema_validate_ok() {
+    let v = map_value! {
+        "capacity" => 10,
+        "name" => "a",
+        "keys" => seq_value!["x", "y"],
+        "kind" => map_value! { "_B" => map_value! { "id" => 1 } },
+    };
+    assert_eq!(validate(v), vec![]);
+
+    let v = map_value! {
+        "capacity" => 10,
+        "name" => "a",
+        "opt" => Value::None,
+        "keys" => Value::Seq(vec![]),
+        "kind" => "_A",
+    };
+    assert_eq!(validate(v), vec![]);
+}
+
+#[test]
+fn schema_validate_error_paths() {
+    let v = map_value! {
+        "capacity" => 256,
+        "opt" => "1",
+        "keys" => seq_value!["x", 1],
+        "kind" => "_C",
+    };
+    let paths = validate(v).into_iter().map(|(p, _)| p).collect::<Vec<_>>();
+    assert_eq!(paths, vec!["name", "capacity", "opt", "keys.1", "kind"]);
+}
+
+#[test]
+fn schema_validate_root_type() {
+    assert_eq!(
+        validate(Value::from(1)),
+        vec![(
+            "".to_string(),
+            "invalid type. expected:object, actual:integer".to_string()
+        )]
+    );
+}
@@ -1,18 +1,22 @@
+use crate::parallel::{self, RequestOutgoings};
 use async_trait::async_trait;
 use serde::de::DeserializeOwned;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::SystemTime;
 use tokio::sync::Mutex;
-use toy_core::data::{self, Frame};
+use toy_core::checkpoint::Checkpoints;
+use toy_core::data::{self, Frame, Value};
 use toy_core::error::{Error, OutgoingError, ServiceError};
 use toy_core::executor::{ServiceExecutor, TaskExecutor, TaskExecutorFactory};
-use toy_core::graph::Graph;
+use toy_core::graph::{ErrorPolicy, Graph, InputWire};
+use toy_core::map_value;
 use toy_core::metrics::MetricsEventKind;
 use toy_core::mpsc::{Incoming, Outgoing};
 use toy_core::node_channel::{self, Awaiter, Incomings, Outgoings, SignalOutgoings, Starters};
 use toy_core::registry::{App, ExecuteResult, Registry};
 use toy_core::service::{Service, ServiceContext, ServiceFactory};
-use toy_core::task::TaskContext;
+use toy_core::task::{TaskContext, TaskId};
 use toy_core::ServiceType;
 use toy_core::Uri;
 use tracing::Span;
@@ -83,7 +87,8 @@ impl Executor {
         panic!("invalid channel stack...");
     }
 
-    async fn run_0(&mut self, start_frame: Frame) -> Result<(), OutgoingError> {
+    /// Returns true if all upstream finished, false if stopped.
+    async fn run_0(&mut self, start_frame: Frame) -> Result<bool, OutgoingError> {
         for (_, tx) in &mut self.starters.iter_mut() {
             tx.send(start_frame.clone()).await?
         }
@@ -96,7 +101,7 @@ impl Executor {
         while let Some(req) = self.awaiter.next().await {
             if req.is_stop() {
                 tracing::info!(parent: &span, ?uri, "receive stop signal.");
-                break;
+                return Ok(false);
             }
             if req.is_upstream_finish() {
                 finish_count += 1;
@@ -113,7 +118,7 @@ impl Executor {
                 }
             }
         }
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -128,23 +133,118 @@ impl ServiceExecutor for Executor {
         F::Config: DeserializeOwned + Send,
     {
         let (tx, (rx, upstream_count)) = self.pop_channels(uri);
-        let uri = uri.clone();
-        let service_type = service_type.clone();
 
-        let config_value = self.graph.by_uri(&uri);
-        if config_value.is_none() {
-            tracing::error!(?uri, "not found service.");
+        let node = match self.graph.by_uri(uri) {
+            Some(node) => node,
+            None => {
+                tracing::error!(?uri, "not found service.");
+                return;
+            }
+        };
+
+        let factory = Arc::new(factory);
+        let parallelism = node.parallelism().clone();
+        if parallelism.replicas() <= 1 {
+            self.spawn_replica(
+                service_type,
+                uri,
+                Arc::clone(&factory),
+                (rx, upstream_count, tx, None),
+                None,
+                &format!("task-{}", uri),
+            );
             return;
         }
 
+        let input_ports = match self.graph.inputs().get(uri) {
+            Some(InputWire::Single(_, _, port)) => *port + 1,
+            Some(InputWire::Fanin(o, _)) => o.iter().map(|(_, p)| *p + 1).max().unwrap_or(1),
+            _ => 1,
+        };
+        let capacity = node.channel_config().unwrap_or_default().capacity();
+        let (replicas, dispatcher, collector) = parallel::replicate(
+            uri,
+            &parallelism,
+            rx,
+            upstream_count,
+            input_ports,
+            tx,
+            capacity,
+        );
+        toy_rt::spawn_named(dispatcher, &format!("task-{}-dispatch", uri));
+        toy_rt::spawn_named(collector, &format!("task-{}-collect", uri));
+        for (idx, r) in replicas.into_iter().enumerate() {
+            self.spawn_replica(
+                service_type,
+                uri,
+                Arc::clone(&factory),
+                (r.rx, upstream_count, r.tx, r.outgoings),
+                Some(idx),
+                &format!("task-{}-{}", uri, idx),
+            );
+        }
+    }
+}
+
+impl Executor {
+    /// Spawn a task that runs the service of node.
+    /// Replicas of node are spawned as many as `parallelism`, with its own service and context.
+    fn spawn_replica<F>(
+        &self,
+        service_type: &ServiceType,
+        uri: &Uri,
+        factory: Arc<F>,
+        channels: (
+            Incoming<Frame>,
+            u32,
+            Outgoing<Frame>,
+            Option<RequestOutgoings>,
+        ),
+        replica: Option<usize>,
+        task_name: &str,
+    ) where
+        F: ServiceFactory<Request = Frame> + Send + Sync + 'static,
+        F::Service: Send,
+        F::Context: Send,
+        F::Config: DeserializeOwned + Send,
+    {
+        let (rx, upstream_count, tx, request_outgoings) = channels;
+        let uri = uri.clone();
+        let service_type = service_type.clone();
+
+        let node = match self.graph.by_uri(&uri) {
+            Some(node) => node,
+            None => {
+                tracing::error!(?uri, "not found service.");
+                return;
+            }
+        };
+
         let task_ctx = self.ctx.clone();
-        let task_ctx = task_ctx.with_uri(&uri);
+        let task_ctx = match replica {
+            Some(idx) => task_ctx.with_uri(&uri).with_replica(idx),
+            None => task_ctx.with_uri(&uri),
+        };
         let errors = Arc::clone(&self.errors);
-        match data::unpack::<F::Config>(&config_value.unwrap().config()) {
+        let config_value = node.config();
+        match data::unpack::<F::Config>(&config_value) {
             Ok(config) => {
-                let task_name = uri.to_string();
                 toy_rt::spawn_named(
                     async move {
+                        let on_error = node.error_policy();
+                        if !factory.accepts_error_policy(&on_error) {
+                            push_error(
+                                Arc::clone(&errors),
+                                &uri,
+                                &service_type,
+                                ServiceError::error(format!(
+                                    "on_error {:?} is not supported by the service. uri:{}",
+                                    on_error, uri
+                                )),
+                            )
+                            .await;
+                            return;
+                        }
                         let new_service = factory.new_service(service_type.clone()).await;
                         let new_ctx = factory.new_context(service_type.clone(), config).await;
                         match (new_service, new_ctx) {
@@ -154,9 +254,15 @@ impl ServiceExecutor for Executor {
                                     rx,
                                     upstream_count,
                                     tx,
+                                    request_outgoings,
                                     service,
                                     service_type.clone(),
                                     ctx,
+                                    ContextFactory {
+                                        factory: &*factory,
+                                        config: &config_value,
+                                        on_error,
+                                    },
                                 )
                                 .await
                                 {
@@ -191,7 +297,7 @@ impl ServiceExecutor for Executor {
                             }
                         }
                     },
-                    &format!("task-{}", task_name),
+                    task_name,
                 );
             }
             Err(e) => tracing::error!("config initialize failed. uri:{:?}, error:{:?}", uri, e),
@@ -217,6 +323,17 @@ impl TaskExecutor for Executor {
             Operation::StartTask.as_message()
         );
         self.ctx.push_task_event(MetricsEventKind::StartTask).await;
+
+        let checkpoints = self.ctx.checkpoints().cloned();
+        let checkpointing = Arc::new(AtomicBool::new(true));
+        if let Some(ref c) = checkpoints {
+            c.restore(self.ctx.id()).await?;
+            toy_rt::spawn_named(
+                save_checkpoints(Arc::clone(c), self.ctx.id(), Arc::clone(&checkpointing)),
+                "task-checkpoint",
+            );
+        }
+
         // need to reverse ....
         let nodes = self
             .graph
@@ -234,11 +351,25 @@ impl TaskExecutor for Executor {
 
         let errors = Arc::clone(&self.errors);
         let r = self.run_0(start_frame).await;
+        let completed = matches!(r, Ok(true));
         if let Err(e) = r {
             let mut lock = errors.lock().await;
             lock.push(e.into());
         }
 
+        checkpointing.store(false, Ordering::SeqCst);
+        if let Some(c) = checkpoints {
+            // keep the checkpoints to resume, unless the task completed successfully.
+            let r = if completed && errors.lock().await.is_empty() {
+                c.clear(self.ctx.id()).await
+            } else {
+                c.save(self.ctx.id()).await
+            };
+            if let Err(e) = r {
+                tracing::error!(parent: &span, err = %e, "error, checkpoint.");
+            }
+        }
+
         log_total_time(started_at, &span, None, Operation::FinishTask);
         self.ctx.push_task_event(MetricsEventKind::FinishTask).await;
 
@@ -266,17 +397,50 @@ impl TaskExecutorFactory for ExecutorFactory {
     }
 }
 
-async fn process<S, Ctx>(
+/// Create service context again after the stateless service returned an error.
+struct ContextFactory<'a, F> {
+    factory: &'a F,
+    config: &'a Value,
+    on_error: ErrorPolicy,
+}
+
+impl<'a, F> ContextFactory<'a, F>
+where
+    F: ServiceFactory,
+    F::Config: DeserializeOwned,
+{
+    /// Returns the context kept by the service, or the context created again from the config.
+    async fn keep_or_renew(
+        &self,
+        kept: Option<F::Context>,
+        service_type: &ServiceType,
+    ) -> Result<F::Context, F::Error> {
+        if let Some(c) = kept {
+            return Ok(c);
+        }
+        let config = data::unpack::<F::Config>(self.config).map_err(F::Error::custom)?;
+        self.factory
+            .new_context(service_type.clone(), config)
+            .await
+            .map_err(F::Error::custom)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process<F>(
     mut task_ctx: TaskContext,
     mut rx: Incoming<Frame>,
     upstream_count: u32,
     tx: Outgoing<Frame>,
-    mut service: S,
+    mut request_outgoings: Option<RequestOutgoings>,
+    mut service: F::Service,
     service_type: ServiceType,
-    context: Ctx,
-) -> Result<(), S::Error>
+    context: F::Context,
+    ctx_factory: ContextFactory<'_, F>,
+) -> Result<(), F::Error>
 where
-    S: Service<Request = Frame, Context = Ctx>,
+    F: ServiceFactory<Request = Frame>,
+    F::Config: DeserializeOwned,
 {
     let service_starded_at = SystemTime::now();
     let tx_signal = tx.clone();
@@ -301,14 +465,38 @@ where
         .push_service_event(&uri, &service_type, MetricsEventKind::StartService)
         .await;
 
-    sc = service.started(task_ctx.clone(), sc.into());
+    sc = failed_to_err(service.started(task_ctx.clone(), sc.into()))?;
+
+    // data frame received while checking signals in `Next`, handle it on next `Ready`.
+    let mut pending: Option<Frame> = None;
 
     //main loop, receive on message
     loop {
+        // false if the frame is not received, but created to call the service in `Next`.
+        let mut received = true;
         let item = match sc {
-            ServiceContext::Ready(_) => rx.next().await,
+            ServiceContext::Ready(_) => match pending.take() {
+                Some(req) => Some(req),
+                None => rx.next().await,
+            },
             ServiceContext::Complete(_) => None,
-            ServiceContext::Next(_) => Some(Frame::default()),
+            ServiceContext::Next(_) if pending.is_none() => match rx.try_next() {
+                Some(req) if req.is_signal() => Some(req),
+                Some(req) => {
+                    pending = Some(req);
+                    received = false;
+                    Some(Frame::default())
+                }
+                None => {
+                    received = false;
+                    Some(Frame::default())
+                }
+            },
+            ServiceContext::Next(_) => {
+                received = false;
+                Some(Frame::default())
+            }
+            ServiceContext::Failed(..) => unreachable!("failed context is handled after the call."),
         };
 
         task_ctx
@@ -323,6 +511,14 @@ where
                     .await;
                 break;
             }
+            Some(req) if req.is_drain() => {
+                tracing::info!(parent: &info_span, ?uri, "receive drain signal.");
+                task_ctx
+                    .push_service_event(uri, &service_type, MetricsEventKind::ReceiveDrain)
+                    .await;
+                on_finish(tx_signal, task_ctx.uri().clone()).await;
+                break;
+            }
             Some(req) if req.is_upstream_finish() => {
                 finish_count += 1;
                 tracing::info!(
@@ -342,7 +538,7 @@ where
                 {
                     let tx = tx.clone();
                     let ctx = task_ctx.clone();
-                    sc = service.upstream_finish(ctx, sc.into(), req, tx).await?;
+                    sc = failed_to_err(service.upstream_finish(ctx, sc.into(), req, tx).await?)?;
                     task_ctx
                         .push_service_event(
                             &uri,
@@ -355,7 +551,7 @@ where
                     tracing::info!(parent: &info_span, ?uri, "all upstream finish.");
                     let tx = tx.clone();
                     let ctx = task_ctx.clone();
-                    sc = service.upstream_finish_all(ctx, sc.into(), tx).await?;
+                    sc = failed_to_err(service.upstream_finish_all(ctx, sc.into(), tx).await?)?;
                     task_ctx
                         .push_service_event(
                             &uri,
@@ -366,11 +562,76 @@ where
                 }
             }
             Some(req) => {
-                let tx = tx.clone();
-                sc = {
-                    let task_ctx = task_ctx.clone();
-                    service.handle(task_ctx, sc.into(), req, tx).await?
+                // ordered replica sends the output of request to its own channel.
+                let tx = match request_outgoings.as_mut() {
+                    Some(o) if received => o.recv().await.unwrap_or_else(|| tx.clone()),
+                    _ => tx.clone(),
                 };
+                // a source in `Next` is resumed after the error.
+                let next = matches!(sc, ServiceContext::Next(_));
+                let mut retry_count = 0;
+                loop {
+                    let r = {
+                        let tx = tx.clone();
+                        let task_ctx = task_ctx.clone();
+                        service.handle(task_ctx, sc.into(), req.clone(), tx).await
+                    };
+                    // the context is kept if reported by `Failed`, otherwise lost with the error,
+                    // and the node fails unless the context can be created again.
+                    let (kept, e) = match r {
+                        Ok(ServiceContext::Failed(c, e)) => (Some(c), F::Error::custom(e)),
+                        Ok(c) => {
+                            sc = c;
+                            break;
+                        }
+                        Err(e) if ctx_factory.factory.is_stateless() => (None, e),
+                        Err(e) => return Err(e),
+                    };
+                    tracing::warn!(
+                        parent: &info_span,
+                        ?uri,
+                        err = %e,
+                        on_error = ?ctx_factory.on_error,
+                        "service returned error."
+                    );
+                    task_ctx
+                        .push_service_event(uri, &service_type, MetricsEventKind::ReceiveError)
+                        .await;
+                    match ctx_factory.on_error {
+                        ErrorPolicy::Fail => return Err(e),
+                        ErrorPolicy::Skip => (),
+                        policy @ ErrorPolicy::Retry { .. } => {
+                            match policy.backoff_mills(retry_count) {
+                                Some(mills) => {
+                                    retry_count += 1;
+                                    toy_rt::sleep(mills).await;
+                                    sc = ServiceContext::Ready(
+                                        ctx_factory.keep_or_renew(kept, &service_type).await?,
+                                    );
+                                    continue;
+                                }
+                                None => return Err(e),
+                            }
+                        }
+                        ErrorPolicy::DeadLetter { port } => {
+                            let dead_letter = Frame::from_value(map_value! {
+                                "uri" => uri.to_string(),
+                                "error" => e.to_string(),
+                                "frame" => req.value().cloned().unwrap_or(Value::None),
+                            });
+                            let mut tx = tx.clone();
+                            tx.send_to(port, dead_letter)
+                                .await
+                                .map_err(F::Error::custom)?;
+                        }
+                    }
+                    let c = ctx_factory.keep_or_renew(kept, &service_type).await?;
+                    sc = match next {
+                        true => ServiceContext::Next(c),
+                        false => ServiceContext::Ready(c),
+                    };
+                    break;
+                }
                 task_ctx
                     .push_service_event(&uri, &service_type, MetricsEventKind::SendRequest)
                     .await;
@@ -395,7 +656,15 @@ where
     Ok(())
 }
 
-async fn on_finish(mut tx: Outgoing<Frame>, uri: Uri) {
+/// `Failed` is handled by the error policy only for the request, otherwise the node fails.
+fn failed_to_err<C, E: Error>(sc: ServiceContext<C>) -> Result<ServiceContext<C>, E> {
+    match sc {
+        ServiceContext::Failed(_, e) => Err(E::custom(e)),
+        sc => Ok(sc),
+    }
+}
+
+pub(crate) async fn on_finish(mut tx: Outgoing<Frame>, uri: Uri) {
     let results = tx.send_ok_all(Frame::upstream_finish()).await;
     let errors = results
         .iter()
@@ -432,6 +701,19 @@ fn log_total_time(
     }
 }
 
+/// Save the checkpoints periodically while the task is running.
+async fn save_checkpoints(checkpoints: Arc<Checkpoints>, id: TaskId, running: Arc<AtomicBool>) {
+    loop {
+        toy_rt::sleep(checkpoints.interval_mills()).await;
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+        if let Err(e) = checkpoints.save(id).await {
+            tracing::error!(task = %id, err = %e, "error, save checkpoints.");
+        }
+    }
+}
+
 async fn push_error(
     errors: Arc<Mutex<Vec<ServiceError>>>,
     uri: &Uri,
@@ -2,5 +2,6 @@
 //!
 
 mod executor;
+mod parallel;
 
 pub use self::executor::{Executor, ExecutorFactory};
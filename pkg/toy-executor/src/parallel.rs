@@ -0,0 +1,196 @@
+//! Replicas of node. (`parallelism` of node)
+//!
+//! ```text
+//!             +-> replica 0 -+
+//! upstream -> dispatcher -> replica 1 -> collector -> downstream
+//!             +-> replica 2 -+
+//! ```
+//!
+//! Dispatcher chooses the replica of each frame, and sends the upstream finish signals to all replicas.
+//! Collector sends the output of replicas to downstream,
+//! and sends the upstream finish signal once after all replicas finished.
+//!
+//! If the output is ordered, dispatcher creates the channel of each frame and passes it to the replica with the frame.
+//! Collector reads these channels in the order of input.
+//! The frames sent outside of the request (e.g. on upstream finish) are sent after all ordered frames.
+
+use crate::executor::on_finish;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc as tokio_mpsc;
+use toy_core::data::Frame;
+use toy_core::graph::Parallelism;
+use toy_core::mpsc::{self, Incoming, Outgoing};
+use toy_core::Uri;
+
+/// Outgoing of each request, received by the ordered replica in the order of input.
+pub(crate) type RequestOutgoings = tokio_mpsc::Receiver<Outgoing<Frame>>;
+
+/// Channels of one replica.
+pub(crate) struct Replica {
+    pub rx: Incoming<Frame>,
+    pub tx: Outgoing<Frame>,
+    pub outgoings: Option<RequestOutgoings>,
+}
+
+/// Split the channels of node to the replicas.
+///
+/// Returns the channels of replicas, and the futures of dispatcher and collector.
+pub(crate) fn replicate(
+    uri: &Uri,
+    parallelism: &Parallelism,
+    rx: Incoming<Frame>,
+    upstream_count: u32,
+    input_ports: u8,
+    tx: Outgoing<Frame>,
+    capacity: usize,
+) -> (
+    Vec<Replica>,
+    impl Future<Output = ()> + Send,
+    impl Future<Output = ()> + Send,
+) {
+    let output_ports = tx.ports_len().max(1) as u8;
+    let (signal_tx, signal_rx) = mpsc::channel::<Frame>(capacity);
+    let signal_tx = with_ports(signal_tx, output_ports);
+
+    let (queue_tx, queue_rx) = if parallelism.ordered() {
+        let (t, r) = tokio_mpsc::channel::<Incoming<Frame>>(capacity);
+        (Some(t), Some(r))
+    } else {
+        (None, None)
+    };
+
+    let mut replicas = Vec::new();
+    let mut inputs = Vec::new();
+    let mut outgoings = Vec::new();
+    for _ in 0..parallelism.replicas() {
+        let (input_tx, input_rx) = mpsc::channel::<Frame>(capacity);
+        inputs.push(with_ports(input_tx, input_ports));
+        let request_outgoings = if parallelism.ordered() {
+            let (t, r) = tokio_mpsc::channel::<Outgoing<Frame>>(capacity);
+            outgoings.push(t);
+            Some(r)
+        } else {
+            None
+        };
+        replicas.push(Replica {
+            rx: input_rx,
+            tx: signal_tx.clone(),
+            outgoings: request_outgoings,
+        });
+    }
+
+    let stopped = Arc::new(AtomicBool::new(false));
+    let dispatcher = dispatch(Dispatcher {
+        parallelism: parallelism.clone(),
+        rx,
+        upstream_count,
+        inputs,
+        outgoings,
+        queue: queue_tx,
+        output_ports,
+        capacity,
+        stopped: Arc::clone(&stopped),
+    });
+    let collector = collect(uri.clone(), tx, queue_rx, signal_rx, stopped);
+    (replicas, dispatcher, collector)
+}
+
+struct Dispatcher {
+    parallelism: Parallelism,
+    rx: Incoming<Frame>,
+    upstream_count: u32,
+    inputs: Vec<Outgoing<Frame>>,
+    outgoings: Vec<tokio_mpsc::Sender<Outgoing<Frame>>>,
+    queue: Option<tokio_mpsc::Sender<Incoming<Frame>>>,
+    output_ports: u8,
+    capacity: usize,
+    stopped: Arc<AtomicBool>,
+}
+
+async fn dispatch(mut d: Dispatcher) {
+    let mut seq = 0u64;
+    let mut finish_count = 0;
+    while let Some(frame) = d.rx.next().await {
+        if frame.is_signal() {
+            if frame.is_stop() {
+                d.stopped.store(true, Ordering::SeqCst);
+            }
+            for tx in d.inputs.iter_mut() {
+                let _ = tx.send_to(frame.port(), frame.clone()).await;
+            }
+            if frame.is_upstream_finish() {
+                finish_count += 1;
+                if finish_count < d.upstream_count {
+                    continue;
+                }
+            }
+            break;
+        }
+
+        let idx = d
+            .parallelism
+            .dispatch()
+            .select(&frame, seq, d.parallelism.replicas());
+        seq += 1;
+        if let Some(ref queue) = d.queue {
+            let (req_tx, req_rx) = mpsc::channel::<Frame>(d.capacity);
+            if queue.send(req_rx).await.is_err() {
+                break;
+            }
+            if d.outgoings[idx]
+                .send(with_ports(req_tx, d.output_ports))
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+        if let Err(e) = d.inputs[idx].send_to(frame.port(), frame).await {
+            tracing::error!(err = ?e, "error, dispatch frame to replica.");
+            break;
+        }
+    }
+}
+
+async fn collect(
+    uri: Uri,
+    mut tx: Outgoing<Frame>,
+    queue: Option<tokio_mpsc::Receiver<Incoming<Frame>>>,
+    mut signal_rx: Incoming<Frame>,
+    stopped: Arc<AtomicBool>,
+) {
+    if let Some(mut queue) = queue {
+        while let Some(mut rx) = queue.recv().await {
+            while let Some(frame) = rx.next().await {
+                forward(&uri, &mut tx, frame).await;
+            }
+        }
+    }
+    // closed after all replicas finished.
+    while let Some(frame) = signal_rx.next().await {
+        if !frame.is_signal() {
+            forward(&uri, &mut tx, frame).await;
+        }
+    }
+    if !stopped.load(Ordering::SeqCst) {
+        on_finish(tx, uri).await;
+    }
+}
+
+/// Port of frame is the output port of replica, same as the output port of node.
+async fn forward(uri: &Uri, tx: &mut Outgoing<Frame>, frame: Frame) {
+    if let Err(e) = tx.send_to(frame.port(), frame).await {
+        tracing::error!(?uri, err = ?e, "error, send frame of replica.");
+    }
+}
+
+/// Outgoing that keeps the port of frame. (output port `n` -> input port `n`)
+fn with_ports(tx: Outgoing<Frame>, ports: u8) -> Outgoing<Frame> {
+    let mut o = Outgoing::empty();
+    for port in 0..ports.max(1) {
+        o.merge_by(port, tx.clone());
+    }
+    o
+}
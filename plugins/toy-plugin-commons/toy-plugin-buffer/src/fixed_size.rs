@@ -1,6 +1,6 @@
 use crate::config::FixedSizeConfig;
 use std::future::Future;
-use toy_core::data::{Frame, Value};
+use toy_core::data::{Frame, Map, Value};
 use toy_core::error::{OutgoingError, ServiceError};
 use toy_core::mpsc::Outgoing;
 use toy_core::prelude::{
@@ -15,6 +15,8 @@ pub struct FixedSizeContext {
     config: FixedSizeConfig,
     buf: Option<Vec<Value>>,
     timestamps: Option<Vec<Value>>,
+    /// headers of the first frame in the buffer, kept on the flushed frame.
+    headers: Option<Map<String, Value>>,
 }
 
 impl FixedSizeContext {
@@ -46,6 +48,9 @@ impl Service for FixedSize {
         mut tx: Outgoing<Self::Request>,
     ) -> Self::Future {
         async move {
+            if ctx.headers.is_none() {
+                ctx.headers = Some(req.headers().clone());
+            }
             let v = req.into_value().unwrap_or(Value::None);
             ctx.buf.as_mut().map(|x| x.push(v));
             ctx.timestamps.as_mut().map(|x| x.push(Value::now()));
@@ -87,7 +92,8 @@ async fn flush(ctx: &mut FixedSizeContext, tx: &mut Outgoing<Frame>) -> Result<(
         };
         ctx.buf = Some(Vec::with_capacity(ctx.config.size));
         ctx.timestamps = Some(Vec::with_capacity(ctx.config.size));
-        tx.send_ok(Frame::from_value(v)).await
+        let headers = ctx.headers.take().unwrap_or_default();
+        tx.send_ok(Frame::from_value(v).with_headers(headers)).await
     } else {
         Ok(())
     }
@@ -114,6 +120,7 @@ impl ServiceFactory for FixedSize {
                 config,
                 buf: Some(Vec::with_capacity(s)),
                 timestamps: Some(Vec::with_capacity(s)),
+                headers: None,
             })
         }
     }
@@ -23,7 +23,33 @@ async fn chunked() {
     }
 }
 
+#[tokio::test]
+async fn chunked_headers() {
+    let frames = (1..=4)
+        .map(|i| Frame::from(i).with_header("id", i))
+        .collect::<Vec<_>>();
+    let r = go_frames(frames, 2).await;
+
+    assert_eq!(r.len(), 2);
+    assert_eq!(r[0].header("id"), Some(&Value::from(1)));
+    assert_eq!(r[1].header("id"), Some(&Value::from(3)));
+}
+
 async fn go(data: Value, size: usize) -> Vec<Value> {
+    let frames = data
+        .as_vec()
+        .unwrap()
+        .iter()
+        .map(|v| Frame::from_value(v.clone()))
+        .collect();
+    go_frames(frames, size)
+        .await
+        .into_iter()
+        .map(|x| x.into_value().unwrap())
+        .collect()
+}
+
+async fn go_frames(frames: Vec<Frame>, size: usize) -> Vec<Frame> {
     let mut service = FixedSize;
 
     let (tx, mut rx) = toy_core::mpsc::channel(10);
@@ -36,15 +62,8 @@ async fn go(data: Value, size: usize) -> Vec<Value> {
         .unwrap();
 
     // send...
-    for v in data.as_vec().unwrap() {
-        let r = service
-            .handle(
-                task_ctx.clone(),
-                c,
-                Frame::from_value(v.clone()),
-                tx.clone(),
-            )
-            .await;
+    for f in frames {
+        let r = service.handle(task_ctx.clone(), c, f, tx.clone()).await;
         assert!(r.is_ok());
         c = r.unwrap().into();
     }
@@ -57,7 +76,7 @@ async fn go(data: Value, size: usize) -> Vec<Value> {
 
     let mut result = vec![];
     while let Some(item) = rx.next().await {
-        result.push(item.into_value().unwrap());
+        result.push(item);
     }
 
     result
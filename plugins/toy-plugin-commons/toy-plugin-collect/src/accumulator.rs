@@ -0,0 +1,227 @@
+use crate::config::AggregateFunction;
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use toy_core::data::Value;
+
+/// State of an aggregate function for a group.
+/// Saved to the files with the group, and merged with the state of the same group read again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum Accumulator {
+    Sum {
+        int: i64,
+        float: f64,
+        is_float: bool,
+        seen: bool,
+    },
+    Min(Option<Value>),
+    Max(Option<Value>),
+    /// mean is updated incrementally, to keep the precision of TimeStamp as milliseconds.
+    Avg {
+        mean: f64,
+        count: u64,
+        timestamp: bool,
+    },
+    Count(u64),
+    CountDistinct(BTreeSet<Value>),
+    Distinct(BTreeSet<Value>),
+}
+
+impl Accumulator {
+    pub(crate) fn new(function: AggregateFunction) -> Self {
+        match function {
+            AggregateFunction::Sum => Accumulator::Sum {
+                int: 0,
+                float: 0.0,
+                is_float: false,
+                seen: false,
+            },
+            AggregateFunction::Min => Accumulator::Min(None),
+            AggregateFunction::Max => Accumulator::Max(None),
+            AggregateFunction::Avg => Accumulator::Avg {
+                mean: 0.0,
+                count: 0,
+                timestamp: false,
+            },
+            AggregateFunction::Count => Accumulator::Count(0),
+            AggregateFunction::CountDistinct => Accumulator::CountDistinct(BTreeSet::new()),
+            AggregateFunction::Distinct => Accumulator::Distinct(BTreeSet::new()),
+        }
+    }
+
+    /// Values of unsupported type are ignored.
+    pub(crate) fn add(&mut self, v: &Value) {
+        match self {
+            Accumulator::Sum {
+                int,
+                float,
+                is_float,
+                seen,
+            } => match v {
+                Value::Integer(x) => {
+                    add_int(int, float, is_float, *x);
+                    *seen = true;
+                }
+                Value::Number(x) => {
+                    *float += x;
+                    *is_float = true;
+                    *seen = true;
+                }
+                _ => {}
+            },
+            Accumulator::Min(current) => {
+                if is_comparable(v) && current.as_ref().is_none_or(|c| compare(v, c).is_lt()) {
+                    *current = Some(v.clone());
+                }
+            }
+            Accumulator::Max(current) => {
+                if is_comparable(v) && current.as_ref().is_none_or(|c| compare(v, c).is_gt()) {
+                    *current = Some(v.clone());
+                }
+            }
+            Accumulator::Avg {
+                mean,
+                count,
+                timestamp,
+            } => {
+                let x = match v {
+                    Value::Integer(x) => *x as f64,
+                    Value::Number(x) => *x,
+                    Value::TimeStamp(x) => {
+                        *timestamp = true;
+                        x.timestamp_millis() as f64
+                    }
+                    _ => return,
+                };
+                *count += 1;
+                *mean += (x - *mean) / *count as f64;
+            }
+            Accumulator::Count(count) => {
+                if v != &Value::None {
+                    *count += 1;
+                }
+            }
+            Accumulator::CountDistinct(set) | Accumulator::Distinct(set) => {
+                if !set.contains(v) {
+                    set.insert(v.clone());
+                }
+            }
+        }
+    }
+
+    /// Merge the state of the same function.
+    pub(crate) fn merge(&mut self, other: Accumulator) {
+        match (self, other) {
+            (
+                Accumulator::Sum {
+                    int,
+                    float,
+                    is_float,
+                    seen,
+                },
+                Accumulator::Sum {
+                    int: o_int,
+                    float: o_float,
+                    is_float: o_is_float,
+                    seen: o_seen,
+                },
+            ) => {
+                add_int(int, float, is_float, o_int);
+                *float += o_float;
+                *is_float |= o_is_float;
+                *seen |= o_seen;
+            }
+            (a @ Accumulator::Min(_), Accumulator::Min(Some(v)))
+            | (a @ Accumulator::Max(_), Accumulator::Max(Some(v))) => a.add(&v),
+            (
+                Accumulator::Avg {
+                    mean,
+                    count,
+                    timestamp,
+                },
+                Accumulator::Avg {
+                    mean: o_mean,
+                    count: o_count,
+                    timestamp: o_timestamp,
+                },
+            ) => {
+                let total = *count + o_count;
+                if total > 0 {
+                    *mean += (o_mean - *mean) * (o_count as f64 / total as f64);
+                }
+                *count = total;
+                *timestamp |= o_timestamp;
+            }
+            (Accumulator::Count(count), Accumulator::Count(o_count)) => *count += o_count,
+            (Accumulator::CountDistinct(set), Accumulator::CountDistinct(o_set))
+            | (Accumulator::Distinct(set), Accumulator::Distinct(o_set)) => set.extend(o_set),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn result(&self) -> Value {
+        match self {
+            Accumulator::Sum { seen: false, .. } => Value::None,
+            Accumulator::Sum {
+                int,
+                float,
+                is_float,
+                ..
+            } => {
+                if *is_float {
+                    Value::from(*int as f64 + float)
+                } else {
+                    Value::from(*int)
+                }
+            }
+            Accumulator::Min(v) | Accumulator::Max(v) => v.clone().unwrap_or(Value::None),
+            Accumulator::Avg { count: 0, .. } => Value::None,
+            Accumulator::Avg {
+                mean, timestamp, ..
+            } => {
+                if *timestamp {
+                    DateTime::from_timestamp_millis(mean.round() as i64)
+                        .map(Value::from)
+                        .unwrap_or(Value::None)
+                } else {
+                    Value::from(*mean)
+                }
+            }
+            Accumulator::Count(count) => Value::from(*count),
+            Accumulator::CountDistinct(set) => Value::from(set.len() as u64),
+            Accumulator::Distinct(set) => Value::from(set.iter().cloned().collect::<Vec<_>>()),
+        }
+    }
+}
+
+/// The sum falls back to Number on overflow of Integer.
+fn add_int(int: &mut i64, float: &mut f64, is_float: &mut bool, x: i64) {
+    match int.checked_add(x) {
+        Some(v) => *int = v,
+        None => {
+            *float += *int as f64 + x as f64;
+            *int = 0;
+            *is_float = true;
+        }
+    }
+}
+
+fn is_comparable(v: &Value) -> bool {
+    matches!(
+        v,
+        Value::Integer(_) | Value::Number(_) | Value::TimeStamp(_)
+    )
+}
+
+/// Integer and Number are compared as number.
+fn compare(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Integer(x), Value::Integer(y)) => x.cmp(y),
+        (Value::TimeStamp(x), Value::TimeStamp(y)) => x.cmp(y),
+        _ => match (a.parse_f64(), b.parse_f64()) {
+            (Some(x), Some(y)) => x.total_cmp(&y),
+            _ => a.cmp(b),
+        },
+    }
+}
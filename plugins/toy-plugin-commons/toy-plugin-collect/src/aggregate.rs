@@ -0,0 +1,226 @@
+use crate::accumulator::Accumulator;
+use crate::config::AggregateConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use toy_core::data::{Map, Value};
+use toy_core::prelude::{
+    Frame, Outgoing, PortType, Service, ServiceContext, ServiceError, ServiceFactory, ServiceType,
+    TaskContext,
+};
+use toy_spill::Spill;
+
+/// Groups by the values of keys.
+type Groups = BTreeMap<Vec<Value>, Vec<Accumulator>>;
+
+/// A group saved to the files, the states of the same group are saved to the same file.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    key: Vec<Value>,
+    accumulators: Vec<Accumulator>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Aggregate;
+
+pub struct AggregateContext {
+    config: AggregateConfig,
+    groups: Groups,
+    spill: Option<Spill>,
+    last_flush: Instant,
+}
+
+impl AggregateContext {
+    /// Count of the groups on memory.
+    pub fn groups(&self) -> usize {
+        self.groups.len()
+    }
+
+    async fn add(&mut self, task_ctx: &TaskContext, v: &Value) -> Result<(), ServiceError> {
+        let key = self
+            .config
+            .keys()
+            .iter()
+            .map(|x| v.path(x).cloned().unwrap_or(Value::None))
+            .collect::<Vec<_>>();
+        let aggregates = self.config.aggregates();
+        let accumulators = self.groups.entry(key).or_insert_with(|| {
+            aggregates
+                .iter()
+                .map(|x| Accumulator::new(x.function()))
+                .collect()
+        });
+        for (a, f) in accumulators.iter_mut().zip(aggregates) {
+            if let Some(x) = v.path(f.path()) {
+                a.add(x);
+            }
+        }
+
+        if self.groups.len() > self.config.max_groups() as usize {
+            if let Some(dir) = self.config.persist() {
+                let spill = self.spill.get_or_insert_with(|| {
+                    let name = task_ctx.uri().to_string().replace("/", "-");
+                    // replicas share the uri, so each replica has its own files.
+                    let name = match task_ctx.replica() {
+                        Some(idx) => format!("{}-{}-{}", task_ctx.id(), name, idx),
+                        None => format!("{}-{}", task_ctx.id(), name),
+                    };
+                    Spill::new(dir, &name)
+                });
+                tracing::debug!(parent: task_ctx.span(), groups = self.groups.len(), "too many groups, save to the files.");
+                save(spill, std::mem::take(&mut self.groups)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Send all groups, and clear them.
+    async fn flush(&mut self, tx: &mut Outgoing<Frame>) -> Result<(), ServiceError> {
+        self.last_flush = Instant::now();
+        let groups = std::mem::take(&mut self.groups);
+        match self.spill.take() {
+            None => self.send(groups, tx).await,
+            Some(spill) => {
+                save(&spill, groups).await?;
+                for p in 0..spill.partitions() {
+                    let groups = load(&spill, p).await?;
+                    self.send(groups, tx).await?;
+                }
+                Ok(spill.remove().await?)
+            }
+        }
+    }
+
+    async fn send(&self, groups: Groups, tx: &mut Outgoing<Frame>) -> Result<(), ServiceError> {
+        for (key, accumulators) in groups {
+            let mut map = Map::new();
+            for (name, v) in self.config.keys().iter().zip(key) {
+                map.insert(name.clone(), v);
+            }
+            for (f, a) in self.config.aggregates().iter().zip(accumulators) {
+                map.insert(f.name().to_string(), a.result());
+            }
+            tx.send_ok(Frame::from(map)).await?;
+        }
+        Ok(())
+    }
+
+    fn is_flush_time(&self) -> bool {
+        self.config
+            .flush_after_mills()
+            .is_some_and(|x| self.last_flush.elapsed() >= Duration::from_millis(x))
+    }
+}
+
+/// Append the groups to the files of their partitions.
+async fn save(spill: &Spill, groups: Groups) -> Result<(), ServiceError> {
+    let entries = groups.into_iter().map(|(key, accumulators)| {
+        let p = spill.partition(&key);
+        (p, Entry { key, accumulators })
+    });
+    Ok(spill.write(entries).await?)
+}
+
+/// Read the groups of the partition, the states of the same group are merged.
+async fn load(spill: &Spill, partition: usize) -> Result<Groups, ServiceError> {
+    let mut r = Groups::new();
+    spill
+        .read(partition, |e: Entry| match r.get_mut(&e.key) {
+            Some(accumulators) => {
+                for (a, o) in accumulators.iter_mut().zip(e.accumulators) {
+                    a.merge(o);
+                }
+            }
+            None => {
+                r.insert(e.key, e.accumulators);
+            }
+        })
+        .await?;
+    Ok(r)
+}
+
+impl Service for Aggregate {
+    type Context = AggregateContext;
+    type Request = Frame;
+    type Future =
+        impl Future<Output = Result<ServiceContext<AggregateContext>, ServiceError>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<AggregateContext>, ServiceError>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<AggregateContext>, ServiceError>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::flow()
+    }
+
+    fn handle(
+        &mut self,
+        task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            if let Some(v) = req.value() {
+                // the groups are kept for the next frame.
+                if let Err(e) = ctx.add(&task_ctx, v).await {
+                    return Ok(ServiceContext::Failed(ctx, e));
+                }
+            }
+            if ctx.is_flush_time() {
+                ctx.flush(&mut tx).await?;
+            }
+            Ok(ServiceContext::Ready(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move {
+            ctx.flush(&mut tx).await?;
+            Ok(ServiceContext::Complete(ctx))
+        }
+    }
+}
+
+impl ServiceFactory for Aggregate {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Aggregate;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = AggregateContext;
+    type Config = AggregateConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Aggregate) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
+        async move {
+            Ok(AggregateContext {
+                config,
+                groups: Groups::new(),
+                spill: None,
+                last_flush: Instant::now(),
+            })
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use toy_pack::Schema;
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize, Schema)]
@@ -9,3 +10,126 @@ pub struct LastConfig {}
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize, Schema)]
 pub struct CountConfig {}
+
+pub const fn default_max_groups() -> u32 {
+    10000
+}
+
+/// Function to aggregate the values of a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Schema)]
+pub enum AggregateFunction {
+    /// Sum of Integer or Number. Integer if all values are Integer and the sum does not overflow.
+    Sum,
+    /// Minimum of Integer, Number or TimeStamp.
+    Min,
+    /// Maximum of Integer, Number or TimeStamp.
+    Max,
+    /// Average of Integer, Number or TimeStamp. Number, or TimeStamp if the values are TimeStamp.
+    Avg,
+    /// Count of the frames which have the value.
+    Count,
+    /// Count of the distinct values.
+    CountDistinct,
+    /// Distinct values, in order of the value.
+    Distinct,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Schema)]
+pub struct AggregateField {
+    /// Name of the result in the sent frame.
+    name: String,
+    /// Path of the value.
+    path: String,
+    function: AggregateFunction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Schema)]
+pub struct AggregateConfig {
+    /// Paths of the group keys. All frames are in the same group if empty.
+    #[serde(default)]
+    keys: Vec<String>,
+    aggregates: Vec<AggregateField>,
+    /// Send the groups when a frame arrives `flush_after_mills` or more after the last send.
+    /// No timer is used, so the groups are kept while no frame arrives.
+    /// The groups are sent only after all upstreams finished if not set.
+    flush_after_mills: Option<u64>,
+    #[serde(default = "default_max_groups")]
+    max_groups: u32,
+    /// When the count of groups exceeds `max_groups`,
+    /// it will save the groups to the files in this directory and clear the groups.
+    /// All groups are kept on memory if not set.
+    persist: Option<PathBuf>,
+}
+
+impl AggregateField {
+    pub fn new(
+        name: impl Into<String>,
+        path: impl Into<String>,
+        function: AggregateFunction,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            path: path.into(),
+            function,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn function(&self) -> AggregateFunction {
+        self.function
+    }
+}
+
+impl AggregateConfig {
+    pub fn with(keys: &[&str], aggregates: Vec<AggregateField>) -> Self {
+        Self {
+            keys: keys.iter().map(|x| x.to_string()).collect(),
+            aggregates,
+            flush_after_mills: None,
+            max_groups: default_max_groups(),
+            persist: None,
+        }
+    }
+
+    pub fn with_flush_after_mills(self, flush_after_mills: u64) -> Self {
+        Self {
+            flush_after_mills: Some(flush_after_mills),
+            ..self
+        }
+    }
+
+    pub fn with_persist(self, path: impl Into<PathBuf>, max_groups: u32) -> Self {
+        Self {
+            persist: Some(path.into()),
+            max_groups,
+            ..self
+        }
+    }
+
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    pub fn aggregates(&self) -> &[AggregateField] {
+        &self.aggregates
+    }
+
+    pub fn flush_after_mills(&self) -> Option<u64> {
+        self.flush_after_mills
+    }
+
+    pub fn max_groups(&self) -> u32 {
+        self.max_groups
+    }
+
+    pub fn persist(&self) -> Option<&PathBuf> {
+        self.persist.as_ref()
+    }
+}
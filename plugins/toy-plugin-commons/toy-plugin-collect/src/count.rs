@@ -1,8 +1,8 @@
 use crate::config::CountConfig;
 use std::future::Future;
 use toy_core::prelude::{
-    Frame, Outgoing, PortType, Service, ServiceContext, ServiceError, ServiceFactory, ServiceType,
-    TaskContext,
+    map_value, Frame, Outgoing, PortType, Service, ServiceContext, ServiceError, ServiceFactory,
+    ServiceType, TaskContext,
 };
 
 #[derive(Clone, Debug)]
@@ -26,15 +26,31 @@ impl Service for Count {
         PortType::sink()
     }
 
+    fn started(
+        &mut self,
+        task_ctx: TaskContext,
+        mut ctx: Self::Context,
+    ) -> ServiceContext<Self::Context> {
+        if let Some(count) = task_ctx
+            .restored()
+            .and_then(|x| x.path("count"))
+            .and_then(|x| x.as_u64())
+        {
+            ctx.count = count;
+        }
+        ServiceContext::Ready(ctx)
+    }
+
     fn handle(
         &mut self,
-        _task_ctx: TaskContext,
+        task_ctx: TaskContext,
         mut ctx: Self::Context,
         _req: Self::Request,
         _tx: Outgoing<Self::Request>,
     ) -> Self::Future {
         async move {
             ctx.count += 1;
+            task_ctx.checkpoint(map_value! { "count" => ctx.count });
             Ok(ServiceContext::Ready(ctx))
         }
     }
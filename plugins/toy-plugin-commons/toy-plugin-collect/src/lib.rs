@@ -3,13 +3,16 @@
 pub mod config;
 mod plugin;
 
+mod accumulator;
+mod aggregate;
 mod count;
 mod first;
 mod last;
 
-pub use plugin::{all, count, first, last};
+pub use plugin::{aggregate, all, count, first, last, All};
 
 pub mod service {
+    pub use super::aggregate::{Aggregate, AggregateContext};
     pub use super::count::{Count, CountContext};
     pub use super::first::{First, FirstContext};
     pub use super::last::{Last, LastContext};
@@ -16,6 +16,16 @@ pub fn count() -> (&'static str, &'static str, Count) {
     (NAME_SPACE, "count", Count)
 }
 
-pub fn all() -> Layered<Layered<Layered<NoopEntry, First>, Last>, Count> {
-    layer(first()).layer(last()).layer(count())
+pub fn aggregate() -> (&'static str, &'static str, Aggregate) {
+    (NAME_SPACE, "aggregate", Aggregate)
+}
+
+/// Services of all collect plugins.
+pub type All = Layered<Layered<Layered<Layered<NoopEntry, First>, Last>, Count>, Aggregate>;
+
+pub fn all() -> All {
+    layer(first())
+        .layer(last())
+        .layer(count())
+        .layer(aggregate())
 }
@@ -0,0 +1,217 @@
+use tempdir::TempDir;
+use toy_core::prelude::*;
+use toy_plugin_collect::config::{AggregateConfig, AggregateField, AggregateFunction};
+use toy_plugin_collect::service::Aggregate;
+
+fn reading(device: &str, v: Value, t: i64) -> Frame {
+    let ts = chrono::DateTime::from_timestamp_millis(t).unwrap();
+    Frame::from_value(map_value! {
+        "device" => device,
+        "v" => v,
+        "ts" => ts
+    })
+}
+
+fn fields() -> Vec<AggregateField> {
+    vec![
+        AggregateField::new("sum", "v", AggregateFunction::Sum),
+        AggregateField::new("min", "v", AggregateFunction::Min),
+        AggregateField::new("max", "v", AggregateFunction::Max),
+        AggregateField::new("avg", "v", AggregateFunction::Avg),
+        AggregateField::new("count", "v", AggregateFunction::Count),
+        AggregateField::new("distinct", "v", AggregateFunction::CountDistinct),
+    ]
+}
+
+#[tokio::test]
+async fn aggregate_by_key() {
+    let frames = vec![
+        reading("a", Value::from(1), 0),
+        reading("b", Value::from(10), 0),
+        reading("a", Value::from(3), 0),
+        reading("a", Value::from(3), 0),
+    ];
+    let config = AggregateConfig::with(&["device"], fields());
+    let r = go(frames, config).await;
+
+    assert_eq!(r.len(), 2);
+    let a = &r[0];
+    assert_eq!(a.path("device"), Some(&Value::from("a")));
+    assert_eq!(a.path("sum"), Some(&Value::from(7i64)));
+    assert_eq!(a.path("min"), Some(&Value::from(1)));
+    assert_eq!(a.path("max"), Some(&Value::from(3)));
+    let avg = a.path("avg").unwrap().parse_f64().unwrap();
+    assert!((avg - 7.0 / 3.0).abs() < 1e-9);
+    assert_eq!(a.path("count"), Some(&Value::from(3u64)));
+    assert_eq!(a.path("distinct"), Some(&Value::from(2u64)));
+    assert_eq!(r[1].path("device"), Some(&Value::from("b")));
+    assert_eq!(r[1].path("sum"), Some(&Value::from(10i64)));
+}
+
+#[tokio::test]
+async fn aggregate_number() {
+    let frames = vec![
+        reading("a", Value::from(1), 0),
+        reading("a", Value::from(2.5), 0),
+        reading("a", Value::from("x"), 0),
+    ];
+    let config = AggregateConfig::with(&[], fields());
+    let r = go(frames, config).await;
+
+    assert_eq!(r.len(), 1);
+    assert_eq!(r[0].path("sum"), Some(&Value::from(3.5)));
+    assert_eq!(r[0].path("min"), Some(&Value::from(1)));
+    assert_eq!(r[0].path("max"), Some(&Value::from(2.5)));
+    assert_eq!(r[0].path("avg"), Some(&Value::from(1.75)));
+    // "x" is counted, but not summed.
+    assert_eq!(r[0].path("count"), Some(&Value::from(3u64)));
+}
+
+#[tokio::test]
+async fn aggregate_timestamp() {
+    let frames = vec![
+        reading("a", Value::None, 1000),
+        reading("a", Value::None, 3000),
+        reading("a", Value::None, 2000),
+    ];
+    let config = AggregateConfig::with(
+        &["device"],
+        vec![
+            AggregateField::new("first", "ts", AggregateFunction::Min),
+            AggregateField::new("last", "ts", AggregateFunction::Max),
+            AggregateField::new("mean", "ts", AggregateFunction::Avg),
+        ],
+    );
+    let r = go(frames, config).await;
+
+    let millis = |name: &str| {
+        r[0].path(name)
+            .unwrap()
+            .as_timestamp()
+            .unwrap()
+            .timestamp_millis()
+    };
+    assert_eq!(millis("first"), 1000);
+    assert_eq!(millis("last"), 3000);
+    assert_eq!(millis("mean"), 2000);
+}
+
+#[tokio::test]
+async fn aggregate_flush_after() {
+    let frames = vec![
+        reading("a", Value::from(1), 0),
+        reading("a", Value::from(2), 0),
+    ];
+    let config = AggregateConfig::with(&["device"], fields()).with_flush_after_mills(0);
+    let r = go(frames, config).await;
+
+    // sent on each frame.
+    assert_eq!(r.len(), 2);
+    assert_eq!(r[0].path("sum"), Some(&Value::from(1i64)));
+    assert_eq!(r[1].path("sum"), Some(&Value::from(2i64)));
+}
+
+#[tokio::test]
+async fn aggregate_persist() {
+    let root = TempDir::new("toy-plugin-collect").unwrap();
+    let mut frames = Vec::new();
+    for round in 0..3 {
+        for i in 0..10 {
+            frames.push(reading(&format!("d{}", i), Value::from(round), 0));
+        }
+    }
+    let config = AggregateConfig::with(
+        &["device"],
+        vec![
+            AggregateField::new("sum", "v", AggregateFunction::Sum),
+            AggregateField::new("values", "v", AggregateFunction::Distinct),
+        ],
+    )
+    .with_persist(root.path(), 3);
+    let r = go(frames, config).await;
+
+    assert_eq!(r.len(), 10);
+    for v in r {
+        assert_eq!(v.path("sum"), Some(&Value::from(3i64)));
+        assert_eq!(v.path("values"), Some(&seq_value![0, 1, 2]));
+    }
+    // saved files are removed.
+    assert_eq!(std::fs::read_dir(root.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn aggregate_sum_overflow() {
+    let frames = vec![
+        reading("a", Value::from(i64::MAX), 0),
+        reading("a", Value::from(1), 0),
+    ];
+    let config = AggregateConfig::with(&[], fields());
+    let r = go(frames, config).await;
+
+    // falls back to Number, not wrapped.
+    assert_eq!(r[0].path("sum"), Some(&Value::from(i64::MAX as f64 + 1.0)));
+}
+
+#[tokio::test]
+async fn aggregate_persist_replicas() {
+    let root = TempDir::new("toy-plugin-collect").unwrap();
+    let frames = (0..10)
+        .map(|i| reading(&format!("d{}", i), Value::from(1), 0))
+        .collect::<Vec<_>>();
+    let config = AggregateConfig::with(
+        &["device"],
+        vec![AggregateField::new("sum", "v", AggregateFunction::Sum)],
+    )
+    .with_persist(root.path(), 3);
+
+    // the replicas of the same node save the groups to their own files.
+    let task_ctx = toy_plugin_test::dummy_task_context();
+    let (r0, r1) = tokio::join!(
+        go_with(
+            frames.clone(),
+            config.clone(),
+            task_ctx.clone().with_replica(0)
+        ),
+        go_with(frames, config, task_ctx.with_replica(1))
+    );
+    for r in [r0, r1] {
+        assert_eq!(r.len(), 10);
+        for v in r {
+            assert_eq!(v.path("sum"), Some(&Value::from(1i64)));
+        }
+    }
+    assert_eq!(std::fs::read_dir(root.path()).unwrap().count(), 0);
+}
+
+async fn go(frames: Vec<Frame>, config: AggregateConfig) -> Vec<Value> {
+    go_with(frames, config, toy_plugin_test::dummy_task_context()).await
+}
+
+async fn go_with(frames: Vec<Frame>, config: AggregateConfig, task_ctx: TaskContext) -> Vec<Value> {
+    let mut service = Aggregate;
+
+    let (tx, mut rx) = toy_core::mpsc::channel(100);
+
+    let mut c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+
+    for f in frames {
+        let r = service.handle(task_ctx.clone(), c, f, tx.clone()).await;
+        assert!(r.is_ok());
+        c = r.unwrap().into();
+    }
+
+    let r = service
+        .upstream_finish_all(task_ctx.clone(), c, tx.clone())
+        .await;
+    assert!(r.is_ok());
+    drop(tx);
+
+    let mut result = vec![];
+    while let Some(item) = rx.next().await {
+        result.push(item.into_value().unwrap());
+    }
+    result
+}
@@ -42,4 +42,16 @@ pub mod filter {
 
 pub mod stat {
     pub use toy_plugin_stat::*;
-}
\ No newline at end of file
+}
+
+pub mod window {
+    pub use toy_plugin_window::*;
+}
+
+pub mod join {
+    pub use toy_plugin_join::*;
+}
+
+pub mod dedup {
+    pub use toy_plugin_dedup::*;
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use toy_core::data::schema::JsonSchema;
+use toy_core::prelude::*;
+use toy_core::registry::{app, Registry};
+
+fn schemas() -> HashMap<ServiceType, JsonSchema> {
+    let app = app(toy_plugin_commons::map::all())
+        .with(toy_plugin_commons::buffer::all())
+        .with(toy_plugin_commons::collect::all())
+        .with(toy_plugin_commons::fanout::all())
+        .with(toy_plugin_commons::stdio::all())
+        .with(toy_plugin_commons::file::all())
+        .with(toy_plugin_commons::filter::all())
+        .with(toy_plugin_commons::tcp::all())
+        .with(toy_plugin_commons::timer::all())
+        .with(toy_plugin_commons::sort::all())
+        .with(toy_plugin_commons::stat::all())
+        .with(toy_plugin_commons::window::all())
+        .with(toy_plugin_commons::join::all())
+        .with(toy_plugin_commons::dedup::all())
+        .build();
+    app.schemas()
+        .into_iter()
+        .filter_map(|x| Some((x.service_type().clone(), x.schema()?.clone())))
+        .collect()
+}
+
+/// Validate the service configs of graph, in the same way as the api server does on PUT.
+fn validate(graph: Value) {
+    let schemas = schemas();
+    let graph = Graph::from(graph).unwrap();
+    for node in graph.iter() {
+        let schema = schemas
+            .get(&node.service_type())
+            .unwrap_or_else(|| panic!("schema not found. {}", node.service_type()));
+        if let Err(e) = schema.validate(&node.config()) {
+            panic!("uri:{}, {:?}", node.uri(), e);
+        }
+    }
+}
+
+fn is_valid(name_space: &str, name: &str, config: Value) -> bool {
+    let tp = ServiceType::new(name_space, name).unwrap();
+    schemas().get(&tp).unwrap().validate(&config).is_ok()
+}
+
+fn read(path: &str) -> String {
+    std::fs::read_to_string(format!(
+        "{}/../examples/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        path
+    ))
+    .unwrap()
+}
+
+#[test]
+fn example_graphs_are_valid() {
+    validate(toy_pack_yaml::unpack::<Value>(&read("file.yml")).unwrap());
+    validate(toy_pack_json::unpack::<Value>(read("tick.json").as_bytes()).unwrap());
+    validate(toy_pack_json::unpack::<Value>(read("stat.json").as_bytes()).unwrap());
+}
+
+#[test]
+fn condition_configs_are_valid() {
+    let filter = |config| is_valid("plugin.common.filter", "filter", config);
+    assert!(filter(map_value! { "condition" => "qtr > 1" }));
+    assert!(filter(map_value! {
+        "preds" => seq_value!["qtr > 1"],
+        "condition" => map_value! {
+            "any" => seq_value![
+                "status in [paid, shipped]",
+                map_value! {
+                    "all" => seq_value![
+                        "amount between 100..1000",
+                        map_value! { "not" => "coupon exists" }
+                    ],
+                }
+            ],
+        },
+    }));
+    assert!(!filter(
+        map_value! { "condition" => map_value! { "any" => "qtr > 1" } }
+    ));
+    assert!(!filter(map_value! { "condition" => 1 }));
+
+    let switch = |config| is_valid("plugin.common.fanout", "switch", config);
+    assert!(switch(map_value! {
+        "routes" => seq_value![
+            map_value! { "condition" => "type == order", "port" => 0 },
+            map_value! {
+                "condition" => map_value! { "any" => seq_value!["type == user", "type == member"] },
+                "port" => 1,
+            }
+        ],
+        "default" => 2,
+    }));
+}
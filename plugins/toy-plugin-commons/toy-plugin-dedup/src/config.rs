@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use toy_pack::Schema;
+
+/// How to remember the keys already seen.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Schema)]
+pub enum SeenKind {
+    /// Remember the keys exactly.
+    /// The least recently seen keys are forgotten over the capacity,
+    /// and the keys are forgotten `ttl_mills` after they are first seen.
+    Lru {
+        capacity: usize,
+        ttl_mills: Option<u64>,
+    },
+
+    /// Remember the keys approximately in the fixed size bloom filter, for huge streams.
+    /// The keys are hashed with the fixed seed, so the saved filter is valid for the next run.
+    /// Unique frames are dropped at `false_positive_rate` when `expected_items` keys are seen,
+    /// and the keys are never forgotten.
+    Bloom {
+        expected_items: usize,
+        false_positive_rate: f64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Schema)]
+pub struct DedupConfig {
+    /// Paths of the key fields. The whole value is the key if empty.
+    #[serde(default)]
+    pub(crate) keys: Vec<String>,
+    pub(crate) seen: SeenKind,
+    /// File to save the seen keys when the upstream finished, and to load them on the next run.
+    /// The replicas of the node save to their own files, the index is inserted before the extension.
+    /// e.g. `seen.db` -> `seen-0.db`, `seen-1.db`
+    pub(crate) persist_path: Option<String>,
+}
+
+impl DedupConfig {
+    pub fn with(keys: &[&str], seen: SeenKind) -> DedupConfig {
+        DedupConfig {
+            keys: keys.iter().map(|x| x.to_string()).collect(),
+            seen,
+            persist_path: None,
+        }
+    }
+
+    pub fn with_persist_path(self, path: impl Into<String>) -> DedupConfig {
+        DedupConfig {
+            persist_path: Some(path.into()),
+            ..self
+        }
+    }
+
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    pub fn seen(&self) -> &SeenKind {
+        &self.seen
+    }
+
+    pub fn persist_path(&self) -> Option<&str> {
+        self.persist_path.as_deref()
+    }
+}
@@ -0,0 +1,163 @@
+use crate::config::DedupConfig;
+use crate::seen::{key_bytes, Seen};
+use chrono::Utc;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use toy_core::data::Frame;
+use toy_core::error::ServiceError;
+use toy_core::metrics::context::metrics;
+use toy_core::metrics::kind::{MeasureKind, MetricsKind};
+use toy_core::mpsc::Outgoing;
+use toy_core::prelude::{PortType, Service, ServiceContext, ServiceFactory, TaskContext};
+use toy_core::ServiceType;
+
+/// Name of the counter of the dropped duplicate frames.
+pub const DROP_DUPLICATE_METRICS: &str = "dedup_drop_duplicate";
+
+#[derive(Clone, Debug)]
+pub struct Dedup;
+
+pub struct DedupContext {
+    keys: Vec<String>,
+    seen: Seen,
+    persist_path: Option<PathBuf>,
+    /// the saved keys are loaded by the first request, when the replica is known.
+    loaded: bool,
+    metrics_kind: MetricsKind,
+}
+
+impl DedupContext {
+    /// Replicas share the config, so each replica has its own file suffixed by the index.
+    fn persist_path(&self, task_ctx: &TaskContext) -> Option<PathBuf> {
+        let path = self.persist_path.as_ref()?;
+        Some(match task_ctx.replica() {
+            Some(idx) => replica_path(path, idx),
+            None => path.clone(),
+        })
+    }
+
+    async fn load(&mut self, task_ctx: &TaskContext) -> Result<(), ServiceError> {
+        if self.loaded {
+            return Ok(());
+        }
+        if let Some(path) = self.persist_path(task_ctx) {
+            self.seen.load(&path, Utc::now().timestamp_millis()).await?;
+        }
+        self.loaded = true;
+        Ok(())
+    }
+}
+
+/// `seen.db` -> `seen-{idx}.db`
+fn replica_path(path: &Path, idx: usize) -> PathBuf {
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+    name.push(format!("-{}", idx));
+    if let Some(ext) = path.extension() {
+        name.push(".");
+        name.push(ext);
+    }
+    path.with_file_name(name)
+}
+
+impl Service for Dedup {
+    type Context = DedupContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<DedupContext>, ServiceError>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<DedupContext>, ServiceError>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<DedupContext>, ServiceError>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::fan_out_flow(2)
+    }
+
+    fn handle(
+        &mut self,
+        task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            let span = task_ctx.span();
+            ctx.load(&task_ctx).await?;
+
+            if let Some(v) = req.value() {
+                let key = key_bytes(v, &ctx.keys);
+                if !ctx.seen.check(key, Utc::now().timestamp_millis()) {
+                    tx.send_ok(req).await?;
+                } else {
+                    tracing::debug!(parent: span, "drop duplicate.");
+                    metrics()
+                        .counter(&ctx.metrics_kind, |c| c.increment())
+                        .await;
+                    if tx.ports_len() > 1 {
+                        tx.send_ok_to(1, req).await?;
+                    }
+                }
+            }
+            Ok(ServiceContext::Ready(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move {
+            // not to overwrite the saved keys, if no frame arrived.
+            ctx.load(&task_ctx).await?;
+            if let Some(path) = ctx.persist_path(&task_ctx) {
+                ctx.seen.save(&path).await?;
+                tracing::info!(parent: task_ctx.span(), ?path, "save seen keys.");
+            }
+            Ok(ServiceContext::Complete(ctx))
+        }
+    }
+}
+
+impl ServiceFactory for Dedup {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Dedup;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = DedupContext;
+    type Config = DedupConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Dedup) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
+        async move {
+            let seen = Seen::new(config.seen())?;
+            let persist_path = config.persist_path().map(PathBuf::from);
+            Ok(DedupContext {
+                keys: config.keys,
+                seen,
+                persist_path,
+                loaded: false,
+                metrics_kind: MetricsKind::Custom(
+                    DROP_DUPLICATE_METRICS.to_string(),
+                    MeasureKind::Counter,
+                ),
+            })
+        }
+    }
+}
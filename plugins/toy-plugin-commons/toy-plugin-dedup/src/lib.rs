@@ -0,0 +1,16 @@
+#![feature(impl_trait_in_assoc_type)]
+
+//! "Dedup" plugin.
+//! Dropping the frames of the keys already seen.
+
+pub mod config;
+mod plugin;
+
+mod dedup;
+mod seen;
+
+pub use plugin::{all, dedup};
+
+pub mod service {
+    pub use super::dedup::{Dedup, DedupContext, DROP_DUPLICATE_METRICS};
+}
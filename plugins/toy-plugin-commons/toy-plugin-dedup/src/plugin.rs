@@ -0,0 +1,12 @@
+use crate::service::Dedup;
+use toy_core::prelude::{layer, Layered, NoopEntry};
+
+const NAME_SPACE: &str = "plugin.common.dedup";
+
+pub fn dedup() -> (&'static str, &'static str, Dedup) {
+    (NAME_SPACE, "dedup", Dedup)
+}
+
+pub fn all() -> Layered<NoopEntry, Dedup> {
+    layer(dedup())
+}
@@ -0,0 +1,254 @@
+use crate::config::SeenKind;
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::LN_2;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use toy_core::data::Value;
+use toy_core::error::ServiceError;
+use xxhash_rust::xxh3::xxh3_64_with_seed;
+
+/// Max count of the hash functions of bloom filter.
+const MAX_BLOOM_HASHES: u32 = 16;
+
+/// Seed of the hash of bloom filter.
+/// Fixed, so the bits saved to the file are valid for the next run and the other builds.
+const BLOOM_SEED: u64 = 0x746f_792d_6465_6475;
+
+/// Keys already seen.
+/// Lru remembers the encoded keys, and Bloom remembers the bits of their hashes.
+pub(crate) enum Seen {
+    Lru {
+        cache: LruCache<Vec<u8>, i64>,
+        ttl_mills: Option<u64>,
+    },
+    Bloom(Bloom),
+}
+
+/// Seen keys saved to the file.
+#[derive(Serialize, Deserialize)]
+enum Snapshot {
+    /// Encoded keys and the times first seen, from the least recently seen.
+    Lru(Vec<(Vec<u8>, i64)>),
+    Bloom {
+        bits: Vec<u64>,
+        hashes: u32,
+    },
+}
+
+pub(crate) struct Bloom {
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl Seen {
+    pub(crate) fn new(kind: &SeenKind) -> Result<Seen, ServiceError> {
+        match kind {
+            SeenKind::Lru {
+                capacity,
+                ttl_mills,
+            } => {
+                let capacity = NonZeroUsize::new(*capacity)
+                    .ok_or_else(|| ServiceError::error("capacity must be greater than 0."))?;
+                Ok(Seen::Lru {
+                    cache: LruCache::new(capacity),
+                    ttl_mills: *ttl_mills,
+                })
+            }
+            SeenKind::Bloom {
+                expected_items,
+                false_positive_rate,
+            } => {
+                if *expected_items == 0 {
+                    return Err(ServiceError::error(
+                        "expected_items must be greater than 0.",
+                    ));
+                }
+                if !(*false_positive_rate > 0.0 && *false_positive_rate < 1.0) {
+                    return Err(ServiceError::error(
+                        "false_positive_rate must be between 0 and 1.",
+                    ));
+                }
+                Ok(Seen::Bloom(Bloom::new(
+                    *expected_items,
+                    *false_positive_rate,
+                )))
+            }
+        }
+    }
+
+    /// Remember the key, and returns true if it is already seen.
+    pub(crate) fn check(&mut self, key: Vec<u8>, now: i64) -> bool {
+        match self {
+            Seen::Lru { cache, ttl_mills } => match cache.get_mut(&key) {
+                Some(at) if !is_expired(*at, now, *ttl_mills) => true,
+                Some(at) => {
+                    *at = now;
+                    false
+                }
+                None => {
+                    cache.put(key, now);
+                    false
+                }
+            },
+            Seen::Bloom(bloom) => bloom.check(xxh3_64_with_seed(&key, BLOOM_SEED)),
+        }
+    }
+
+    /// Load the keys saved by [`Seen::save`].
+    /// The file is ignored if it does not exist, or is saved by the other kind of `Seen`.
+    pub(crate) async fn load(&mut self, path: &Path, now: i64) -> Result<(), ServiceError> {
+        let bytes = match tokio::fs::read(path).await {
+            Ok(b) => b,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(ServiceError::error(e)),
+        };
+        let snapshot = toy_pack_mp::unpack::<Snapshot>(&bytes).map_err(ServiceError::error)?;
+        match (self, snapshot) {
+            (Seen::Lru { cache, ttl_mills }, Snapshot::Lru(entries)) => {
+                for (key, at) in entries {
+                    if !is_expired(at, now, *ttl_mills) {
+                        cache.put(key, at);
+                    }
+                }
+            }
+            (Seen::Bloom(bloom), Snapshot::Bloom { bits, hashes })
+                if bits.len() == bloom.bits.len() && hashes == bloom.hashes =>
+            {
+                bloom.bits = bits;
+            }
+            _ => {
+                tracing::warn!(path = ?path, "ignore the seen keys saved by the other config.");
+            }
+        }
+        Ok(())
+    }
+
+    /// Save the keys to the file, replacing the file saved before.
+    pub(crate) async fn save(&self, path: &Path) -> Result<(), ServiceError> {
+        let snapshot = match self {
+            Seen::Lru { cache, .. } => {
+                Snapshot::Lru(cache.iter().rev().map(|(k, v)| (k.clone(), *v)).collect())
+            }
+            Seen::Bloom(bloom) => Snapshot::Bloom {
+                bits: bloom.bits.clone(),
+                hashes: bloom.hashes,
+            },
+        };
+        let bytes = toy_pack_mp::pack(&snapshot).map_err(ServiceError::error)?;
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, bytes).await?;
+        tokio::fs::rename(&tmp, path).await?;
+        Ok(())
+    }
+}
+
+impl Bloom {
+    fn new(expected_items: usize, false_positive_rate: f64) -> Bloom {
+        let n = expected_items as f64;
+        let m = (-n * false_positive_rate.ln() / (LN_2 * LN_2))
+            .ceil()
+            .max(64.0);
+        let hashes = ((m / n) * LN_2).round().clamp(1.0, MAX_BLOOM_HASHES as f64) as u32;
+        Bloom {
+            bits: vec![0; (m as usize).div_ceil(64)],
+            hashes,
+        }
+    }
+
+    /// Set the bits of the hash, and returns true if all of them are already set.
+    fn check(&mut self, hash: u64) -> bool {
+        let len = self.bits.len() as u64 * 64;
+        // double hashing, the second hash is odd to visit the different bits.
+        let h2 = hash.rotate_left(32) | 1;
+        let mut seen = true;
+        for i in 0..self.hashes as u64 {
+            let bit = hash.wrapping_add(i.wrapping_mul(h2)) % len;
+            let (idx, mask) = ((bit / 64) as usize, 1u64 << (bit % 64));
+            if self.bits[idx] & mask == 0 {
+                seen = false;
+                self.bits[idx] |= mask;
+            }
+        }
+        seen
+    }
+}
+
+fn is_expired(at: i64, now: i64, ttl_mills: Option<u64>) -> bool {
+    ttl_mills.is_some_and(|ttl| now.saturating_sub(at) >= ttl as i64)
+}
+
+/// Encode the values of the key paths, or the whole value if no path.
+///
+/// The encoding does not depend on the process or the build,
+/// and the entries of a map are sorted by the key, as the maps are equal regardless of the order.
+pub(crate) fn key_bytes(v: &Value, keys: &[String]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    if keys.is_empty() {
+        encode(Some(v), &mut buf);
+    } else {
+        for k in keys {
+            encode(v.path(k), &mut buf);
+        }
+    }
+    buf
+}
+
+/// Tag and the bytes of the value, the length is prefixed to the variable length value.
+fn encode(v: Option<&Value>, buf: &mut Vec<u8>) {
+    let v = match v {
+        Some(v) => v,
+        None => {
+            // the path not found, distinguished from `Value::None`.
+            buf.push(0xff);
+            return;
+        }
+    };
+    match v {
+        Value::None => buf.push(0),
+        Value::Bool(b) => buf.extend_from_slice(&[1, *b as u8]),
+        Value::Integer(i) => {
+            buf.push(2);
+            buf.extend_from_slice(&i.to_le_bytes());
+        }
+        Value::Number(n) => {
+            buf.push(3);
+            buf.extend_from_slice(&n.to_bits().to_le_bytes());
+        }
+        Value::String(s) => {
+            buf.push(4);
+            encode_bytes(s.as_bytes(), buf);
+        }
+        Value::Bytes(b) => {
+            buf.push(5);
+            encode_bytes(b, buf);
+        }
+        Value::Seq(seq) => {
+            buf.push(6);
+            buf.extend_from_slice(&(seq.len() as u64).to_le_bytes());
+            for x in seq {
+                encode(Some(x), buf);
+            }
+        }
+        Value::Map(map) => {
+            buf.push(7);
+            buf.extend_from_slice(&(map.len() as u64).to_le_bytes());
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            for (k, x) in entries {
+                encode_bytes(k.as_bytes(), buf);
+                encode(Some(x), buf);
+            }
+        }
+        Value::TimeStamp(t) => {
+            buf.push(8);
+            buf.extend_from_slice(&t.timestamp().to_le_bytes());
+            buf.extend_from_slice(&t.timestamp_subsec_nanos().to_le_bytes());
+        }
+    }
+}
+
+fn encode_bytes(b: &[u8], buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&(b.len() as u64).to_le_bytes());
+    buf.extend_from_slice(b);
+}
@@ -0,0 +1,309 @@
+use std::time::Duration;
+use tempdir::TempDir;
+use toy_core::metrics::context::metrics;
+use toy_core::metrics::kind::{MeasureKind, MetricsKind};
+use toy_core::prelude::*;
+use toy_plugin_dedup::config::{DedupConfig, SeenKind};
+use toy_plugin_dedup::service::{Dedup, DedupContext, DROP_DUPLICATE_METRICS};
+
+fn lru(capacity: usize, ttl_mills: Option<u64>) -> SeenKind {
+    SeenKind::Lru {
+        capacity,
+        ttl_mills,
+    }
+}
+
+#[tokio::test]
+async fn dedup_by_keys() {
+    let data = vec![
+        map_value!("id" => 1, "kind" => "a", "v" => 1),
+        map_value!("id" => 1, "kind" => "b", "v" => 2),
+        map_value!("id" => 1, "kind" => "a", "v" => 3),
+        map_value!("id" => 2, "kind" => "a", "v" => 4),
+    ];
+    let config = DedupConfig::with(&["id", "kind"], lru(10, None));
+
+    let before = dropped().await;
+    let mut c = context(config).await;
+    let (unique, dup) = go(&mut c, data).await;
+
+    assert_eq!(
+        unique,
+        vec![
+            map_value!("id" => 1, "kind" => "a", "v" => 1),
+            map_value!("id" => 1, "kind" => "b", "v" => 2),
+            map_value!("id" => 2, "kind" => "a", "v" => 4),
+        ]
+    );
+    assert_eq!(dup, vec![map_value!("id" => 1, "kind" => "a", "v" => 3)]);
+    assert!(dropped().await > before);
+}
+
+#[tokio::test]
+async fn dedup_lru_capacity() {
+    let data = vec![
+        Value::from(1),
+        Value::from(2),
+        Value::from(3),
+        Value::from(1),
+        Value::from(3),
+    ];
+    let config = DedupConfig::with(&[], lru(2, None));
+
+    let mut c = context(config).await;
+    let (unique, dup) = go(&mut c, data).await;
+
+    // 1 is forgotten when 3 is seen.
+    assert_eq!(
+        unique,
+        vec![
+            Value::from(1),
+            Value::from(2),
+            Value::from(3),
+            Value::from(1)
+        ]
+    );
+    assert_eq!(dup, vec![Value::from(3)]);
+}
+
+#[tokio::test]
+async fn dedup_lru_ttl() {
+    let config = DedupConfig::with(&["id"], lru(10, Some(50)));
+
+    let mut c = context(config).await;
+    let (unique, _) = go(&mut c, vec![map_value!("id" => 1), map_value!("id" => 1)]).await;
+    assert_eq!(unique.len(), 1);
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let (unique, _) = go(&mut c, vec![map_value!("id" => 1)]).await;
+    assert_eq!(unique.len(), 1);
+}
+
+#[tokio::test]
+async fn dedup_bloom() {
+    let data = (0..1000)
+        .chain(0..1000)
+        .map(|x| map_value!("id" => x))
+        .collect::<Vec<_>>();
+    let config = DedupConfig::with(
+        &["id"],
+        SeenKind::Bloom {
+            expected_items: 1000,
+            false_positive_rate: 0.01,
+        },
+    );
+
+    let mut c = context(config).await;
+    let (unique, dup) = go(&mut c, data).await;
+
+    // all duplicates are dropped, and a few unique frames may be dropped as false positive.
+    assert!(unique.len() <= 1000 && unique.len() > 950);
+    assert_eq!(unique.len() + dup.len(), 2000);
+}
+
+#[tokio::test]
+async fn dedup_persist() {
+    let root = TempDir::new("toy-plugin-dedup").unwrap();
+    let path = root.path().join("seen");
+    let config =
+        DedupConfig::with(&["id"], lru(10, None)).with_persist_path(path.to_str().unwrap());
+
+    let mut c = context(config.clone()).await;
+    let (unique, _) = go(&mut c, vec![map_value!("id" => 1), map_value!("id" => 2)]).await;
+    assert_eq!(unique.len(), 2);
+    finish(c).await;
+
+    let mut c = context(config).await;
+    let (unique, _) = go(&mut c, vec![map_value!("id" => 2), map_value!("id" => 3)]).await;
+    assert_eq!(unique, vec![map_value!("id" => 3)]);
+}
+
+#[tokio::test]
+async fn dedup_whole_value_exactly() {
+    let mut a = Map::new();
+    a.insert("x".to_string(), Value::from(1));
+    a.insert("y".to_string(), Value::from("a"));
+    let mut b = Map::new();
+    b.insert("y".to_string(), Value::from("a"));
+    b.insert("x".to_string(), Value::from(1));
+    let data = vec![
+        Value::Map(a),
+        Value::Map(b),
+        Value::from("1"),
+        Value::from(1),
+        seq_value![Value::from("a"), Value::from("b")],
+        seq_value![Value::from("ab")],
+    ];
+    let config = DedupConfig::with(&[], lru(10, None));
+
+    let mut c = context(config).await;
+    let (unique, dup) = go(&mut c, data).await;
+
+    // the maps are equal regardless of the order of the entries.
+    assert_eq!(unique.len(), 5);
+    assert_eq!(dup.len(), 1);
+}
+
+#[tokio::test]
+async fn dedup_persist_bloom() {
+    let root = TempDir::new("toy-plugin-dedup").unwrap();
+    let path = root.path().join("seen");
+    let config = DedupConfig::with(
+        &["id"],
+        SeenKind::Bloom {
+            expected_items: 100,
+            false_positive_rate: 0.01,
+        },
+    )
+    .with_persist_path(path.to_str().unwrap());
+
+    let mut c = context(config.clone()).await;
+    let (unique, _) = go(&mut c, vec![map_value!("id" => 1), map_value!("id" => 2)]).await;
+    assert_eq!(unique.len(), 2);
+    finish(c).await;
+
+    let mut c = context(config).await;
+    let (_, dup) = go(&mut c, vec![map_value!("id" => 2), map_value!("id" => 1)]).await;
+    assert_eq!(dup, vec![map_value!("id" => 2), map_value!("id" => 1)]);
+}
+
+#[tokio::test]
+async fn dedup_persist_replicas() {
+    let root = TempDir::new("toy-plugin-dedup").unwrap();
+    let path = root.path().join("seen.db");
+    let config =
+        DedupConfig::with(&["id"], lru(10, None)).with_persist_path(path.to_str().unwrap());
+    let task_ctx = toy_plugin_test::dummy_task_context();
+    let replicas = [task_ctx.clone().with_replica(0), task_ctx.with_replica(1)];
+
+    for (i, task_ctx) in replicas.iter().enumerate() {
+        let mut c = context(config.clone()).await;
+        go_with(&mut c, vec![map_value!("id" => i as u32)], task_ctx.clone()).await;
+        finish_with(c, task_ctx.clone()).await;
+    }
+    assert!(root.path().join("seen-0.db").exists());
+    assert!(root.path().join("seen-1.db").exists());
+
+    // each replica loads only the keys saved by itself.
+    for (i, task_ctx) in replicas.iter().enumerate() {
+        let mut c = context(config.clone()).await;
+        let (unique, dup) = go_with(
+            &mut c,
+            vec![map_value!("id" => 0), map_value!("id" => 1)],
+            task_ctx.clone(),
+        )
+        .await;
+        assert_eq!(dup, vec![map_value!("id" => i as u32)]);
+        assert_eq!(unique, vec![map_value!("id" => 1 - i as u32)]);
+    }
+}
+
+#[tokio::test]
+async fn dedup_persist_no_frame() {
+    let root = TempDir::new("toy-plugin-dedup").unwrap();
+    let path = root.path().join("seen");
+    let config =
+        DedupConfig::with(&["id"], lru(10, None)).with_persist_path(path.to_str().unwrap());
+
+    let mut c = context(config.clone()).await;
+    go(&mut c, vec![map_value!("id" => 1)]).await;
+    finish(c).await;
+
+    // the saved keys are kept, if finished without any frame.
+    finish(context(config.clone()).await).await;
+
+    let mut c = context(config).await;
+    let (_, dup) = go(&mut c, vec![map_value!("id" => 1)]).await;
+    assert_eq!(dup, vec![map_value!("id" => 1)]);
+}
+
+#[tokio::test]
+async fn dedup_invalid_config() {
+    let configs = vec![
+        DedupConfig::with(&[], lru(0, None)),
+        DedupConfig::with(
+            &[],
+            SeenKind::Bloom {
+                expected_items: 10,
+                false_positive_rate: 1.5,
+            },
+        ),
+    ];
+    for config in configs {
+        let r = Dedup
+            .new_context(toy_plugin_test::dummy_service_type(), config)
+            .await;
+        assert!(r.is_err());
+    }
+}
+
+async fn dropped() -> u64 {
+    let kind = MetricsKind::Custom(DROP_DUPLICATE_METRICS.to_string(), MeasureKind::Counter);
+    metrics()
+        .get_counters()
+        .await
+        .get(&kind)
+        .and_then(|c| c.get())
+        .unwrap_or(0)
+}
+
+async fn context(config: DedupConfig) -> Option<DedupContext> {
+    let c = Dedup
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+    Some(c)
+}
+
+async fn finish(c: Option<DedupContext>) {
+    finish_with(c, toy_plugin_test::dummy_task_context()).await
+}
+
+async fn finish_with(c: Option<DedupContext>, task_ctx: TaskContext) {
+    let (tx, _rx) = toy_core::mpsc::channel(10);
+    let r = Dedup.upstream_finish_all(task_ctx, c.unwrap(), tx).await;
+    assert!(r.is_ok());
+}
+
+/// Returns the unique frames and the duplicate frames.
+async fn go(c: &mut Option<DedupContext>, data: Vec<Value>) -> (Vec<Value>, Vec<Value>) {
+    go_with(c, data, toy_plugin_test::dummy_task_context()).await
+}
+
+async fn go_with(
+    c: &mut Option<DedupContext>,
+    data: Vec<Value>,
+    task_ctx: TaskContext,
+) -> (Vec<Value>, Vec<Value>) {
+    let mut service = Dedup;
+
+    let (tx0, mut rx0) = toy_core::mpsc::channel(data.len());
+    let (tx1, mut rx1) = toy_core::mpsc::channel(data.len());
+    let mut tx = Outgoing::empty();
+    tx.merge(tx0);
+    tx.merge(tx1);
+
+    for v in data {
+        let r = service
+            .handle(
+                task_ctx.clone(),
+                c.take().unwrap(),
+                Frame::from_value(v),
+                tx.clone(),
+            )
+            .await;
+        assert!(r.is_ok());
+        *c = Some(r.unwrap().into());
+    }
+    drop(tx);
+
+    let mut unique = vec![];
+    while let Some(item) = rx0.next().await {
+        unique.push(item.into_value().unwrap());
+    }
+    let mut dup = vec![];
+    while let Some(item) = rx1.next().await {
+        dup.push(item.into_value().unwrap());
+    }
+    (unique, dup)
+}
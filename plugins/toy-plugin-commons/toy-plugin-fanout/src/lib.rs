@@ -5,5 +5,6 @@
 
 mod plugin;
 pub mod service;
+pub mod switch;
 
-pub use plugin::{all, broadcast, FanOutFlowPort};
+pub use plugin::{all, broadcast, switch, FanOutFlowPort};
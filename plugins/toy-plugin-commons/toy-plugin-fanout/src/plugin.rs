@@ -1,4 +1,5 @@
 use super::service::*;
+use super::switch::Switch;
 use toy_core::prelude::{layer, Layered, NoopEntry, PortType};
 use toy_core::service::FnPortType;
 
@@ -17,6 +18,10 @@ pub fn broadcast() -> (&'static str, &'static str, Broadcast) {
     (NAME_SPACE, "broadcast", Broadcast)
 }
 
-pub fn all() -> Layered<NoopEntry, Broadcast> {
-    layer(broadcast())
+pub fn switch() -> (&'static str, &'static str, Switch) {
+    (NAME_SPACE, "switch", Switch)
+}
+
+pub fn all() -> Layered<Layered<NoopEntry, Broadcast>, Switch> {
+    layer(broadcast()).layer(switch())
 }
@@ -82,4 +82,8 @@ impl ServiceFactory for Broadcast {
     fn new_context(&self, _tp: ServiceType, _config: Self::Config) -> Self::CtxFuture {
         async move { Ok(BroadcastContext {}) }
     }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
 }
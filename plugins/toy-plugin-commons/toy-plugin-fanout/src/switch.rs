@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use toy_core::prelude::{
+    Frame, Outgoing, PortType, Service, ServiceContext, ServiceError, ServiceFactory, ServiceType,
+};
+use toy_core::task::TaskContext;
+use toy_pack::Schema;
+use toy_plugin_filter::condition::{Condition, Matcher};
+
+/// Max count of the output ports.
+const MAX_PORTS: u8 = 20;
+
+/// How many routes a frame is sent to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Schema)]
+pub enum SwitchMode {
+    /// Only the first matched route.
+    #[default]
+    First,
+    /// All the matched routes.
+    All,
+}
+
+/// Output port for the frames matching the condition.
+/// The frames routed to the port not wired to any node are dropped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Schema)]
+pub struct Route {
+    condition: Condition,
+    port: u8,
+}
+
+impl Route {
+    pub fn new(condition: impl Into<Condition>, port: u8) -> Route {
+        Route {
+            condition: condition.into(),
+            port,
+        }
+    }
+
+    pub fn condition(&self) -> &Condition {
+        &self.condition
+    }
+
+    pub fn port(&self) -> u8 {
+        self.port
+    }
+}
+
+/// ```yaml
+/// mode: First
+/// routes:
+///   - condition: "type == order"
+///     port: 0
+///   - condition:
+///       any: ["type == user", "type == member"]
+///     port: 1
+/// default: 2
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Schema)]
+pub struct SwitchConfig {
+    /// Routes are evaluated in order.
+    routes: Vec<Route>,
+    #[serde(default)]
+    mode: SwitchMode,
+    /// Output port for the frames matching no route. The frames are dropped if not set.
+    #[serde(default)]
+    default: Option<u8>,
+}
+
+impl SwitchConfig {
+    pub fn with(routes: Vec<Route>) -> SwitchConfig {
+        SwitchConfig {
+            routes,
+            mode: SwitchMode::First,
+            default: None,
+        }
+    }
+
+    pub fn with_mode(self, mode: SwitchMode) -> SwitchConfig {
+        SwitchConfig { mode, ..self }
+    }
+
+    pub fn with_default(self, port: u8) -> SwitchConfig {
+        SwitchConfig {
+            default: Some(port),
+            ..self
+        }
+    }
+
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    pub fn mode(&self) -> SwitchMode {
+        self.mode
+    }
+
+    pub fn default(&self) -> Option<u8> {
+        self.default
+    }
+}
+
+pub struct SwitchContext {
+    routes: Vec<(Matcher, u8)>,
+    mode: SwitchMode,
+    default: Option<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Switch;
+
+impl Service for Switch {
+    type Context = SwitchContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<SwitchContext>, ServiceError>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<SwitchContext>, ServiceError>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<SwitchContext>, ServiceError>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::fan_out_flow(MAX_PORTS as u32)
+    }
+
+    fn handle(
+        &mut self,
+        task_ctx: TaskContext,
+        ctx: Self::Context,
+        req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            let span = task_ctx.span();
+
+            if let Some(v) = req.value() {
+                let matched = ctx
+                    .routes
+                    .iter()
+                    .filter(|(m, _)| m.is_match(v, span))
+                    .map(|(_, port)| *port);
+                let ports = match ctx.mode {
+                    SwitchMode::First => matched.take(1).collect::<Vec<_>>(),
+                    SwitchMode::All => matched.collect::<Vec<_>>(),
+                };
+                let ports = if ports.is_empty() {
+                    ctx.default.into_iter().collect()
+                } else {
+                    ports
+                };
+                tracing::debug!(parent: span, ?ports, "route.");
+                for p in ports {
+                    // the port not wired to any node, the frame is dropped.
+                    if (p as usize) >= tx.ports_len() {
+                        tracing::warn!(parent: span, port = p, "output port is not wired, drop.");
+                        continue;
+                    }
+                    tx.send_ok_to(p, req.clone()).await?;
+                }
+            }
+            Ok(ServiceContext::Ready(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move { Ok(ServiceContext::Complete(ctx)) }
+    }
+}
+
+impl ServiceFactory for Switch {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Switch;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = SwitchContext;
+    type Config = SwitchConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Switch) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
+        async move {
+            let mut ports = config.routes.iter().map(|x| x.port).chain(config.default);
+            if let Some(p) = ports.find(|x| *x >= MAX_PORTS) {
+                return Err(ServiceError::error(format!(
+                    "invalid output port {}, must be less than {}.",
+                    p, MAX_PORTS
+                )));
+            }
+            let routes = config
+                .routes
+                .iter()
+                .map(|x| x.condition.compile().map(|m| (m, x.port)))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(SwitchContext {
+                routes,
+                mode: config.mode,
+                default: config.default,
+            })
+        }
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+use toy_core::prelude::*;
+use toy_plugin_test::harness::Harness;
+
+fn node(tp: &str, uri: &str, config: Value, wires: Value) -> Value {
+    map_value! {
+        "type" => tp,
+        "uri" => uri,
+        "config" => config,
+        "wires" => wires,
+    }
+}
+
+fn graph(switch: Value) -> Value {
+    map_value! {
+        "name" => "switch",
+        "services" => seq_value![
+            node("test.source", "source", Value::None, Value::from("switch")),
+            node(
+                "plugin.common.fanout.switch",
+                "switch",
+                switch,
+                seq_value!["orders", "others"]
+            ),
+            node("test.sink", "orders", Value::None, Value::None),
+            node("test.sink", "others", Value::None, Value::None)
+        ],
+    }
+}
+
+#[test]
+fn switch_graph() {
+    let data = vec![
+        map_value!("type" => "order", "id" => 1),
+        map_value!("type" => "user", "id" => 2),
+        map_value!("type" => "order", "id" => 3),
+    ];
+
+    let config = map_value! {
+        "routes" => seq_value![map_value! { "condition" => "type == order", "port" => 0 }],
+        "default" => 1,
+    };
+    let r = Harness::new(toy_plugin_fanout::all(), graph(config))
+        .inject("source", data)
+        .capture("orders")
+        .capture("others")
+        .paused(true)
+        .timeout(Duration::from_secs(10))
+        .run();
+
+    assert!(r.is_ok(), "{:?}", r.error());
+    assert_eq!(
+        r.outputs("orders"),
+        &[
+            map_value!("type" => "order", "id" => 1),
+            map_value!("type" => "order", "id" => 3),
+        ]
+    );
+    assert_eq!(
+        r.outputs("others"),
+        &[map_value!("type" => "user", "id" => 2)]
+    );
+}
+
+#[test]
+fn switch_graph_invalid_config() {
+    let config = map_value! { "routes" => Value::Seq(vec![]), "default" => 99 };
+    let r = Harness::new(toy_plugin_fanout::all(), graph(config))
+        .inject("source", vec![Value::from(1)])
+        .capture("orders")
+        .capture("others")
+        .paused(true)
+        .timeout(Duration::from_secs(10))
+        .run();
+
+    assert!(r
+        .error()
+        .unwrap()
+        .to_string()
+        .contains("invalid output port"));
+    assert!(r.outputs("orders").is_empty());
+}
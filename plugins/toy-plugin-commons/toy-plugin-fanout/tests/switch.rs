@@ -0,0 +1,133 @@
+use std::str::FromStr;
+use toy_core::prelude::*;
+use toy_plugin_fanout::switch::{Route, Switch, SwitchConfig, SwitchMode};
+use toy_plugin_filter::condition::Condition;
+use toy_plugin_filter::predicate::Predicate;
+
+fn route(s: &str, port: u8) -> Route {
+    Route::new(Predicate::from_str(s).unwrap(), port)
+}
+
+fn data() -> Vec<Value> {
+    vec![
+        map_value!("type" => "order", "amount" => 500),
+        map_value!("type" => "user"),
+        map_value!("type" => "order", "amount" => 5),
+        map_value!("type" => "log"),
+    ]
+}
+
+#[tokio::test]
+async fn switch_first() {
+    let config = SwitchConfig::with(vec![
+        route("amount > 100", 0),
+        route("type == order", 1),
+        route("type == user", 2),
+    ]);
+    let r = go(data(), config, 3).await;
+
+    assert_eq!(r[0], vec![map_value!("type" => "order", "amount" => 500)]);
+    assert_eq!(r[1], vec![map_value!("type" => "order", "amount" => 5)]);
+    assert_eq!(r[2], vec![map_value!("type" => "user")]);
+}
+
+#[tokio::test]
+async fn switch_all_with_default() {
+    let config = SwitchConfig::with(vec![
+        route("amount > 100", 0),
+        Route::new(
+            Condition::any(vec![
+                Predicate::from_str("type == order").unwrap().into(),
+                Predicate::from_str("type == user").unwrap().into(),
+            ]),
+            1,
+        ),
+    ])
+    .with_mode(SwitchMode::All)
+    .with_default(2);
+    let r = go(data(), config, 3).await;
+
+    assert_eq!(r[0], vec![map_value!("type" => "order", "amount" => 500)]);
+    assert_eq!(r[1].len(), 3);
+    assert_eq!(r[2], vec![map_value!("type" => "log")]);
+}
+
+#[tokio::test]
+async fn switch_unwired_port() {
+    let config = SwitchConfig::with(vec![route("amount > 100", 0), route("type == user", 3)])
+        .with_default(5);
+    let r = go(data(), config, 2).await;
+
+    assert_eq!(r[0], vec![map_value!("type" => "order", "amount" => 500)]);
+    assert!(r[1].is_empty());
+}
+
+#[tokio::test]
+async fn switch_invalid_port() {
+    let config = SwitchConfig::with(vec![route("a == 1", 0)]).with_default(20);
+    let r = Switch
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await;
+
+    assert!(r.is_err());
+}
+
+#[test]
+fn switch_config() {
+    let v = map_value! {
+        "mode" => "All",
+        "routes" => seq_value![
+            map_value! { "condition" => "a == 1", "port" => 0 },
+            map_value! { "condition" => map_value! { "not" => "b exists" }, "port" => 1 }
+        ],
+        "default" => 2,
+    };
+    let config = toy_core::data::unpack::<SwitchConfig>(&v).unwrap();
+
+    assert_eq!(config.mode(), SwitchMode::All);
+    assert_eq!(config.default(), Some(2));
+    assert_eq!(
+        config.routes()[1],
+        Route::new(
+            Condition::not(Predicate::from_str("b exists").unwrap().into()),
+            1
+        )
+    );
+}
+
+async fn go(data: Vec<Value>, config: SwitchConfig, ports: usize) -> Vec<Vec<Value>> {
+    let mut service = Switch;
+
+    let mut tx = Outgoing::empty();
+    let mut rxs = vec![];
+    for _ in 0..ports {
+        let (t, r) = toy_core::mpsc::channel(10);
+        tx.merge(t);
+        rxs.push(r);
+    }
+    let task_ctx = toy_plugin_test::dummy_task_context();
+
+    let mut c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+
+    for v in data {
+        let r = service
+            .handle(task_ctx.clone(), c, Frame::from_value(v), tx.clone())
+            .await;
+        assert!(r.is_ok());
+        c = r.unwrap().into();
+    }
+    drop(tx);
+
+    let mut result = vec![];
+    for mut rx in rxs {
+        let mut values = vec![];
+        while let Some(item) = rx.next().await {
+            values.push(item.into_value().unwrap());
+        }
+        result.push(values);
+    }
+    result
+}
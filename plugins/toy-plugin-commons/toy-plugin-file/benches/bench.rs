@@ -4,10 +4,13 @@ extern crate csv;
 extern crate test;
 
 use std::fs::File;
-use std::io::Read;
+use std::io::Read as _;
 use std::path::PathBuf;
 use test::test::Bencher;
 
+use toy_core::prelude::*;
+use toy_plugin_file::config::{ReadConfig, ReadOption, WriteConfig, WriteOption};
+use toy_plugin_file::service::{Read, Write};
 use toy_plugin_file::FileReaderBuilder;
 use toy_text_parser::dfa::*;
 use toy_text_parser::Line;
@@ -119,3 +122,109 @@ fn read_toy_raw_reader(b: &mut Bencher) {
         count = 0;
     })
 }
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+#[bench]
+fn read_toy_service(b: &mut Bencher) {
+    let text = file_to_mem(CSV_DATA);
+    b.bytes = text.len() as u64;
+    let rt = runtime();
+
+    b.iter(|| {
+        rt.block_on(async {
+            let mut service = Read;
+            let config = ReadConfig::with(CSV_DATA.to_string(), ReadOption::common_csv());
+            let (tx, mut rx) = toy_core::mpsc::channel(1024);
+            let c = service
+                .new_context(toy_plugin_test::dummy_service_type(), config)
+                .await
+                .unwrap();
+            let count = tokio::spawn(async move {
+                let mut count = 0u32;
+                while rx.next().await.is_some() {
+                    count += 1;
+                }
+                count
+            });
+            let r = service
+                .handle(
+                    toy_plugin_test::dummy_task_context(),
+                    c,
+                    Frame::default(),
+                    tx,
+                )
+                .await;
+            assert!(r.is_ok());
+            assert_eq!(count.await.unwrap(), DATA_ROW_COUNT);
+        })
+    })
+}
+
+#[bench]
+fn write_toy_service(b: &mut Bencher) {
+    let text = file_to_mem(CSV_DATA);
+    b.bytes = text.len() as u64;
+    let rows = {
+        let files = vec![PathBuf::from(CSV_DATA)];
+        let mut s = FileReaderBuilder::default().from_file(File::open(CSV_DATA).unwrap(), files);
+        let headers = s
+            .headers()
+            .unwrap()
+            .iter()
+            .map(|x| String::from_utf8_lossy(x).to_string())
+            .collect::<Vec<_>>();
+        let mut row = Line::new();
+        let mut rows = Vec::new();
+        while s.read(&mut row).unwrap() {
+            let v = headers
+                .iter()
+                .zip(row.iter())
+                .map(|(h, v)| {
+                    (
+                        h.clone(),
+                        Value::from(String::from_utf8_lossy(v).to_string()),
+                    )
+                })
+                .collect::<Map<_, _>>();
+            rows.push(Value::from(v));
+        }
+        rows
+    };
+    let root = tempdir::TempDir::new("toy-bench").unwrap();
+    let rt = runtime();
+
+    b.iter(|| {
+        rt.block_on(async {
+            let mut service = Write;
+            let config = WriteConfig::with(
+                Some(root.path().join("bench.csv")),
+                WriteOption::common_csv(),
+            );
+            let (tx, _rx) = toy_core::mpsc::channel(rows.len() + 1);
+            let task_ctx = toy_plugin_test::dummy_task_context();
+            let mut c = service
+                .new_context(toy_plugin_test::dummy_service_type(), config)
+                .await
+                .unwrap();
+            for v in rows.iter() {
+                let r = service
+                    .handle(
+                        task_ctx.clone(),
+                        c,
+                        Frame::from_value(v.clone()),
+                        tx.clone(),
+                    )
+                    .await;
+                c = r.unwrap().into();
+            }
+            let r = service.upstream_finish_all(task_ctx, c, tx).await;
+            assert!(r.is_ok());
+        })
+    })
+}
@@ -0,0 +1,216 @@
+//! Run the blocking file IO on the blocking threads of the runtime, not to stall the async workers.
+//!
+//! The channel from the reading thread is bounded, so the thread waits for the service when the downstream is slow.
+//! The writing encodes the records on the service, and writes the bytes to the files on the blocking threads.
+
+use std::io::{Error, ErrorKind, Write};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use toy_core::data::Value;
+
+/// Records sent from the reading thread at once.
+const BATCH_SIZE: usize = 256;
+
+/// Batches buffered in the channel from the reading thread.
+const READ_CAPACITY: usize = 4;
+
+/// Bytes encoded in the memory before written to the files.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Interval to write the bytes encoded, so the values received at a low rate are not kept in the memory.
+/// No timer is used, the bytes are written when a value arrives after the interval.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Run the function on a blocking thread, and wait for the result.
+pub(crate) async fn run<T, F>(f: F) -> Result<T, Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| Error::other(e.to_string()))?
+}
+
+/// Call `next` on a blocking thread until it returns `None` or the error, and receive the items in batches.
+///
+/// The error is received after the items read before it.
+/// The thread stops when the receiver is dropped.
+pub(crate) fn spawn_reader<T, E, F>(mut next: F) -> mpsc::Receiver<Result<Vec<T>, E>>
+where
+    T: Send + 'static,
+    E: Send + 'static,
+    F: FnMut() -> Result<Option<T>, E> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(READ_CAPACITY);
+    tokio::task::spawn_blocking(move || loop {
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        let r = loop {
+            if batch.len() >= BATCH_SIZE {
+                break Ok(true);
+            }
+            match next() {
+                Ok(Some(item)) => batch.push(item),
+                Ok(None) => break Ok(false),
+                Err(e) => break Err(e),
+            }
+        };
+        if !batch.is_empty() && tx.blocking_send(Ok(batch)).is_err() {
+            return;
+        }
+        match r {
+            Ok(true) => (),
+            Ok(false) => return,
+            Err(e) => {
+                let _ = tx.blocking_send(Err(e));
+                return;
+            }
+        }
+    });
+    rx
+}
+
+/// Output written by [`BlockingWriter`].
+pub(crate) trait WriteValue: Send + 'static {
+    /// Encode the value to the memory, `now` is the time the value was received by the service.
+    fn write_value(&mut self, v: &Value, now: Instant) -> Result<(), Error>;
+
+    /// Returns true if writing the value opens or closes the files.
+    fn opens_file(&self, v: &Value, now: Instant) -> bool;
+
+    /// Bytes encoded to the memory, and not written to the files yet.
+    fn buffered(&self) -> usize;
+
+    /// Write the encoded bytes to the files.
+    fn drain(&mut self) -> Result<(), Error>;
+
+    /// Called once after the last value.
+    fn finish(&mut self) -> Result<(), Error>;
+}
+
+/// Keep the bytes in the memory until `drain`, so the records are encoded without the IO.
+pub(crate) struct Spool<W: Write> {
+    inner: W,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> Spool<W> {
+    pub(crate) fn new(inner: W) -> Spool<W> {
+        Spool {
+            inner,
+            buf: Vec::new(),
+        }
+    }
+
+    pub(crate) fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Write the bytes to the inner writer.
+    pub(crate) fn drain(&mut self) -> Result<(), Error> {
+        self.inner.write_all(&self.buf)?;
+        self.buf.clear();
+        Ok(())
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+}
+
+impl<W: Write> Write for Spool<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.drain()?;
+        self.inner.flush()
+    }
+}
+
+/// Write the values on the service, and the files on a blocking thread.
+///
+/// The value is encoded to the memory, and the bytes are written to the files
+/// when [`CHUNK_SIZE`] is buffered or [`FLUSH_INTERVAL`] passed since the last writing.
+/// The value opening or closing the file is written on a blocking thread.
+/// The error is returned by the `write_value` failed, and the bytes written by it may include the values before.
+/// The output is finished also when dropped.
+pub(crate) struct BlockingWriter<W: WriteValue> {
+    /// `None` while running on the blocking thread, or after finished.
+    w: Option<W>,
+    drained_at: Instant,
+}
+
+impl<W: WriteValue> BlockingWriter<W> {
+    pub(crate) fn new(w: W) -> BlockingWriter<W> {
+        BlockingWriter {
+            w: Some(w),
+            drained_at: Instant::now(),
+        }
+    }
+
+    pub(crate) async fn write_value(&mut self, v: Value) -> Result<(), Error> {
+        let now = Instant::now();
+        let w = self.w.as_mut().ok_or_else(closed)?;
+        if w.opens_file(&v, now) {
+            self.run(move |w| w.write_value(&v, now)).await?;
+        } else {
+            w.write_value(&v, now)?;
+        }
+
+        let buffered = self.w.as_ref().map_or(0, |w| w.buffered());
+        if buffered >= CHUNK_SIZE
+            || (buffered > 0 && now.saturating_duration_since(self.drained_at) >= FLUSH_INTERVAL)
+        {
+            self.drained_at = now;
+            self.run(|w| w.drain()).await?;
+        }
+        Ok(())
+    }
+
+    /// Write all values and finish the output.
+    pub(crate) async fn finish(&mut self) -> Result<(), Error> {
+        if self.w.is_none() {
+            return Ok(());
+        }
+        self.run(|w| w.finish()).await?;
+        self.w = None;
+        Ok(())
+    }
+
+    /// Run the function on a blocking thread, the writer is kept even if it returns the error.
+    async fn run<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut W) -> Result<(), Error> + Send + 'static,
+    {
+        let mut w = self.w.take().ok_or_else(closed)?;
+        let (w, r) = run(move || {
+            let r = f(&mut w);
+            Ok((w, r))
+        })
+        .await?;
+        self.w = Some(w);
+        r
+    }
+}
+
+impl<W: WriteValue> Drop for BlockingWriter<W> {
+    fn drop(&mut self) {
+        if let Some(mut w) = self.w.take() {
+            match tokio::runtime::Handle::try_current() {
+                Ok(rt) => {
+                    rt.spawn_blocking(move || w.finish());
+                }
+                Err(_) => {
+                    let _ = w.finish();
+                }
+            }
+        }
+    }
+}
+
+fn closed() -> Error {
+    Error::new(ErrorKind::BrokenPipe, "writer is already finished.")
+}
@@ -0,0 +1,113 @@
+//! Compressed files, gzip and zstd.
+//!
+//! If the compression is not configured, it is chosen by the extension of the file.
+
+use crate::Compression;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use std::fs::File;
+use std::io::{self, Error, ErrorKind, Read, Write};
+use std::path::Path;
+
+impl Compression {
+    /// Returns the compression by the extension of the path, `.gz` or `.zst`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Compression {
+        match path.as_ref().extension().and_then(|x| x.to_str()) {
+            Some("gz") | Some("gzip") => Compression::Gzip,
+            Some("zst") | Some("zstd") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Open the file, and returns the reader decompressing the content.
+///
+/// The concatenated members of gzip, and the concatenated frames of zstd are read through.
+pub fn open<P: AsRef<Path>>(
+    path: P,
+    compression: Option<Compression>,
+) -> Result<Box<dyn Read + Send>, Error> {
+    let compression = compression.unwrap_or_else(|| Compression::from_path(&path));
+    let f = File::open(path)?;
+    Ok(match compression {
+        Compression::None => Box::new(f),
+        Compression::Gzip => Box::new(MultiGzDecoder::new(f)),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(f)?),
+    })
+}
+
+/// Writer compressing the output.
+///
+/// `finish` must be called to write the end of the compressed stream.
+pub enum CompressWriter<W: Write> {
+    None(W),
+    Gzip(GzEncoder<W>),
+    Zstd(zstd::stream::write::Encoder<'static, W>),
+}
+
+impl<W: Write> CompressWriter<W> {
+    /// Create the writer with the compression level.
+    /// The default level of each compression is used if `level` is `None`.
+    pub fn new(
+        w: W,
+        compression: Compression,
+        level: Option<i32>,
+    ) -> Result<CompressWriter<W>, Error> {
+        match compression {
+            Compression::None => Ok(CompressWriter::None(w)),
+            Compression::Gzip => {
+                let level = match level {
+                    Some(l) if (0..=9).contains(&l) => flate2::Compression::new(l as u32),
+                    Some(l) => return Err(invalid_level(compression, l)),
+                    None => flate2::Compression::default(),
+                };
+                Ok(CompressWriter::Gzip(GzEncoder::new(w, level)))
+            }
+            Compression::Zstd => {
+                let level = match level {
+                    Some(l) if zstd::compression_level_range().contains(&l) => l,
+                    Some(l) => return Err(invalid_level(compression, l)),
+                    None => zstd::DEFAULT_COMPRESSION_LEVEL,
+                };
+                Ok(CompressWriter::Zstd(zstd::stream::write::Encoder::new(
+                    w, level,
+                )?))
+            }
+        }
+    }
+
+    /// Write the end of the compressed stream.
+    /// Nothing can be written after this.
+    pub fn finish(&mut self) -> Result<(), Error> {
+        match self {
+            CompressWriter::None(w) => w.flush(),
+            CompressWriter::Gzip(w) => w.try_finish(),
+            CompressWriter::Zstd(w) => w.do_finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for CompressWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            CompressWriter::None(w) => w.write(buf),
+            CompressWriter::Gzip(w) => w.write(buf),
+            CompressWriter::Zstd(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            CompressWriter::None(w) => w.flush(),
+            CompressWriter::Gzip(w) => w.flush(),
+            CompressWriter::Zstd(w) => w.flush(),
+        }
+    }
+}
+
+fn invalid_level(compression: Compression, level: i32) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("invalid compression level {} for {:?}.", level, compression),
+    )
+}
@@ -1,4 +1,4 @@
-use super::QuoteStyle;
+use super::{Compression, FileFormat, QuoteStyle};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use toy_pack::Schema;
@@ -20,6 +20,14 @@ const fn default_escape() -> char {
     '\\'
 }
 
+const fn default_interval_millis() -> u64 {
+    1000
+}
+
+const fn default_max_open_files() -> usize {
+    64
+}
+
 pub fn char_to_u8(v: char) -> u8 {
     let mut dest = [0u8; 4];
     let _ = v.encode_utf8(&mut dest);
@@ -38,6 +46,10 @@ pub fn char_to_u8_opt(v: Option<char>) -> Option<u8> {
 pub struct ReadConfig {
     pub(crate) path: String,
     #[serde(default)]
+    pub(crate) format: FileFormat,
+    /// Chosen by the extension of each file if not set.
+    pub(crate) compression: Option<Compression>,
+    #[serde(default)]
     pub(crate) option: ReadOption,
 }
 
@@ -66,12 +78,30 @@ impl ReadConfig {
     pub fn new(path: String) -> ReadConfig {
         ReadConfig {
             path,
+            format: FileFormat::default(),
+            compression: None,
             option: ReadOption::default(),
         }
     }
 
     pub fn with(path: String, option: ReadOption) -> ReadConfig {
-        ReadConfig { path, option }
+        ReadConfig {
+            path,
+            format: FileFormat::default(),
+            compression: None,
+            option,
+        }
+    }
+
+    pub fn with_format(self, format: FileFormat) -> ReadConfig {
+        ReadConfig { format, ..self }
+    }
+
+    pub fn with_compression(self, compression: Compression) -> ReadConfig {
+        ReadConfig {
+            compression: Some(compression),
+            ..self
+        }
     }
 }
 
@@ -109,13 +139,179 @@ impl ReadOption {
     }
 }
 
+/// Follow the files matched by the path, and read the lines appended to them. (like `tail -F`)
+///
+/// A file is followed across the rename by the rotation, and read from the beginning when truncated.
+/// Only the complete lines are read, so a record must not contain the line terminator.
+#[derive(Debug, Clone, Serialize, Deserialize, Schema)]
+pub struct WatchConfig {
+    /// Glob pattern, the files created after the start are also followed.
+    pub(crate) path: String,
+    #[serde(default)]
+    pub(crate) format: FileFormat,
+    /// Milliseconds between the scans of the files.
+    #[serde(default = "default_interval_millis")]
+    pub(crate) interval_millis: u64,
+    /// File saving the offset of each followed file, to resume after the restart.
+    /// The files are read from the beginning at every start if not set.
+    pub(crate) state_path: Option<PathBuf>,
+    /// Max count of the files kept open between the scans.
+    /// Over it, the files read to the end are closed from the least recently read,
+    /// and opened again when grown. The rotated files not read to the end are kept open.
+    ///
+    /// The lines appended to the closed file are not read,
+    /// if it is renamed out of the pattern or removed before found grown.
+    #[serde(default = "default_max_open_files")]
+    pub(crate) max_open_files: usize,
+    #[serde(default)]
+    pub(crate) option: ReadOption,
+}
+
+impl WatchConfig {
+    pub fn new(path: String) -> WatchConfig {
+        WatchConfig {
+            path,
+            format: FileFormat::default(),
+            interval_millis: default_interval_millis(),
+            state_path: None,
+            max_open_files: default_max_open_files(),
+            option: ReadOption::default(),
+        }
+    }
+
+    pub fn with_format(self, format: FileFormat) -> WatchConfig {
+        WatchConfig { format, ..self }
+    }
+
+    pub fn with_interval_millis(self, interval_millis: u64) -> WatchConfig {
+        WatchConfig {
+            interval_millis,
+            ..self
+        }
+    }
+
+    pub fn with_state_path(self, state_path: PathBuf) -> WatchConfig {
+        WatchConfig {
+            state_path: Some(state_path),
+            ..self
+        }
+    }
+
+    pub fn with_max_open_files(self, max_open_files: usize) -> WatchConfig {
+        WatchConfig {
+            max_open_files,
+            ..self
+        }
+    }
+
+    pub fn with_option(self, option: ReadOption) -> WatchConfig {
+        WatchConfig { option, ..self }
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, Schema)]
 pub struct WriteConfig {
+    /// Write to the stdout if not set.
+    ///
+    /// The path can contain the placeholders,
+    /// `{name}` is replaced with the value of the path `name` of the record, e.g. `out/{date}/{region}.csv`.
+    /// `{index}` is replaced with the sequence number of the file rolled, starting at 0.
     pub(crate) path: Option<PathBuf>,
     #[serde(default)]
+    pub(crate) format: FileFormat,
+    /// Chosen by the extension of the path if not set.
+    pub(crate) compression: Option<Compression>,
+    /// gzip: 0-9, zstd: 1-22. The default level of the compression if not set.
+    pub(crate) compression_level: Option<i32>,
+    pub(crate) roll: Option<RollConfig>,
+    #[serde(default)]
     pub(crate) option: WriteOption,
 }
 
+/// Close the file and write to the next file, when any of the limits is reached.
+///
+/// The file is written with the temporary name, and renamed to the path when closed.
+/// If the path has no `{index}` and any limit is set, `-{index}` is inserted before the extension of the file name.
+/// The file of the path without `{index}` is appended when opened again after closed by `max_open_files`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Schema)]
+pub struct RollConfig {
+    /// Bytes written to the file, before the compression.
+    pub(crate) max_bytes: Option<u64>,
+    /// Records written to the file, except the header.
+    pub(crate) max_rows: Option<u64>,
+    /// Seconds from the file opened, checked when a record is written.
+    pub(crate) interval_secs: Option<u64>,
+    /// Max count of the files opened at once for the partitions.
+    /// The file written least recently is closed when exceeded.
+    pub(crate) max_open_files: Option<usize>,
+}
+
+impl WriteConfig {
+    pub fn with(path: Option<PathBuf>, option: WriteOption) -> WriteConfig {
+        WriteConfig {
+            path,
+            format: FileFormat::default(),
+            compression: None,
+            compression_level: None,
+            roll: None,
+            option,
+        }
+    }
+
+    pub fn with_format(self, format: FileFormat) -> WriteConfig {
+        WriteConfig { format, ..self }
+    }
+
+    pub fn with_compression(self, compression: Compression, level: Option<i32>) -> WriteConfig {
+        WriteConfig {
+            compression: Some(compression),
+            compression_level: level,
+            ..self
+        }
+    }
+
+    pub fn with_roll(self, roll: RollConfig) -> WriteConfig {
+        WriteConfig {
+            roll: Some(roll),
+            ..self
+        }
+    }
+}
+
+impl RollConfig {
+    pub fn with_max_bytes(self, max_bytes: u64) -> RollConfig {
+        RollConfig {
+            max_bytes: Some(max_bytes),
+            ..self
+        }
+    }
+
+    pub fn with_max_rows(self, max_rows: u64) -> RollConfig {
+        RollConfig {
+            max_rows: Some(max_rows),
+            ..self
+        }
+    }
+
+    pub fn with_interval_secs(self, interval_secs: u64) -> RollConfig {
+        RollConfig {
+            interval_secs: Some(interval_secs),
+            ..self
+        }
+    }
+
+    pub fn with_max_open_files(self, max_open_files: usize) -> RollConfig {
+        RollConfig {
+            max_open_files: Some(max_open_files),
+            ..self
+        }
+    }
+    /// Returns true if any limit to roll the file is set.
+    pub(crate) fn has_limit(&self) -> bool {
+        self.max_bytes.is_some() || self.max_rows.is_some() || self.interval_secs.is_some()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Schema)]
 pub struct WriteOption {
     #[serde(default = "default_has_headers")]
@@ -1,36 +1,48 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader, Error};
+use std::fmt;
+use std::io::{BufRead, BufReader, Error, Read};
 use std::path::PathBuf;
 
+use crate::{compression, Compression, FileFormat};
 use toy_text_parser::dfa::{ByteParser, ParseResult};
 use toy_text_parser::Line;
 
-#[derive(Debug)]
 pub struct FileReader {
     reader: ByteParser,
-    src: BufReader<File>,
+    src: BufReader<Box<dyn Read + Send>>,
     state: FileReaderState,
 }
 
 #[derive(Debug)]
 pub struct FileReaderState {
+    format: FileFormat,
+    compression: Option<Compression>,
     has_headers: bool,
     headers: Option<Line>,
     current_path_index: usize,
     paths: Vec<PathBuf>,
     eof: bool,
     has_read: bool,
+    /// lines read by `read_line`, or records read by `read` in the current file.
+    line: u64,
 }
 
 impl FileReaderState {
-    pub fn new(has_headers: bool, paths: Vec<PathBuf>) -> FileReaderState {
+    pub fn new(
+        format: FileFormat,
+        compression: Option<Compression>,
+        has_headers: bool,
+        paths: Vec<PathBuf>,
+    ) -> FileReaderState {
         FileReaderState {
+            format,
+            compression,
             has_headers,
             headers: None,
             current_path_index: 0,
             paths,
             eof: false,
             has_read: false,
+            line: 0,
         }
     }
 
@@ -39,6 +51,7 @@ impl FileReaderState {
         self.has_read = false;
         self.current_path_index += 1;
         self.headers = None;
+        self.line = 0;
         self.paths.get(self.current_path_index)
     }
 }
@@ -46,7 +59,11 @@ impl FileReaderState {
 impl FileReader {
     /// Create a new Source given a built `ByteReader` and a source underlying IO reader.
     ///
-    pub fn new(reader: ByteParser, src: BufReader<File>, state: FileReaderState) -> FileReader {
+    pub fn new(
+        reader: ByteParser,
+        src: BufReader<Box<dyn Read + Send>>,
+        state: FileReaderState,
+    ) -> FileReader {
         FileReader { reader, src, state }
     }
 
@@ -62,12 +79,33 @@ impl FileReader {
         RowIntoIterator::new(self)
     }
 
+    /// Returns the format of the files.
+    ///
+    pub fn format(&self) -> FileFormat {
+        self.state.format
+    }
+
     /// Returns true if reader configured to first row as a header.
     ///
     pub fn has_headers(&self) -> bool {
         self.state.has_headers
     }
 
+    /// Returns the path of the file currently read.
+    ///
+    pub fn current_path(&self) -> Option<&PathBuf> {
+        self.state.paths.get(self.state.current_path_index)
+    }
+
+    /// Returns the line number of the last line read in the current file, starting at 1.
+    ///
+    /// For `read`, the records are counted including the header row,
+    /// so it is the line number unless a quoted field contains the line terminator.
+    ///
+    pub fn current_line(&self) -> u64 {
+        self.state.line
+    }
+
     /// Returns a reference to the first row.
     ///
     /// If has been read yet, then this will force parsing of the first row.
@@ -88,18 +126,50 @@ impl FileReader {
         let r = self.read_current_path(line)?;
 
         // next file
-        if !r && (self.state.current_path_index + 1) < self.state.paths.len() {
-            self.reader.reset();
-            let cp = self.src.capacity();
-            let next_path = self.state.prepare_next_file().unwrap();
-            tracing::info!("read next file. path: {}", next_path.display());
-            let next = File::open(next_path)?;
-            self.src = BufReader::with_capacity(cp, next);
+        if !r && self.open_next_file()? {
             return self.read_current_path(line);
         }
         Ok(r)
     }
 
+    /// Read a single line into the given buffer, without the line terminator.
+    /// Blank lines are skipped.
+    /// Returns false when no more lines could be read.
+    ///
+    pub fn read_line(&mut self, buf: &mut Vec<u8>) -> Result<bool, Error> {
+        loop {
+            buf.clear();
+            if self.src.read_until(b'\n', buf)? == 0 {
+                if self.open_next_file()? {
+                    continue;
+                }
+                return Ok(false);
+            }
+            self.state.line += 1;
+
+            while let Some(b'\n' | b'\r') = buf.last() {
+                buf.pop();
+            }
+            if buf.iter().any(|b| !b.is_ascii_whitespace()) {
+                return Ok(true);
+            }
+        }
+    }
+
+    fn open_next_file(&mut self) -> Result<bool, Error> {
+        if (self.state.current_path_index + 1) >= self.state.paths.len() {
+            return Ok(false);
+        }
+        self.reader.reset();
+        let cp = self.src.capacity();
+        let compression = self.state.compression;
+        let next_path = self.state.prepare_next_file().unwrap();
+        tracing::info!("read next file. path: {}", next_path.display());
+        let next = compression::open(next_path, compression)?;
+        self.src = BufReader::with_capacity(cp, next);
+        Ok(true)
+    }
+
     fn read_current_path(&mut self, line: &mut Line) -> Result<bool, Error> {
         let r = self.read_core(line)?;
 
@@ -150,6 +220,7 @@ impl FileReader {
                     return Ok(false);
                 }
                 ParseResult::Record => {
+                    self.state.line += 1;
                     line.set_len(column);
                     return Ok(true);
                 }
@@ -158,6 +229,15 @@ impl FileReader {
     }
 }
 
+impl fmt::Debug for FileReader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FileReader")
+            .field("reader", &self.reader)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
 pub struct RowIterator<'a> {
     src: &'a mut FileReader,
     line: Line,
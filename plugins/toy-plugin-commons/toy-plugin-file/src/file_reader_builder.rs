@@ -1,8 +1,9 @@
-use super::config::{char_to_u8, char_to_u8_opt, default_capacity, ReadConfig};
+use super::config::{char_to_u8, char_to_u8_opt, default_capacity, ReadConfig, ReadOption};
 use super::file_reader::{FileReader, FileReaderState};
+use crate::{compression, Compression, FileFormat};
 use std::cmp::Ordering;
 use std::fs::File;
-use std::io::{BufReader, Error, ErrorKind};
+use std::io::{BufReader, Error, ErrorKind, Read};
 use std::path::PathBuf;
 use toy_text_parser::dfa::ByteParserBuilder;
 use toy_text_parser::Terminator;
@@ -11,6 +12,8 @@ use toy_text_parser::Terminator;
 pub struct FileReaderBuilder {
     parser_builder: ByteParserBuilder,
     capacity: usize,
+    format: FileFormat,
+    compression: Option<Compression>,
     has_headers: bool,
 }
 
@@ -30,19 +33,28 @@ impl FileReaderBuilder {
 
         paths.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
 
-        let b = FileReaderBuilder::default()
-            .delimiter(char_to_u8_opt(config.option.delimiter))
-            .quote(char_to_u8(config.option.quote))
-            .quoting(config.option.quoting)
-            .terminator(config.option.terminator)
-            .escape(config.option.escape)
-            .double_quote(config.option.double_quote)
-            .comment(config.option.comment)
-            .has_headers(config.option.has_headers)
-            .capacity(config.option.capacity)
+        let b = FileReaderBuilder::from_option(&config.option)
+            .format(config.format)
+            .compression(config.compression)
             .clone();
 
-        Ok(b.from_file(File::open(paths.first().unwrap())?, paths))
+        let first = compression::open(paths.first().unwrap(), config.compression)?;
+        Ok(b.from_reader(first, paths))
+    }
+
+    /// Returns the builder configured with the option of the parser.
+    pub fn from_option(option: &ReadOption) -> FileReaderBuilder {
+        FileReaderBuilder::default()
+            .delimiter(char_to_u8_opt(option.delimiter))
+            .quote(char_to_u8(option.quote))
+            .quoting(option.quoting)
+            .terminator(option.terminator)
+            .escape(option.escape)
+            .double_quote(option.double_quote)
+            .comment(option.comment)
+            .has_headers(option.has_headers)
+            .capacity(option.capacity)
+            .clone()
     }
 
     pub fn capacity(&mut self, cap: usize) -> &mut Self {
@@ -50,16 +62,32 @@ impl FileReaderBuilder {
         self
     }
 
+    pub fn format(&mut self, format: FileFormat) -> &mut Self {
+        self.format = format;
+        self
+    }
+
+    /// Chosen by the extension of each file if `None`.
+    pub fn compression(&mut self, compression: Option<Compression>) -> &mut Self {
+        self.compression = compression;
+        self
+    }
+
     pub fn has_headers(&mut self, yes: bool) -> &mut Self {
         self.has_headers = yes;
         self
     }
 
     pub fn from_file(&self, f: File, paths: Vec<PathBuf>) -> FileReader {
+        self.from_reader(Box::new(f), paths)
+    }
+
+    /// `r` is the reader of the first file of `paths`.
+    pub fn from_reader(&self, r: Box<dyn Read + Send>, paths: Vec<PathBuf>) -> FileReader {
         FileReader::new(
             self.parser_builder.build(),
-            BufReader::with_capacity(self.capacity, f),
-            FileReaderState::new(self.has_headers, paths),
+            BufReader::with_capacity(self.capacity, r),
+            FileReaderState::new(self.format, self.compression, self.has_headers, paths),
         )
     }
 
@@ -106,6 +134,8 @@ impl Default for FileReaderBuilder {
         Self {
             parser_builder: ByteParserBuilder::default(),
             capacity: default_capacity(),
+            format: FileFormat::default(),
+            compression: None,
             has_headers: true,
         }
     }
@@ -1,12 +1,15 @@
 use std::fmt;
-use std::io::{BufWriter, Error, IntoInnerError, Write};
+use std::io::{BufWriter, Error, ErrorKind, IntoInnerError, Write};
 
-use super::QuoteStyle;
+use super::{jsonl, FileFormat, QuoteStyle};
 use toy_core::data::Value;
 use toy_text_parser::{Line, Terminator};
 
 pub struct FileWriter<W: Write> {
     raw: BufWriter<W>,
+    format: FileFormat,
+    /// buffer of the encoded json, reused for each row.
+    json_buf: Vec<u8>,
     delimiter: Option<u8>,
     requires_quotes: [bool; 256],
     quote: u8,
@@ -27,6 +30,7 @@ pub struct FileWriterState {
 impl<W: Write> FileWriter<W> {
     pub fn new(
         raw: BufWriter<W>,
+        format: FileFormat,
         has_headers: bool,
         delimiter: Option<u8>,
         quote: u8,
@@ -53,6 +57,8 @@ impl<W: Write> FileWriter<W> {
 
         FileWriter {
             raw,
+            format,
+            json_buf: Vec::new(),
             delimiter,
             requires_quotes,
             quote,
@@ -97,6 +103,17 @@ impl<W: Write> FileWriter<W> {
         self.raw.into_inner()
     }
 
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        self.raw.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    /// The buffered data is not written to it until `flush`.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.raw.get_mut()
+    }
+
     pub fn get_wrote_bytes(&self) -> u64 {
         self.state.wrote_bytes
     }
@@ -118,6 +135,9 @@ impl<W: Write> FileWriter<W> {
     }
 
     pub fn write_value(&mut self, value: &Value) -> Result<(), Error> {
+        if self.format == FileFormat::Jsonl {
+            return self.write_json(value);
+        }
         if self.state.has_headers && self.state.wrote_row == 0 {
             let mut buf = Vec::new();
             self.write_value_headers(value, false, &mut buf)?;
@@ -131,6 +151,18 @@ impl<W: Write> FileWriter<W> {
         Ok(())
     }
 
+    fn write_json(&mut self, value: &Value) -> Result<(), Error> {
+        self.json_buf.clear();
+        jsonl::encode(&mut self.json_buf, value)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        self.json_buf.push(b'\n');
+        self.raw.write_all(&self.json_buf)?;
+
+        self.state.wrote_bytes += self.json_buf.len() as u64;
+        self.state.wrote_row += 1;
+        Ok(())
+    }
+
     fn write_value_0(&mut self, value: &Value, need_delimiter: bool) -> Result<(), Error> {
         match value {
             Value::Bool(v) => {
@@ -287,6 +319,7 @@ impl<W: Write> fmt::Debug for FileWriter<W> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Sink")
             .field("state", &self.state)
+            .field("format", &self.format)
             .field("delimiter", &self.delimiter)
             .field("quote", &self.quote)
             .field("quote_style", &self.quote_style)
@@ -4,12 +4,13 @@ use std::path::Path;
 
 use super::config::{self, char_to_u8, char_to_u8_opt, WriteConfig};
 use super::file_writer::FileWriter;
-use crate::QuoteStyle;
+use crate::{CompressWriter, Compression, FileFormat, QuoteStyle};
 use toy_text_parser::Terminator;
 
 #[derive(Clone)]
 pub struct FileWriterBuilder {
     capacity: usize,
+    format: FileFormat,
     has_headers: bool,
     delimiter: Option<u8>,
     quote: u8,
@@ -20,8 +21,35 @@ pub struct FileWriterBuilder {
 }
 
 impl FileWriterBuilder {
-    pub fn configure(config: &WriteConfig) -> Result<FileWriter<Box<dyn io::Write + Send>>, Error> {
-        let b = FileWriterBuilder::default()
+    /// Create the writer of the path, or the stdout if the path is not set.
+    pub fn configure(
+        config: &WriteConfig,
+    ) -> Result<FileWriter<CompressWriter<Box<dyn io::Write + Send>>>, Error> {
+        Ok(FileWriterBuilder::from_config(config).from_writer(FileWriterBuilder::output(config)?))
+    }
+
+    /// Create the output of the path compressed by the config, or the stdout if the path is not set.
+    pub fn output(
+        config: &WriteConfig,
+    ) -> Result<CompressWriter<Box<dyn io::Write + Send>>, Error> {
+        let (w, compression): (Box<dyn io::Write + Send>, _) = match config.path.as_ref() {
+            Some(path) => (
+                Box::new(File::create(path)?),
+                config
+                    .compression
+                    .unwrap_or_else(|| Compression::from_path(path)),
+            ),
+            None => (
+                Box::new(io::stdout()),
+                config.compression.unwrap_or(Compression::None),
+            ),
+        };
+        CompressWriter::new(w, compression, config.compression_level)
+    }
+
+    pub fn from_config(config: &WriteConfig) -> FileWriterBuilder {
+        FileWriterBuilder::default()
+            .format(config.format)
             .has_headers(config.option.has_headers)
             .delimiter(char_to_u8_opt(config.option.delimiter))
             .quote(char_to_u8(config.option.quote))
@@ -30,11 +58,7 @@ impl FileWriterBuilder {
             .escape(char_to_u8(config.option.escape))
             .double_quote(config.option.double_quote)
             .capacity(config.option.capacity)
-            .clone();
-
-        Ok(b.from_writer(Box::new(File::create(
-            config.path.as_ref().unwrap().as_path(),
-        )?)))
+            .clone()
     }
 
     pub fn capacity(&mut self, cap: usize) -> &mut Self {
@@ -42,6 +66,11 @@ impl FileWriterBuilder {
         self
     }
 
+    pub fn format(&mut self, format: FileFormat) -> &mut Self {
+        self.format = format;
+        self
+    }
+
     pub fn has_headers(&mut self, yes: bool) -> &mut Self {
         self.has_headers = yes;
         self
@@ -80,6 +109,7 @@ impl FileWriterBuilder {
     pub fn from_writer<W: io::Write>(&self, w: W) -> FileWriter<W> {
         FileWriter::new(
             BufWriter::with_capacity(self.capacity, w),
+            self.format,
             self.has_headers,
             self.delimiter,
             self.quote,
@@ -99,6 +129,7 @@ impl Default for FileWriterBuilder {
     fn default() -> Self {
         Self {
             capacity: config::default_capacity(),
+            format: FileFormat::default(),
             has_headers: true,
             delimiter: Some(b','),
             quote: b'"',
@@ -0,0 +1,87 @@
+//! Encode and decode a `Value` as one line of json.
+//!
+//! Json has no type for bytes and timestamp,
+//! so these are written as the object which has a single key, and read back to the original type.
+//!
+//! ```text
+//! {"$bytes":[97,98]}
+//! {"$timestamp":"2021-01-02T03:04:05+00:00"}
+//! ```
+
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+use std::io;
+use toy_core::data::{Map, Value};
+use toy_pack_json::{DecodeError, EncodeError};
+
+/// Key of the object holding the bytes, as a array of the integers.
+pub const BYTES_KEY: &str = "$bytes";
+
+/// Key of the object holding the timestamp, as a rfc3339 string.
+pub const TIMESTAMP_KEY: &str = "$timestamp";
+
+/// Write the value as json, without the line terminator.
+pub fn encode<W: io::Write>(writer: W, v: &Value) -> Result<(), EncodeError> {
+    toy_pack_json::pack_to_writer(writer, &Typed(v))
+}
+
+/// Read the value from a line of json.
+pub fn decode(line: &[u8]) -> Result<Value, DecodeError> {
+    let mut v = toy_pack_json::unpack::<Value>(line)?;
+    restore(&mut v);
+    Ok(v)
+}
+
+struct Typed<'a>(&'a Value);
+
+impl Serialize for Typed<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.0 {
+            Value::Bytes(v) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(BYTES_KEY, v.as_slice())?;
+                map.end()
+            }
+            Value::TimeStamp(v) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(TIMESTAMP_KEY, &v.to_rfc3339())?;
+                map.end()
+            }
+            Value::Seq(v) => serializer.collect_seq(v.iter().map(Typed)),
+            Value::Map(v) => serializer.collect_map(v.iter().map(|(k, v)| (k, Typed(v)))),
+            v => v.serialize(serializer),
+        }
+    }
+}
+
+fn restore(v: &mut Value) {
+    match v {
+        Value::Map(map) => match typed(map) {
+            Some(typed) => *v = typed,
+            None => map.iter_mut().for_each(|(_, x)| restore(x)),
+        },
+        Value::Seq(seq) => seq.iter_mut().for_each(restore),
+        _ => (),
+    }
+}
+
+fn typed(map: &Map<String, Value>) -> Option<Value> {
+    if map.len() != 1 {
+        return None;
+    }
+    match map.iter().next()? {
+        (k, Value::Seq(seq)) if k == BYTES_KEY => seq
+            .iter()
+            .map(|x| match x {
+                Value::Integer(b) => u8::try_from(*b).ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<u8>>>()
+            .map(Value::from),
+        (k, v @ Value::String(_)) if k == TIMESTAMP_KEY => v.parse_timestamp().map(Value::from),
+        _ => None,
+    }
+}
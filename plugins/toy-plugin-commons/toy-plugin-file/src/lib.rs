@@ -1,21 +1,27 @@
-//! Toy Plugin for File read and write.
+//! Toy Plugin for File read, write and watch.
 
 #![feature(type_alias_impl_trait, impl_trait_in_assoc_type)]
 
 use serde::{Deserialize, Serialize};
 use toy_pack::Schema;
 
+pub use compression::CompressWriter;
 pub use file_reader_builder::FileReaderBuilder;
 pub use file_writer_builder::FileWriterBuilder;
-pub use plugin::{all, read, write};
+pub use plugin::{all, read, watch, write};
 
+mod blocking;
+pub mod compression;
 pub mod config;
 pub mod file_reader;
 pub mod file_reader_builder;
 pub mod file_writer;
 pub mod file_writer_builder;
+pub mod jsonl;
 mod plugin;
+pub mod rolling;
 pub mod service;
+pub mod watch;
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Schema)]
 pub enum QuoteStyle {
@@ -29,3 +35,31 @@ impl Default for QuoteStyle {
         QuoteStyle::Necessary
     }
 }
+
+/// Format of the records in the file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Schema)]
+pub enum FileFormat {
+    /// Delimited text, e.g. csv.
+    #[default]
+    #[serde(rename = "text")]
+    #[toy(rename = "text")]
+    Text,
+    /// One json value per line. (JSON Lines / NDJSON)
+    #[serde(rename = "jsonl")]
+    #[toy(rename = "jsonl")]
+    Jsonl,
+}
+
+/// Compression of the file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Schema)]
+pub enum Compression {
+    #[serde(rename = "none")]
+    #[toy(rename = "none")]
+    None,
+    #[serde(rename = "gzip")]
+    #[toy(rename = "gzip")]
+    Gzip,
+    #[serde(rename = "zstd")]
+    #[toy(rename = "zstd")]
+    Zstd,
+}
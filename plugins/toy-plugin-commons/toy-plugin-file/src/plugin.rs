@@ -11,6 +11,10 @@ pub fn write() -> (&'static str, &'static str, Write) {
     (NAME_SPACE, "write", Write)
 }
 
-pub fn all() -> Layered<Layered<NoopEntry, Read>, Write> {
-    layer(read()).layer(write())
+pub fn watch() -> (&'static str, &'static str, Watch) {
+    (NAME_SPACE, "watch", Watch)
+}
+
+pub fn all() -> Layered<Layered<Layered<NoopEntry, Read>, Write>, Watch> {
+    layer(read()).layer(write()).layer(watch())
 }
@@ -0,0 +1,336 @@
+//! Write the records to the files rolled by the limits, and partitioned by the path template.
+
+use crate::blocking::Spool;
+use crate::config::{RollConfig, WriteConfig};
+use crate::file_writer::FileWriter;
+use crate::file_writer_builder::FileWriterBuilder;
+use crate::{CompressWriter, Compression};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Error, ErrorKind};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use toy_core::data::Value;
+
+const DEFAULT_MAX_OPEN_FILES: usize = 64;
+
+const INDEX: &str = "{index}";
+
+/// Written in place of the value not found in the record.
+const UNKNOWN: &str = "unknown";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Field(String),
+}
+
+/// Path with the placeholders of the values of the record.
+///
+/// `{index}` is not replaced by the template, it is kept in the rendered path.
+#[derive(Debug, Clone)]
+pub struct PathTemplate {
+    segments: Vec<Segment>,
+}
+
+impl PathTemplate {
+    pub fn parse(path: &str) -> Result<PathTemplate, Error> {
+        let invalid = || {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid path template. {}", path),
+            )
+        };
+
+        let mut segments = Vec::new();
+        let mut rest = path;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..]
+                .find('}')
+                .map(|x| start + x)
+                .ok_or_else(invalid)?;
+            let (text, name) = (&rest[..start], &rest[start + 1..end]);
+            if text.contains('}') || name.is_empty() || name.contains('{') {
+                return Err(invalid());
+            }
+            if !text.is_empty() {
+                segments.push(Segment::Text(text.to_string()));
+            }
+            if &rest[start..=end] == INDEX {
+                segments.push(Segment::Text(INDEX.to_string()));
+            } else {
+                segments.push(Segment::Field(name.to_string()));
+            }
+            rest = &rest[end + 1..];
+        }
+        if rest.contains('}') {
+            return Err(invalid());
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+        Ok(PathTemplate { segments })
+    }
+
+    /// Returns the path replaced the placeholders with the values of the record.
+    ///
+    /// The separators and the braces in the values are replaced with `_`,
+    /// so a value never changes the directory or adds a placeholder.
+    pub fn render(&self, v: &Value) -> String {
+        let mut path = String::new();
+        for s in &self.segments {
+            match s {
+                Segment::Text(text) => path.push_str(text),
+                Segment::Field(name) => {
+                    let value = v
+                        .path(name)
+                        .and_then(|x| x.parse_str())
+                        .unwrap_or_else(|| UNKNOWN.to_string());
+                    if value == ".." || value == "." {
+                        path.push('_');
+                        continue;
+                    }
+                    path.extend(value.chars().map(|c| match c {
+                        '{' | '}' => '_',
+                        c if std::path::is_separator(c) => '_',
+                        c => c,
+                    }));
+                }
+            }
+        }
+        path
+    }
+}
+
+struct RollingFile {
+    writer: FileWriter<Spool<CompressWriter<File>>>,
+    /// written to the temporary path, and renamed to the path when closed.
+    tmp: PathBuf,
+    path: PathBuf,
+    rows: u64,
+    opened_at: Instant,
+    updated_at: Instant,
+}
+
+pub struct RollingWriter {
+    builder: FileWriterBuilder,
+    template: PathTemplate,
+    compression: Compression,
+    compression_level: Option<i32>,
+    roll: RollConfig,
+    /// opened files by the rendered path.
+    files: HashMap<String, RollingFile>,
+    /// next index by the rendered path, also the paths opened before.
+    indexes: HashMap<String, u32>,
+}
+
+impl RollingWriter {
+    /// Returns true if the config needs the rolling writer,
+    /// rolling the file or having the placeholders in the path.
+    pub fn is_required(config: &WriteConfig) -> bool {
+        match config.path.as_ref() {
+            Some(path) => config.roll.is_some() || path.to_string_lossy().contains('{'),
+            None => false,
+        }
+    }
+
+    pub fn new(config: &WriteConfig) -> Result<RollingWriter, Error> {
+        let path = config
+            .path
+            .as_ref()
+            .and_then(|x| x.to_str())
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "path is not set."))?;
+        let path = match config.roll {
+            Some(ref roll) if roll.has_limit() && !path.contains(INDEX) => insert_index(path),
+            _ => path.to_string(),
+        };
+        let compression = config
+            .compression
+            .unwrap_or_else(|| Compression::from_path(&path));
+        // check the level before opening the file.
+        CompressWriter::new(io::sink(), compression, config.compression_level)?;
+
+        Ok(RollingWriter {
+            builder: FileWriterBuilder::from_config(config),
+            template: PathTemplate::parse(&path)?,
+            compression,
+            compression_level: config.compression_level,
+            roll: config.roll.clone().unwrap_or_default(),
+            files: HashMap::new(),
+            indexes: HashMap::new(),
+        })
+    }
+
+    /// Write the record to the file of the partition,
+    /// the file is rolled before writing if the limit is reached.
+    ///
+    /// The bytes are kept in the memory until `drain` or the file is closed.
+    pub fn write_value(&mut self, v: &Value, now: Instant) -> Result<(), Error> {
+        let key = self.template.render(v);
+        if let Some(f) = self.files.get(&key) {
+            if self.should_roll(f, now) {
+                self.close(&key)?;
+            }
+        }
+        if !self.files.contains_key(&key) {
+            if self.files.len() >= self.max_open_files() {
+                let oldest = self
+                    .files
+                    .iter()
+                    .min_by_key(|(_, f)| f.updated_at)
+                    .map(|(k, _)| k.clone());
+                if let Some(oldest) = oldest {
+                    self.close(&oldest)?;
+                }
+            }
+            let f = self.open(&key, now)?;
+            self.files.insert(key.clone(), f);
+        }
+
+        let f = self.files.get_mut(&key).unwrap();
+        f.writer.write_value(v)?;
+        f.rows += 1;
+        f.updated_at = now;
+        Ok(())
+    }
+
+    /// Returns true if writing the record opens or closes the files.
+    pub fn opens_file(&self, v: &Value, now: Instant) -> bool {
+        match self.files.get(&self.template.render(v)) {
+            Some(f) => self.should_roll(f, now),
+            None => true,
+        }
+    }
+
+    /// Bytes of the records not written to the files yet.
+    pub fn buffered(&self) -> usize {
+        self.files
+            .values()
+            .map(|f| f.writer.get_ref().buffered())
+            .sum()
+    }
+
+    /// Write the bytes of the records to the files.
+    pub fn drain(&mut self) -> Result<(), Error> {
+        for f in self.files.values_mut() {
+            f.writer.get_mut().drain()?;
+        }
+        Ok(())
+    }
+
+    /// Close all files.
+    pub fn finish(&mut self) -> Result<(), Error> {
+        let keys = self.files.keys().cloned().collect::<Vec<_>>();
+        for key in keys {
+            self.close(&key)?;
+        }
+        Ok(())
+    }
+
+    fn max_open_files(&self) -> usize {
+        self.roll
+            .max_open_files
+            .unwrap_or(DEFAULT_MAX_OPEN_FILES)
+            .max(1)
+    }
+
+    fn should_roll(&self, f: &RollingFile, now: Instant) -> bool {
+        let roll = &self.roll;
+        roll.max_rows.is_some_and(|x| f.rows >= x)
+            || roll
+                .max_bytes
+                .is_some_and(|x| f.writer.get_wrote_bytes() >= x)
+            || roll.interval_secs.is_some_and(|x| {
+                now.saturating_duration_since(f.opened_at) >= Duration::from_secs(x)
+            })
+    }
+
+    /// Open the file of the rendered path.
+    ///
+    /// The path without `{index}` is never rolled, so the file reopened after closed by `max_open_files`
+    /// is moved back to the temporary path, and the records are appended without the headers.
+    fn open(&mut self, key: &str, now: Instant) -> Result<RollingFile, Error> {
+        let reopen = !key.contains(INDEX) && self.indexes.contains_key(key);
+        let mut index = self.indexes.get(key).copied().unwrap_or(0);
+        // not overwrite the file written before the restart.
+        let path = loop {
+            let path = PathBuf::from(key.replace(INDEX, &index.to_string()));
+            if !key.contains(INDEX) || !path.exists() {
+                break path;
+            }
+            index += 1;
+        };
+        self.indexes.insert(key.to_string(), index + 1);
+
+        let name = path
+            .file_name()
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid file path. {}", path.display()),
+                )
+            })?
+            .to_string_lossy();
+        let tmp = path.with_file_name(format!(".{}.tmp", name));
+        if let Some(parent) = path.parent().filter(|x| !x.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut builder = self.builder.clone();
+        let file = if reopen && path.exists() {
+            tracing::info!("reopen file. path: {}", path.display());
+            fs::rename(&path, &tmp)?;
+            builder.has_headers(false);
+            OpenOptions::new().append(true).open(&tmp)?
+        } else {
+            tracing::info!("open file. path: {}", path.display());
+            File::create(&tmp)?
+        };
+        // the compressed stream is appended as the next member or frame.
+        let w = CompressWriter::new(file, self.compression, self.compression_level)?;
+        Ok(RollingFile {
+            writer: builder.from_writer(Spool::new(w)),
+            tmp,
+            path,
+            rows: 0,
+            opened_at: now,
+            updated_at: now,
+        })
+    }
+
+    fn close(&mut self, key: &str) -> Result<(), Error> {
+        if let Some(mut f) = self.files.remove(key) {
+            f.writer.flush()?;
+            f.writer.get_mut().get_mut().finish()?;
+            drop(f.writer);
+            fs::rename(&f.tmp, &f.path)?;
+            tracing::info!("close file. path: {}, rows: {}", f.path.display(), f.rows);
+        }
+        Ok(())
+    }
+}
+
+/// Insert `-{index}` before the extension of the file name, `out/{region}.csv` -> `out/{region}-{index}.csv`.
+fn insert_index(path: &str) -> String {
+    let name_start = path.rfind(std::path::is_separator).map_or(0, |x| x + 1);
+    let mut depth = 0;
+    let dot = path[name_start..]
+        .char_indices()
+        .find(|(i, c)| match c {
+            '{' => {
+                depth += 1;
+                false
+            }
+            '}' => {
+                depth -= 1;
+                false
+            }
+            '.' => depth == 0 && *i > 0,
+            _ => false,
+        })
+        .map(|(i, _)| name_start + i);
+    match dot {
+        Some(dot) => format!("{}-{}{}", &path[..dot], INDEX, &path[dot..]),
+        None => format!("{}-{}", path, INDEX),
+    }
+}
@@ -1,23 +1,102 @@
-use crate::config::{ReadConfig, WriteConfig};
+use crate::blocking::{self, BlockingWriter, Spool, WriteValue};
+use crate::config::{ReadConfig, WatchConfig, WriteConfig};
 use crate::file_reader::FileReader;
 use crate::file_reader_builder::FileReaderBuilder;
 use crate::file_writer::FileWriter;
 use crate::file_writer_builder::FileWriterBuilder;
+use crate::rolling::RollingWriter;
+use crate::watch::Tailer;
+use crate::{jsonl, CompressWriter, FileFormat};
 use core::fmt::Formatter;
 use std::future::Future;
 use std::io;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use toy_core::data::headers;
+use toy_core::graph::ErrorPolicy;
 use toy_core::prelude::*;
 use toy_text_parser::Line;
 
 pub struct ReadContext {
     line: u32,
-    reader: FileReader,
-    buf: Line,
+    /// lines already sent before the task resumed.
+    skip: u32,
+    /// moved to the reading thread when the reading starts.
+    reader: Option<FileReader>,
+    /// batches of the records from the reading thread.
+    batches: Option<Batches>,
 }
 
+/// Records and their numbers in the files, received from the reading thread.
+type Batches = mpsc::Receiver<Result<Vec<(u32, Frame)>, ServiceError>>;
+
 pub struct WriteContext {
     line: u32,
-    writer: FileWriter<Box<dyn io::Write + Send>>,
+    writer: BlockingWriter<Output>,
+}
+
+/// Parse the records of the files, on the reading thread.
+struct Records {
+    reader: FileReader,
+    line: u32,
+    skip: u32,
+    buf: Line,
+    /// line buffer for jsonl.
+    raw: Vec<u8>,
+}
+
+pub struct WatchContext {
+    tailer: Tailer,
+    interval: Duration,
+    frames: Vec<Frame>,
+}
+
+/// Writer of the file or the stdout, not rolled.
+type SingleWriter = FileWriter<Spool<CompressWriter<Box<dyn io::Write + Send>>>>;
+
+enum Output {
+    Single(Box<SingleWriter>),
+    Rolling(Box<RollingWriter>),
+}
+
+impl WriteValue for Output {
+    fn write_value(&mut self, v: &Value, now: Instant) -> Result<(), io::Error> {
+        match self {
+            Output::Single(w) => w.write_value(v),
+            Output::Rolling(w) => w.write_value(v, now),
+        }
+    }
+
+    fn opens_file(&self, v: &Value, now: Instant) -> bool {
+        match self {
+            Output::Single(_) => false,
+            Output::Rolling(w) => w.opens_file(v, now),
+        }
+    }
+
+    fn buffered(&self) -> usize {
+        match self {
+            Output::Single(w) => w.get_ref().buffered(),
+            Output::Rolling(w) => w.buffered(),
+        }
+    }
+
+    fn drain(&mut self) -> Result<(), io::Error> {
+        match self {
+            Output::Single(w) => w.get_mut().drain(),
+            Output::Rolling(w) => w.drain(),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), io::Error> {
+        match self {
+            Output::Single(w) => {
+                w.flush()?;
+                w.get_mut().get_mut().finish()
+            }
+            Output::Rolling(w) => w.finish(),
+        }
+    }
 }
 
 impl std::fmt::Debug for WriteContext {
@@ -36,6 +115,14 @@ impl std::fmt::Debug for ReadContext {
     }
 }
 
+impl std::fmt::Debug for WatchContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        f.debug_struct(std::any::type_name::<WatchContext>())
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Read;
 
@@ -53,6 +140,21 @@ impl Service for Read {
         PortType::source()
     }
 
+    fn started(
+        &mut self,
+        task_ctx: TaskContext,
+        mut ctx: Self::Context,
+    ) -> ServiceContext<Self::Context> {
+        if let Some(line) = task_ctx
+            .restored()
+            .and_then(|x| x.path("line"))
+            .and_then(|x| x.as_u32())
+        {
+            ctx.skip = line;
+        }
+        ServiceContext::Ready(ctx)
+    }
+
     fn handle(
         &mut self,
         task_ctx: TaskContext,
@@ -99,17 +201,24 @@ impl ServiceFactory for Read {
 
     fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
         async move {
-            FileReaderBuilder::configure(&config)
+            blocking::run(move || FileReaderBuilder::configure(&config))
+                .await
                 .map(|r| ReadContext {
                     line: 0u32,
-                    reader: r,
-                    buf: Line::new(),
+                    skip: 0u32,
+                    reader: Some(r),
+                    batches: None,
                 })
                 .map_err(|e| e.into())
         }
     }
 }
 
+/// Write the records to the file, or the stdout.
+///
+/// The records are encoded on the service, and written to the file on the blocking thread in chunks.
+/// The error of the writing is reported by the record writing the chunk, and the chunk may include the records before,
+/// so `on_error` other than `fail` is not accepted.
 #[derive(Debug, Clone)]
 pub struct Write;
 
@@ -150,10 +259,13 @@ impl Service for Write {
     fn upstream_finish_all(
         &mut self,
         _task_ctx: TaskContext,
-        ctx: Self::Context,
+        mut ctx: Self::Context,
         _tx: Outgoing<Self::Request>,
     ) -> Self::UpstreamFinishAllFuture {
-        async move { Ok(ServiceContext::Complete(ctx)) }
+        async move {
+            ctx.writer.finish().await?;
+            Ok(ServiceContext::Complete(ctx))
+        }
     }
 }
 
@@ -173,40 +285,201 @@ impl ServiceFactory for Write {
 
     fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
         async move {
-            FileWriterBuilder::configure(&config)
+            let writer = blocking::run(move || {
+                if RollingWriter::is_required(&config) {
+                    RollingWriter::new(&config).map(|w| Output::Rolling(Box::new(w)))
+                } else {
+                    FileWriterBuilder::output(&config).map(|w| {
+                        let w = FileWriterBuilder::from_config(&config).from_writer(Spool::new(w));
+                        Output::Single(Box::new(w))
+                    })
+                }
+            })
+            .await;
+            writer
                 .map(|w| WriteContext {
                     line: 0u32,
-                    writer: w,
+                    writer: BlockingWriter::new(w),
                 })
                 .map_err(|e| e.into())
         }
     }
+
+    fn accepts_error_policy(&self, policy: &ErrorPolicy) -> bool {
+        *policy == ErrorPolicy::Fail
+    }
 }
 
+#[derive(Debug, Clone)]
+pub struct Watch;
+
+impl Service for Watch {
+    type Context = WatchContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<WatchContext>, ServiceError>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<WatchContext>, ServiceError>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<WatchContext>, ServiceError>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::source()
+    }
+
+    fn handle(
+        &mut self,
+        task_ctx: TaskContext,
+        ctx: Self::Context,
+        req: Self::Request,
+        tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move { watch(task_ctx, ctx, req, tx).await }
+    }
+
+    fn completed(&mut self, _task_ctx: TaskContext, mut ctx: Self::Context) {
+        if let Err(e) = ctx.tailer.save() {
+            tracing::error!("failed to save the state of the watched files. {}", e);
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move { Ok(ServiceContext::Complete(ctx)) }
+    }
+}
+
+impl ServiceFactory for Watch {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Watch;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = WatchContext;
+    type Config = WatchConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Watch) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
+        async move {
+            let interval = config.interval_millis;
+            blocking::run(move || Tailer::new(&config))
+                .await
+                .map(|t| WatchContext {
+                    tailer: t,
+                    interval: Duration::from_millis(interval),
+                    frames: Vec::new(),
+                })
+                .map_err(|e| e.into())
+        }
+    }
+}
+
+/// Send a batch of the records, and returns `Next` until all records are sent,
+/// so the task can be stopped or drained between the batches.
 async fn read(
-    _task_ctx: TaskContext,
+    task_ctx: TaskContext,
     mut ctx: ReadContext,
     _req: Frame,
     mut tx: Outgoing<Frame>,
 ) -> Result<ServiceContext<ReadContext>, ServiceError> {
-    while ctx.reader.read(&mut ctx.buf)? {
-        let v = if ctx.reader.has_headers() {
-            let v = ctx
-                .reader
-                .headers()?
-                .iter()
-                .zip(ctx.buf.iter())
-                .map(|(h, v)| (String::from_utf8_lossy(h).to_string(), Value::from(v)))
-                .collect::<Map<_, _>>();
-            Frame::from(v)
-        } else {
-            let v = ctx.buf.iter().map(|c| Value::from(c)).collect::<Vec<_>>();
-            Frame::from(v)
+    if let Some(reader) = ctx.reader.take() {
+        let mut records = Records {
+            reader,
+            line: 0,
+            skip: ctx.skip,
+            buf: Line::new(),
+            raw: Vec::new(),
         };
-        tx.send(v).await?;
-        ctx.line += 1;
+        ctx.batches = Some(blocking::spawn_reader(move || records.next()));
+    }
+    let batch = match ctx.batches.as_mut() {
+        Some(rx) => rx.recv().await,
+        None => None,
+    };
+    match batch {
+        Some(batch) => {
+            for (line, v) in batch? {
+                tx.send(v).await?;
+                ctx.line = line;
+                task_ctx.checkpoint(map_value! { "line" => ctx.line });
+            }
+            Ok(ServiceContext::Next(ctx))
+        }
+        None => {
+            ctx.batches = None;
+            Ok(ServiceContext::Complete(ctx))
+        }
+    }
+}
+
+impl Records {
+    /// Returns the next record, and the record number in the files starting at 1.
+    fn next(&mut self) -> Result<Option<(u32, Frame)>, ServiceError> {
+        loop {
+            let has_record = match self.reader.format() {
+                FileFormat::Text => self.reader.read(&mut self.buf)?,
+                FileFormat::Jsonl => self.reader.read_line(&mut self.raw)?,
+            };
+            if !has_record {
+                return Ok(None);
+            }
+            if self.line < self.skip {
+                self.line += 1;
+                continue;
+            }
+            let v = if self.reader.format() == FileFormat::Jsonl {
+                let v = jsonl::decode(&self.raw).map_err(|e| {
+                    ServiceError::error(format!(
+                        "invalid json at line {} of {}. {}",
+                        self.reader.current_line(),
+                        self.reader
+                            .current_path()
+                            .map(|x| x.display().to_string())
+                            .unwrap_or_default(),
+                        e
+                    ))
+                })?;
+                Frame::from_value(v)
+            } else if self.reader.has_headers() {
+                let v = self
+                    .reader
+                    .headers()?
+                    .iter()
+                    .zip(self.buf.iter())
+                    .map(|(h, v)| (String::from_utf8_lossy(h).to_string(), Value::from(v)))
+                    .collect::<Map<_, _>>();
+                Frame::from(v)
+            } else {
+                let v = self.buf.iter().map(Value::from).collect::<Vec<_>>();
+                Frame::from(v)
+            };
+            let v = match self.reader.current_path() {
+                Some(path) => v.with_header(headers::SOURCE, path.display().to_string()),
+                None => v,
+            };
+            self.line += 1;
+            let v = v.with_header(headers::LINE, self.reader.current_line());
+            return Ok(Some((self.line, v)));
+        }
     }
-    Ok(ServiceContext::Complete(ctx))
 }
 
 async fn write(
@@ -215,14 +488,41 @@ async fn write(
     req: Frame,
     mut tx: Outgoing<Frame>,
 ) -> Result<ServiceContext<WriteContext>, ServiceError> {
-    match req.value() {
-        Some(v) => {
-            ctx.writer.write_value(v)?;
-            ctx.line += 1;
-            tx.send(Frame::none()).await?;
+    if let Some(v) = req.into_value() {
+        if let Err(e) = ctx.writer.write_value(v).await {
+            return Ok(ServiceContext::Failed(ctx, e.into()));
         }
-        None => (),
+        ctx.line += 1;
+        tx.send(Frame::none()).await?;
     }
 
     Ok(ServiceContext::Ready(ctx))
 }
+
+/// Send the lines appended since the last scan, and wait for the next scan.
+/// Runs until the task is stopped.
+async fn watch(
+    _task_ctx: TaskContext,
+    mut ctx: WatchContext,
+    _req: Frame,
+    mut tx: Outgoing<Frame>,
+) -> Result<ServiceContext<WatchContext>, ServiceError> {
+    ctx = blocking::run(move || ctx.tailer.scan().map(|_| ctx)).await?;
+    loop {
+        let (c, has_record) = blocking::run(move || {
+            let r = ctx.tailer.read(&mut ctx.frames)?;
+            Ok((ctx, r))
+        })
+        .await?;
+        ctx = c;
+        if !has_record {
+            break;
+        }
+        for v in ctx.frames.drain(..) {
+            tx.send(v).await?;
+        }
+    }
+    ctx = blocking::run(move || ctx.tailer.save().map(|_| ctx)).await?;
+    tokio::time::sleep(ctx.interval).await;
+    Ok(ServiceContext::Next(ctx))
+}
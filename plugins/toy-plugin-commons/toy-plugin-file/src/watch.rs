@@ -0,0 +1,488 @@
+//! Follow the files matched by the glob pattern, and read the lines appended to them.
+//!
+//! The files are scanned at every interval, no file system notification is used.
+//! A file is identified by the device and the inode, not by the path,
+//! so the file renamed by the rotation is read to the end before it is released,
+//! and the file created at the same path is read from the beginning.
+//!
+//! The offset of each file is saved to the state file,
+//! and the file found at the next start is resumed from the saved offset.
+//!
+//! A file is opened when it has the bytes not read yet.
+//! The files read to the end are kept open up to `max_open_files`,
+//! over it they are closed with only the offset kept, and opened again when grown.
+
+use crate::config::WatchConfig;
+use crate::file_reader_builder::FileReaderBuilder;
+use crate::{jsonl, FileFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, Metadata};
+use std::io::{BufRead, BufReader, Cursor, Error, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use toy_core::data::{headers, Frame, Map, Value};
+use toy_text_parser::Line;
+
+/// Bytes read from a file at once, extended if a line is longer than this.
+const CHUNK_SIZE: u64 = 64 * (1 << 10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct FileId {
+    dev: u64,
+    ino: u64,
+}
+
+#[cfg(unix)]
+fn file_id(meta: &Metadata, _path: &Path) -> FileId {
+    use std::os::unix::fs::MetadataExt;
+    FileId {
+        dev: meta.dev(),
+        ino: meta.ino(),
+    }
+}
+
+/// No inode, the file is identified by the path.
+#[cfg(not(unix))]
+fn file_id(_meta: &Metadata, path: &Path) -> FileId {
+    use std::hash::{Hash, Hasher};
+    let mut h = std::collections::hash_map::DefaultHasher::new();
+    path.hash(&mut h);
+    FileId {
+        dev: 0,
+        ino: h.finish(),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct State {
+    files: Vec<FileState>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FileState {
+    dev: u64,
+    ino: u64,
+    path: String,
+    offset: u64,
+}
+
+struct TailedFile {
+    id: FileId,
+    path: PathBuf,
+    /// `None` if closed, opened again when the file has the bytes not read yet.
+    file: Option<File>,
+    /// bytes already read.
+    offset: u64,
+    /// bytes known to be in the file, including the last line without the line terminator.
+    end: u64,
+    /// length of the file found by the last scan.
+    len: u64,
+    /// lines before the offset.
+    line: u64,
+    headers: Option<Vec<String>>,
+    /// not found by the last scan, released after read to the end.
+    rotated: bool,
+    /// read to the end by the last read, can be closed.
+    eof: bool,
+    /// sequence number of the last read, the least recently read file is closed first.
+    read_at: u64,
+}
+
+pub struct Tailer {
+    pattern: String,
+    format: FileFormat,
+    has_headers: bool,
+    /// builder of the parser for a chunk of the lines, the headers are handled by the tailer.
+    builder: FileReaderBuilder,
+    state_path: Option<PathBuf>,
+    /// offsets loaded from the state file, for the files not found yet.
+    saved: HashMap<FileId, u64>,
+    /// last content of the state file.
+    saved_state: Vec<u8>,
+    max_open_files: usize,
+    files: Vec<TailedFile>,
+    buf: Vec<u8>,
+    reads: u64,
+}
+
+impl Tailer {
+    pub fn new(config: &WatchConfig) -> Result<Tailer, Error> {
+        glob::Pattern::new(&config.path).map_err(|e| Error::new(ErrorKind::InvalidInput, e.msg))?;
+        let saved = match &config.state_path {
+            Some(path) => load(path)?,
+            None => HashMap::new(),
+        };
+        Ok(Tailer {
+            pattern: config.path.clone(),
+            format: config.format,
+            has_headers: config.option.has_headers,
+            builder: FileReaderBuilder::from_option(&config.option)
+                .has_headers(false)
+                .clone(),
+            state_path: config.state_path.clone(),
+            saved,
+            saved_state: Vec::new(),
+            max_open_files: config.max_open_files,
+            files: Vec::new(),
+            buf: Vec::new(),
+            reads: 0,
+        })
+    }
+
+    /// Find the files matched by the pattern.
+    ///
+    /// The new file is followed from the saved offset, or the beginning.
+    /// The file smaller than the offset is truncated, and read again from the beginning.
+    pub fn scan(&mut self) -> Result<(), Error> {
+        let paths =
+            glob::glob(&self.pattern).map_err(|e| Error::new(ErrorKind::InvalidInput, e.msg))?;
+        let mut found = Vec::new();
+        for path in paths.filter_map(Result::ok) {
+            match fs::metadata(&path) {
+                Ok(meta) if meta.is_file() => found.push((file_id(&meta, &path), path, meta.len())),
+                _ => (),
+            }
+        }
+
+        for f in self.files.iter_mut() {
+            match found.iter().find(|(id, _, _)| *id == f.id) {
+                Some((_, path, len)) => {
+                    if *path != f.path {
+                        tracing::info!("file renamed. {} -> {}", f.path.display(), path.display());
+                        f.path = path.clone();
+                    }
+                    if *len < f.offset {
+                        tracing::info!("file truncated. path: {}", f.path.display());
+                        f.offset = 0;
+                        f.end = 0;
+                        f.line = 0;
+                        f.headers = None;
+                        f.eof = false;
+                    }
+                    f.len = *len;
+                    f.rotated = false;
+                }
+                None => {
+                    if !f.rotated {
+                        tracing::info!("file rotated or removed. path: {}", f.path.display());
+                    }
+                    f.rotated = true;
+                }
+            }
+        }
+
+        for (id, path, len) in found {
+            if self.files.iter().any(|f| f.id == id) {
+                continue;
+            }
+            let offset = match self.saved.get(&id) {
+                Some(offset) if *offset <= len => *offset,
+                _ => 0,
+            };
+            let mut f = TailedFile {
+                id,
+                path,
+                file: None,
+                offset,
+                end: offset,
+                len,
+                line: 0,
+                headers: None,
+                rotated: false,
+                eof: false,
+                read_at: 0,
+            };
+            // resumed, the file is opened only to read the headers and count the lines before the offset.
+            if offset > 0 {
+                let mut file = match open(&f)? {
+                    Some(file) => file,
+                    None => continue,
+                };
+                f.line = count_lines(&mut file, offset)?;
+                if self.format == FileFormat::Text && self.has_headers {
+                    f.headers = read_headers(&self.builder, &mut file)?;
+                }
+            }
+            tracing::info!("watch file. path: {}, offset: {}", f.path.display(), offset);
+            self.saved.remove(&id);
+            self.files.push(f);
+        }
+        // the rotated file is read before the file created at the path.
+        self.files
+            .sort_by(|a, b| (!a.rotated, &a.path).cmp(&(!b.rotated, &b.path)));
+        self.close_idle(self.max_open_files);
+        Ok(())
+    }
+
+    /// Read the lines appended to a file, and push the records to `frames`.
+    /// Returns false when no more lines could be read from any file.
+    ///
+    /// The rotated file is released after read to the end,
+    /// and the last line without the line terminator is read only from the rotated file.
+    pub fn read(&mut self, frames: &mut Vec<Frame>) -> Result<bool, Error> {
+        let mut i = 0;
+        while i < self.files.len() {
+            if self.files[i].file.is_none() {
+                let f = &self.files[i];
+                if f.rotated {
+                    tracing::info!("release closed file. path: {}", f.path.display());
+                    self.files.remove(i);
+                    continue;
+                }
+                if f.len <= f.end {
+                    i += 1;
+                    continue;
+                }
+                self.close_idle(self.max_open_files.saturating_sub(1));
+                match open(&self.files[i])? {
+                    Some(file) => self.files[i].file = Some(file),
+                    None => {
+                        i += 1;
+                        continue;
+                    }
+                }
+            }
+
+            let f = &mut self.files[i];
+            if read_chunk(f, &mut self.buf)? {
+                match self.format {
+                    FileFormat::Text => {
+                        parse_text(&self.builder, self.has_headers, f, &self.buf, frames)?
+                    }
+                    FileFormat::Jsonl => parse_jsonl(f, &self.buf, frames),
+                }
+                f.offset += self.buf.len() as u64;
+                f.end = f.end.max(f.offset);
+                f.line += self.buf.iter().filter(|b| **b == b'\n').count() as u64;
+                f.eof = false;
+                self.reads += 1;
+                f.read_at = self.reads;
+                return Ok(true);
+            }
+            // the last line without the line terminator is left in the buffer.
+            f.end = f.offset + self.buf.len() as u64;
+            f.eof = true;
+            if f.rotated {
+                tracing::info!("release file. path: {}", f.path.display());
+                self.files.remove(i);
+                continue;
+            }
+            i += 1;
+        }
+        Ok(false)
+    }
+
+    /// Close the files read to the end from the least recently read, until the open files are `keep` or less.
+    fn close_idle(&mut self, keep: usize) {
+        let open = self.files.iter().filter(|f| f.file.is_some()).count();
+        if open <= keep {
+            return;
+        }
+        let mut idle = self
+            .files
+            .iter_mut()
+            .filter(|f| f.file.is_some() && f.eof && !f.rotated)
+            .collect::<Vec<_>>();
+        idle.sort_by_key(|f| f.read_at);
+        for f in idle.into_iter().take(open - keep) {
+            tracing::debug!("close idle file. path: {}", f.path.display());
+            f.file = None;
+        }
+    }
+
+    /// Save the offsets of the followed files to the state file, if changed.
+    pub fn save(&mut self) -> Result<(), Error> {
+        let path = match &self.state_path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let state = State {
+            files: self
+                .files
+                .iter()
+                .map(|f| FileState {
+                    dev: f.id.dev,
+                    ino: f.id.ino,
+                    path: f.path.display().to_string(),
+                    offset: f.offset,
+                })
+                .collect(),
+        };
+        let bytes = toy_pack_json::pack(&state)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+        if bytes == self.saved_state {
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent().filter(|x| !x.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, &bytes)?;
+        fs::rename(&tmp, path)?;
+        self.saved_state = bytes;
+        Ok(())
+    }
+}
+
+fn load(path: &Path) -> Result<HashMap<FileId, u64>, Error> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e),
+    };
+    let state = toy_pack_json::unpack::<State>(&bytes).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid state file. path: {}, {}", path.display(), e),
+        )
+    })?;
+    Ok(state
+        .files
+        .into_iter()
+        .map(|f| {
+            (
+                FileId {
+                    dev: f.dev,
+                    ino: f.ino,
+                },
+                f.offset,
+            )
+        })
+        .collect())
+}
+
+/// Open the file at the path, `None` if not found or replaced by another file.
+/// The renamed file is found by the next scan.
+fn open(f: &TailedFile) -> Result<Option<File>, Error> {
+    let file = match File::open(&f.path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if file_id(&file.metadata()?, &f.path) != f.id {
+        return Ok(None);
+    }
+    Ok(Some(file))
+}
+
+fn count_lines(file: &mut File, offset: u64) -> Result<u64, Error> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = BufReader::new((&*file).take(offset));
+    let mut count = 0;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(count);
+        }
+        count += buf.iter().filter(|b| **b == b'\n').count() as u64;
+        let len = buf.len();
+        reader.consume(len);
+    }
+}
+
+/// Read the complete lines from the offset into `buf`.
+/// The last line without the line terminator is read only from the rotated file.
+fn read_chunk(f: &mut TailedFile, buf: &mut Vec<u8>) -> Result<bool, Error> {
+    let file = match f.file.as_mut() {
+        Some(file) => file,
+        None => return Ok(false),
+    };
+    let mut limit = CHUNK_SIZE;
+    loop {
+        buf.clear();
+        file.seek(SeekFrom::Start(f.offset))?;
+        (&*file).take(limit).read_to_end(buf)?;
+        let eof = (buf.len() as u64) < limit;
+        match buf.iter().rposition(|b| *b == b'\n') {
+            Some(p) => {
+                buf.truncate(p + 1);
+                return Ok(true);
+            }
+            None if eof => return Ok(f.rotated && !buf.is_empty()),
+            None => limit *= 2,
+        }
+    }
+}
+
+fn read_headers(
+    builder: &FileReaderBuilder,
+    file: &mut File,
+) -> Result<Option<Vec<String>>, Error> {
+    let mut first = Vec::new();
+    file.seek(SeekFrom::Start(0))?;
+    BufReader::new(&*file).read_until(b'\n', &mut first)?;
+    let mut reader = builder.from_reader(Box::new(Cursor::new(first)), Vec::new());
+    let mut line = Line::new();
+    Ok(match reader.read(&mut line)? {
+        true => Some(to_strings(&line)),
+        false => None,
+    })
+}
+
+fn parse_text(
+    builder: &FileReaderBuilder,
+    has_headers: bool,
+    f: &mut TailedFile,
+    chunk: &[u8],
+    frames: &mut Vec<Frame>,
+) -> Result<(), Error> {
+    let mut reader = builder.from_reader(Box::new(Cursor::new(chunk.to_vec())), Vec::new());
+    let mut line = Line::new();
+    while reader.read(&mut line)? {
+        if has_headers && f.headers.is_none() {
+            f.headers = Some(to_strings(&line));
+            continue;
+        }
+        let v = match &f.headers {
+            Some(headers) => {
+                let v = headers
+                    .iter()
+                    .zip(line.iter())
+                    .map(|(h, v)| (h.clone(), Value::from(v)))
+                    .collect::<Map<_, _>>();
+                Frame::from(v)
+            }
+            None => Frame::from(line.iter().map(Value::from).collect::<Vec<_>>()),
+        };
+        let line = f.line + reader.current_line();
+        frames.push(
+            v.with_header(headers::SOURCE, f.path.display().to_string())
+                .with_header(headers::LINE, line),
+        );
+    }
+    Ok(())
+}
+
+/// The invalid line is skipped, not to stop following the file.
+fn parse_jsonl(f: &TailedFile, chunk: &[u8], frames: &mut Vec<Frame>) {
+    let mut offset = f.offset;
+    for (n, line) in chunk.split_inclusive(|b| *b == b'\n').enumerate() {
+        let start = offset;
+        offset += line.len() as u64;
+        let line = line.trim_ascii_end();
+        if line.is_empty() {
+            continue;
+        }
+        match jsonl::decode(line) {
+            Ok(v) => frames.push(
+                Frame::from_value(v)
+                    .with_header(headers::SOURCE, f.path.display().to_string())
+                    .with_header(headers::LINE, f.line + n as u64 + 1),
+            ),
+            Err(e) => tracing::warn!(
+                "skip invalid json at offset {} of {}. {}",
+                start,
+                f.path.display(),
+                e
+            ),
+        }
+    }
+}
+
+fn to_strings(line: &Line) -> Vec<String> {
+    line.iter()
+        .map(|x| String::from_utf8_lossy(x).to_string())
+        .collect()
+}
@@ -1,9 +1,15 @@
 use std::fs::File;
 use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
 use tempdir::TempDir;
+use toy_core::checkpoint::{CheckpointStore, Checkpoints, MemoryCheckpointStore};
+use toy_core::data::headers;
 use toy_core::prelude::*;
 use toy_plugin_file::config::{ReadConfig, ReadOption};
 use toy_plugin_file::service::Read;
+use toy_plugin_file::{Compression, FileFormat};
+use toy_plugin_test::harness::Harness;
 
 #[tokio::test]
 async fn read_glob() {
@@ -27,24 +33,288 @@ async fn read_glob() {
     assert_eq!(result.get(1).unwrap(), data.get(1).unwrap());
 }
 
-async fn read(root: &TempDir, file_name: &str) -> Vec<Value> {
+#[tokio::test]
+async fn read_glob_line_per_file() {
+    let root = preapre_temp();
+
+    let mut f = File::create(root.path().join("line-1.csv")).unwrap();
+    writeln!(f, "column1,column2").unwrap();
+    writeln!(f, "a,b").unwrap();
+    writeln!(f, "c,d").unwrap();
+    let mut f = File::create(root.path().join("line-2.csv")).unwrap();
+    writeln!(f, "column1,column2").unwrap();
+    writeln!(f, "e,f").unwrap();
+
+    let frames = read_frames(&root, "line-*.csv", toy_plugin_test::dummy_task_context()).await;
+
+    let lines = frames
+        .iter()
+        .map(|x| x.header(headers::LINE).cloned())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        vec![
+            Some(Value::from(2u64)),
+            Some(Value::from(3u64)),
+            Some(Value::from(2u64))
+        ]
+    );
+}
+
+#[tokio::test]
+async fn read_headers() {
+    let root = preapre_temp();
+
+    let path = root.path().join("headers.csv");
+    let mut f = File::create(&path).unwrap();
+    writeln!(f, "column1,column2").unwrap();
+    writeln!(f, "a,b").unwrap();
+    writeln!(f, "c,d").unwrap();
+
+    let frames = read_frames(&root, "headers.csv", toy_plugin_test::dummy_task_context()).await;
+
+    assert_eq!(frames.len(), 2);
+    let source = Value::from(path.display().to_string());
+    assert_eq!(frames[0].header(headers::SOURCE), Some(&source));
+    // the header row is the line 1.
+    assert_eq!(frames[0].header(headers::LINE), Some(&Value::from(2u64)));
+    assert_eq!(frames[1].header(headers::LINE), Some(&Value::from(3u64)));
+}
+
+#[tokio::test]
+async fn read_resume() {
+    let root = preapre_temp();
+
+    let mut f = File::create(root.path().join("resume.csv")).unwrap();
+    writeln!(f, "column1,column2").unwrap();
+    writeln!(f, "a,b").unwrap();
+    writeln!(f, "c,d").unwrap();
+
+    let store = Arc::new(MemoryCheckpointStore::new());
+    let task_ctx = toy_plugin_test::dummy_task_context()
+        .with_checkpoints(Checkpoints::new(store.clone(), 1000));
+    let id = task_ctx.id();
+    let mut states = Map::new();
+    states.insert(task_ctx.uri().to_string(), map_value! { "line" => 1 });
+    store.save(id, &states).await.unwrap();
+    task_ctx.checkpoints().unwrap().restore(id).await.unwrap();
+
+    let result = read_with(&root, "resume.csv", task_ctx.clone()).await;
+
+    assert_eq!(
+        result,
+        vec![map_value!("column1" => &b"c"[..], "column2" => &b"d"[..])]
+    );
+    task_ctx.checkpoints().unwrap().save(id).await.unwrap();
+    let saved = store.load(id).await.unwrap().unwrap();
+    assert_eq!(
+        saved.get(&task_ctx.uri().to_string()),
+        Some(&map_value! { "line" => 2 })
+    );
+}
+
+#[tokio::test]
+async fn read_jsonl() {
+    let root = preapre_temp();
+
+    let mut f = File::create(root.path().join("test-1.jsonl")).unwrap();
+    writeln!(
+        f,
+        r#"{{"id":1,"tags":["a","b"],"nested":{{"v":1.5,"n":null}}}}"#
+    )
+    .unwrap();
+    writeln!(f).unwrap();
+    writeln!(f, r#"{{"bytes":{{"$bytes":[97,98]}}}}"#).unwrap();
+    let mut f = File::create(root.path().join("test-2.jsonl")).unwrap();
+    // crlf terminated.
+    f.write_all(b"{\"ts\":{\"$timestamp\":\"2021-01-02T03:04:05+00:00\"}}\r\n")
+        .unwrap();
+
+    let config = jsonl_config(&root, "test*.jsonl");
+    let frames = read_frames_with(config, toy_plugin_test::dummy_task_context()).await;
+
+    let ts = Value::from("2021-01-02T03:04:05+00:00")
+        .parse_timestamp()
+        .unwrap();
+    let values = frames
+        .iter()
+        .map(|x| x.value().unwrap().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        vec![
+            map_value! {
+                "id" => 1,
+                "tags" => seq_value!["a", "b"],
+                "nested" => map_value! { "v" => 1.5, "n" => Value::None },
+            },
+            map_value! { "bytes" => &b"ab"[..] },
+            map_value! { "ts" => ts },
+        ]
+    );
+    assert_eq!(frames[1].header(headers::LINE), Some(&Value::from(3u64)));
+    // the line number in the second file.
+    assert_eq!(frames[2].header(headers::LINE), Some(&Value::from(1u64)));
+}
+
+#[tokio::test]
+async fn read_jsonl_invalid_line() {
+    let root = preapre_temp();
+
+    let mut f = File::create(root.path().join("invalid.jsonl")).unwrap();
+    writeln!(f, r#"{{"id":1}}"#).unwrap();
+    writeln!(f).unwrap();
+    writeln!(f, r#"{{"id":2,}}"#).unwrap();
+
     let mut service = Read;
     let (tx, mut rx) = toy_core::mpsc::channel(100);
     let task_ctx = toy_plugin_test::dummy_task_context();
-    let config = ReadConfig::with(
-        format!(
-            "{}{}{}",
-            root.path().display(),
-            std::path::MAIN_SEPARATOR,
-            file_name
-        ),
-        ReadOption::common_csv(),
+    let c = service
+        .new_context(
+            toy_plugin_test::dummy_service_type(),
+            jsonl_config(&root, "invalid.jsonl"),
+        )
+        .await
+        .unwrap();
+
+    // the records before the invalid line are sent by the batch before the error.
+    let mut c = c;
+    let e = loop {
+        match service
+            .handle(task_ctx.clone(), c, Frame::default(), tx.clone())
+            .await
+        {
+            Ok(ServiceContext::Next(r)) => c = r,
+            Ok(_) => panic!("the error must be returned."),
+            Err(e) => break e.to_string(),
+        }
+    };
+    assert!(e.contains("invalid json at line 3"), "{}", e);
+    assert_eq!(
+        rx.next().await.unwrap().into_value(),
+        Some(map_value! { "id" => 1 })
+    );
+}
+
+#[tokio::test]
+async fn read_gzip_multi_member() {
+    let root = preapre_temp();
+
+    // two members, such as the files concatenated by `cat a.gz b.gz`.
+    let mut f = File::create(root.path().join("test.csv.gz")).unwrap();
+    for text in ["column1,column2\na,b\n", "c,d\n"] {
+        let mut e = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        e.write_all(text.as_bytes()).unwrap();
+        f.write_all(&e.finish().unwrap()).unwrap();
+    }
+
+    let result = read(&root, "test.csv.gz").await;
+
+    assert_eq!(
+        result,
+        vec![
+            map_value!("column1" => &b"a"[..], "column2" => &b"b"[..]),
+            map_value!("column1" => &b"c"[..], "column2" => &b"d"[..]),
+        ]
     );
+}
+
+#[tokio::test]
+async fn read_zstd() {
+    let root = preapre_temp();
+
+    let text = "{\"id\":1}\n{\"id\":2}\n";
+    let compressed = zstd::encode_all(text.as_bytes(), 0).unwrap();
+    std::fs::write(root.path().join("test-1.jsonl.zst"), &compressed).unwrap();
+    // not chosen by the extension.
+    std::fs::write(root.path().join("test-2.data"), &compressed).unwrap();
 
-    let mut c = service
+    let by_extension = jsonl_config(&root, "test-1.jsonl.zst");
+    let by_config = jsonl_config(&root, "test-2.data").with_compression(Compression::Zstd);
+
+    for config in [by_extension, by_config] {
+        let frames = read_frames_with(config, toy_plugin_test::dummy_task_context()).await;
+        let values = frames
+            .into_iter()
+            .map(|x| x.into_value().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![map_value! { "id" => 1 }, map_value! { "id" => 2 }]
+        );
+    }
+}
+
+#[test]
+fn read_drained() {
+    let root = preapre_temp();
+    let total = 100_000u32;
+    let mut f = File::create(root.path().join("many.jsonl")).unwrap();
+    for i in 1..=total {
+        writeln!(f, r#"{{"id":{}}}"#, i).unwrap();
+    }
+
+    let graph = map_value! {
+        "name" => "read_drained",
+        "services" => seq_value![
+            map_value! {
+                "type" => "plugin.common.file.read",
+                "uri" => "source",
+                "config" => map_value! {
+                    "path" => path(&root, "many.jsonl"),
+                    "format" => "jsonl",
+                },
+                "wires" => "sink",
+            },
+            map_value! {
+                "type" => "plugin.common.file.write",
+                "uri" => "sink",
+                "wires" => Value::None,
+            }
+        ]
+    };
+    let r = Harness::new(toy_plugin_file::all(), graph)
+        .capture("sink")
+        .drain(Duration::ZERO)
+        .timeout(Duration::from_secs(30))
+        .run();
+    assert!(r.is_ok(), "{:?}", r.error());
+
+    // stopped between the batches, and the frames already sent are received.
+    let outputs = r.outputs("sink");
+    assert!(outputs.len() < total as usize, "{}", outputs.len());
+    let expected = (1..=outputs.len() as u32)
+        .map(|x| map_value! { "id" => x })
+        .collect::<Vec<_>>();
+    assert_eq!(outputs, expected);
+}
+
+async fn read(root: &TempDir, file_name: &str) -> Vec<Value> {
+    read_with(root, file_name, toy_plugin_test::dummy_task_context()).await
+}
+
+async fn read_with(root: &TempDir, file_name: &str, task_ctx: TaskContext) -> Vec<Value> {
+    read_frames(root, file_name, task_ctx)
+        .await
+        .into_iter()
+        .map(|x| x.into_value().unwrap())
+        .collect()
+}
+
+async fn read_frames(root: &TempDir, file_name: &str, task_ctx: TaskContext) -> Vec<Frame> {
+    let config = ReadConfig::with(path(root, file_name), ReadOption::common_csv());
+    read_frames_with(config, task_ctx).await
+}
+
+async fn read_frames_with(config: ReadConfig, task_ctx: TaskContext) -> Vec<Frame> {
+    let mut service = Read;
+    let (tx, mut rx) = toy_core::mpsc::channel(100);
+
+    let c = service
         .new_context(toy_plugin_test::dummy_service_type(), config)
         .await
         .unwrap();
+    let mut c = service.started(task_ctx.clone(), c).into();
 
     loop {
         let frame = Frame::default();
@@ -69,11 +339,24 @@ async fn read(root: &TempDir, file_name: &str) -> Vec<Value> {
 
     let mut result = vec![];
     while let Some(item) = rx.next().await {
-        result.push(item.value().unwrap().clone());
+        result.push(item);
     }
     result
 }
 
+fn jsonl_config(root: &TempDir, file_name: &str) -> ReadConfig {
+    ReadConfig::new(path(root, file_name)).with_format(FileFormat::Jsonl)
+}
+
+fn path(root: &TempDir, file_name: &str) -> String {
+    format!(
+        "{}{}{}",
+        root.path().display(),
+        std::path::MAIN_SEPARATOR,
+        file_name
+    )
+}
+
 fn preapre_temp() -> TempDir {
     let root = TempDir::new("toy-reader-test");
     let root = root.ok().expect("Should have created a temp directory");
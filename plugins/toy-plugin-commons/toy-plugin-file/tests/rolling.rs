@@ -0,0 +1,201 @@
+use flate2::read::MultiGzDecoder;
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tempdir::TempDir;
+use toy_core::prelude::*;
+use toy_plugin_file::config::{RollConfig, WriteConfig, WriteOption};
+use toy_plugin_file::rolling::{PathTemplate, RollingWriter};
+use toy_plugin_file::service::Write;
+
+fn config(root: &TempDir, path: &str) -> WriteConfig {
+    WriteConfig::with(Some(root.path().join(path)), WriteOption::common_csv())
+}
+
+fn read(root: &TempDir, path: &str) -> String {
+    std::fs::read_to_string(root.path().join(path)).unwrap()
+}
+
+fn read_gz(root: &TempDir, path: &str) -> String {
+    let f = std::fs::File::open(root.path().join(path)).unwrap();
+    let mut text = String::new();
+    MultiGzDecoder::new(f).read_to_string(&mut text).unwrap();
+    text
+}
+
+fn files(dir: &Path) -> Vec<String> {
+    let mut files = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|x| x.unwrap().file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+#[test]
+fn roll_by_rows_partitioned() {
+    let root = TempDir::new("toy-rolling-test").unwrap();
+    let config =
+        config(&root, "out/{region}.csv").with_roll(RollConfig::default().with_max_rows(2));
+    let mut w = RollingWriter::new(&config).unwrap();
+
+    let now = Instant::now();
+    for (region, v) in [("tokyo", 1), ("osaka", 2), ("tokyo", 3), ("tokyo", 4)] {
+        w.write_value(&map_value!("region" => region, "v" => v), now)
+            .unwrap();
+    }
+    // only the full file is renamed.
+    assert_eq!(
+        files(&root.path().join("out")),
+        vec![".osaka-0.csv.tmp", ".tokyo-1.csv.tmp", "tokyo-0.csv"]
+    );
+
+    w.finish().unwrap();
+    assert_eq!(
+        files(&root.path().join("out")),
+        vec!["osaka-0.csv", "tokyo-0.csv", "tokyo-1.csv"]
+    );
+    assert_eq!(
+        read(&root, "out/tokyo-0.csv"),
+        "region,v\r\ntokyo,1\r\ntokyo,3\r\n"
+    );
+    assert_eq!(read(&root, "out/tokyo-1.csv"), "region,v\r\ntokyo,4\r\n");
+    assert_eq!(read(&root, "out/osaka-0.csv"), "region,v\r\nosaka,2\r\n");
+}
+
+#[test]
+fn roll_by_interval_and_bytes() {
+    let root = TempDir::new("toy-rolling-test").unwrap();
+    let config = config(&root, "{index}.csv").with_roll(
+        RollConfig::default()
+            .with_interval_secs(60)
+            .with_max_bytes(20),
+    );
+    let mut w = RollingWriter::new(&config).unwrap();
+
+    let now = Instant::now();
+    w.write_value(&map_value!("v" => 1), now).unwrap();
+    w.write_value(&map_value!("v" => 2), now + Duration::from_secs(59))
+        .unwrap();
+    // interval
+    w.write_value(&map_value!("v" => 3), now + Duration::from_secs(60))
+        .unwrap();
+    w.write_value(
+        &map_value!("v" => "0123456789abcdef"),
+        now + Duration::from_secs(61),
+    )
+    .unwrap();
+    // bytes
+    w.write_value(&map_value!("v" => 5), now + Duration::from_secs(62))
+        .unwrap();
+    w.finish().unwrap();
+
+    assert_eq!(files(root.path()), vec!["0.csv", "1.csv", "2.csv"]);
+    assert_eq!(read(&root, "0.csv"), "v\r\n1\r\n2\r\n");
+    assert_eq!(read(&root, "1.csv"), "v\r\n3\r\n0123456789abcdef\r\n");
+    assert_eq!(read(&root, "2.csv"), "v\r\n5\r\n");
+}
+
+#[test]
+fn roll_not_overwrite_after_restart() {
+    let root = TempDir::new("toy-rolling-test").unwrap();
+    let config = config(&root, "out.csv").with_roll(RollConfig::default().with_max_rows(1));
+
+    for v in [1, 2] {
+        let mut w = RollingWriter::new(&config).unwrap();
+        w.write_value(&map_value!("v" => v), Instant::now())
+            .unwrap();
+        w.finish().unwrap();
+    }
+
+    assert_eq!(files(root.path()), vec!["out-0.csv", "out-1.csv"]);
+    assert_eq!(read(&root, "out-1.csv"), "v\r\n2\r\n");
+}
+
+#[test]
+fn reopen_partition_appended() {
+    let root = TempDir::new("toy-rolling-test").unwrap();
+    let config = config(&root, "out/{region}.csv.gz")
+        .with_roll(RollConfig::default().with_max_open_files(1));
+    let mut w = RollingWriter::new(&config).unwrap();
+
+    let now = Instant::now();
+    // the file of the other region is closed by max_open_files each time.
+    for (region, v) in [("tokyo", 1), ("osaka", 2), ("tokyo", 3), ("osaka", 4)] {
+        w.write_value(&map_value!("region" => region, "v" => v), now)
+            .unwrap();
+    }
+    w.finish().unwrap();
+
+    assert_eq!(
+        files(&root.path().join("out")),
+        vec!["osaka.csv.gz", "tokyo.csv.gz"]
+    );
+    assert_eq!(
+        read_gz(&root, "out/tokyo.csv.gz"),
+        "region,v\r\ntokyo,1\r\ntokyo,3\r\n"
+    );
+    assert_eq!(
+        read_gz(&root, "out/osaka.csv.gz"),
+        "region,v\r\nosaka,2\r\nosaka,4\r\n"
+    );
+}
+
+#[test]
+fn path_template() {
+    let t = PathTemplate::parse("out/{date}/{a.b}-{index}.csv").unwrap();
+    assert_eq!(
+        t.render(&map_value!("date" => "2021-01-02", "a" => map_value!("b" => 1))),
+        "out/2021-01-02/1-{index}.csv"
+    );
+    // the value never changes the directory.
+    assert_eq!(
+        t.render(&map_value!("date" => "..", "a" => "x")),
+        "out/_/unknown-{index}.csv"
+    );
+    assert_eq!(
+        t.render(&map_value!("date" => "../a/{index}")),
+        "out/.._a__index_/unknown-{index}.csv"
+    );
+
+    assert!(PathTemplate::parse("out/{date.csv").is_err());
+    assert!(PathTemplate::parse("out/}.csv").is_err());
+    assert!(PathTemplate::parse("out/{}.csv").is_err());
+}
+
+#[tokio::test]
+async fn write_partitioned() {
+    let root = TempDir::new("toy-rolling-test").unwrap();
+    let config = config(&root, "{date}/{region}.csv");
+    let data = vec![
+        map_value!("date" => "20210101", "region" => "tokyo", "v" => 1),
+        map_value!("date" => "20210102", "region" => "tokyo", "v" => 2),
+        map_value!("date" => "20210101", "region" => "tokyo", "v" => 3),
+    ];
+
+    let mut service = Write;
+    let (tx, _rx) = toy_core::mpsc::channel(100);
+    let task_ctx = toy_plugin_test::dummy_task_context();
+    let mut c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+    for v in data {
+        let r = service
+            .handle(task_ctx.clone(), c, Frame::from_value(v), tx.clone())
+            .await;
+        c = r.unwrap().into();
+    }
+    let r = service.upstream_finish_all(task_ctx, c, tx).await;
+    assert!(r.is_ok());
+
+    assert_eq!(files(root.path()), vec!["20210101", "20210102"]);
+    assert_eq!(
+        read(&root, "20210101/tokyo.csv"),
+        "date,region,v\r\n20210101,tokyo,1\r\n20210101,tokyo,3\r\n"
+    );
+    assert_eq!(
+        read(&root, "20210102/tokyo.csv"),
+        "date,region,v\r\n20210102,tokyo,2\r\n"
+    );
+}
@@ -0,0 +1,200 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use tempdir::TempDir;
+use toy_core::data::headers;
+use toy_core::prelude::*;
+use toy_plugin_file::config::{ReadOption, WatchConfig};
+use toy_plugin_file::service::{Watch, WatchContext};
+use toy_plugin_file::FileFormat;
+
+fn config(root: &TempDir, pattern: &str) -> WatchConfig {
+    WatchConfig::new(root.path().join(pattern).display().to_string())
+        .with_format(FileFormat::Jsonl)
+        .with_interval_millis(0)
+}
+
+fn append(path: &Path, text: &str) {
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .unwrap();
+    f.write_all(text.as_bytes()).unwrap();
+}
+
+async fn start(config: WatchConfig) -> WatchContext {
+    Watch
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap()
+}
+
+/// Scan the files once, and returns the sent values.
+async fn poll(ctx: WatchContext) -> (WatchContext, Vec<Value>) {
+    let (ctx, frames) = poll_frames(ctx).await;
+    let values = frames
+        .into_iter()
+        .map(|f| f.into_value().unwrap())
+        .collect();
+    (ctx, values)
+}
+
+/// Scan the files once, and returns the sent frames.
+async fn poll_frames(ctx: WatchContext) -> (WatchContext, Vec<Frame>) {
+    let (tx, mut rx) = toy_core::mpsc::channel(100);
+    let r = Watch
+        .handle(
+            toy_plugin_test::dummy_task_context(),
+            ctx,
+            Frame::default(),
+            tx,
+        )
+        .await
+        .unwrap();
+    let ctx = match r {
+        ServiceContext::Next(ctx) => ctx,
+        _ => panic!("watch must continue."),
+    };
+    let mut frames = Vec::new();
+    while let Some(f) = rx.next().await {
+        frames.push(f);
+    }
+    (ctx, frames)
+}
+
+fn lines(frames: &[Frame]) -> Vec<u64> {
+    frames
+        .iter()
+        .map(|f| {
+            f.header(headers::LINE)
+                .and_then(|x| x.parse_integer::<u64>())
+                .unwrap()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn watch_new_and_appended_files() {
+    let root = TempDir::new("toy-watch-test").unwrap();
+    let a = root.path().join("a.jsonl");
+    append(&a, "{\"v\":1}\n{\"v\":");
+
+    let ctx = start(config(&root, "*.jsonl")).await;
+    let (ctx, values) = poll(ctx).await;
+    // the incomplete line is read later.
+    assert_eq!(values, vec![map_value!("v" => 1)]);
+
+    append(&a, "2}\n");
+    append(
+        &root.path().join("b.jsonl"),
+        "{\"v\":3}\n\ninvalid\n{\"v\":4}\n",
+    );
+    append(&root.path().join("c.txt"), "{\"v\":5}\n");
+    let (ctx, values) = poll(ctx).await;
+    assert_eq!(
+        values,
+        vec![
+            map_value!("v" => 2),
+            map_value!("v" => 3),
+            map_value!("v" => 4)
+        ]
+    );
+
+    let (_, values) = poll(ctx).await;
+    assert!(values.is_empty());
+}
+
+#[tokio::test]
+async fn watch_rotated_and_truncated_file() {
+    let root = TempDir::new("toy-watch-test").unwrap();
+    let a = root.path().join("a.jsonl");
+    append(&a, "{\"v\":1}\n");
+
+    let ctx = start(config(&root, "*.jsonl")).await;
+    let (ctx, _) = poll(ctx).await;
+
+    // written before the rotation, the last line without the terminator.
+    append(&a, "{\"v\":2}\n{\"v\":3}");
+    fs::rename(&a, root.path().join("a.jsonl.1")).unwrap();
+    append(&a, "{\"v\":4}\n{\"v\":5}\n");
+    let (ctx, values) = poll(ctx).await;
+    assert_eq!(
+        values,
+        vec![
+            map_value!("v" => 2),
+            map_value!("v" => 3),
+            map_value!("v" => 4),
+            map_value!("v" => 5)
+        ]
+    );
+
+    fs::write(&a, "{\"v\":6}\n").unwrap();
+    let (_, values) = poll(ctx).await;
+    assert_eq!(values, vec![map_value!("v" => 6)]);
+}
+
+#[tokio::test]
+async fn watch_resume_from_state() {
+    let root = TempDir::new("toy-watch-test").unwrap();
+    let a = root.path().join("a.csv");
+    append(&a, "id,name\n1,aaa\n");
+    let config = config(&root, "*.csv")
+        .with_format(FileFormat::Text)
+        .with_option(ReadOption::common_csv())
+        .with_state_path(root.path().join("state/watch.json"));
+
+    let ctx = start(config.clone()).await;
+    let (ctx, values) = poll(ctx).await;
+    assert_eq!(
+        values,
+        vec![map_value!("id" => &b"1"[..], "name" => &b"aaa"[..])]
+    );
+    Watch.completed(toy_plugin_test::dummy_task_context(), ctx);
+
+    // the headers are read again after the restart, and the lines are counted from the beginning.
+    append(&a, "2,bbb\n");
+    let ctx = start(config).await;
+    let (_, frames) = poll_frames(ctx).await;
+    assert_eq!(lines(&frames), vec![3]);
+    assert_eq!(
+        frames[0].value(),
+        Some(&map_value!("id" => &b"2"[..], "name" => &b"bbb"[..]))
+    );
+}
+
+#[tokio::test]
+async fn watch_reopen_closed_files() {
+    let root = TempDir::new("toy-watch-test").unwrap();
+    let paths = ["a", "b", "c"].map(|x| root.path().join(format!("{}.jsonl", x)));
+    for (i, path) in paths.iter().enumerate() {
+        append(path, &format!("{{\"v\":{}}}\n", i));
+    }
+
+    // only one file is kept open, the others are closed after read to the end.
+    let ctx = start(config(&root, "*.jsonl").with_max_open_files(1)).await;
+    let (ctx, frames) = poll_frames(ctx).await;
+    assert_eq!(lines(&frames), vec![1, 1, 1]);
+
+    for (i, path) in paths.iter().enumerate() {
+        append(path, &format!("\n{{\"v\":{}}}\n", i + 10));
+    }
+    let (ctx, frames) = poll_frames(ctx).await;
+    let values = frames
+        .iter()
+        .map(|f| f.value().unwrap().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        vec![
+            map_value!("v" => 10),
+            map_value!("v" => 11),
+            map_value!("v" => 12)
+        ]
+    );
+    // the empty line is counted.
+    assert_eq!(lines(&frames), vec![3, 3, 3]);
+
+    let (_, values) = poll(ctx).await;
+    assert!(values.is_empty());
+}
@@ -1,5 +1,10 @@
-use toy_core::data::{Map, Value};
+use std::io::Read;
+use tempdir::TempDir;
+use toy_core::prelude::*;
+use toy_plugin_file::config::{WriteConfig, WriteOption};
+use toy_plugin_file::service::Write;
 use toy_plugin_file::{file_writer::FileWriter, file_writer_builder::FileWriterBuilder};
+use toy_plugin_file::{jsonl, Compression, FileFormat};
 
 #[test]
 fn write_value() {
@@ -26,6 +31,161 @@ fn write_value() {
     )
 }
 
+#[test]
+fn write_value_jsonl() {
+    let vec: Vec<u8> = Vec::new();
+    let mut w = FileWriterBuilder::default()
+        .format(FileFormat::Jsonl)
+        .from_writer(vec);
+
+    let ts = Value::from("2021-01-02T03:04:05+00:00")
+        .parse_timestamp()
+        .unwrap();
+    let mut map = Map::new();
+    map.insert("one".to_string(), Value::from(1u32));
+    map.insert("two".to_string(), Value::from(2.5));
+    map.insert("name".to_string(), Value::from("a\"b"));
+    map.insert(
+        "seq".to_string(),
+        Value::from(vec![Value::from(true), Value::None]),
+    );
+    map.insert("bytes".to_string(), Value::from(&b"ab"[..]));
+    map.insert("ts".to_string(), Value::from(ts));
+    let v = Value::from(map);
+
+    w.write_value(&v).unwrap();
+    w.write_value(&Value::from(3u32)).unwrap();
+    w.flush().unwrap();
+
+    let text = w_to_string(w);
+    assert_eq!(
+        text,
+        concat!(
+            r#"{"one":1,"two":2.5,"name":"a\"b","seq":[true,null],"#,
+            r#""bytes":{"$bytes":[97,98]},"ts":{"$timestamp":"2021-01-02T03:04:05+00:00"}}"#,
+            "\n3\n"
+        )
+    );
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(jsonl::decode(lines[0].as_bytes()).unwrap(), v);
+    assert_eq!(
+        jsonl::decode(lines[1].as_bytes()).unwrap(),
+        Value::from(3u32)
+    );
+}
+
+#[tokio::test]
+async fn write_gzip() {
+    let root = TempDir::new("toy-writer-test").unwrap();
+    let path = root.path().join("out.csv.gz");
+    let config = WriteConfig::with(Some(path.clone()), WriteOption::common_csv());
+
+    write(
+        config,
+        vec![
+            map_value!("a" => 1, "b" => 2),
+            map_value!("a" => 3, "b" => 4),
+        ],
+    )
+    .await;
+
+    let mut text = String::new();
+    flate2::read::MultiGzDecoder::new(std::fs::File::open(&path).unwrap())
+        .read_to_string(&mut text)
+        .unwrap();
+    assert_eq!(text, "a,b\r\n1,2\r\n3,4\r\n");
+}
+
+#[tokio::test]
+async fn write_zstd_jsonl() {
+    let root = TempDir::new("toy-writer-test").unwrap();
+    let path = root.path().join("out.data");
+    let config = WriteConfig::with(Some(path.clone()), WriteOption::default())
+        .with_format(FileFormat::Jsonl)
+        .with_compression(Compression::Zstd, Some(19));
+
+    write(config, vec![map_value!("a" => 1), map_value!("a" => 2)]).await;
+
+    let text = zstd::decode_all(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(text, b"{\"a\":1}\n{\"a\":2}\n");
+}
+
+#[tokio::test]
+async fn write_invalid_compression_level() {
+    let root = TempDir::new("toy-writer-test").unwrap();
+    for (compression, level) in [(Compression::Gzip, 10), (Compression::Zstd, 100)] {
+        let config = WriteConfig::with(Some(root.path().join("out")), WriteOption::default())
+            .with_compression(compression, Some(level));
+        let r = Write
+            .new_context(toy_plugin_test::dummy_service_type(), config)
+            .await;
+        assert!(r.is_err());
+    }
+}
+
+#[tokio::test]
+async fn write_error_of_record() {
+    let root = TempDir::new("toy-writer-test").unwrap();
+    // the directory of the second record can not be created.
+    std::fs::write(root.path().join("b"), "").unwrap();
+    let config = WriteConfig::with(
+        Some(root.path().join("{dir}/out.csv")),
+        WriteOption::common_csv(),
+    );
+
+    let mut service = Write;
+    let (tx, _rx) = toy_core::mpsc::channel(100);
+    let task_ctx = toy_plugin_test::dummy_task_context();
+    let mut c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+    for (dir, failed) in [("a", false), ("b", true), ("a", false)] {
+        let r = service
+            .handle(
+                task_ctx.clone(),
+                c,
+                Frame::from_value(map_value!("dir" => dir)),
+                tx.clone(),
+            )
+            .await;
+        // the error is returned by the record failed, and the context is kept.
+        c = match r {
+            Ok(ServiceContext::Failed(c, _)) if failed => c,
+            Ok(ServiceContext::Ready(c)) if !failed => c,
+            _ => panic!("unexpected result of the record in {}.", dir),
+        };
+    }
+    let r = service.upstream_finish_all(task_ctx, c, tx).await;
+    assert!(r.is_ok());
+
+    let mut text = String::new();
+    std::fs::File::open(root.path().join("a/out.csv"))
+        .unwrap()
+        .read_to_string(&mut text)
+        .unwrap();
+    assert_eq!(text, "dir\r\na\r\na\r\n");
+}
+
+async fn write(config: WriteConfig, data: Vec<Value>) {
+    let mut service = Write;
+    let (tx, _rx) = toy_core::mpsc::channel(100);
+    let task_ctx = toy_plugin_test::dummy_task_context();
+
+    let mut c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+    for v in data {
+        let r = service
+            .handle(task_ctx.clone(), c, Frame::from_value(v), tx.clone())
+            .await;
+        c = r.unwrap().into();
+    }
+    let r = service.upstream_finish_all(task_ctx, c, tx).await;
+    assert!(r.is_ok());
+}
+
 fn w_to_string(w: FileWriter<Vec<u8>>) -> String {
     String::from_utf8(w.into_inner().unwrap()).unwrap()
 }
@@ -0,0 +1,135 @@
+use crate::predicate::{Predicate, PredicateMatcher};
+use serde::{Deserialize, Serialize};
+use toy_core::data::Value;
+use toy_core::error::ServiceError;
+use toy_pack::schema::{EnumVisitor, PrimitiveTypes, Schema, SchemaVisitor, StructVisitor};
+use tracing::Span;
+
+/// Boolean composition of predicates.
+///
+/// A string is a single predicate, and a map of `all`, `any` or `not` is a group.
+///
+/// ```yaml
+/// any:
+///   - "status in [paid, shipped]"
+///   - all:
+///       - "amount between 100..1000"
+///       - not: "coupon exists"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Condition {
+    Predicate(Predicate),
+    Group(Group),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Group {
+    /// matches if all conditions match, or no condition.
+    All(Vec<Condition>),
+    /// matches if any of conditions matches.
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    pub fn all(conditions: Vec<Condition>) -> Condition {
+        Condition::Group(Group::All(conditions))
+    }
+
+    pub fn any(conditions: Vec<Condition>) -> Condition {
+        Condition::Group(Group::Any(conditions))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(condition: Condition) -> Condition {
+        Condition::Group(Group::Not(Box::new(condition)))
+    }
+
+    pub fn compile(&self) -> Result<Matcher, ServiceError> {
+        let compile_all = |x: &[Condition]| x.iter().map(|c| c.compile()).collect::<Result<_, _>>();
+        Ok(match self {
+            Condition::Predicate(p) => Matcher::Predicate(p.compile()?),
+            Condition::Group(Group::All(x)) => Matcher::All(compile_all(x)?),
+            Condition::Group(Group::Any(x)) => Matcher::Any(compile_all(x)?),
+            Condition::Group(Group::Not(x)) => Matcher::Not(Box::new(x.compile()?)),
+        })
+    }
+}
+
+impl From<Predicate> for Condition {
+    fn from(p: Predicate) -> Self {
+        Condition::Predicate(p)
+    }
+}
+
+/// A predicate string, or a map of `all`, `any` or `not`.
+///
+/// The schema can not be recursive, so the nested conditions are described only as a string or a map.
+impl Schema for Condition {
+    fn scan<V>(name: &str, mut visitor: V) -> Result<V::Value, V::Error>
+    where
+        V: SchemaVisitor,
+    {
+        let mut e = visitor.enum_visitor(name, "Condition")?;
+        e.variant(
+            name,
+            "Predicate",
+            visitor.visit(name, PrimitiveTypes::String)?,
+        )?;
+
+        let mut all = visitor.struct_visitor(name)?;
+        all.field::<Vec<Nested>>("all")?;
+        e.variant(name, "All", all.end()?)?;
+
+        let mut any = visitor.struct_visitor(name)?;
+        any.field::<Vec<Nested>>("any")?;
+        e.variant(name, "Any", any.end()?)?;
+
+        let mut not = visitor.struct_visitor(name)?;
+        not.field::<Nested>("not")?;
+        e.variant(name, "Not", not.end()?)?;
+
+        e.end()
+    }
+}
+
+/// Schema of the nested condition, a string or any map.
+struct Nested;
+
+impl Schema for Nested {
+    fn scan<V>(name: &str, mut visitor: V) -> Result<V::Value, V::Error>
+    where
+        V: SchemaVisitor,
+    {
+        let mut e = visitor.enum_visitor(name, "Condition")?;
+        e.variant(
+            name,
+            "Predicate",
+            visitor.visit(name, PrimitiveTypes::String)?,
+        )?;
+        e.variant(name, "Group", visitor.struct_visitor(name)?.end()?)?;
+        e.end()
+    }
+}
+
+/// Compiled [`Condition`].
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    All(Vec<Matcher>),
+    Any(Vec<Matcher>),
+    Not(Box<Matcher>),
+    Predicate(PredicateMatcher),
+}
+
+impl Matcher {
+    pub fn is_match(&self, v: &Value, span: &Span) -> bool {
+        match self {
+            Matcher::All(x) => x.iter().all(|m| m.is_match(v, span)),
+            Matcher::Any(x) => x.iter().any(|m| m.is_match(v, span)),
+            Matcher::Not(x) => !x.is_match(v, span),
+            Matcher::Predicate(p) => p.is_match(v, span),
+        }
+    }
+}
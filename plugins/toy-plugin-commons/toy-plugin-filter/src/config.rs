@@ -1,20 +1,53 @@
 use serde::{Deserialize, Serialize};
 use toy_pack::Schema;
+use crate::condition::Condition;
 use crate::predicate::Predicate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Schema)]
 pub struct FilterConfig {
+    #[serde(default)]
     preds: Vec<Predicate>,
+    /// Nested `all`/`any`/`not` groups of predicates, combined with `preds` by "and".
+    #[serde(default)]
+    condition: Option<Condition>,
+    /// Expression evaluated with the value of frame, matches if it is neither `false` nor `null`.
+    /// Compiled when the context is initialized, and combined with `preds` by "and".
+    #[serde(default)]
+    when: Option<String>,
 }
 
 impl FilterConfig {
     pub fn with(preds: &[Predicate]) -> FilterConfig {
         FilterConfig {
-            preds: preds.to_vec()
+            preds: preds.to_vec(),
+            condition: None,
+            when: None,
+        }
+    }
+
+    pub fn with_condition(self, condition: Condition) -> FilterConfig {
+        FilterConfig {
+            condition: Some(condition),
+            ..self
+        }
+    }
+
+    pub fn with_when(self, when: &str) -> FilterConfig {
+        FilterConfig {
+            when: Some(when.to_string()),
+            ..self
         }
     }
 
     pub fn preds(&self) -> &[Predicate] {
         &self.preds
     }
+
+    pub fn condition(&self) -> Option<&Condition> {
+        self.condition.as_ref()
+    }
+
+    pub fn when(&self) -> Option<&str> {
+        self.when.as_deref()
+    }
 }
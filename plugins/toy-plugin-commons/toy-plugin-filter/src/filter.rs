@@ -1,16 +1,19 @@
 use std::future::Future;
-use toy_core::data::{Frame, Value};
+use toy_core::data::expr::Expr;
+use toy_core::data::Frame;
 use toy_core::error::ServiceError;
 use toy_core::mpsc::Outgoing;
 use toy_core::prelude::{PortType, Service, ServiceContext, ServiceFactory, TaskContext};
 use toy_core::ServiceType;
+use crate::condition::{Condition, Matcher};
 use crate::config::FilterConfig;
 
 #[derive(Clone, Debug)]
 pub struct Filter;
 
 pub struct FilterContext {
-    config: FilterConfig,
+    matcher: Matcher,
+    when: Option<Expr>,
 }
 
 impl Service for Filter {
@@ -27,22 +30,24 @@ impl Service for Filter {
         PortType::fan_out_flow(2)
     }
 
-    fn handle(&mut self, task_ctx: TaskContext, ctx: Self::Context, req: Self::Request, mut tx: Outgoing<Self::Request>) -> Self::Future {
+    fn handle(
+        &mut self,
+        task_ctx: TaskContext,
+        ctx: Self::Context,
+        req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
         async move {
             let span = task_ctx.span();
 
-            if req.value().is_some() {
-                let all_match = ctx.config.preds().iter().all(|x| {
-                    let candidate = match req.value().unwrap() {
-                        v @ Value::Map(_) | v @ Value::Seq(_) => {
-                            v.path(x.field())
-                        }
-                        other => Some(other),
-                    };
-                    let r = candidate.map(|v| x.is_match(v));
-                    tracing::debug!(parent: span, predicate = %x, ?candidate, matched = r);
-                    r.unwrap_or(false)
-                });
+            if let Some(v) = req.value() {
+                let all_match = ctx.matcher.is_match(v, span);
+                let all_match = all_match
+                    && ctx.when.as_ref().is_none_or(|x| {
+                        let r = x.is_match(v);
+                        tracing::debug!(parent: span, when = %x, matched = r);
+                        r
+                    });
 
                 if all_match {
                     tx.send_ok_to(0, req).await?
@@ -80,6 +85,28 @@ impl ServiceFactory for Filter {
     }
 
     fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
-        async move { Ok(FilterContext { config }) }
+        async move {
+            let mut conditions = config
+                .preds()
+                .iter()
+                .map(|x| Condition::from(x.clone()))
+                .collect::<Vec<_>>();
+            conditions.extend(config.condition().cloned());
+            let matcher = Condition::all(conditions).compile()?;
+            let when = match config.when() {
+                Some(x) => {
+                    let expr = Expr::compile(x).map_err(|e| {
+                        ServiceError::error(format!("invalid when expression. {}", e))
+                    })?;
+                    Some(expr)
+                }
+                None => None,
+            };
+            Ok(FilterContext { matcher, when })
+        }
     }
-}
\ No newline at end of file
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
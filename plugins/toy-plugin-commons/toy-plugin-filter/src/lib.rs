@@ -5,6 +5,7 @@
 
 mod plugin;
 mod filter;
+pub mod condition;
 pub mod config;
 pub mod predicate;
 
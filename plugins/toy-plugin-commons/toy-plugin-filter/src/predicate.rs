@@ -2,10 +2,14 @@ use regex::Regex;
 use serde::de::{Error, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use toy_core::data::Value;
+use toy_core::error::ServiceError;
+use toy_pack::schema::{Schema, SchemaVisitor};
 use toy_pack::Schema;
+use tracing::Span;
 
-#[derive(Debug, Clone, Eq, PartialEq, Schema)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Predicate {
     field: String,
     op: Operator,
@@ -30,10 +34,45 @@ pub enum Operator {
     Match,
     /// Regex unmatch. supported by string only.
     Unmatch,
+    /// candidate is one of the comma separated predicate values, e.g. `[a, b, c]`.
+    In,
+    /// candidate is none of the comma separated predicate values.
+    NotIn,
+    /// field exists, even if the value is null. no predicate value.
+    Exists,
+    /// field does not exist, or the value is null. no predicate value.
+    IsNull,
+    /// string contains predicate value, or seq contains the element.
+    Contains,
+    /// string starts with predicate value.
+    StartsWith,
+    /// string ends with predicate value.
+    EndsWith,
+    /// candidate is in the inclusive range `min..max`, either side can be omitted.
+    Between,
 }
 
 pub const fn operator_strings() -> &'static [&'static str] {
-    &["!==", "!=", ">=", ">", "<=", "<", "==", "=~", "!~", "="]
+    &[
+        "!==",
+        "!=",
+        ">=",
+        ">",
+        "<=",
+        "<",
+        "==",
+        "=~",
+        "!~",
+        "=",
+        "not in",
+        "in",
+        "exists",
+        "is null",
+        "contains",
+        "starts_with",
+        "ends_with",
+        "between",
+    ]
 }
 
 impl Predicate {
@@ -57,77 +96,221 @@ impl Predicate {
         &self.value
     }
 
+    /// Prefer [`Predicate::compile`] to match many values, the regex is compiled on each call.
     pub fn is_match(&self, candidate: &Value) -> bool {
-        let l_opt = Value::from(&self.value).as_same_type(candidate);
-        let r = candidate;
+        self.compile()
+            .map(|x| x.is_match_candidate(Some(candidate)))
+            .unwrap_or(false)
+    }
 
-        if l_opt.is_none() {
-            return false;
-        }
+    /// Prepare the predicate value, the regex of `=~` and `!~` is compiled only once.
+    pub fn compile(&self) -> Result<PredicateMatcher, ServiceError> {
+        let prepared = match self.op {
+            Operator::Match | Operator::Unmatch => {
+                Prepared::Regex(Regex::new(&self.value).map_err(|e| {
+                    ServiceError::error(format!("invalid predicate {}. {}", self, e))
+                })?)
+            }
+            Operator::In | Operator::NotIn => {
+                let list = self
+                    .value
+                    .trim()
+                    .trim_start_matches(['[', '('])
+                    .trim_end_matches([']', ')'])
+                    .split(',')
+                    .map(|x| x.trim())
+                    .filter(|x| !x.is_empty())
+                    .map(Value::from)
+                    .collect();
+                Prepared::List(list)
+            }
+            Operator::Between => match self.value.split_once("..") {
+                Some((min, max)) => {
+                    let bound = |x: &str| match x.trim() {
+                        "" => None,
+                        x => Some(Value::from(x)),
+                    };
+                    Prepared::Range(bound(min), bound(max))
+                }
+                None => {
+                    return Err(ServiceError::error(format!(
+                        "invalid predicate {}. range must be \"min..max\".",
+                        self
+                    )))
+                }
+            },
+            _ => Prepared::Value(Value::from(&self.value)),
+        };
+        Ok(PredicateMatcher {
+            predicate: self.clone(),
+            prepared,
+        })
+    }
+}
 
-        let l = &l_opt.unwrap();
-
-        match self.op {
-            Operator::Eq => l == r,
-            Operator::NotEq => l != r,
-            Operator::LessThan => l > r,
-            Operator::LessThanOrEqual => l >= r,
-            Operator::GreaterThan => l < r,
-            Operator::GreaterThanOrEqual => l <= r,
-            Operator::Match => {
-                if l.is_string() && r.is_string() {
-                    if let Ok(reg) = Regex::new(l.as_str().unwrap()) {
-                        reg.is_match(r.as_str().unwrap())
-                    } else {
-                        false
+/// Longer one first, to match `>=` before `>`.
+const SYMBOL_OPERATORS: &[&str] = &["!==", "!=", ">=", ">", "<=", "<", "==", "=~", "!~", "="];
+
+/// `not in` before `in`.
+const WORD_OPERATORS: &[&str] = &[
+    "not in",
+    "in",
+    "exists",
+    "is null",
+    "is_null",
+    "contains",
+    "starts_with",
+    "ends_with",
+    "between",
+];
+
+impl FromStr for Predicate {
+    type Err = ServiceError;
+
+    /// Parse `field op value`, the first operator found from the left splits the field and the value.
+    /// Word operators (e.g. `in`, `exists`) must be separated by whitespaces.
+    fn from_str(v: &str) -> Result<Predicate, ServiceError> {
+        let invalid =
+            |reason: &str| ServiceError::error(format!("invalid predicate \"{}\". {}", v, reason));
+        let v = v.trim();
+        for (i, _) in v.char_indices() {
+            let rest = &v[i..];
+            let found = SYMBOL_OPERATORS
+                .iter()
+                .find(|x| rest.starts_with(**x))
+                .or_else(|| {
+                    if i == 0 || !v[..i].ends_with(char::is_whitespace) {
+                        return None;
                     }
-                } else {
-                    false
+                    WORD_OPERATORS.iter().find(|x| {
+                        rest.strip_prefix(**x)
+                            .is_some_and(|r| r.is_empty() || r.starts_with(char::is_whitespace))
+                    })
+                });
+            if let Some(t) = found {
+                let field = v[..i].trim();
+                let op = Operator::try_from(t).map_err(|_| invalid("unknown operator."))?;
+                let value = rest[t.len()..].trim();
+                if field.is_empty() {
+                    return Err(invalid("field is empty."));
                 }
-            }
-            Operator::Unmatch => {
-                if l.is_string() && r.is_string() {
-                    if let Ok(reg) = Regex::new(l.as_str().unwrap()) {
-                        !reg.is_match(r.as_str().unwrap())
-                    } else {
-                        false
-                    }
-                } else {
-                    false
+                if op.is_unary() && !value.is_empty() {
+                    return Err(invalid(&format!("\"{}\" takes no value.", op)));
                 }
+                if !op.is_unary() && value.is_empty() {
+                    return Err(invalid(&format!("\"{}\" requires a value.", op)));
+                }
+                return Ok(Predicate {
+                    field: field.to_string(),
+                    op,
+                    value: value.to_string(),
+                });
             }
         }
+        Err(invalid("operator not found."))
     }
+}
 
-    pub fn from_str(v: &str) -> Result<Predicate, ()> {
-        let mut left: Option<&str> = None;
-        let mut op: Result<Operator, ()> = Err(());
-        let mut right: Option<&str> = None;
-
-        for t in operator_strings() {
-            if v.contains(t) {
-                let vec: Vec<&str> = v.split(t).collect();
-                left = vec.get(0).map(|x| x.trim());
-                op = Operator::try_from(t);
-                right = vec.get(1).map(|x| x.trim());
-                break;
-            }
-        }
+/// The predicate value prepared to match.
+#[derive(Debug, Clone)]
+enum Prepared {
+    Value(Value),
+    Regex(Regex),
+    List(Vec<Value>),
+    Range(Option<Value>, Option<Value>),
+}
 
-        match (left, op, right) {
-            (Some(l), Ok(o), Some(r)) => Ok(Predicate {
-                field: l.to_string(),
-                op: o,
-                value: r.to_string(),
-            }),
-            _ => Err(()),
+/// Compiled [`Predicate`].
+#[derive(Debug, Clone)]
+pub struct PredicateMatcher {
+    predicate: Predicate,
+    prepared: Prepared,
+}
+
+impl PredicateMatcher {
+    pub fn predicate(&self) -> &Predicate {
+        &self.predicate
+    }
+
+    /// Match the field of the value, or the value itself if it is neither map nor seq.
+    pub fn is_match(&self, v: &Value, span: &Span) -> bool {
+        let candidate = match v {
+            Value::Map(_) | Value::Seq(_) => v.path(self.predicate.field()),
+            other => Some(other),
+        };
+        let r = self.is_match_candidate(candidate);
+        tracing::debug!(parent: span, predicate = %self.predicate, ?candidate, matched = r);
+        r
+    }
+
+    fn is_match_candidate(&self, candidate: Option<&Value>) -> bool {
+        match self.predicate.op {
+            Operator::Exists => return candidate.is_some(),
+            Operator::IsNull => return candidate.is_none_or(|x| x == &Value::None),
+            _ => {}
+        }
+        let r = match candidate {
+            Some(r) => r,
+            None => return false,
+        };
+        let same_type = |x: &Value| x.as_same_type(r);
+
+        match (&self.prepared, self.predicate.op) {
+            (Prepared::Regex(reg), op) => match r.as_str() {
+                Some(s) if op == Operator::Match => reg.is_match(s),
+                Some(s) => !reg.is_match(s),
+                None => false,
+            },
+            (Prepared::List(list), op) => {
+                let found = list.iter().any(|x| same_type(x).as_ref() == Some(r));
+                found == (op == Operator::In)
+            }
+            (Prepared::Range(min, max), _) => {
+                let ge_min = min.as_ref().map(|x| same_type(x).is_some_and(|x| r >= &x));
+                let le_max = max.as_ref().map(|x| same_type(x).is_some_and(|x| r <= &x));
+                ge_min.unwrap_or(true) && le_max.unwrap_or(true)
+            }
+            (Prepared::Value(v), Operator::Contains) => match (r, v.as_str()) {
+                (Value::String(s), Some(x)) => s.contains(x),
+                (Value::Seq(vec), _) => vec.iter().any(|e| v.as_same_type(e).as_ref() == Some(e)),
+                _ => false,
+            },
+            (Prepared::Value(v), Operator::StartsWith) => match (r.as_str(), v.as_str()) {
+                (Some(s), Some(x)) => s.starts_with(x),
+                _ => false,
+            },
+            (Prepared::Value(v), Operator::EndsWith) => match (r.as_str(), v.as_str()) {
+                (Some(s), Some(x)) => s.ends_with(x),
+                _ => false,
+            },
+            (Prepared::Value(v), op) => {
+                let l = match same_type(v) {
+                    Some(l) => l,
+                    None => return false,
+                };
+                match op {
+                    Operator::Eq => &l == r,
+                    Operator::NotEq => &l != r,
+                    Operator::LessThan => &l > r,
+                    Operator::LessThanOrEqual => &l >= r,
+                    Operator::GreaterThan => &l < r,
+                    Operator::GreaterThanOrEqual => &l <= r,
+                    _ => false,
+                }
+            }
         }
     }
 }
 
 impl Display for Predicate {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}{}{}", self.field, self.op, self.value)
+        if self.op.is_unary() {
+            write!(f, "{} {}", self.field, self.op)
+        } else if self.op.is_word() {
+            write!(f, "{} {} {}", self.field, self.op, self.value)
+        } else {
+            write!(f, "{}{}{}", self.field, self.op, self.value)
+        }
     }
 }
 
@@ -140,6 +323,16 @@ impl Serialize for Predicate {
     }
 }
 
+/// Predicate is written as a string, e.g. `"qtr > 1"`.
+impl Schema for Predicate {
+    fn scan<V>(name: &str, visitor: V) -> Result<V::Value, V::Error>
+    where
+        V: SchemaVisitor,
+    {
+        String::scan(name, visitor)
+    }
+}
+
 impl<'de> Deserialize<'de> for Predicate {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -162,10 +355,7 @@ impl<'de> Visitor<'de> for PredicateVisitor {
     where
         E: Error,
     {
-        match Predicate::from_str(v) {
-            Ok(p) => Ok(p),
-            Err(()) => Err(E::custom(format!("invalid predicate: {}", v))),
-        }
+        v.parse::<Predicate>().map_err(E::custom)
     }
 }
 
@@ -182,6 +372,14 @@ impl TryFrom<&str> for Operator {
             "<=" => Ok(Operator::LessThanOrEqual),
             "=~" => Ok(Operator::Match),
             "!~" => Ok(Operator::Unmatch),
+            "in" => Ok(Operator::In),
+            "not in" => Ok(Operator::NotIn),
+            "exists" => Ok(Operator::Exists),
+            "is null" | "is_null" => Ok(Operator::IsNull),
+            "contains" => Ok(Operator::Contains),
+            "starts_with" => Ok(Operator::StartsWith),
+            "ends_with" => Ok(Operator::EndsWith),
+            "between" => Ok(Operator::Between),
             _ => Err(()),
         }
     }
@@ -206,8 +404,26 @@ impl Operator {
             Operator::LessThanOrEqual => "<=",
             Operator::Match => "=~",
             Operator::Unmatch => "!~",
+            Operator::In => "in",
+            Operator::NotIn => "not in",
+            Operator::Exists => "exists",
+            Operator::IsNull => "is null",
+            Operator::Contains => "contains",
+            Operator::StartsWith => "starts_with",
+            Operator::EndsWith => "ends_with",
+            Operator::Between => "between",
         }
     }
+
+    /// Operator without predicate value.
+    pub fn is_unary(&self) -> bool {
+        matches!(self, Operator::Exists | Operator::IsNull)
+    }
+
+    /// Operator written in words, separated from the field and the value by whitespaces.
+    pub fn is_word(&self) -> bool {
+        self.as_str().starts_with(char::is_alphabetic)
+    }
 }
 
 impl Display for Operator {
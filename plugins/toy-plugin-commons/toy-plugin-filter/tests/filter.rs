@@ -1,4 +1,6 @@
+use std::str::FromStr;
 use toy_core::prelude::*;
+use toy_plugin_filter::condition::Condition;
 use toy_plugin_filter::config::{FilterConfig};
 use toy_plugin_filter::predicate::{Operator, Predicate};
 use toy_plugin_filter::service::Filter;
@@ -19,7 +21,7 @@ async fn filter() {
         Predicate::new("b", Operator::LessThanOrEqual, "7"),
     ];
 
-    let r = go(data, preds).await;
+    let r = go(data, FilterConfig::with(&preds)).await;
 
     {
         let map = r.get(0).unwrap().as_map().unwrap();
@@ -31,13 +33,125 @@ async fn filter() {
     }
 }
 
-async fn go(data: Vec<Value>, preds: Vec<Predicate>) -> Vec<Value> {
+#[tokio::test]
+async fn filter_when() {
+    let data = vec![
+        map_value!("a" => 1, "b" => "x"),
+        map_value!("a" => 5, "b" => "X"),
+        map_value!("a" => 7),
+    ];
+
+    let config = FilterConfig::with(&[]).with_when("a > 2 && lower(b ?? '') == 'x'");
+    let r = go(data, config).await;
+
+    assert_eq!(r, vec![map_value!("a" => 5, "b" => "X")]);
+}
+
+#[tokio::test]
+async fn filter_when_invalid() {
+    let config = FilterConfig::with(&[]).with_when("a >");
+    let r = Filter
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await;
+
+    assert!(r.is_err());
+}
+
+#[tokio::test]
+async fn filter_condition() {
+    let data = vec![
+        map_value!("status" => "paid", "amount" => 500),
+        map_value!("status" => "paid", "amount" => 500, "coupon" => "x"),
+        map_value!("status" => "shipped", "amount" => 5),
+        map_value!("status" => "canceled", "amount" => 500),
+    ];
+
+    let p = |s: &str| Condition::from(Predicate::from_str(s).unwrap());
+    let condition = Condition::all(vec![
+        p("status in [paid, shipped]"),
+        Condition::any(vec![
+            p("amount between 100..1000"),
+            p("status == shipped"),
+        ]),
+        Condition::not(p("coupon exists")),
+    ]);
+    let config = FilterConfig::with(&[]).with_condition(condition);
+    let (matched, unmatched) = go_ports(data, config).await;
+
+    assert_eq!(
+        matched,
+        vec![
+            map_value!("status" => "paid", "amount" => 500),
+            map_value!("status" => "shipped", "amount" => 5),
+        ]
+    );
+    assert_eq!(unmatched.len(), 2);
+}
+
+#[test]
+fn filter_condition_config() {
+    let v = map_value! {
+        "preds" => seq_value!["a > 1"],
+        "condition" => map_value! {
+            "any" => seq_value![
+                "b == x",
+                map_value! { "not" => "c exists" }
+            ],
+        },
+    };
+    let config = toy_core::data::unpack::<FilterConfig>(&v).unwrap();
+
+    let p = |s: &str| Condition::from(Predicate::from_str(s).unwrap());
+    assert_eq!(
+        config.condition(),
+        Some(&Condition::any(vec![
+            p("b == x"),
+            Condition::not(p("c exists")),
+        ]))
+    );
+}
+
+async fn go_ports(data: Vec<Value>, config: FilterConfig) -> (Vec<Value>, Vec<Value>) {
+    let mut service = Filter;
+
+    let (tx0, mut rx0) = toy_core::mpsc::channel(10);
+    let (tx1, mut rx1) = toy_core::mpsc::channel(10);
+    let mut tx = Outgoing::empty();
+    tx.merge(tx0);
+    tx.merge(tx1);
+    let task_ctx = toy_plugin_test::dummy_task_context();
+
+    let mut c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+
+    for v in data {
+        let r = service
+            .handle(task_ctx.clone(), c, Frame::from_value(v), tx.clone())
+            .await;
+        assert!(r.is_ok());
+        c = r.unwrap().into();
+    }
+    drop(tx);
+
+    let mut matched = vec![];
+    while let Some(item) = rx0.next().await {
+        matched.push(item.into_value().unwrap());
+    }
+    let mut unmatched = vec![];
+    while let Some(item) = rx1.next().await {
+        unmatched.push(item.into_value().unwrap());
+    }
+    (matched, unmatched)
+}
+
+async fn go(data: Vec<Value>, config: FilterConfig) -> Vec<Value> {
     let mut service = Filter;
 
     let (tx, mut rx) = toy_core::mpsc::channel(10);
     let task_ctx = toy_plugin_test::dummy_task_context();
 
-    let config = FilterConfig::with(&preds);
     let mut c = service
         .new_context(toy_plugin_test::dummy_service_type(), config)
         .await
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use toy_core::data::Value;
+use toy_core::prelude::*;
 use toy_plugin_filter::predicate::{Operator, Predicate};
 
 #[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
@@ -38,3 +40,84 @@ fn predicate_match_no_str() {
     let r = p.is_match(&cp);
     assert_eq!(r, false);
 }
+
+#[test]
+fn predicate_in() {
+    let p = Predicate::from_str("status in [paid, shipped]").unwrap();
+    assert_eq!(p.op(), Operator::In);
+    assert!(p.is_match(&Value::from("paid")));
+    assert!(!p.is_match(&Value::from("canceled")));
+
+    let p = Predicate::from_str("code not in 1,2").unwrap();
+    assert!(p.is_match(&Value::from(3)));
+    assert!(!p.is_match(&Value::from(2)));
+}
+
+#[test]
+fn predicate_string() {
+    let p = Predicate::from_str("name contains oy").unwrap();
+    assert!(p.is_match(&Value::from("toy")));
+    let p = Predicate::from_str("tags contains b").unwrap();
+    assert!(p.is_match(&Value::from(vec![Value::from("a"), Value::from("b")])));
+    let p = Predicate::from_str("name starts_with to").unwrap();
+    assert!(p.is_match(&Value::from("toy")));
+    let p = Predicate::from_str("name ends_with to").unwrap();
+    assert!(!p.is_match(&Value::from("toy")));
+}
+
+#[test]
+fn predicate_between() {
+    let p = Predicate::from_str("amount between 10..20").unwrap();
+    assert!(p.is_match(&Value::from(10)));
+    assert!(p.is_match(&Value::from(20)));
+    assert!(!p.is_match(&Value::from(21)));
+    assert!(p.is_match(&Value::from(15.5)));
+
+    let p = Predicate::from_str("amount between ..0").unwrap();
+    assert!(p.is_match(&Value::from(-1)));
+    assert!(!p.is_match(&Value::from(1)));
+
+    let p = Predicate::new("amount", Operator::Between, "10");
+    assert!(p.compile().is_err());
+}
+
+#[test]
+fn predicate_exists() {
+    let v = map_value! { "a" => 1, "b" => Value::None };
+    let span = tracing::Span::none();
+
+    let exists = Predicate::from_str("b exists").unwrap().compile().unwrap();
+    assert!(exists.is_match(&v, &span));
+    let exists = Predicate::from_str("c exists").unwrap().compile().unwrap();
+    assert!(!exists.is_match(&v, &span));
+
+    let is_null = Predicate::from_str("b is null").unwrap().compile().unwrap();
+    assert!(is_null.is_match(&v, &span));
+    let is_null = Predicate::from_str("c is_null").unwrap().compile().unwrap();
+    assert!(is_null.is_match(&v, &span));
+    let is_null = Predicate::from_str("a is null").unwrap().compile().unwrap();
+    assert!(!is_null.is_match(&v, &span));
+}
+
+#[test]
+fn predicate_from_str() {
+    for s in [
+        "a==1",
+        "a>=1",
+        "a=~^x",
+        "index in [a, b]",
+        "a not in [x]",
+        "a exists",
+        "a between 1..2",
+    ] {
+        let p = Predicate::from_str(s).unwrap();
+        assert_eq!(p.to_string(), s);
+    }
+    let p = Predicate::from_str("index == 1").unwrap();
+    assert_eq!((p.field(), p.op(), p.value()), ("index", Operator::Eq, "1"));
+
+    assert!(Predicate::from_str("a exists 1").is_err());
+    let e = Predicate::from_str("a in").unwrap_err();
+    assert!(e.to_string().contains("\"in\" requires a value."), "{}", e);
+    assert!(Predicate::from_str("a b").is_err());
+}
@@ -0,0 +1,148 @@
+use crate::pair::{Merger, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use toy_core::data::{Frame, Value};
+use toy_core::error::ServiceError;
+use toy_core::mpsc::Outgoing;
+use toy_core::task::TaskContext;
+use toy_spill::Spill;
+
+/// Frames by key, the left frames and the right frames.
+pub(crate) type Entries = BTreeMap<Value, [Vec<Frame>; 2]>;
+
+/// A frame saved to the files, the frames of the same key are saved to the same file.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    side: u8,
+    key: Value,
+    frame: Frame,
+}
+
+/// Buffer of `Buffered` and `Persist` mode.
+pub(crate) struct Buffered {
+    entries: Entries,
+    len: usize,
+    capacity: usize,
+    /// directory of the files, `None` if all frames are kept on memory.
+    persist: Option<PathBuf>,
+    spill: Option<Spill>,
+}
+
+impl Buffered {
+    pub(crate) fn new(capacity: u32, persist: Option<PathBuf>) -> Self {
+        Self {
+            entries: Entries::new(),
+            len: 0,
+            capacity: capacity as usize,
+            persist,
+            spill: None,
+        }
+    }
+
+    pub(crate) async fn add(
+        &mut self,
+        task_ctx: &TaskContext,
+        side: Side,
+        key: Value,
+        frame: Frame,
+    ) -> Result<(), ServiceError> {
+        self.entries.entry(key).or_default()[side.index()].push(frame);
+        self.len += 1;
+        if self.len > self.capacity {
+            if let Some(dir) = &self.persist {
+                let spill = self.spill.get_or_insert_with(|| {
+                    let name = task_ctx.uri().to_string().replace("/", "-");
+                    // replicas share the uri, so each replica has its own files.
+                    let name = match task_ctx.replica() {
+                        Some(idx) => format!("{}-{}-{}", task_ctx.id(), name, idx),
+                        None => format!("{}-{}", task_ctx.id(), name),
+                    };
+                    Spill::new(dir, &name)
+                });
+                tracing::debug!(parent: task_ctx.span(), len = self.len, "buffer full, save to the files.");
+                save(spill, std::mem::take(&mut self.entries)).await?;
+                self.len = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Join all frames, and remove the saved files.
+    pub(crate) async fn finish(
+        &mut self,
+        merger: &Merger,
+        tx: &mut Outgoing<Frame>,
+    ) -> Result<(), ServiceError> {
+        let entries = std::mem::take(&mut self.entries);
+        self.len = 0;
+        match self.spill.take() {
+            None => join(entries, merger, tx).await,
+            Some(spill) => {
+                save(&spill, entries).await?;
+                for p in 0..spill.partitions() {
+                    let entries = load(&spill, p).await?;
+                    join(entries, merger, tx).await?;
+                }
+                Ok(spill.remove().await?)
+            }
+        }
+    }
+}
+
+/// Append the frames to the files of the partitions of their keys.
+async fn save(spill: &Spill, entries: Entries) -> Result<(), ServiceError> {
+    let entries = entries.into_iter().flat_map(|(key, sides)| {
+        let p = spill.partition(&key);
+        sides
+            .into_iter()
+            .enumerate()
+            .flat_map(move |(side, frames)| {
+                let key = key.clone();
+                frames.into_iter().map(move |frame| {
+                    let entry = Entry {
+                        side: side as u8,
+                        key: key.clone(),
+                        frame,
+                    };
+                    (p, entry)
+                })
+            })
+    });
+    Ok(spill.write(entries).await?)
+}
+
+async fn load(spill: &Spill, partition: usize) -> Result<Entries, ServiceError> {
+    let mut r = Entries::new();
+    spill
+        .read(partition, |e: Entry| {
+            r.entry(e.key).or_default()[e.side as usize].push(e.frame)
+        })
+        .await?;
+    Ok(r)
+}
+
+async fn join(
+    entries: Entries,
+    merger: &Merger,
+    tx: &mut Outgoing<Frame>,
+) -> Result<(), ServiceError> {
+    for (_, [lefts, rights]) in entries {
+        if lefts.is_empty() || rights.is_empty() {
+            for (side, frames) in [(Side::Left, lefts), (Side::Right, rights)] {
+                for f in frames {
+                    if let Some(f) = merger.unmatched(side, &f) {
+                        tx.send_ok(f).await?;
+                    }
+                }
+            }
+            continue;
+        }
+        for l in &lefts {
+            for r in &rights {
+                tx.send_ok(merger.pair(l, r)).await?;
+            }
+        }
+    }
+    Ok(())
+}
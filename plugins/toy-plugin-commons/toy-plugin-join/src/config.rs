@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use toy_pack::Schema;
+
+pub const fn default_capacity() -> u32 {
+    10000
+}
+
+pub fn default_left_prefix() -> String {
+    "left_".to_string()
+}
+
+pub fn default_right_prefix() -> String {
+    "right_".to_string()
+}
+
+/// Which frames without the pair are sent.
+/// The frames of input port 0 are "left", and the frames of input port 1 are "right".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, Schema)]
+pub enum JoinType {
+    /// Only the pairs of the same key.
+    #[default]
+    Inner,
+
+    /// The pairs, and the left frames without the pair.
+    Left,
+
+    /// The pairs, and the left and right frames without the pair.
+    Outer,
+}
+
+/// When the frames are joined.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Schema)]
+pub enum JoinMode {
+    /// Join the frames as soon as they arrive, with the frames of other port within `within_mills`.
+    /// The frames older than `within_mills` from the latest one are removed from the buffer,
+    /// and the frames without the pair are sent at that time.
+    TimeBounded { within_mills: u64 },
+
+    /// Buffer all frames on memory, and join them after all upstreams finished.
+    #[default]
+    Buffered,
+
+    /// Same as `Buffered`, but when the buffer is full,
+    /// it will save the contents to the files partitioned by the key and clear the buffer.
+    /// After all upstreams finished, it joins the saved contents partition by partition.
+    Persist { path: PathBuf },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Schema)]
+pub struct JoinConfig {
+    /// Path of the key of the frames of input port 0.
+    left_key: String,
+    /// Path of the key of the frames of input port 1.
+    right_key: String,
+    #[serde(default)]
+    join_type: JoinType,
+    #[serde(default)]
+    mode: JoinMode,
+    /// Path of the event time field used by `TimeBounded` mode.
+    /// The arrival time is used if not set.
+    timestamp: Option<String>,
+    #[serde(default = "default_capacity")]
+    buffer_capacity: u32,
+    /// Prefix of the fields of left frame in the joined frame.
+    #[serde(default = "default_left_prefix")]
+    left_prefix: String,
+    /// Prefix of the fields of right frame in the joined frame.
+    #[serde(default = "default_right_prefix")]
+    right_prefix: String,
+}
+
+impl JoinConfig {
+    pub fn with(left_key: impl Into<String>, right_key: impl Into<String>) -> Self {
+        Self {
+            left_key: left_key.into(),
+            right_key: right_key.into(),
+            join_type: JoinType::default(),
+            mode: JoinMode::default(),
+            timestamp: None,
+            buffer_capacity: default_capacity(),
+            left_prefix: default_left_prefix(),
+            right_prefix: default_right_prefix(),
+        }
+    }
+
+    pub fn with_join_type(self, join_type: JoinType) -> Self {
+        Self { join_type, ..self }
+    }
+
+    pub fn with_mode(self, mode: JoinMode) -> Self {
+        Self { mode, ..self }
+    }
+
+    pub fn with_timestamp(self, timestamp: impl Into<String>) -> Self {
+        Self {
+            timestamp: Some(timestamp.into()),
+            ..self
+        }
+    }
+
+    pub fn with_buffer_capacity(self, buffer_capacity: u32) -> Self {
+        Self {
+            buffer_capacity,
+            ..self
+        }
+    }
+
+    pub fn with_prefix(
+        self,
+        left_prefix: impl Into<String>,
+        right_prefix: impl Into<String>,
+    ) -> Self {
+        Self {
+            left_prefix: left_prefix.into(),
+            right_prefix: right_prefix.into(),
+            ..self
+        }
+    }
+
+    pub fn left_key(&self) -> &str {
+        &self.left_key
+    }
+
+    pub fn right_key(&self) -> &str {
+        &self.right_key
+    }
+
+    pub fn join_type(&self) -> JoinType {
+        self.join_type
+    }
+
+    pub fn mode(&self) -> &JoinMode {
+        &self.mode
+    }
+
+    pub fn timestamp(&self) -> Option<&str> {
+        self.timestamp.as_deref()
+    }
+
+    pub fn buffer_capacity(&self) -> u32 {
+        self.buffer_capacity
+    }
+
+    pub fn left_prefix(&self) -> &str {
+        &self.left_prefix
+    }
+
+    pub fn right_prefix(&self) -> &str {
+        &self.right_prefix
+    }
+}
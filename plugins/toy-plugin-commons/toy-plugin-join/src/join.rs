@@ -0,0 +1,156 @@
+use crate::buffered::Buffered;
+use crate::config::{JoinConfig, JoinMode};
+use crate::pair::{Merger, Side};
+use crate::time_bounded::TimeBounded;
+use chrono::Utc;
+use std::future::Future;
+use toy_core::data::Frame;
+use toy_core::error::ServiceError;
+use toy_core::mpsc::Outgoing;
+use toy_core::prelude::{PortType, Service, ServiceContext, ServiceFactory, TaskContext};
+use toy_core::ServiceType;
+
+#[derive(Clone, Debug)]
+pub struct Join;
+
+pub struct JoinContext {
+    config: JoinConfig,
+    merger: Merger,
+    state: State,
+}
+
+enum State {
+    TimeBounded(TimeBounded),
+    Buffered(Buffered),
+}
+
+impl Service for Join {
+    type Context = JoinContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<JoinContext>, ServiceError>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<JoinContext>, ServiceError>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<JoinContext>, ServiceError>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::fan_in_flow(2)
+    }
+
+    fn handle(
+        &mut self,
+        task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            let v = match req.value() {
+                Some(v) => v,
+                None => return Ok(ServiceContext::Ready(ctx)),
+            };
+            let side = Side::from_port(req.port());
+            let key_path = match side {
+                Side::Left => ctx.config.left_key(),
+                Side::Right => ctx.config.right_key(),
+            };
+            let key = match v.path(key_path) {
+                Some(key) => key.clone(),
+                None => {
+                    // never joined.
+                    if let Some(f) = ctx.merger.unmatched(side, &req) {
+                        tx.send_ok(f).await?;
+                    }
+                    return Ok(ServiceContext::Ready(ctx));
+                }
+            };
+
+            match &mut ctx.state {
+                State::TimeBounded(s) => {
+                    let time = ctx
+                        .config
+                        .timestamp()
+                        .and_then(|x| v.path(x))
+                        .and_then(|x| x.parse_timestamp())
+                        .unwrap_or_else(Utc::now)
+                        .timestamp_millis();
+                    for f in s.add(side, key, time, req, &ctx.merger) {
+                        tx.send_ok(f).await?;
+                    }
+                }
+                State::Buffered(s) => {
+                    // the buffered frames are kept for the next frame.
+                    if let Err(e) = s.add(&task_ctx, side, key, req).await {
+                        return Ok(ServiceContext::Failed(ctx, e));
+                    }
+                }
+            }
+            Ok(ServiceContext::Ready(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move {
+            match &mut ctx.state {
+                State::TimeBounded(s) => {
+                    for f in s.finish(&ctx.merger) {
+                        tx.send_ok(f).await?;
+                    }
+                }
+                State::Buffered(s) => s.finish(&ctx.merger, &mut tx).await?,
+            }
+            Ok(ServiceContext::Complete(ctx))
+        }
+    }
+}
+
+impl ServiceFactory for Join {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Join;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = JoinContext;
+    type Config = JoinConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Join) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
+        async move {
+            let capacity = config.buffer_capacity();
+            let state = match config.mode() {
+                JoinMode::TimeBounded { within_mills } => {
+                    State::TimeBounded(TimeBounded::new(*within_mills, capacity))
+                }
+                JoinMode::Buffered => State::Buffered(Buffered::new(capacity, None)),
+                JoinMode::Persist { path } => {
+                    State::Buffered(Buffered::new(capacity, Some(path.clone())))
+                }
+            };
+            Ok(JoinContext {
+                merger: Merger::new(&config),
+                config,
+                state,
+            })
+        }
+    }
+}
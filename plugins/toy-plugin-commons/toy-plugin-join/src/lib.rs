@@ -0,0 +1,18 @@
+#![feature(impl_trait_in_assoc_type)]
+
+//! "Join" plugin.
+//! Joining the frames of two input ports by the keys.
+
+pub mod config;
+mod plugin;
+
+mod buffered;
+mod join;
+mod pair;
+mod time_bounded;
+
+pub use plugin::{all, join};
+
+pub mod service {
+    pub use super::join::{Join, JoinContext};
+}
@@ -0,0 +1,101 @@
+use crate::config::{JoinConfig, JoinType};
+use toy_core::data::{Frame, Map, Value};
+
+/// Input port of frame. Port 0 is left, and the others are right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    pub(crate) fn from_port(port: u8) -> Side {
+        match port {
+            0 => Side::Left,
+            _ => Side::Right,
+        }
+    }
+
+    pub(crate) fn index(&self) -> usize {
+        match self {
+            Side::Left => 0,
+            Side::Right => 1,
+        }
+    }
+
+    pub(crate) fn other(&self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+}
+
+/// Create the joined frames.
+#[derive(Debug, Clone)]
+pub(crate) struct Merger {
+    join_type: JoinType,
+    left_prefix: String,
+    right_prefix: String,
+}
+
+impl Merger {
+    pub(crate) fn new(config: &JoinConfig) -> Self {
+        Self {
+            join_type: config.join_type(),
+            left_prefix: config.left_prefix().to_string(),
+            right_prefix: config.right_prefix().to_string(),
+        }
+    }
+
+    /// Whether the frame of the side without the pair is sent.
+    pub(crate) fn keeps_unmatched(&self, side: Side) -> bool {
+        match self.join_type {
+            JoinType::Inner => false,
+            JoinType::Left => side == Side::Left,
+            JoinType::Outer => true,
+        }
+    }
+
+    pub(crate) fn pair(&self, left: &Frame, right: &Frame) -> Frame {
+        self.merge(Some(left), Some(right))
+    }
+
+    /// The frame without the pair. `None` if the frame is not sent by the join type.
+    pub(crate) fn unmatched(&self, side: Side, frame: &Frame) -> Option<Frame> {
+        if !self.keeps_unmatched(side) {
+            return None;
+        }
+        Some(match side {
+            Side::Left => self.merge(Some(frame), None),
+            Side::Right => self.merge(None, Some(frame)),
+        })
+    }
+
+    /// Fields of both frames are put under the prefixes. The headers of left frame are kept.
+    fn merge(&self, left: Option<&Frame>, right: Option<&Frame>) -> Frame {
+        let mut map = Map::new();
+        put(&mut map, &self.left_prefix, left.and_then(|x| x.value()));
+        put(&mut map, &self.right_prefix, right.and_then(|x| x.value()));
+        let headers = left
+            .or(right)
+            .map(|x| x.headers().clone())
+            .unwrap_or_default();
+        Frame::from_value(Value::from(map)).with_headers(headers)
+    }
+}
+
+/// Value other than map is put as `{prefix}value`.
+fn put(map: &mut Map<String, Value>, prefix: &str, v: Option<&Value>) {
+    match v {
+        Some(Value::Map(fields)) => {
+            for (k, v) in fields {
+                map.insert(format!("{}{}", prefix, k), v.clone());
+            }
+        }
+        Some(v) => {
+            map.insert(format!("{}value", prefix), v.clone());
+        }
+        None => {}
+    }
+}
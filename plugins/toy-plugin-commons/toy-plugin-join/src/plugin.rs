@@ -0,0 +1,12 @@
+use crate::service::Join;
+use toy_core::prelude::{layer, Layered, NoopEntry};
+
+const NAME_SPACE: &str = "plugin.common.join";
+
+pub fn join() -> (&'static str, &'static str, Join) {
+    (NAME_SPACE, "join", Join)
+}
+
+pub fn all() -> Layered<NoopEntry, Join> {
+    layer(join())
+}
@@ -0,0 +1,119 @@
+use crate::pair::{Merger, Side};
+use std::collections::{BTreeMap, VecDeque};
+use toy_core::data::{Frame, Value};
+
+struct Item {
+    time: i64,
+    frame: Frame,
+    matched: bool,
+}
+
+/// Frames of one input port, in order of arrival.
+#[derive(Default)]
+struct Store {
+    items: BTreeMap<Value, VecDeque<Item>>,
+    keys: VecDeque<Value>,
+}
+
+/// Buffer of `TimeBounded` mode.
+pub(crate) struct TimeBounded {
+    within: i64,
+    capacity: usize,
+    stores: [Store; 2],
+    latest: Option<i64>,
+}
+
+impl TimeBounded {
+    pub(crate) fn new(within_mills: u64, capacity: u32) -> Self {
+        Self {
+            within: within_mills as i64,
+            capacity: capacity as usize,
+            stores: [Store::default(), Store::default()],
+            latest: None,
+        }
+    }
+
+    /// Add the frame, and returns the frames to send.
+    /// (the pairs of the frame, and the frames removed from the buffer without the pair)
+    pub(crate) fn add(
+        &mut self,
+        side: Side,
+        key: Value,
+        time: i64,
+        frame: Frame,
+        merger: &Merger,
+    ) -> Vec<Frame> {
+        let latest = self.latest.map_or(time, |x| x.max(time));
+        self.latest = Some(latest);
+
+        let mut r = Vec::new();
+        for s in [Side::Left, Side::Right] {
+            while self.front_time(s).is_some_and(|x| x < latest - self.within) {
+                r.extend(self.pop_front(s, merger));
+            }
+        }
+
+        let mut matched = false;
+        if let Some(items) = self.stores[side.other().index()].items.get_mut(&key) {
+            for item in items.iter_mut() {
+                if (item.time - time).abs() > self.within {
+                    continue;
+                }
+                matched = true;
+                item.matched = true;
+                r.push(match side {
+                    Side::Left => merger.pair(&frame, &item.frame),
+                    Side::Right => merger.pair(&item.frame, &frame),
+                });
+            }
+        }
+
+        let store = &mut self.stores[side.index()];
+        store.keys.push_back(key.clone());
+        store.items.entry(key).or_default().push_back(Item {
+            time,
+            frame,
+            matched,
+        });
+        while self.stores[side.index()].keys.len() > self.capacity {
+            r.extend(self.pop_front(side, merger));
+        }
+        r
+    }
+
+    /// Remove all frames, and returns the frames without the pair.
+    pub(crate) fn finish(&mut self, merger: &Merger) -> Vec<Frame> {
+        let mut r = Vec::new();
+        for s in [Side::Left, Side::Right] {
+            while !self.stores[s.index()].keys.is_empty() {
+                r.extend(self.pop_front(s, merger));
+            }
+        }
+        r
+    }
+
+    fn front_time(&self, side: Side) -> Option<i64> {
+        let store = &self.stores[side.index()];
+        store
+            .keys
+            .front()
+            .and_then(|k| store.items.get(k))
+            .and_then(|x| x.front())
+            .map(|x| x.time)
+    }
+
+    fn pop_front(&mut self, side: Side, merger: &Merger) -> Option<Frame> {
+        let store = &mut self.stores[side.index()];
+        let key = store.keys.pop_front()?;
+        let items = store.items.get_mut(&key)?;
+        let item = items.pop_front()?;
+        if items.is_empty() {
+            store.items.remove(&key);
+        }
+        if item.matched {
+            None
+        } else {
+            merger.unmatched(side, &item.frame)
+        }
+    }
+}
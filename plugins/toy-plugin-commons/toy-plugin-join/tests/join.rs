@@ -0,0 +1,196 @@
+use tempdir::TempDir;
+use toy_core::mpsc::OutgoingMessage;
+use toy_core::prelude::*;
+use toy_plugin_join::config::{JoinConfig, JoinMode, JoinType};
+use toy_plugin_join::service::Join;
+
+fn user(id: u32, name: &str) -> Frame {
+    Frame::from_value(map_value! { "id" => id, "name" => name })
+}
+
+fn order(user_id: u32, item: &str) -> Frame {
+    let mut f = Frame::from_value(map_value! { "user_id" => user_id, "item" => item });
+    OutgoingMessage::set_port(&mut f, 1);
+    f
+}
+
+fn timed(f: Frame, t: i64) -> Frame {
+    let ts = chrono::DateTime::from_timestamp_millis(t).unwrap();
+    let mut map = f.value().unwrap().as_map().unwrap().clone();
+    map.insert("ts".to_string(), Value::from(ts));
+    let mut r = Frame::from_value(Value::from(map));
+    OutgoingMessage::set_port(&mut r, f.port());
+    r
+}
+
+/// (left name, right item) of the joined frames.
+fn pairs(r: &[Value]) -> Vec<(Option<String>, Option<String>)> {
+    r.iter()
+        .map(|x| {
+            (
+                x.path("left_name")
+                    .and_then(|x| x.as_str())
+                    .map(|x| x.to_string()),
+                x.path("right_item")
+                    .and_then(|x| x.as_str())
+                    .map(|x| x.to_string()),
+            )
+        })
+        .collect()
+}
+
+fn pair(l: Option<&str>, r: Option<&str>) -> (Option<String>, Option<String>) {
+    (l.map(|x| x.to_string()), r.map(|x| x.to_string()))
+}
+
+fn frames() -> Vec<Frame> {
+    vec![
+        user(1, "a"),
+        order(1, "x"),
+        user(2, "b"),
+        order(1, "y"),
+        order(3, "z"),
+    ]
+}
+
+#[tokio::test]
+async fn inner() {
+    let config = JoinConfig::with("id", "user_id");
+    let r = go(frames(), config).await;
+
+    assert_eq!(
+        pairs(&r),
+        vec![pair(Some("a"), Some("x")), pair(Some("a"), Some("y"))]
+    );
+    assert_eq!(r[0].path("left_id"), Some(&Value::from(1u32)));
+    assert_eq!(r[0].path("right_user_id"), Some(&Value::from(1u32)));
+}
+
+#[tokio::test]
+async fn left() {
+    let config = JoinConfig::with("id", "user_id").with_join_type(JoinType::Left);
+    let r = go(frames(), config).await;
+
+    assert_eq!(
+        pairs(&r),
+        vec![
+            pair(Some("a"), Some("x")),
+            pair(Some("a"), Some("y")),
+            pair(Some("b"), None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn outer() {
+    let config = JoinConfig::with("id", "user_id").with_join_type(JoinType::Outer);
+    let r = go(frames(), config).await;
+
+    assert_eq!(
+        pairs(&r),
+        vec![
+            pair(Some("a"), Some("x")),
+            pair(Some("a"), Some("y")),
+            pair(Some("b"), None),
+            pair(None, Some("z")),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn prefix() {
+    let config = JoinConfig::with("id", "user_id").with_prefix("", "order.");
+    let r = go(vec![user(1, "a"), order(1, "x")], config).await;
+
+    assert_eq!(r.len(), 1);
+    let map = r[0].as_map().unwrap();
+    assert_eq!(map.get("name"), Some(&Value::from("a")));
+    assert_eq!(map.get("order.item"), Some(&Value::from("x")));
+}
+
+#[tokio::test]
+async fn time_bounded() {
+    let frames = vec![
+        timed(user(1, "a"), 0),
+        timed(order(1, "x"), 500),
+        // the user "a" is removed from the buffer.
+        timed(order(1, "y"), 2000),
+        timed(user(2, "b"), 2100),
+        timed(order(2, "z"), 2200),
+    ];
+    let config = JoinConfig::with("id", "user_id")
+        .with_join_type(JoinType::Outer)
+        .with_mode(JoinMode::TimeBounded { within_mills: 1000 })
+        .with_timestamp("ts");
+    let r = go(frames, config).await;
+
+    assert_eq!(
+        pairs(&r),
+        vec![
+            // sent as soon as they arrive.
+            pair(Some("a"), Some("x")),
+            pair(Some("b"), Some("z")),
+            // sent at upstream finish.
+            pair(None, Some("y")),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn persist() {
+    let root = TempDir::new("toy-plugin-join").unwrap();
+    let mut frames = Vec::new();
+    for i in 0..20u32 {
+        frames.push(user(i, &format!("u{}", i)));
+        frames.push(order(i, &format!("o{}", i)));
+    }
+    frames.push(user(100, "alone"));
+    let config = JoinConfig::with("id", "user_id")
+        .with_join_type(JoinType::Left)
+        .with_mode(JoinMode::Persist {
+            path: root.path().to_path_buf(),
+        })
+        .with_buffer_capacity(5);
+    let r = go(frames, config).await;
+
+    let mut r = pairs(&r);
+    r.sort();
+    let mut expected = (0..20)
+        .map(|i| (Some(format!("u{}", i)), Some(format!("o{}", i))))
+        .collect::<Vec<_>>();
+    expected.push(pair(Some("alone"), None));
+    expected.sort();
+    assert_eq!(r, expected);
+    // saved files are removed.
+    assert_eq!(std::fs::read_dir(root.path()).unwrap().count(), 0);
+}
+
+async fn go(frames: Vec<Frame>, config: JoinConfig) -> Vec<Value> {
+    let mut service = Join;
+
+    let (tx, mut rx) = toy_core::mpsc::channel(100);
+    let task_ctx = toy_plugin_test::dummy_task_context();
+
+    let mut c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+
+    for f in frames {
+        let r = service.handle(task_ctx.clone(), c, f, tx.clone()).await;
+        assert!(r.is_ok());
+        c = r.unwrap().into();
+    }
+
+    let r = service
+        .upstream_finish_all(task_ctx.clone(), c, tx.clone())
+        .await;
+    assert!(r.is_ok());
+    drop(tx);
+
+    let mut result = vec![];
+    while let Some(item) = rx.next().await {
+        result.push(item.into_value().unwrap());
+    }
+    result
+}
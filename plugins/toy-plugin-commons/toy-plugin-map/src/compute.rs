@@ -0,0 +1,85 @@
+use crate::config::ComputeConfig;
+use crate::transform::{ComputeTransformer, Transformer};
+use std::future::Future;
+use toy_core::prelude::*;
+
+#[derive(Clone, Debug)]
+pub struct Compute;
+
+pub struct ComputeContext {
+    transformer: ComputeTransformer,
+}
+
+impl Service for Compute {
+    type Context = ComputeContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<ComputeContext>, ServiceError>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<ComputeContext>, ServiceError>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<ComputeContext>, ServiceError>> + Send;
+    type Error = ServiceError;
+
+    fn handle(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        mut req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            if let Some(v) = req.value_mut() {
+                let _ = ctx.transformer.transform(v);
+                tx.send_ok(req).await?;
+            }
+            Ok(ServiceContext::Ready(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move { Ok(ServiceContext::Complete(ctx)) }
+    }
+}
+
+impl ServiceFactory for Compute {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Compute;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = ComputeContext;
+    type Config = ComputeConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Compute) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
+        async move {
+            let transformer = config
+                .compile()
+                .map_err(|e| ServiceError::error(format!("invalid compute expression. {}", e)))?;
+            Ok(ComputeContext { transformer })
+        }
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
@@ -1,12 +1,14 @@
 use crate::transform::{
-    IndexingTransformer, MappingTransformer, NameOrIndexTransformer, NamingTransformer,
-    PutTransformer, PutValueTransformer, ReindexingTransformer, RemoveByIndexTransformer,
-    RemoveByNameTransformer, RenameTransformer, SingleValueTransformer, ToMapTransformer,
-    ToSeqTransformer, Transformer,
+    ComputeTransformer, IndexingTransformer, MappingTransformer, NameOrIndexTransformer,
+    NamingTransformer, PutTransformer, PutValueTransformer, ReindexingTransformer,
+    RemoveByIndexTransformer, RemoveByNameTransformer, RenameTransformer, SingleValueTransformer,
+    ToMapTransformer, ToSeqTransformer, Transformer,
 };
 use crate::typed::AllowedTypes;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use toy_core::data::error::ExprError;
+use toy_core::data::expr::Expr;
 use toy_core::data::Map;
 use toy_pack::Schema;
 
@@ -101,6 +103,25 @@ pub struct ToMapConfig {
 #[derive(Debug, Clone, Default, Deserialize, Serialize, Schema)]
 pub struct ToSeqConfig;
 
+/// put the results of expressions to map value.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Schema)]
+pub struct ComputeConfig {
+    /// key: field path, value: expression.
+    /// evaluated in order, so the later expression can refer the field computed before.
+    pub compute: Map<String, String>,
+}
+
+impl ComputeConfig {
+    /// compile all expressions, fails if any of them is invalid.
+    pub fn compile(&self) -> Result<ComputeTransformer, ExprError> {
+        self.compute
+            .iter()
+            .map(|(k, v)| Expr::compile(v).map(|x| (k.clone(), x)))
+            .collect::<Result<Vec<_>, _>>()
+            .map(ComputeTransformer)
+    }
+}
+
 pub trait ToTransform<T>
 where
     T: Transformer,
@@ -1,6 +1,7 @@
 use super::transform_service::*;
 use toy_core::prelude::{layer, Layered, NoopEntry};
 use crate::typed::Typed;
+use crate::compute::Compute;
 
 const NAME_SPACE: &str = &"plugin.common.map";
 
@@ -52,7 +53,12 @@ pub fn typed() -> (&'static str, &'static str, Typed) {
     (NAME_SPACE, "typed", Typed)
 }
 
+pub fn compute() -> (&'static str, &'static str, Compute) {
+    (NAME_SPACE, "compute", Compute)
+}
+
 pub fn all() -> Layered<
+    Layered<
     Layered<
         Layered<
             Layered<
@@ -78,7 +84,8 @@ pub fn all() -> Layered<
         >,
         ToSeq,
     >,
-    Typed> {
+    Typed>,
+    Compute> {
     layer(mapping())
         .layer(indexing())
         .layer(reindexing())
@@ -91,4 +98,5 @@ pub fn all() -> Layered<
         .layer(to_map())
         .layer(to_seq())
         .layer(typed())
+        .layer(compute())
 }
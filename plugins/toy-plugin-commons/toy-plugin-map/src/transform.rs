@@ -2,6 +2,7 @@ use crate::typed;
 use crate::typed::AllowedTypes;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use toy_core::data::expr::Expr;
 use toy_core::data::{Map, Value};
 use toy_pack::Schema;
 
@@ -60,6 +61,9 @@ pub struct ToMapTransformer(pub String);
 #[derive(Clone, Debug)]
 pub struct ToSeqTransformer();
 
+#[derive(Clone, Debug)]
+pub struct ComputeTransformer(pub Vec<(String, Expr)>);
+
 impl Transformer for MappingTransformer {
     fn transform(&self, value: &mut Value) -> Result<(), ()> {
         match value {
@@ -261,3 +265,18 @@ impl Transformer for ToSeqTransformer {
         Ok(())
     }
 }
+
+impl Transformer for ComputeTransformer {
+    fn transform(&self, value: &mut Value) -> Result<(), ()> {
+        match value {
+            Value::Map(_) => {
+                for (k, expr) in &self.0 {
+                    let v = expr.eval(value);
+                    value.insert_by_path(k, v);
+                }
+                Ok(())
+            }
+            _ => Err(()),
+        }
+    }
+}
@@ -131,6 +131,10 @@ macro_rules! transform_service {
                     })
                 }
             }
+
+            fn is_stateless(&self) -> bool {
+                true
+            }
         }
     };
 }
@@ -101,6 +101,10 @@ impl ServiceFactory for Typed {
             Ok(TypedContext { config })
         }
     }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
 }
 
 pub fn convert(v: &mut Value, config: &TypedConfig) {
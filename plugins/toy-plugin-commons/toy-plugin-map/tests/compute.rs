@@ -0,0 +1,68 @@
+use toy_core::prelude::*;
+use toy_plugin_map::compute::Compute;
+use toy_plugin_map::config::ComputeConfig;
+use toy_plugin_map::transform::Transformer;
+
+fn config(compute: &[(&str, &str)]) -> ComputeConfig {
+    let mut map = Map::new();
+    for (k, v) in compute {
+        map.insert(k.to_string(), v.to_string());
+    }
+    ComputeConfig { compute: map }
+}
+
+#[test]
+fn compute() {
+    let mut target = map_value! {
+        "price" => 120,
+        "qty" => 3,
+        "name" => " toy ",
+    };
+
+    let expected = map_value! {
+        "price" => 120,
+        "qty" => 3,
+        "name" => "TOY",
+        "total" => 360,
+        "summary" => map_value! {
+            "expensive" => true,
+            "discount" => 0,
+        },
+    };
+
+    config(&[
+        ("name", "upper(trim(name))"),
+        ("total", "price * qty"),
+        // refer the field computed before.
+        ("summary.expensive", "total > 300"),
+        ("summary.discount", "coupon ?? 0"),
+    ])
+    .compile()
+    .unwrap()
+    .transform(&mut target)
+    .unwrap();
+    assert_eq!(target, expected);
+}
+
+#[test]
+fn compute_not_map() {
+    let mut target = Value::from(1);
+
+    let r = config(&[("a", "1")])
+        .compile()
+        .unwrap()
+        .transform(&mut target);
+    assert!(r.is_err());
+    assert_eq!(target, Value::from(1));
+}
+
+#[tokio::test]
+async fn compute_invalid_expression() {
+    let r = Compute
+        .new_context(
+            toy_plugin_test::dummy_service_type(),
+            config(&[("a", "1 +")]),
+        )
+        .await;
+    assert!(r.is_err());
+}
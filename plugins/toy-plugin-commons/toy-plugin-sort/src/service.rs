@@ -5,9 +5,11 @@ use std::cmp::{Ordering, Reverse};
 use std::collections::{BinaryHeap, HashSet};
 use std::future::Future;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use toy_core::data;
 use toy_core::prelude::{
-    Frame, Outgoing, PortType, Service, ServiceContext, ServiceError, ServiceFactory, ServiceType,
-    TaskContext, Value,
+    map_value, Frame, Outgoing, PortType, Service, ServiceContext, ServiceError, ServiceFactory,
+    ServiceType, TaskContext, Value,
 };
 
 #[derive(Clone, Debug)]
@@ -17,6 +19,7 @@ pub struct SortContext {
     config: SortConfig,
     buffer: BinaryHeap<Reverse<Candidate>>,
     paths: HashSet<PathBuf>,
+    checkpointed_at: Option<Instant>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -60,6 +63,42 @@ impl Ord for Candidate {
 }
 
 impl SortContext {
+    /// Report the buffered values and the persisted files as the state of the service.
+    ///
+    /// The whole buffer is reported, so it is done at most once in the checkpoint interval,
+    /// unless `force` (e.g. the buffer is flushed).
+    pub fn checkpoint(&mut self, task_ctx: &TaskContext, force: bool) -> Result<(), ServiceError> {
+        let interval = match task_ctx.checkpoints() {
+            Some(c) => Duration::from_millis(c.interval_mills()),
+            None => return Ok(()),
+        };
+        if !force && self.checkpointed_at.is_some_and(|x| x.elapsed() < interval) {
+            return Ok(());
+        }
+        let buffer = self
+            .buffer
+            .iter()
+            .map(|Reverse(x)| data::pack(x))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ServiceError::error)?;
+        let paths = data::pack(&self.paths).map_err(ServiceError::error)?;
+        task_ctx.checkpoint(map_value! { "paths" => paths, "buffer" => Value::Seq(buffer) });
+        self.checkpointed_at = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Restore the buffer and the persisted files reported by the previous run of the task.
+    pub fn restore(&mut self, state: &Value) -> Result<(), ServiceError> {
+        if let Some(paths) = state.path("paths") {
+            self.paths = data::unpack::<HashSet<PathBuf>>(paths).map_err(ServiceError::error)?;
+        }
+        if let Some(buffer) = state.path("buffer") {
+            let buffer = data::unpack::<Vec<Candidate>>(buffer).map_err(ServiceError::error)?;
+            self.buffer.extend(buffer.into_iter().map(Reverse));
+        }
+        Ok(())
+    }
+
     pub async fn flush_if_needed(
         &mut self,
         task_ctx: &TaskContext,
@@ -109,7 +148,7 @@ impl SortContext {
                 self.buffer.clear();
             }
         }
-        Ok(())
+        self.checkpoint(task_ctx, true)
     }
 }
 
@@ -127,6 +166,19 @@ impl Service for Sort {
         PortType::flow()
     }
 
+    fn started(
+        &mut self,
+        task_ctx: TaskContext,
+        mut ctx: Self::Context,
+    ) -> ServiceContext<Self::Context> {
+        if let Some(state) = task_ctx.restored() {
+            if let Err(e) = ctx.restore(state) {
+                return ServiceContext::Failed(ctx, e);
+            }
+        }
+        ServiceContext::Ready(ctx)
+    }
+
     fn handle(
         &mut self,
         task_ctx: TaskContext,
@@ -150,6 +202,7 @@ impl Service for Sort {
                 None => {}
             }
             ctx.flush_if_needed(&task_ctx, &mut tx).await?;
+            ctx.checkpoint(&task_ctx, false)?;
             Ok(ServiceContext::Ready(ctx))
         }
     }
@@ -208,6 +261,7 @@ impl ServiceFactory for Sort {
                 config,
                 buffer: BinaryHeap::with_capacity(count as usize),
                 paths: HashSet::new(),
+                checkpointed_at: None,
             })
         }
     }
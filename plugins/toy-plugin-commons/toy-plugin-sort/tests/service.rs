@@ -1,3 +1,5 @@
+use std::sync::Arc;
+use toy_core::checkpoint::{Checkpoints, MemoryCheckpointStore};
 use toy_core::prelude::*;
 use toy_plugin_sort::config::{BufferFullStrategy, SortConfig, SortKey};
 use toy_plugin_sort::service::Sort;
@@ -38,6 +40,70 @@ async fn test_sort_by_name() {
     assert_eq!(r.get(2).unwrap().value().unwrap(), data.get(0).unwrap());
 }
 
+#[tokio::test]
+async fn test_sort_resume_from_checkpoint() {
+    let store = Arc::new(MemoryCheckpointStore::new());
+    let id = TaskId::new();
+    let task_ctx = |id| {
+        let mut t = TaskContext::new(id, toy_plugin_test::dummy_graph())
+            .with_checkpoints(Checkpoints::new(store.clone(), 0))
+            .with_uri(&Uri::from("sort"));
+        t.set_span(t.info_span().clone());
+        t
+    };
+    let config = SortConfig::with(10, BufferFullStrategy::Flush, SortKey::Value);
+
+    let mut service = Sort;
+    let (tx, _rx) = toy_core::mpsc::channel(10);
+    let before = task_ctx(id);
+    let mut c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config.clone())
+        .await
+        .unwrap();
+    for v in [3, 1, 2] {
+        c = service
+            .handle(before.clone(), c, Frame::from(v), tx.clone())
+            .await
+            .unwrap()
+            .into();
+    }
+    before.checkpoints().unwrap().save(id).await.unwrap();
+
+    // resume the task with same id, the buffered values are sorted with the rest.
+    let (tx, mut rx) = toy_core::mpsc::channel(10);
+    let after = task_ctx(id);
+    after.checkpoints().unwrap().restore(id).await.unwrap();
+    let c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+    let c = service.started(after.clone(), c).into();
+    let c = service
+        .handle(after.clone(), c, Frame::from(0), tx.clone())
+        .await
+        .unwrap()
+        .into();
+    service
+        .upstream_finish_all(after.clone(), c, tx.clone())
+        .await
+        .unwrap();
+    drop(tx);
+
+    let mut result = vec![];
+    while let Some(item) = rx.next().await {
+        result.push(item.value().cloned().unwrap());
+    }
+    assert_eq!(
+        result,
+        vec![
+            Value::from(0),
+            Value::from(1),
+            Value::from(2),
+            Value::from(3)
+        ]
+    );
+}
+
 async fn sort(data: &Vec<Value>, key: SortKey) -> Vec<Frame> {
     let _ = tokio::fs::create_dir(TMP_PATH).await;
 
@@ -2,9 +2,11 @@
 
 mod plugin;
 pub mod service;
+pub mod throttle;
 
 pub mod config {
     pub use crate::service::TickConfig;
+    pub use crate::throttle::ThrottleConfig;
 }
 
-pub use plugin::{all, tick};
+pub use plugin::{all, throttle, tick};
@@ -1,4 +1,5 @@
 use super::service::*;
+use super::throttle::Throttle;
 use toy_core::prelude::{Layered, NoopEntry};
 use toy_core::registry::layer;
 
@@ -8,6 +9,10 @@ pub fn tick() -> (&'static str, &'static str, Tick) {
     (NAME_SPACE, "tick", Tick)
 }
 
-pub fn all() -> Layered<NoopEntry, Tick> {
-    layer(tick())
+pub fn throttle() -> (&'static str, &'static str, Throttle) {
+    (NAME_SPACE, "throttle", Throttle)
+}
+
+pub fn all() -> Layered<Layered<NoopEntry, Tick>, Throttle> {
+    layer(tick()).layer(throttle())
 }
@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use tokio::time::{Duration, Instant};
+use toy_core::prelude::{
+    Frame, Outgoing, PortType, Service, ServiceContext, ServiceError, ServiceFactory, ServiceType,
+    TaskContext,
+};
+use toy_pack::Schema;
+
+/// Default max count of the buckets of per-key limit.
+const DEFAULT_MAX_KEYS: usize = 10_000;
+
+/// Limit the frames per second by the token bucket.
+///
+/// The frame waits in the service until the token is available,
+/// so the upstream is slowed down by the backpressure of the channel, and no frame is dropped.
+/// With `key`, each key has its own bucket, but the frames of the all keys wait in order.
+#[derive(Debug, Clone, Serialize, Deserialize, Schema)]
+pub struct ThrottleConfig {
+    /// Tokens added per second.
+    per_second: f64,
+    /// Max tokens of the bucket, the count of frames sent at once after idle.
+    #[serde(default = "default_burst")]
+    burst: u32,
+    /// Path of the key to limit per key. All frames share one bucket if not set.
+    key: Option<String>,
+    /// The buckets already filled up are removed when the count of the buckets exceeds this.
+    max_keys: Option<usize>,
+}
+
+fn default_burst() -> u32 {
+    1
+}
+
+impl ThrottleConfig {
+    pub fn with(per_second: f64, burst: u32) -> ThrottleConfig {
+        ThrottleConfig {
+            per_second,
+            burst,
+            key: None,
+            max_keys: None,
+        }
+    }
+
+    pub fn with_key(self, key: impl Into<String>) -> ThrottleConfig {
+        ThrottleConfig {
+            key: Some(key.into()),
+            ..self
+        }
+    }
+
+    pub fn with_max_keys(self, max_keys: usize) -> ThrottleConfig {
+        ThrottleConfig {
+            max_keys: Some(max_keys),
+            ..self
+        }
+    }
+
+    pub fn per_second(&self) -> f64 {
+        self.per_second
+    }
+
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    pub fn max_keys(&self) -> usize {
+        self.max_keys.unwrap_or(DEFAULT_MAX_KEYS)
+    }
+}
+
+struct Bucket {
+    /// Available tokens, negative if the tokens are reserved by the waiting frames.
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    fn new(burst: f64, now: Instant) -> Bucket {
+        Bucket {
+            tokens: burst,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, per_second: f64, burst: f64, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(burst);
+        self.updated_at = now;
+    }
+
+    /// Reserve a token, and returns the time to wait for it.
+    fn acquire(&mut self, per_second: f64, burst: f64, now: Instant) -> Duration {
+        self.refill(per_second, burst, now);
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / per_second)
+        }
+    }
+}
+
+pub struct ThrottleContext {
+    config: ThrottleConfig,
+    buckets: HashMap<String, Bucket>,
+}
+
+impl ThrottleContext {
+    fn acquire(&mut self, key: String, now: Instant) -> Duration {
+        let (per_second, burst) = (self.config.per_second, self.config.burst as f64);
+        let wait = self
+            .buckets
+            .entry(key)
+            .or_insert_with(|| Bucket::new(burst, now))
+            .acquire(per_second, burst, now);
+
+        if self.buckets.len() > self.config.max_keys() {
+            // a full bucket is the same as the new one.
+            self.buckets.retain(|_, b| {
+                b.refill(per_second, burst, now);
+                b.tokens < burst
+            });
+        }
+        wait
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Throttle;
+
+impl Service for Throttle {
+    type Context = ThrottleContext;
+    type Request = Frame;
+    type Future =
+        impl Future<Output = Result<ServiceContext<ThrottleContext>, ServiceError>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<ThrottleContext>, ServiceError>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<ThrottleContext>, ServiceError>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::flow()
+    }
+
+    fn handle(
+        &mut self,
+        task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            let key = match (ctx.config.key.as_ref(), req.value()) {
+                (Some(path), Some(v)) => format!("{:?}", v.path(path)),
+                _ => String::new(),
+            };
+            let wait = ctx.acquire(key, Instant::now());
+            if !wait.is_zero() {
+                tracing::trace!(parent: task_ctx.span(), ?wait, "throttle.");
+                tokio::time::sleep(wait).await;
+            }
+            tx.send_ok(req).await?;
+            Ok(ServiceContext::Ready(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move { Ok(ServiceContext::Complete(ctx)) }
+    }
+}
+
+impl ServiceFactory for Throttle {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Throttle;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = ThrottleContext;
+    type Config = ThrottleConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Throttle) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
+        async move {
+            if !(config.per_second.is_finite() && config.per_second > 0.0) {
+                return Err(ServiceError::error("per_second must be greater than 0."));
+            }
+            if config.burst == 0 {
+                return Err(ServiceError::error("burst must be greater than 0."));
+            }
+            Ok(ThrottleContext {
+                config,
+                buckets: HashMap::new(),
+            })
+        }
+    }
+}
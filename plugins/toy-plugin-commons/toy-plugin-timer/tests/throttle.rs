@@ -0,0 +1,71 @@
+use std::time::Duration;
+use tokio::time::Instant;
+use toy_core::prelude::*;
+use toy_plugin_timer::config::ThrottleConfig;
+use toy_plugin_timer::throttle::Throttle;
+
+#[tokio::test(start_paused = true)]
+async fn throttle_global() {
+    let data = (0..6).map(Value::from).collect::<Vec<_>>();
+
+    let (r, elapsed) = go(data.clone(), ThrottleConfig::with(10.0, 2)).await;
+
+    assert_eq!(r, data);
+    // 2 frames by the burst, and the rest at 10 per second.
+    assert_eq!(elapsed, Duration::from_millis(400));
+}
+
+#[tokio::test(start_paused = true)]
+async fn throttle_per_key() {
+    let data = vec![
+        map_value!("k" => "a"),
+        map_value!("k" => "b"),
+        map_value!("k" => "a"),
+        map_value!("k" => "b"),
+    ];
+
+    let (r, elapsed) = go(data.clone(), ThrottleConfig::with(1.0, 1).with_key("k")).await;
+
+    assert_eq!(r, data);
+    // the second "b" is ready while waiting for the second "a".
+    assert_eq!(elapsed, Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn throttle_invalid_config() {
+    for config in [ThrottleConfig::with(0.0, 1), ThrottleConfig::with(1.0, 0)] {
+        let r = Throttle
+            .new_context(toy_plugin_test::dummy_service_type(), config)
+            .await;
+        assert!(r.is_err());
+    }
+}
+
+async fn go(data: Vec<Value>, config: ThrottleConfig) -> (Vec<Value>, Duration) {
+    let mut service = Throttle;
+
+    let (tx, mut rx) = toy_core::mpsc::channel(data.len());
+    let task_ctx = toy_plugin_test::dummy_task_context();
+
+    let mut c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+
+    let start = Instant::now();
+    for v in data {
+        let r = service
+            .handle(task_ctx.clone(), c, Frame::from_value(v), tx.clone())
+            .await;
+        assert!(r.is_ok());
+        c = r.unwrap().into();
+    }
+    let elapsed = start.elapsed();
+    drop(tx);
+
+    let mut result = vec![];
+    while let Some(item) = rx.next().await {
+        result.push(item.into_value().unwrap());
+    }
+    (result, elapsed)
+}
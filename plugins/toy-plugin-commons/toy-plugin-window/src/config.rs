@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use toy_pack::Schema;
+
+/// Kind of window, the sizes are in milliseconds of event time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Schema)]
+pub enum WindowKind {
+    /// Fixed size, non-overlapping windows.
+    Tumbling { size_mills: u64 },
+
+    /// Fixed size windows starting at every `slide_mills`.
+    /// A frame belongs to all the windows which contain its event time.
+    /// `slide_mills` must not be greater than `size_mills`, not to leave gaps between the windows.
+    Sliding { size_mills: u64, slide_mills: u64 },
+
+    /// Windows of activity, closed when no frame of the key arrives within `gap_mills`.
+    Session { gap_mills: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Schema)]
+pub struct WindowConfig {
+    pub(crate) kind: WindowKind,
+    /// Path of the event time field. `Value::TimeStamp` or RFC3339 string.
+    pub(crate) timestamp: String,
+    /// Path of the grouping key. All frames are in the same group if not set.
+    pub(crate) key: Option<String>,
+    /// How long the watermark lags behind the latest event time, in milliseconds.
+    /// Frames older than the watermark are late, and are sent to the output port 1.
+    #[serde(default)]
+    pub(crate) allowed_lateness_mills: u64,
+}
+
+impl WindowConfig {
+    pub fn with(kind: WindowKind, timestamp: impl Into<String>) -> WindowConfig {
+        WindowConfig {
+            kind,
+            timestamp: timestamp.into(),
+            key: None,
+            allowed_lateness_mills: 0,
+        }
+    }
+
+    pub fn with_key(self, key: impl Into<String>) -> WindowConfig {
+        WindowConfig {
+            key: Some(key.into()),
+            ..self
+        }
+    }
+
+    pub fn with_allowed_lateness_mills(self, allowed_lateness_mills: u64) -> WindowConfig {
+        WindowConfig {
+            allowed_lateness_mills,
+            ..self
+        }
+    }
+
+    pub fn kind(&self) -> &WindowKind {
+        &self.kind
+    }
+
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    pub fn allowed_lateness_mills(&self) -> u64 {
+        self.allowed_lateness_mills
+    }
+}
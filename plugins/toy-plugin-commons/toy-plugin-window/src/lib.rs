@@ -0,0 +1,16 @@
+#![feature(impl_trait_in_assoc_type)]
+
+//! "Window" plugin.
+//! Grouping frames by the key and the event time, and sending the group when the window is closed.
+
+pub mod config;
+mod plugin;
+
+mod pane;
+mod window;
+
+pub use plugin::{all, window};
+
+pub mod service {
+    pub use super::window::{Window, WindowContext};
+}
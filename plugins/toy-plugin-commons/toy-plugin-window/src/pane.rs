@@ -0,0 +1,175 @@
+use crate::config::WindowKind;
+use std::collections::BTreeMap;
+use toy_core::data::{Map, Value};
+
+/// Identity of window, ordered by the end to close the windows in order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct PaneId {
+    pub(crate) end: i64,
+    pub(crate) key: Value,
+    pub(crate) start: i64,
+}
+
+/// Frames of one window.
+#[derive(Debug)]
+pub(crate) struct Pane {
+    pub(crate) values: Vec<Value>,
+    /// headers of the first frame in the window, kept on the sent frame.
+    pub(crate) headers: Map<String, Value>,
+}
+
+/// Open windows and the watermark. All times are milliseconds of event time.
+#[derive(Debug)]
+pub(crate) struct Panes {
+    kind: WindowKind,
+    allowed_lateness: i64,
+    panes: BTreeMap<PaneId, Pane>,
+    max_event_time: Option<i64>,
+}
+
+impl Panes {
+    pub(crate) fn new(kind: WindowKind, allowed_lateness_mills: u64) -> Self {
+        Self {
+            kind,
+            allowed_lateness: allowed_lateness_mills as i64,
+            panes: BTreeMap::new(),
+            max_event_time: None,
+        }
+    }
+
+    /// Windows ending at or before the watermark are closed.
+    pub(crate) fn watermark(&self) -> Option<i64> {
+        self.max_event_time.map(|x| x - self.allowed_lateness)
+    }
+
+    /// Add the value to the windows of event time.
+    /// Returns false if all the windows are already closed. (late data)
+    pub(crate) fn add(
+        &mut self,
+        key: Value,
+        event_time: i64,
+        value: Value,
+        headers: &Map<String, Value>,
+    ) -> bool {
+        let watermark = self.watermark();
+        let ranges = self
+            .ranges(event_time)
+            .into_iter()
+            .filter(|(_, end)| watermark.is_none_or(|w| *end > w))
+            .collect::<Vec<_>>();
+        if ranges.is_empty() {
+            return false;
+        }
+        self.max_event_time = Some(
+            self.max_event_time
+                .map_or(event_time, |x| x.max(event_time)),
+        );
+
+        match self.kind {
+            WindowKind::Session { .. } => {
+                let (start, end) = ranges[0];
+                self.merge_session(key, start, end, value, headers);
+            }
+            _ => {
+                for (start, end) in ranges {
+                    let id = PaneId {
+                        end,
+                        key: key.clone(),
+                        start,
+                    };
+                    self.panes
+                        .entry(id)
+                        .or_insert_with(|| Pane {
+                            values: Vec::new(),
+                            headers: headers.clone(),
+                        })
+                        .values
+                        .push(value.clone());
+                }
+            }
+        }
+        true
+    }
+
+    /// Remove the windows closed by the watermark, in order of the end.
+    pub(crate) fn close(&mut self) -> Vec<(PaneId, Pane)> {
+        let watermark = match self.watermark() {
+            Some(w) => w,
+            None => return Vec::new(),
+        };
+        let mut r = Vec::new();
+        while let Some(entry) = self.panes.first_entry() {
+            if entry.key().end > watermark {
+                break;
+            }
+            r.push(entry.remove_entry());
+        }
+        r
+    }
+
+    /// Remove all windows, in order of the end.
+    pub(crate) fn close_all(&mut self) -> Vec<(PaneId, Pane)> {
+        std::mem::take(&mut self.panes).into_iter().collect()
+    }
+
+    /// Ranges `[start, end)` of the windows containing the event time.
+    fn ranges(&self, event_time: i64) -> Vec<(i64, i64)> {
+        match self.kind {
+            WindowKind::Tumbling { size_mills } => {
+                let size = size_mills as i64;
+                let start = event_time.div_euclid(size) * size;
+                vec![(start, start + size)]
+            }
+            WindowKind::Sliding {
+                size_mills,
+                slide_mills,
+            } => {
+                let (size, slide) = (size_mills as i64, slide_mills as i64);
+                let mut start = event_time.div_euclid(slide) * slide;
+                let mut r = Vec::new();
+                while start > event_time - size {
+                    r.push((start, start + size));
+                    start -= slide;
+                }
+                r
+            }
+            WindowKind::Session { gap_mills } => vec![(event_time, event_time + gap_mills as i64)],
+        }
+    }
+
+    /// Merge the new session into the overlapped sessions of the same key.
+    fn merge_session(
+        &mut self,
+        key: Value,
+        start: i64,
+        end: i64,
+        value: Value,
+        headers: &Map<String, Value>,
+    ) {
+        let overlapped = self
+            .panes
+            .keys()
+            .filter(|x| x.key == key && x.start < end && start < x.end)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut id = PaneId { end, key, start };
+        let mut pane: Option<Pane> = None;
+        // sessions of the same key do not overlap each other, so they are in order of the start.
+        for x in overlapped {
+            let p = self.panes.remove(&x).unwrap();
+            id.start = id.start.min(x.start);
+            id.end = id.end.max(x.end);
+            match pane.as_mut() {
+                Some(pane) => pane.values.extend(p.values),
+                None => pane = Some(p),
+            }
+        }
+        let mut pane = pane.unwrap_or_else(|| Pane {
+            values: Vec::new(),
+            headers: headers.clone(),
+        });
+        pane.values.push(value);
+        self.panes.insert(id, pane);
+    }
+}
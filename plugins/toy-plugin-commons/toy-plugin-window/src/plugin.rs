@@ -0,0 +1,12 @@
+use super::service::*;
+use toy_core::prelude::{layer, Layered, NoopEntry};
+
+const NAME_SPACE: &str = "plugin.common.window";
+
+pub fn window() -> (&'static str, &'static str, Window) {
+    (NAME_SPACE, "window", Window)
+}
+
+pub fn all() -> Layered<NoopEntry, Window> {
+    layer(window())
+}
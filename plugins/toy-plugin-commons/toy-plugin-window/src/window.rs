@@ -0,0 +1,172 @@
+use crate::config::{WindowConfig, WindowKind};
+use crate::pane::{Pane, PaneId, Panes};
+use chrono::DateTime;
+use std::future::Future;
+use toy_core::data::{Frame, Value};
+use toy_core::error::ServiceError;
+use toy_core::mpsc::Outgoing;
+use toy_core::prelude::{
+    map_value, PortType, Service, ServiceContext, ServiceFactory, TaskContext,
+};
+use toy_core::ServiceType;
+
+/// Output port of the frames arrived after their windows closed.
+const LATE_PORT: u8 = 1;
+
+#[derive(Clone, Debug)]
+pub struct Window;
+
+pub struct WindowContext {
+    config: WindowConfig,
+    panes: Panes,
+}
+
+impl Service for Window {
+    type Context = WindowContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<WindowContext>, ServiceError>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<WindowContext>, ServiceError>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<WindowContext>, ServiceError>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::fan_out_flow(2)
+    }
+
+    fn handle(
+        &mut self,
+        task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            let span = task_ctx.span();
+            let v = match req.value() {
+                Some(v) => v,
+                None => return Ok(ServiceContext::Ready(ctx)),
+            };
+            let event_time = match v
+                .path(ctx.config.timestamp())
+                .and_then(|x| x.parse_timestamp())
+            {
+                Some(t) => t.timestamp_millis(),
+                None => {
+                    tracing::warn!(parent: span, field = ctx.config.timestamp(), "skip the frame without event time.");
+                    return Ok(ServiceContext::Ready(ctx));
+                }
+            };
+            let key = ctx
+                .config
+                .key()
+                .and_then(|x| v.path(x))
+                .cloned()
+                .unwrap_or(Value::None);
+
+            if !ctx.panes.add(key, event_time, v.clone(), req.headers()) {
+                tracing::debug!(parent: span, event_time, watermark = ?ctx.panes.watermark(), "late frame.");
+                if tx.ports_len() > 1 {
+                    tx.send_ok_to(LATE_PORT, req).await?;
+                }
+                return Ok(ServiceContext::Ready(ctx));
+            }
+
+            for (id, pane) in ctx.panes.close() {
+                tx.send_ok(to_frame(id, pane)).await?;
+            }
+            Ok(ServiceContext::Ready(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move {
+            // no more frames, the open windows are closed.
+            for (id, pane) in ctx.panes.close_all() {
+                tx.send_ok(to_frame(id, pane)).await?;
+            }
+            Ok(ServiceContext::Complete(ctx))
+        }
+    }
+}
+
+fn to_frame(id: PaneId, pane: Pane) -> Frame {
+    let v = map_value! {
+        "key" => id.key,
+        "start" => to_timestamp(id.start),
+        "end" => to_timestamp(id.end),
+        "payload" => pane.values
+    };
+    Frame::from_value(v).with_headers(pane.headers)
+}
+
+fn to_timestamp(mills: i64) -> Value {
+    DateTime::from_timestamp_millis(mills)
+        .map(Value::from)
+        .unwrap_or(Value::None)
+}
+
+impl ServiceFactory for Window {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Window;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = WindowContext;
+    type Config = WindowConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Window) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
+        async move {
+            let valid = match config.kind() {
+                WindowKind::Tumbling { size_mills } => *size_mills > 0,
+                WindowKind::Sliding {
+                    size_mills,
+                    slide_mills,
+                } => *size_mills > 0 && *slide_mills > 0,
+                WindowKind::Session { gap_mills } => *gap_mills > 0,
+            };
+            if !valid {
+                return Err(ServiceError::error(format!(
+                    "window size must be greater than 0. {:?}",
+                    config.kind()
+                )));
+            }
+            // the events between the windows would belong to no window, and be sent as late.
+            if let WindowKind::Sliding {
+                size_mills,
+                slide_mills,
+            } = config.kind()
+            {
+                if slide_mills > size_mills {
+                    return Err(ServiceError::error(format!(
+                        "window slide must not be greater than size. {:?}",
+                        config.kind()
+                    )));
+                }
+            }
+            let panes = Panes::new(config.kind().clone(), config.allowed_lateness_mills());
+            Ok(WindowContext { config, panes })
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use toy_core::prelude::*;
+use toy_plugin_window::config::{WindowConfig, WindowKind};
+use toy_plugin_window::service::Window;
+
+fn event(t: i64, key: &str, v: u32) -> Frame {
+    let ts = chrono::DateTime::from_timestamp_millis(t).unwrap();
+    Frame::from_value(map_value! {
+        "ts" => ts,
+        "key" => key,
+        "v" => v
+    })
+}
+
+fn payload(v: &Value) -> Vec<u32> {
+    v.path("payload")
+        .unwrap()
+        .as_vec()
+        .unwrap()
+        .iter()
+        .map(|x| x.path("v").unwrap().as_u32().unwrap())
+        .collect()
+}
+
+fn millis(v: &Value, name: &str) -> i64 {
+    v.path(name)
+        .unwrap()
+        .as_timestamp()
+        .unwrap()
+        .timestamp_millis()
+}
+
+#[tokio::test]
+async fn tumbling() {
+    let frames = vec![
+        event(0, "a", 1),
+        event(500, "a", 2),
+        event(1000, "a", 3),
+        event(2500, "a", 4),
+    ];
+    let config = WindowConfig::with(WindowKind::Tumbling { size_mills: 1000 }, "ts");
+    let (r, late) = go(frames, config).await;
+
+    assert_eq!(r.len(), 3);
+    assert_eq!(payload(&r[0]), vec![1, 2]);
+    assert_eq!((millis(&r[0], "start"), millis(&r[0], "end")), (0, 1000));
+    assert_eq!(payload(&r[1]), vec![3]);
+    // closed by upstream finish.
+    assert_eq!(payload(&r[2]), vec![4]);
+    assert_eq!((millis(&r[2], "start"), millis(&r[2], "end")), (2000, 3000));
+    assert!(late.is_empty());
+}
+
+#[tokio::test]
+async fn tumbling_key() {
+    let frames = vec![
+        event(0, "a", 1),
+        event(100, "b", 2),
+        event(200, "a", 3),
+        event(1000, "b", 4),
+    ];
+    let config =
+        WindowConfig::with(WindowKind::Tumbling { size_mills: 1000 }, "ts").with_key("key");
+    let (r, _) = go(frames, config).await;
+
+    assert_eq!(r.len(), 3);
+    assert_eq!(r[0].path("key"), Some(&Value::from("a")));
+    assert_eq!(payload(&r[0]), vec![1, 3]);
+    assert_eq!(r[1].path("key"), Some(&Value::from("b")));
+    assert_eq!(payload(&r[1]), vec![2]);
+    assert_eq!(payload(&r[2]), vec![4]);
+}
+
+#[tokio::test]
+async fn sliding() {
+    let frames = vec![event(100, "a", 1), event(600, "a", 2), event(1100, "a", 3)];
+    let config = WindowConfig::with(
+        WindowKind::Sliding {
+            size_mills: 1000,
+            slide_mills: 500,
+        },
+        "ts",
+    );
+    let (r, _) = go(frames, config).await;
+
+    let windows = r
+        .iter()
+        .map(|x| (millis(x, "start"), payload(x)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        windows,
+        vec![
+            (-500, vec![1]),
+            (0, vec![1, 2]),
+            (500, vec![2, 3]),
+            (1000, vec![3]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn session() {
+    let frames = vec![
+        event(0, "a", 1),
+        event(300, "a", 2),
+        event(2000, "a", 3),
+        // merged into the session [2000, 2500).
+        event(1600, "a", 4),
+        event(5000, "a", 5),
+    ];
+    let config = WindowConfig::with(WindowKind::Session { gap_mills: 500 }, "ts")
+        .with_allowed_lateness_mills(1000);
+    let (r, late) = go(frames, config).await;
+
+    assert_eq!(r.len(), 3);
+    assert_eq!(payload(&r[0]), vec![1, 2]);
+    assert_eq!((millis(&r[0], "start"), millis(&r[0], "end")), (0, 800));
+    assert_eq!(payload(&r[1]), vec![3, 4]);
+    assert_eq!((millis(&r[1], "start"), millis(&r[1], "end")), (1600, 2500));
+    assert_eq!(payload(&r[2]), vec![5]);
+    assert!(late.is_empty());
+}
+
+#[tokio::test]
+async fn late() {
+    let frames = vec![
+        event(0, "a", 1),
+        event(1500, "a", 2),
+        // watermark is 1000, the window [0, 1000) is closed.
+        event(900, "a", 3),
+        // allowed lateness.
+        event(1200, "a", 4),
+    ];
+    let config = WindowConfig::with(WindowKind::Tumbling { size_mills: 1000 }, "ts")
+        .with_allowed_lateness_mills(500);
+    let (r, late) = go(frames, config).await;
+
+    assert_eq!(r.len(), 2);
+    assert_eq!(payload(&r[0]), vec![1]);
+    assert_eq!(payload(&r[1]), vec![2, 4]);
+    assert_eq!(late.len(), 1);
+    assert_eq!(late[0].path("v"), Some(&Value::from(3u32)));
+}
+
+#[tokio::test]
+async fn skip_without_timestamp() {
+    let frames = vec![Frame::from_value(map_value! { "v" => 1 }), event(0, "a", 2)];
+    let config = WindowConfig::with(WindowKind::Tumbling { size_mills: 1000 }, "ts");
+    let (r, _) = go(frames, config).await;
+
+    assert_eq!(r.len(), 1);
+    assert_eq!(payload(&r[0]), vec![2]);
+}
+
+#[tokio::test]
+async fn invalid_size() {
+    let service = Window;
+    let config = WindowConfig::with(WindowKind::Tumbling { size_mills: 0 }, "ts");
+    let r = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await;
+    assert!(r.is_err());
+}
+
+#[tokio::test]
+async fn invalid_slide() {
+    let service = Window;
+    let config = WindowConfig::with(
+        WindowKind::Sliding {
+            size_mills: 10,
+            slide_mills: 20,
+        },
+        "ts",
+    );
+    let r = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await;
+    assert!(r.is_err());
+}
+
+/// Returns the windows and the late frames.
+async fn go(frames: Vec<Frame>, config: WindowConfig) -> (Vec<Value>, Vec<Value>) {
+    let mut service = Window;
+
+    let (tx0, mut rx0) = toy_core::mpsc::channel(10);
+    let (tx1, mut rx1) = toy_core::mpsc::channel(10);
+    let mut tx = Outgoing::empty();
+    tx.merge(tx0);
+    tx.merge(tx1);
+    let task_ctx = toy_plugin_test::dummy_task_context();
+
+    let mut c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+
+    for f in frames {
+        let r = service.handle(task_ctx.clone(), c, f, tx.clone()).await;
+        assert!(r.is_ok());
+        c = r.unwrap().into();
+    }
+
+    let r = service
+        .upstream_finish_all(task_ctx.clone(), c, tx.clone())
+        .await;
+    assert!(r.is_ok());
+    drop(tx);
+
+    let mut windows = vec![];
+    while let Some(item) = rx0.next().await {
+        windows.push(item.into_value().unwrap());
+    }
+    let mut late = vec![];
+    while let Some(item) = rx1.next().await {
+        late.push(item.into_value().unwrap());
+    }
+    (windows, late)
+}
@@ -60,6 +60,9 @@ pub fn encode<'a>(f: &Frame, scope: &mut HandleScope<'a>) -> Local<'a, Object> {
     let r = v8::Object::new(scope);
     let js_key = v8::String::new(scope, "header").unwrap().into();
     r.set(scope, js_key, header.into());
+    let js_key = v8::String::new(scope, "headers").unwrap().into();
+    let js_value = encode0(&Value::from(f.headers().clone()), scope);
+    r.set(scope, js_key, js_value);
     if f.value().is_some() {
         let js_key = v8::String::new(scope, "payload").unwrap().into();
         let js_value = encode0(f.value().unwrap(), scope);
@@ -69,46 +72,46 @@ pub fn encode<'a>(f: &Frame, scope: &mut HandleScope<'a>) -> Local<'a, Object> {
     r
 }
 
-pub fn decode(v: v8::Local<v8::Value>, scope: &mut v8::HandleScope) -> Value {
-    fn decode0(v: &v8::Value, scope: &mut v8::HandleScope) -> Value {
-        match v {
-            v if v.is_null_or_undefined() => Value::None,
-            v if v.is_boolean() => Value::from(v.boolean_value(scope)),
-            v if v.is_int32() => Value::from(v.int32_value(scope)),
-            v if v.is_uint32() => Value::from(v.uint32_value(scope)),
-            v if v.is_big_int() => Value::from(v.integer_value(scope)),
-            v if v.is_number() => Value::from(v.number_value(scope)),
-            v if v.is_string() => Value::from(v.to_rust_string_lossy(scope)),
-            vec if vec.is_array() => {
-                let mut r = Vec::new();
-                let vec = vec.to_object(scope).unwrap(); /* array */
-                let length = codec::get_length_object(scope, &vec);
-                for i in 0..length {
-                    let v = vec.get_index(scope, i).unwrap();
-                    r.push(decode0(&v, scope));
-                }
-                Value::from(r)
+fn decode0(v: &v8::Value, scope: &mut v8::HandleScope) -> Value {
+    match v {
+        v if v.is_null_or_undefined() => Value::None,
+        v if v.is_boolean() => Value::from(v.boolean_value(scope)),
+        v if v.is_int32() => Value::from(v.int32_value(scope)),
+        v if v.is_uint32() => Value::from(v.uint32_value(scope)),
+        v if v.is_big_int() => Value::from(v.integer_value(scope)),
+        v if v.is_number() => Value::from(v.number_value(scope)),
+        v if v.is_string() => Value::from(v.to_rust_string_lossy(scope)),
+        vec if vec.is_array() => {
+            let mut r = Vec::new();
+            let vec = vec.to_object(scope).unwrap(); /* array */
+            let length = codec::get_length_object(scope, &vec);
+            for i in 0..length {
+                let v = vec.get_index(scope, i).unwrap();
+                r.push(decode0(&v, scope));
             }
-            obj if obj.is_object() => {
-                let mut r = Map::new();
-                let obj = obj.to_object(scope).unwrap();
-                let names = obj
-                    .get_own_property_names(scope, v8::GetPropertyNamesArgs::default())
-                    .unwrap();
-                let length = codec::get_length_array(scope, &names);
-                for i in 0..length {
-                    let n = names.get_index(scope, i).unwrap();
-                    let v = obj.get(scope, n.into()).unwrap();
-                    let map_key = n.to_rust_string_lossy(scope);
-                    let map_value = decode0(&v, scope);
-                    r.insert(map_key, map_value);
-                }
-                Value::from(r)
+            Value::from(r)
+        }
+        obj if obj.is_object() => {
+            let mut r = Map::new();
+            let obj = obj.to_object(scope).unwrap();
+            let names = obj
+                .get_own_property_names(scope, v8::GetPropertyNamesArgs::default())
+                .unwrap();
+            let length = codec::get_length_array(scope, &names);
+            for i in 0..length {
+                let n = names.get_index(scope, i).unwrap();
+                let v = obj.get(scope, n.into()).unwrap();
+                let map_key = n.to_rust_string_lossy(scope);
+                let map_value = decode0(&v, scope);
+                r.insert(map_key, map_value);
             }
-            _ => Value::None,
+            Value::from(r)
         }
+        _ => Value::None,
     }
+}
 
+pub fn decode(v: v8::Local<v8::Value>, scope: &mut v8::HandleScope) -> Value {
     let candidate = match v {
         obj if obj.is_object() => {
             let obj = v.to_object(scope).unwrap();
@@ -120,3 +123,20 @@ pub fn decode(v: v8::Local<v8::Value>, scope: &mut v8::HandleScope) -> Value {
 
     decode0(&candidate, scope)
 }
+
+/// Headers of the returned request, `None` if the request has no headers object.
+pub fn decode_headers(
+    v: v8::Local<v8::Value>,
+    scope: &mut v8::HandleScope,
+) -> Option<Map<String, Value>> {
+    if !v.is_object() {
+        return None;
+    }
+    let obj = v.to_object(scope).unwrap();
+    let js_key = v8::String::new(scope, "headers").unwrap().into();
+    let headers = obj.get(scope, js_key)?;
+    match decode0(&headers, scope) {
+        Value::Map(map) => Some(map),
+        _ => None,
+    }
+}
@@ -183,7 +183,8 @@ pub async fn js_function(
         let args = [arg];
 
         if let Some(result) = func.call(&mut scope, function_obj, &args) {
-            Ok(codec::decode(result, &mut scope))
+            let headers = codec::decode_headers(result, &mut scope);
+            Ok((codec::decode(result, &mut scope), headers))
         } else {
             let message = if let Some(exception) = scope.exception() {
                 let exception = exception.to_object(&mut scope).unwrap();
@@ -199,8 +200,9 @@ pub async fn js_function(
         }
     });
 
-    if let Ok(v) = r {
-        tx.send_ok(Frame::from_value(v)).await?;
+    if let Ok((v, headers)) = r {
+        let headers = headers.unwrap_or_else(|| req.headers().clone());
+        tx.send_ok(Frame::from_value(v).with_headers(headers)).await?;
     } else {
         return Err(r.err().unwrap());
     }
@@ -37,18 +37,24 @@ impl Service for LuaFunction {
         let code = ctx.config.code.clone();
         async move {
             let req_lua = req.clone();
-            let new_value = ctx
-                .raw
-                .context(|lua_ctx| {
-                    encode_and_set(&lua_ctx, req_lua)?;
-                    lua_ctx.load(&code).exec()?;
-                    let v = get_and_decode(&lua_ctx).unwrap();
-                    Result::<Value, LuaFunctionError>::Ok(v)
-                })
-                .map_err(|e| ServiceError::error(e))?;
+            let r = ctx.raw.context(|lua_ctx| {
+                encode_and_set(&lua_ctx, req_lua)?;
+                lua_ctx.load(&code).exec()?;
+                let v = get_and_decode(&lua_ctx).unwrap();
+                let headers = get_headers(&lua_ctx)?;
+                Result::<_, LuaFunctionError>::Ok((v, headers))
+            });
+            // the globals of lua are kept for the next request.
+            let (new_value, headers) = match r {
+                Ok(v) => v,
+                Err(e) => return Ok(ServiceContext::Failed(ctx, ServiceError::error(e))),
+            };
             match req.value_mut() {
                 Some(v) => {
                     *v = new_value;
+                    if let Some(headers) = headers {
+                        *req.headers_mut() = headers;
+                    }
                     tx.send_ok(req).await?;
                 }
                 None => {}
@@ -132,6 +138,11 @@ fn encode_and_set(lua_ctx: &rlua::Context, f: Frame) -> Result<(), LuaFunctionEr
     let toy = lua_ctx.create_table()?;
     let header = lua_ctx.create_table()?;
     header.set("port", f.port())?;
+    let headers = lua_ctx.create_table()?;
+    for (k, v) in f.headers().clone() {
+        headers.set(k, encode0(lua_ctx, v)?)?;
+    }
+    toy.set("headers", headers)?;
     let mut payload = rlua::Value::Nil;
     if f.value().is_some() {
         payload = encode0(lua_ctx, f.into_value().unwrap())?;
@@ -142,38 +153,39 @@ fn encode_and_set(lua_ctx: &rlua::Context, f: Frame) -> Result<(), LuaFunctionEr
     Ok(())
 }
 
-fn get_and_decode(lua_ctx: &rlua::Context) -> Result<Value, LuaFunctionError> {
-    fn decode0(lua_value: rlua::Value) -> Result<Value, LuaFunctionError> {
-        Ok(match lua_value {
-            rlua::Value::Table(rv) => {
-                let len = rv.len()?;
-                if len > 0 {
-                    let mut vec = Vec::with_capacity(len as usize);
-                    for element in rv.sequence_values::<rlua::Value>() {
-                        let element = element?;
-                        let v = decode0(element)?;
-                        vec.push(v);
-                    }
-                    Value::from(vec)
-                } else {
-                    let mut map = Map::new();
-                    for pair in rv.pairs::<rlua::Value, rlua::Value>() {
-                        let (k, v) = pair?;
-                        let k = decode0(k)?;
-                        let v = decode0(v)?;
-                        map.insert(k.parse_str().unwrap().to_owned(), v);
-                    }
-                    Value::from(map)
+fn decode0(lua_value: rlua::Value) -> Result<Value, LuaFunctionError> {
+    Ok(match lua_value {
+        rlua::Value::Table(rv) => {
+            let len = rv.len()?;
+            if len > 0 {
+                let mut vec = Vec::with_capacity(len as usize);
+                for element in rv.sequence_values::<rlua::Value>() {
+                    let element = element?;
+                    let v = decode0(element)?;
+                    vec.push(v);
                 }
+                Value::from(vec)
+            } else {
+                let mut map = Map::new();
+                for pair in rv.pairs::<rlua::Value, rlua::Value>() {
+                    let (k, v) = pair?;
+                    let k = decode0(k)?;
+                    let v = decode0(v)?;
+                    map.insert(k.parse_str().unwrap().to_owned(), v);
+                }
+                Value::from(map)
             }
-            rlua::Value::String(rv) => Value::from(rv.to_str()?),
-            rlua::Value::Integer(rv) => Value::from(rv),
-            rlua::Value::Number(rv) => Value::from(rv),
-            rlua::Value::Boolean(rv) => Value::from(rv),
-            rlua::Value::Nil => Value::None,
-            _ => Value::None,
-        })
-    }
+        }
+        rlua::Value::String(rv) => Value::from(rv.to_str()?),
+        rlua::Value::Integer(rv) => Value::from(rv),
+        rlua::Value::Number(rv) => Value::from(rv),
+        rlua::Value::Boolean(rv) => Value::from(rv),
+        rlua::Value::Nil => Value::None,
+        _ => Value::None,
+    })
+}
+
+fn get_and_decode(lua_ctx: &rlua::Context) -> Result<Value, LuaFunctionError> {
     let lua_value = lua_ctx.globals().get::<_, rlua::Value>("request")?;
     let candidate = match &lua_value {
         rlua::Value::Table(rv) => {
@@ -189,6 +201,19 @@ fn get_and_decode(lua_ctx: &rlua::Context) -> Result<Value, LuaFunctionError> {
     Ok(v)
 }
 
+/// Headers of the request, may be changed by the code.
+fn get_headers(lua_ctx: &rlua::Context) -> Result<Option<Map<String, Value>>, LuaFunctionError> {
+    let lua_value = lua_ctx.globals().get::<_, rlua::Value>("request")?;
+    let headers = match lua_value {
+        rlua::Value::Table(rv) => rv.get::<_, rlua::Value>("headers")?,
+        _ => return Ok(None),
+    };
+    match decode0(headers)? {
+        Value::Map(map) => Ok(Some(map)),
+        _ => Ok(None),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::get_and_decode;
@@ -1,3 +1,4 @@
+use toy_core::data::headers;
 use toy_core::prelude::*;
 use toy_plugin_lua::config::LuaFunctionConfig;
 use toy_plugin_lua::service::LuaFunction;
@@ -39,3 +40,74 @@ async fn test_lua_function() {
         expected.path("number").unwrap().parse_integer::<i64>()
     );
 }
+
+#[tokio::test]
+async fn test_lua_function_headers() {
+    let mut service = LuaFunction;
+    let (tx, mut rx) = toy_core::mpsc::channel(10);
+    let task_ctx = toy_plugin_test::dummy_task_context();
+    let code = r#"
+    request.payload.source = request.headers.source
+    request.headers.trace_id = "t-1"
+    "#;
+    let config = LuaFunctionConfig {
+        code: code.to_string(),
+    };
+
+    let c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+
+    let frame =
+        Frame::from_value(map_value! { "message" => "a" }).with_header(headers::SOURCE, "a.txt");
+
+    let r = service.handle(task_ctx, c, frame, tx).await;
+    assert!(r.is_ok());
+    let r = rx.next().await.unwrap();
+    assert_eq!(
+        r.value().unwrap().path("source"),
+        Some(&Value::from("a.txt"))
+    );
+    assert_eq!(r.header(headers::SOURCE), Some(&Value::from("a.txt")));
+    assert_eq!(r.header(headers::TRACE_ID), Some(&Value::from("t-1")));
+}
+
+#[tokio::test]
+async fn test_lua_function_error_keep_context() {
+    let mut service = LuaFunction;
+    let (tx, mut rx) = toy_core::mpsc::channel(10);
+    let code = r#"
+    count = (count or 0) + 1
+    if request.payload.fail then
+        error("fail")
+    end
+    request.payload.count = count
+    "#;
+    let config = LuaFunctionConfig {
+        code: code.to_string(),
+    };
+
+    let mut c = service
+        .new_context(toy_plugin_test::dummy_service_type(), config)
+        .await
+        .unwrap();
+
+    for fail in [false, true, false] {
+        let frame = Frame::from_value(map_value! { "fail" => fail });
+        let task_ctx = toy_plugin_test::dummy_task_context();
+        c = match service.handle(task_ctx, c, frame, tx.clone()).await {
+            Ok(ServiceContext::Failed(c, _)) if fail => c,
+            Ok(ServiceContext::Ready(c)) if !fail => c,
+            _ => panic!("unexpected result. fail:{}", fail),
+        };
+    }
+    drop(tx);
+
+    let mut counts = Vec::new();
+    while let Some(f) = rx.next().await {
+        counts.push(f.value().unwrap().path("count").cloned());
+    }
+    // the count of failed request is kept.
+    assert_eq!(counts, vec![Some(Value::from(1)), Some(Value::from(3))]);
+}
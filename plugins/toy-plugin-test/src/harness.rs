@@ -0,0 +1,406 @@
+//! Run a graph in process, to test services with the other services and the executor.
+//!
+//! Source nodes of the graph are replaced with [`Inject`] sending the given frames,
+//! and sink nodes are replaced with [`Capture`] collecting the received frames.
+//!
+//! ```ignore
+//! let r = Harness::new(toy_plugin_map::all(), graph)
+//!     .inject("source", vec![Value::from(1)])
+//!     .capture("sink")
+//!     .run();
+//! assert_eq!(r.outputs("sink"), &[Value::from(1)]);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use toy_core::data::{Frame, Value};
+use toy_core::error::ServiceError;
+use toy_core::executor::TaskExecutor;
+use toy_core::graph::Graph;
+use toy_core::mpsc::Outgoing;
+use toy_core::prelude::{app, layer, PortType, Registry};
+use toy_core::service::{Service, ServiceContext, ServiceFactory};
+use toy_core::task::{TaskContext, TaskId};
+use toy_core::{map_value, ServiceType};
+use toy_executor::Executor;
+use toy_pack::Schema;
+
+const NAME_SPACE: &str = "toy.plugin.test";
+
+/// Values by the node uri.
+type Store = Arc<Mutex<HashMap<String, Vec<Value>>>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Schema)]
+pub struct HarnessConfig {
+    /// Uri of the replaced node.
+    name: String,
+}
+
+/// Source service sending the frames given by [`Harness::inject`].
+#[derive(Clone, Debug, Default)]
+pub struct Inject {
+    frames: Store,
+}
+
+/// Sink service collecting the frames, for [`Harness::capture`].
+#[derive(Clone, Debug, Default)]
+pub struct Capture {
+    frames: Store,
+}
+
+pub struct HarnessContext {
+    name: String,
+}
+
+/// Builder of the graph run by the test.
+pub struct Harness<T> {
+    registry: T,
+    graph: Value,
+    inject: Store,
+    captures: Vec<String>,
+    paused: bool,
+    timeout: Option<Duration>,
+    drain: Option<Duration>,
+}
+
+/// Frames collected by the capture nodes, and the error of the task.
+#[derive(Debug)]
+pub struct HarnessResult {
+    outputs: HashMap<String, Vec<Value>>,
+    error: Option<ServiceError>,
+}
+
+impl<T> Harness<T>
+where
+    T: Registry + 'static,
+{
+    /// `graph` is the definition for [`Graph::from`].
+    pub fn new(registry: T, graph: Value) -> Harness<T> {
+        Harness {
+            registry,
+            graph,
+            inject: Store::default(),
+            captures: Vec::new(),
+            paused: false,
+            timeout: None,
+            drain: None,
+        }
+    }
+
+    /// Replace the node with the source sending the frames.
+    pub fn inject(self, uri: &str, frames: Vec<Value>) -> Harness<T> {
+        self.inject.lock().unwrap().insert(uri.to_string(), frames);
+        self
+    }
+
+    /// Replace the node with the sink collecting the received frames.
+    pub fn capture(mut self, uri: &str) -> Harness<T> {
+        self.captures.push(uri.to_string());
+        self
+    }
+
+    /// Start the runtime with the paused time, the time advances automatically when idle.
+    pub fn paused(self, paused: bool) -> Harness<T> {
+        Harness { paused, ..self }
+    }
+
+    /// Stop the task if not finished within the time.
+    pub fn timeout(self, timeout: Duration) -> Harness<T> {
+        Harness {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Send the drain signal to the first nodes after the time, the timeout is counted after it.
+    pub fn drain(self, after: Duration) -> Harness<T> {
+        Harness {
+            drain: Some(after),
+            ..self
+        }
+    }
+
+    /// Run the graph on a current-thread runtime until the task finished.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph is invalid, or the injected or captured node is not in the graph.
+    pub fn run(self) -> HarnessResult {
+        let injects = self
+            .inject
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        let graph = replace_nodes(self.graph, &injects, &self.captures);
+        let graph = Graph::from(graph).expect("invalid graph.");
+
+        let capture = Capture::default();
+        let outputs = Arc::clone(&capture.frames);
+        let app = app(self.registry)
+            .with(
+                layer((
+                    NAME_SPACE,
+                    "inject",
+                    Inject {
+                        frames: self.inject,
+                    },
+                ))
+                .layer((NAME_SPACE, "capture", capture)),
+            )
+            .build();
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .start_paused(self.paused)
+            .build()
+            .unwrap();
+        let timeout = self.timeout;
+        let drain = self.drain;
+        let r = rt.block_on(async move {
+            let ctx = TaskContext::new(TaskId::new(), graph);
+            let (executor, mut signals) = Executor::new(ctx);
+            let task = executor.run(&app, Frame::default());
+            tokio::pin!(task);
+            if let Some(d) = drain {
+                tokio::select! {
+                    r = &mut task => return r,
+                    _ = tokio::time::sleep(d) => {
+                        for (_, tx) in signals.starters_mut() {
+                            let _ = tx.send_ok(Frame::drain()).await;
+                        }
+                    }
+                }
+            }
+            match timeout {
+                Some(t) => {
+                    tokio::select! {
+                        r = &mut task => r,
+                        _ = tokio::time::sleep(t) => {
+                            for (_, tx) in signals.iter_mut() {
+                                for p in tx.ports() {
+                                    let _ = tx.send_ok_to(p, Frame::stop()).await;
+                                }
+                            }
+                            task.await.and(Err(ServiceError::error("timeout.")))
+                        }
+                    }
+                }
+                None => task.await,
+            }
+        });
+
+        let mut outputs = std::mem::take(&mut *outputs.lock().unwrap());
+        for c in self.captures {
+            outputs.entry(c).or_default();
+        }
+        HarnessResult {
+            outputs,
+            error: r.err(),
+        }
+    }
+}
+
+impl HarnessResult {
+    /// Frames received by the captured node, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is not captured.
+    pub fn outputs(&self, uri: &str) -> &[Value] {
+        self.outputs
+            .get(uri)
+            .unwrap_or_else(|| panic!("not captured node. {}", uri))
+    }
+
+    pub fn error(&self) -> Option<&ServiceError> {
+        self.error.as_ref()
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+fn replace_nodes(mut graph: Value, injects: &[String], captures: &[String]) -> Value {
+    let services = match &mut graph {
+        Value::Map(map) => match map.get_mut("services") {
+            Some(Value::Seq(services)) => services,
+            _ => panic!("not found services."),
+        },
+        _ => panic!("graph must be map."),
+    };
+    for uri in injects.iter().chain(captures) {
+        let node = services
+            .iter_mut()
+            .filter_map(|x| match x {
+                Value::Map(node) => Some(node),
+                _ => None,
+            })
+            .find(|x| x.get("uri").and_then(|u| u.as_str()) == Some(uri.as_str()))
+            .unwrap_or_else(|| panic!("not found node. {}", uri));
+        let service = if injects.contains(uri) {
+            "inject"
+        } else {
+            node.insert("wires".to_string(), Value::None);
+            "capture"
+        };
+        node.insert(
+            "type".to_string(),
+            Value::from(format!("{}.{}", NAME_SPACE, service)),
+        );
+        node.insert("config".to_string(), map_value! { "name" => uri.as_str() });
+    }
+    graph
+}
+
+impl Service for Inject {
+    type Context = HarnessContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<HarnessContext>, ServiceError>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<HarnessContext>, ServiceError>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<HarnessContext>, ServiceError>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::source()
+    }
+
+    fn handle(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        let frames = self
+            .frames
+            .lock()
+            .unwrap()
+            .remove(&ctx.name)
+            .unwrap_or_default();
+        async move {
+            for v in frames {
+                tx.send_ok(Frame::from_value(v)).await?;
+            }
+            Ok(ServiceContext::Complete(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move { Ok(ServiceContext::Complete(ctx)) }
+    }
+}
+
+impl Service for Capture {
+    type Context = HarnessContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<HarnessContext>, ServiceError>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<HarnessContext>, ServiceError>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<HarnessContext>, ServiceError>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::sink()
+    }
+
+    fn handle(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        if let Some(v) = req.value() {
+            self.frames
+                .lock()
+                .unwrap()
+                .entry(ctx.name.clone())
+                .or_default()
+                .push(v.clone());
+        }
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move { Ok(ServiceContext::Complete(ctx)) }
+    }
+}
+
+impl ServiceFactory for Inject {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Inject;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = HarnessContext;
+    type Config = HarnessConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        let service = self.clone();
+        async move { Ok(service) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
+        async move { Ok(HarnessContext { name: config.name }) }
+    }
+}
+
+impl ServiceFactory for Capture {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Capture;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = HarnessContext;
+    type Config = HarnessConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        let service = self.clone();
+        async move { Ok(service) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
+        async move { Ok(HarnessContext { name: config.name }) }
+    }
+}
@@ -1,3 +1,7 @@
+#![feature(impl_trait_in_assoc_type)]
+
+pub mod harness;
+
 use toy_core::data::Value;
 use toy_core::graph::Graph;
 use toy_core::task::{TaskContext, TaskId};
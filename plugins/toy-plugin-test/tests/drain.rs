@@ -0,0 +1,214 @@
+#![feature(impl_trait_in_assoc_type)]
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+use toy_core::prelude::*;
+use toy_pack::Schema;
+use toy_plugin_test::harness::{Harness, HarnessResult};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Schema)]
+struct TestConfig {}
+
+/// Source sending 1, 2, 3... every 10 milliseconds, never completes.
+#[derive(Debug, Clone)]
+struct Endless;
+
+struct EndlessContext {
+    next: u32,
+}
+
+/// Flow holding the frames, and sending them as a seq when all upstreams finished.
+#[derive(Debug, Clone)]
+struct Hold;
+
+struct HoldContext {
+    frames: Vec<Value>,
+}
+
+impl Service for Endless {
+    type Context = EndlessContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::source()
+    }
+
+    fn handle(
+        &mut self,
+        _task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        _req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            tx.send_ok(Frame::from(ctx.next)).await?;
+            ctx.next += 1;
+            Ok(ServiceContext::Next(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move { Ok(ServiceContext::Complete(ctx)) }
+    }
+}
+
+impl ServiceFactory for Endless {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Endless;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = EndlessContext;
+    type Config = TestConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Endless) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, _config: Self::Config) -> Self::CtxFuture {
+        async move { Ok(EndlessContext { next: 1 }) }
+    }
+}
+
+impl Service for Hold {
+    type Context = HoldContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::flow()
+    }
+
+    fn handle(
+        &mut self,
+        _task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            if let Some(v) = req.into_value() {
+                ctx.frames.push(v);
+            }
+            Ok(ServiceContext::Ready(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move {
+            let frames = std::mem::take(&mut ctx.frames);
+            tx.send_ok(Frame::from_value(Value::Seq(frames))).await?;
+            Ok(ServiceContext::Complete(ctx))
+        }
+    }
+}
+
+impl ServiceFactory for Hold {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Hold;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = HoldContext;
+    type Config = TestConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Hold) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, _config: Self::Config) -> Self::CtxFuture {
+        async move { Ok(HoldContext { frames: Vec::new() }) }
+    }
+}
+
+fn node(tp: &str, uri: &str, wires: Value) -> Value {
+    map_value! {
+        "type" => tp,
+        "uri" => uri,
+        "config" => map_value! {},
+        "wires" => wires,
+    }
+}
+
+/// `source` -> `hold` -> `sink`, drained after the time.
+fn run(drain: Duration) -> HarnessResult {
+    let graph = map_value! {
+        "name" => "drain",
+        "services" => seq_value![
+            node("test.endless", "source", Value::from("hold")),
+            node("test.hold", "hold", Value::from("sink")),
+            node("test.hold", "sink", Value::None)
+        ]
+    };
+    let registry = layer(("test", "endless", Endless)).layer(("test", "hold", Hold));
+    Harness::new(registry, graph)
+        .capture("sink")
+        .paused(true)
+        .drain(drain)
+        .timeout(Duration::from_secs(10))
+        .run()
+}
+
+#[test]
+fn drain_running_source() {
+    let r = run(Duration::from_millis(1000));
+    assert!(r.is_ok(), "{:?}", r.error());
+
+    // the frames sent before the drain are flushed by `upstream_finish_all` of the flow.
+    let outputs = r.outputs("sink");
+    assert_eq!(outputs.len(), 1);
+    let frames = match &outputs[0] {
+        Value::Seq(frames) => frames.clone(),
+        v => panic!("unexpected output. {:?}", v),
+    };
+    assert!(frames.len() >= 90, "{}", frames.len());
+    let expected = (1..=frames.len() as u32)
+        .map(Value::from)
+        .collect::<Vec<_>>();
+    assert_eq!(frames, expected);
+}
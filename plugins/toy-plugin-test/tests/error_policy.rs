@@ -0,0 +1,403 @@
+#![feature(impl_trait_in_assoc_type)]
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+use toy_core::graph::ErrorPolicy;
+use toy_core::prelude::*;
+use toy_pack::Schema;
+use toy_plugin_test::harness::{Harness, HarnessResult};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Schema)]
+struct TestConfig {
+    #[serde(default)]
+    end: u32,
+}
+
+/// Flow counting the processed frames.
+///
+/// A frame with `"fail" => n` fails the first n times with the context kept,
+/// and a frame with `"lost" => true` fails with the context lost.
+/// The context is created again after lost only if `stateless`.
+/// Only the `fail` policy is accepted if `fail_only`.
+#[derive(Debug, Clone)]
+struct Counter {
+    stateless: bool,
+    fail_only: bool,
+}
+
+struct CounterContext {
+    count: u32,
+    failed: u32,
+}
+
+/// Source sending 1 to `end`, fails once before sending 2.
+#[derive(Debug, Clone)]
+struct Numbers;
+
+struct NumbersContext {
+    next: u32,
+    end: u32,
+    failed: bool,
+}
+
+impl Service for Counter {
+    type Context = CounterContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::fan_out_flow(2)
+    }
+
+    fn handle(
+        &mut self,
+        _task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            let v = req.value().cloned().unwrap_or(Value::None);
+            if v.path("lost").is_some() {
+                return Err(ServiceError::error("lost."));
+            }
+            let fail = v
+                .path("fail")
+                .and_then(|x| x.parse_integer::<u32>())
+                .unwrap_or(0);
+            if ctx.failed < fail {
+                ctx.failed += 1;
+                return Ok(ServiceContext::Failed(ctx, ServiceError::error("failed.")));
+            }
+            ctx.failed = 0;
+            ctx.count += 1;
+            let id = v.path("id").cloned().unwrap_or(Value::None);
+            tx.send_ok(Frame::from_value(
+                map_value! { "id" => id, "count" => ctx.count },
+            ))
+            .await?;
+            Ok(ServiceContext::Ready(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move { Ok(ServiceContext::Complete(ctx)) }
+    }
+}
+
+impl ServiceFactory for Counter {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Counter;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = CounterContext;
+    type Config = TestConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        let counter = self.clone();
+        async move { Ok(counter) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, _config: Self::Config) -> Self::CtxFuture {
+        async move {
+            Ok(CounterContext {
+                count: 0,
+                failed: 0,
+            })
+        }
+    }
+
+    fn is_stateless(&self) -> bool {
+        self.stateless
+    }
+
+    fn accepts_error_policy(&self, policy: &ErrorPolicy) -> bool {
+        !self.fail_only || *policy == ErrorPolicy::Fail
+    }
+}
+
+impl Service for Numbers {
+    type Context = NumbersContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::fan_out_source(2)
+    }
+
+    fn handle(
+        &mut self,
+        _task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        _req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            if ctx.next == 2 && !ctx.failed {
+                ctx.failed = true;
+                return Ok(ServiceContext::Failed(ctx, ServiceError::error("failed.")));
+            }
+            tx.send_ok(Frame::from(ctx.next)).await?;
+            ctx.next += 1;
+            if ctx.next > ctx.end {
+                Ok(ServiceContext::Complete(ctx))
+            } else {
+                Ok(ServiceContext::Next(ctx))
+            }
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move { Ok(ServiceContext::Complete(ctx)) }
+    }
+}
+
+impl ServiceFactory for Numbers {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Numbers;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = NumbersContext;
+    type Config = TestConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Numbers) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, config: Self::Config) -> Self::CtxFuture {
+        async move {
+            Ok(NumbersContext {
+                next: 1,
+                end: config.end,
+                failed: false,
+            })
+        }
+    }
+}
+
+fn node(tp: &str, uri: &str, wires: Value) -> Value {
+    map_value! {
+        "type" => tp,
+        "uri" => uri,
+        "config" => map_value! { "end" => 3 },
+        "wires" => wires,
+    }
+}
+
+/// `source` -> `target` (with the policy) -> `sink`, and `dead` for the dead letter.
+fn run(tp: &str, on_error: Value, inject: Option<Vec<Value>>) -> HarnessResult {
+    let mut target = node(tp, "target", seq_value!["sink", "dead"]);
+    target.insert_by_path("on_error", on_error);
+    let mut services = vec![
+        target,
+        node("test.counter", "sink", Value::None),
+        node("test.counter", "dead", Value::None),
+    ];
+    if inject.is_some() {
+        services.push(node("test.counter", "source", Value::from("target")));
+    }
+    let graph = map_value! { "name" => "error_policy", "services" => Value::Seq(services) };
+    let counter = Counter {
+        stateless: false,
+        fail_only: false,
+    };
+    let registry = layer(("test", "counter", counter.clone()))
+        .layer((
+            "test",
+            "stateless",
+            Counter {
+                stateless: true,
+                ..counter.clone()
+            },
+        ))
+        .layer((
+            "test",
+            "fail_only",
+            Counter {
+                fail_only: true,
+                ..counter
+            },
+        ))
+        .layer(("test", "numbers", Numbers));
+    let harness = Harness::new(registry, graph);
+    let harness = match inject {
+        Some(frames) => harness.inject("source", frames),
+        None => harness,
+    };
+    harness
+        .capture("sink")
+        .capture("dead")
+        .paused(true)
+        .timeout(Duration::from_secs(10))
+        .run()
+}
+
+fn frames() -> Vec<Value> {
+    vec![
+        map_value! { "id" => 1 },
+        map_value! { "id" => 2, "fail" => 1 },
+        map_value! { "id" => 3 },
+    ]
+}
+
+fn counted(v: &[(i32, u32)]) -> Vec<Value> {
+    v.iter()
+        .map(|(id, count)| map_value! { "id" => *id, "count" => *count })
+        .collect()
+}
+
+fn numbers() -> Vec<Value> {
+    vec![Value::from(1u32), Value::from(2u32), Value::from(3u32)]
+}
+
+fn retry() -> Value {
+    map_value! { "retry" => map_value! { "max" => 1, "backoff_mills" => 10 } }
+}
+
+fn dead_letter() -> Value {
+    map_value! { "dead_letter" => map_value! { "port" => 1 } }
+}
+
+#[test]
+fn flow_fail() {
+    let r = run("test.counter", Value::from("fail"), Some(frames()));
+    assert!(r.error().unwrap().to_string().contains("failed."));
+}
+
+#[test]
+fn flow_skip_keeps_context() {
+    let r = run("test.counter", Value::from("skip"), Some(frames()));
+    assert!(r.is_ok(), "{:?}", r.error());
+    assert_eq!(r.outputs("sink"), counted(&[(1, 1), (3, 2)]));
+}
+
+#[test]
+fn flow_skip_renews_lost_context() {
+    let frames = vec![
+        map_value! { "id" => 1 },
+        map_value! { "id" => 2, "lost" => true },
+        map_value! { "id" => 3 },
+    ];
+    let r = run("test.stateless", Value::from("skip"), Some(frames));
+    assert!(r.is_ok(), "{:?}", r.error());
+    assert_eq!(r.outputs("sink"), counted(&[(1, 1), (3, 1)]));
+}
+
+#[test]
+fn flow_skip_fails_on_lost_context() {
+    let frames = vec![
+        map_value! { "id" => 1 },
+        map_value! { "id" => 2, "lost" => true },
+        map_value! { "id" => 3 },
+    ];
+    let r = run("test.counter", Value::from("skip"), Some(frames));
+    assert!(r.error().unwrap().to_string().contains("lost."));
+}
+
+#[test]
+fn flow_retry_keeps_context() {
+    let r = run("test.counter", retry(), Some(frames()));
+    assert!(r.is_ok(), "{:?}", r.error());
+    assert_eq!(r.outputs("sink"), counted(&[(1, 1), (2, 2), (3, 3)]));
+}
+
+#[test]
+fn flow_dead_letter_keeps_context() {
+    let r = run("test.counter", dead_letter(), Some(frames()));
+    assert!(r.is_ok(), "{:?}", r.error());
+    assert_eq!(r.outputs("sink"), counted(&[(1, 1), (3, 2)]));
+    let dead = r.outputs("dead");
+    assert_eq!(dead.len(), 1);
+    assert_eq!(
+        dead[0].path("uri"),
+        Some(&Value::from("error_policy/target"))
+    );
+    assert_eq!(dead[0].path("frame.id"), Some(&Value::from(2)));
+}
+
+#[test]
+fn flow_fail_only_accepts_fail() {
+    let r = run("test.fail_only", Value::from("fail"), Some(frames()));
+    assert!(r.error().unwrap().to_string().contains("failed."));
+}
+
+#[test]
+fn flow_fail_only_rejects_skip() {
+    let r = run("test.fail_only", Value::from("skip"), Some(frames()));
+    let e = r.error().unwrap().to_string();
+    assert!(e.contains("is not supported by the service."), "{}", e);
+    assert!(r.outputs("sink").is_empty());
+}
+
+#[test]
+fn source_fail() {
+    let r = run("test.numbers", Value::from("fail"), None);
+    assert!(r.error().unwrap().to_string().contains("failed."));
+}
+
+#[test]
+fn source_skip_resumes() {
+    let r = run("test.numbers", Value::from("skip"), None);
+    assert!(r.is_ok(), "{:?}", r.error());
+    assert_eq!(r.outputs("sink"), numbers());
+}
+
+#[test]
+fn source_retry_resumes() {
+    let r = run("test.numbers", retry(), None);
+    assert!(r.is_ok(), "{:?}", r.error());
+    assert_eq!(r.outputs("sink"), numbers());
+}
+
+#[test]
+fn source_dead_letter_resumes() {
+    let r = run("test.numbers", dead_letter(), None);
+    assert!(r.is_ok(), "{:?}", r.error());
+    assert_eq!(r.outputs("sink"), numbers());
+    assert_eq!(r.outputs("dead").len(), 1);
+}
@@ -0,0 +1,281 @@
+#![feature(impl_trait_in_assoc_type)]
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use toy_core::prelude::*;
+use toy_pack::Schema;
+use toy_plugin_test::harness::{Harness, HarnessResult};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Schema)]
+struct TestConfig {}
+
+/// Flow waiting for `"delay"` milliseconds of the frame,
+/// and sending the frame with the index of the replica as `"replica"` if replicated.
+#[derive(Debug, Clone)]
+struct Delay;
+
+/// Flow holding the frames, and sending them as a seq when all upstreams finished.
+#[derive(Debug, Clone)]
+struct Hold;
+
+struct HoldContext {
+    frames: Vec<Value>,
+}
+
+impl Service for Delay {
+    type Context = ();
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::flow()
+    }
+
+    fn handle(
+        &mut self,
+        task_ctx: TaskContext,
+        ctx: Self::Context,
+        req: Self::Request,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            let mut v = req.value().cloned().unwrap_or(Value::None);
+            let delay = v
+                .path("delay")
+                .and_then(|x| x.parse_integer::<u64>())
+                .unwrap_or(0);
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            if let Some(idx) = task_ctx.replica() {
+                v.insert_by_path("replica", Value::from(idx as u64));
+            }
+            tx.send_ok(Frame::from_value(v)).await?;
+            Ok(ServiceContext::Ready(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move { Ok(ServiceContext::Complete(ctx)) }
+    }
+}
+
+impl ServiceFactory for Delay {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Delay;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = ();
+    type Config = TestConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Delay) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, _config: Self::Config) -> Self::CtxFuture {
+        async move { Ok(()) }
+    }
+}
+
+impl Service for Hold {
+    type Context = HoldContext;
+    type Request = Frame;
+    type Future = impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type UpstreamFinishFuture =
+        impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type UpstreamFinishAllFuture =
+        impl Future<Output = Result<ServiceContext<Self::Context>, Self::Error>> + Send;
+    type Error = ServiceError;
+
+    fn port_type() -> PortType {
+        PortType::flow()
+    }
+
+    fn handle(
+        &mut self,
+        _task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::Future {
+        async move {
+            if let Some(v) = req.into_value() {
+                ctx.frames.push(v);
+            }
+            Ok(ServiceContext::Ready(ctx))
+        }
+    }
+
+    fn upstream_finish(
+        &mut self,
+        _task_ctx: TaskContext,
+        ctx: Self::Context,
+        _req: Self::Request,
+        _tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishFuture {
+        async move { Ok(ServiceContext::Ready(ctx)) }
+    }
+
+    fn upstream_finish_all(
+        &mut self,
+        _task_ctx: TaskContext,
+        mut ctx: Self::Context,
+        mut tx: Outgoing<Self::Request>,
+    ) -> Self::UpstreamFinishAllFuture {
+        async move {
+            let frames = std::mem::take(&mut ctx.frames);
+            tx.send_ok(Frame::from_value(Value::Seq(frames))).await?;
+            Ok(ServiceContext::Complete(ctx))
+        }
+    }
+}
+
+impl ServiceFactory for Hold {
+    type Future = impl Future<Output = Result<Self::Service, Self::InitError>> + Send;
+    type Service = Hold;
+    type CtxFuture = impl Future<Output = Result<Self::Context, Self::InitError>> + Send;
+    type Context = HoldContext;
+    type Config = TestConfig;
+    type Request = Frame;
+    type Error = ServiceError;
+    type InitError = ServiceError;
+
+    fn new_service(&self, _tp: ServiceType) -> Self::Future {
+        async move { Ok(Hold) }
+    }
+
+    fn new_context(&self, _tp: ServiceType, _config: Self::Config) -> Self::CtxFuture {
+        async move { Ok(HoldContext { frames: Vec::new() }) }
+    }
+}
+
+fn node(tp: &str, uri: &str, wires: Value) -> Value {
+    map_value! {
+        "type" => tp,
+        "uri" => uri,
+        "config" => map_value! {},
+        "wires" => wires,
+    }
+}
+
+/// `source` -> `target` (replicated by the parallelism) -> `downstream`.
+fn run(parallelism: Value, downstream: &str, frames: Vec<Value>) -> HarnessResult {
+    let mut target = node("test.delay", "target", Value::from("downstream"));
+    target.insert_by_path("parallelism", parallelism);
+    let services = vec![
+        node("test.delay", "source", Value::from("target")),
+        target,
+        node(downstream, "downstream", Value::from("sink")),
+        node("test.delay", "sink", Value::None),
+    ];
+    let graph = map_value! { "name" => "parallel", "services" => Value::Seq(services) };
+    let registry = layer(("test", "delay", Delay)).layer(("test", "hold", Hold));
+    Harness::new(registry, graph)
+        .inject("source", frames)
+        .capture("sink")
+        .paused(true)
+        .timeout(Duration::from_secs(10))
+        .run()
+}
+
+/// The later input finishes earlier.
+fn delayed(count: u32) -> Vec<Value> {
+    (0..count)
+        .map(|id| map_value! { "id" => id, "delay" => (count - id) * 10 })
+        .collect()
+}
+
+fn ids(outputs: &[Value]) -> Vec<u32> {
+    outputs
+        .iter()
+        .map(|x| x.path("id").and_then(|x| x.parse_integer::<u32>()).unwrap())
+        .collect()
+}
+
+#[test]
+fn ordered_keeps_input_order() {
+    let parallelism = map_value! { "replicas" => 4, "ordered" => true };
+    let r = run(parallelism, "test.delay", delayed(8));
+    assert!(r.is_ok(), "{:?}", r.error());
+    assert_eq!(ids(r.outputs("sink")), (0..8).collect::<Vec<_>>());
+}
+
+#[test]
+fn unordered_sends_in_finished_order() {
+    let parallelism = map_value! { "replicas" => 4 };
+    let r = run(parallelism, "test.delay", delayed(8));
+    assert!(r.is_ok(), "{:?}", r.error());
+
+    let mut ids = ids(r.outputs("sink"));
+    assert_ne!(ids, (0..8).collect::<Vec<_>>());
+    ids.sort();
+    assert_eq!(ids, (0..8).collect::<Vec<_>>());
+}
+
+#[test]
+fn key_hash_sends_each_key_to_one_replica() {
+    let parallelism = map_value! {
+        "replicas" => 4,
+        "dispatch" => map_value! { "key_hash" => map_value! { "key" => "key" } },
+    };
+    let frames = (0..40u32)
+        .map(|id| map_value! { "id" => id, "key" => format!("k{}", id % 5), "delay" => id % 3 })
+        .collect::<Vec<_>>();
+    let r = run(parallelism, "test.delay", frames);
+    assert!(r.is_ok(), "{:?}", r.error());
+
+    let outputs = r.outputs("sink");
+    assert_eq!(outputs.len(), 40);
+    let mut replicas = HashMap::new();
+    for v in outputs {
+        let key = v.path("key").unwrap().as_str().unwrap().to_string();
+        let replica = v.path("replica").and_then(|x| x.parse_integer::<u64>());
+        assert!(replica.is_some(), "{:?}", v);
+        let first = *replicas.entry(key.clone()).or_insert(replica);
+        assert_eq!(
+            first, replica,
+            "key {} is handled by the other replica.",
+            key
+        );
+    }
+    assert_eq!(replicas.len(), 5);
+}
+
+#[test]
+fn downstream_flushes_once_after_all_replicas() {
+    let parallelism = map_value! { "replicas" => 4 };
+    let r = run(parallelism, "test.hold", delayed(8));
+    assert!(r.is_ok(), "{:?}", r.error());
+
+    // the upstream finish is sent to the downstream once, after the slowest replica finished.
+    let outputs = r.outputs("sink");
+    assert_eq!(outputs.len(), 1, "{:?}", outputs);
+    let mut ids = match &outputs[0] {
+        Value::Seq(frames) => ids(frames),
+        v => panic!("unexpected output. {:?}", v),
+    };
+    ids.sort();
+    assert_eq!(ids, (0..8).collect::<Vec<_>>());
+}
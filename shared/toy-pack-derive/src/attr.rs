@@ -58,6 +58,8 @@ pub struct FieldAttr {
     /// skip ser and deser
     pub ignore: bool,
     pub default: DefaultExpr,
+    /// `#[toy(default)]` or `#[serde(default)]` is specified.
+    pub has_default: bool,
     pub borrowed_lifetimes: BTreeSet<syn::Lifetime>,
     pub rename: RenameExpr,
     pub flatten: bool,
@@ -110,9 +112,12 @@ impl FieldAttr {
         let mut lifetimes = BTreeSet::new();
         collect_lifetimes(&field.ty, &mut lifetimes);
 
+        let has_default = default.is_some() || has_serde_default(&field.attrs);
+
         let r = FieldAttr {
             ignore: ignore.unwrap_or(false),
             default: default.unwrap_or(DefaultExpr::Default),
+            has_default,
             borrowed_lifetimes: lifetimes,
             rename: rename.unwrap_or(RenameExpr::Default),
             flatten: flatten.unwrap_or(false),
@@ -231,6 +236,24 @@ fn get_meta_items(attr: &Attribute) -> Option<Vec<NestedMeta>> {
     }
 }
 
+fn has_serde_default(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.segments.len() == 1 && attr.path.segments[0].ident == "serde")
+        .filter_map(|attr| match attr.interpret_meta() {
+            Some(List(ref meta)) => Some(meta.nested.iter().cloned().collect::<Vec<_>>()),
+            _ => None,
+        })
+        .flatten()
+        .any(|item| match item {
+            // #[serde(default)]
+            Meta(Word(ref w)) => w == "default",
+            // #[serde(default = "...")]
+            Meta(NameValue(ref m)) => m.ident == "default",
+            _ => false,
+        })
+}
+
 fn parse_lit_into_expr_path(
     attr_name: &syn::Ident,
     lit: &syn::Lit,
@@ -2,7 +2,7 @@ use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 use syn::DeriveInput;
 
-use super::ast::{model_from_ast, Data, Model, Style};
+use super::ast::{model_from_ast, Data, Field, Model, Style};
 
 pub fn derive_schema_core(input: DeriveInput) -> Result<TokenStream, Vec<syn::Error>> {
     let body = match body(&input) {
@@ -108,7 +108,7 @@ fn body_enum(target: &Model) -> Result<TokenStream, syn::Error> {
                         .filter(|(_, field)| !field.attr.ignore)
                         .map(|(i, f)| {
                             let idx = i as u32;
-                            let tp = f.ty;
+                            let tp = schema_type(f);
                             let member_name_str = &f.pack_field_name();
                             quote! {
                                 struct_visitor.field::<#tp>(#name_str, #variant_name_str, #idx, #member_name_str)?;
@@ -150,7 +150,7 @@ fn body_struct(target: &Model) -> Result<TokenStream, syn::Error> {
                 .filter(|x| !x.attr.ignore)
                 .map(|field| {
                     let name_str = field.pack_field_name();
-                    let tp = *&field.ty;
+                    let tp = schema_type(field);
                     quote! {
                         struct_visitor.field::<#tp>(#name_str)?;
                     }
@@ -176,6 +176,16 @@ fn body_struct(target: &Model) -> Result<TokenStream, syn::Error> {
     }
 }
 
+/// A field that has a default value is scanned as `Option`, because it is not required.
+fn schema_type(field: &Field) -> TokenStream {
+    let tp = field.ty;
+    if field.attr.has_default {
+        quote!(toy_pack::export::Option<#tp>)
+    } else {
+        quote!(#tp)
+    }
+}
+
 fn member_name(i: usize) -> Ident {
     Ident::new(&format!("__field__{}", i), Span::call_site())
 }
@@ -48,6 +48,13 @@ impl Client {
         }
     }
 
+    pub fn delete<K: AsRef<[u8]>>(&self, k: K) -> Result<(), RocksError> {
+        match self.db.cf_handle(&self.current_cf) {
+            Some(ref h) => self.db.delete_cf(h, k).map_err(|e| e.into()),
+            None => Err(RocksError::error("column family handle not found.")),
+        }
+    }
+
     pub fn put_batch<K: AsRef<[u8]>, V: AsRef<[u8]>>(
         &self,
         values: &[(K, V)],
@@ -28,3 +28,12 @@ fn iterator() {
         assert_eq!(v.as_ref(), &data[idx].1[..]);
     }
 }
+
+#[test]
+fn delete() {
+    let c = util::setup("delete");
+    c.put(b"a", b"111").unwrap();
+    c.delete(b"a").unwrap();
+
+    assert!(c.get("a").unwrap().is_none());
+}
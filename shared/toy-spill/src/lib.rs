@@ -0,0 +1,110 @@
+//! Files of the entries saved when the buffer of a service is full.
+//!
+//! The entries are partitioned by the key, so the entries of the same key are read
+//! together from one file, and a partition at a time is kept on memory.
+
+use bytes::Bytes;
+use futures_util::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{hash_map, HashMap};
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use tokio::fs::{File, OpenOptions};
+use tokio_util::codec::{Decoder, Framed, LengthDelimitedCodec};
+
+/// Default count of the files.
+pub const DEFAULT_PARTITIONS: usize = 16;
+
+/// Files of the entries, named `{name}-{partition}` in the directory.
+#[derive(Debug)]
+pub struct Spill {
+    paths: Vec<PathBuf>,
+}
+
+impl Spill {
+    pub fn new(dir: &Path, name: &str) -> Self {
+        Self::with_partitions(dir, name, DEFAULT_PARTITIONS)
+    }
+
+    pub fn with_partitions(dir: &Path, name: &str, partitions: usize) -> Self {
+        let paths = (0..partitions.max(1))
+            .map(|x| dir.join(format!("{}-{}", name, x)))
+            .collect();
+        Self { paths }
+    }
+
+    pub fn partitions(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Returns the partition of the key.
+    ///
+    /// The partition is stable only in the same process, the files are not shared with others.
+    pub fn partition<K: Debug + ?Sized>(&self, key: &K) -> usize {
+        let mut hasher = DefaultHasher::new();
+        format!("{:?}", key).hash(&mut hasher);
+        (hasher.finish() % self.paths.len() as u64) as usize
+    }
+
+    /// Append the entries to the files of their partitions.
+    pub async fn write<T, I>(&self, entries: I) -> Result<(), Error>
+    where
+        T: Serialize,
+        I: IntoIterator<Item = (usize, T)>,
+    {
+        let mut writers: HashMap<usize, Framed<File, LengthDelimitedCodec>> = HashMap::new();
+        for (p, entry) in entries {
+            let writer = match writers.entry(p) {
+                hash_map::Entry::Occupied(e) => e.into_mut(),
+                hash_map::Entry::Vacant(e) => {
+                    let f = OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(&self.paths[p])
+                        .await?;
+                    e.insert(Framed::new(f, LengthDelimitedCodec::new()))
+                }
+            };
+            let v = toy_pack_mp::pack(&entry).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            writer.send(Bytes::from(v)).await?;
+        }
+        Ok(())
+    }
+
+    /// Read the entries of the partition in the written order.
+    pub async fn read<T, F>(&self, partition: usize, mut f: F) -> Result<(), Error>
+    where
+        T: DeserializeOwned,
+        F: FnMut(T),
+    {
+        let file = match File::open(&self.paths[partition]).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let mut reader = LengthDelimitedCodec::new().framed(file);
+        while let Some(item) = reader.next().await {
+            let bytes = item?;
+            let entry = toy_pack_mp::unpack::<T>(&bytes)
+                .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            f(entry);
+        }
+        Ok(())
+    }
+
+    /// Remove all files.
+    pub async fn remove(&self) -> Result<(), Error> {
+        for p in &self.paths {
+            match tokio::fs::remove_file(p).await {
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use tempdir::TempDir;
+use toy_spill::Spill;
+
+#[tokio::test]
+async fn write_and_read_by_partition() {
+    let dir = TempDir::new("toy-spill-test").unwrap();
+    let spill = Spill::with_partitions(dir.path(), "test", 4);
+
+    let entries = [("a", 1u32), ("b", 2), ("a", 3), ("c", 4)];
+    spill
+        .write(entries.iter().map(|x| (spill.partition(x.0), *x)))
+        .await
+        .unwrap();
+    // appended to the same files.
+    spill
+        .write(vec![(spill.partition("a"), ("a", 5u32))])
+        .await
+        .unwrap();
+
+    let mut read = Vec::new();
+    for p in 0..spill.partitions() {
+        let mut entries = Vec::new();
+        spill
+            .read(p, |x: (String, u32)| entries.push(x))
+            .await
+            .unwrap();
+        assert!(entries.iter().all(|x| spill.partition(x.0.as_str()) == p));
+        read.extend(entries);
+    }
+    read.sort();
+    assert_eq!(
+        read,
+        vec![
+            ("a".to_string(), 1),
+            ("a".to_string(), 3),
+            ("a".to_string(), 5),
+            ("b".to_string(), 2),
+            ("c".to_string(), 4)
+        ]
+    );
+
+    spill.remove().await.unwrap();
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}